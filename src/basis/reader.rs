@@ -13,6 +13,30 @@ pub struct BasisShell {
 #[derive(Clone, Debug)]
 pub struct BasisSet {
     pub shells: Vec<BasisShell>,
+    /// Effective core potential (heavy elements only)
+    pub ecp: Option<Ecp>,
+}
+
+/// One ECP term  d · r^{n−2} · exp(−ζ r²)
+#[derive(Clone, Debug)]
+pub struct EcpTerm {
+    /// n (power of r is n − 2)
+    pub power: i32,
+    pub exponent: f64,
+    pub coefficient: f64,
+}
+
+/// Semilocal effective core potential of one element
+///
+///   U(r) = U_L(r) + Σ_{l<L} Σ_m |lm⟩ (U_l(r) − U_L(r)) ⟨lm|
+#[derive(Clone, Debug)]
+pub struct Ecp {
+    /// Core electrons replaced by the potential
+    pub n_core: usize,
+    /// Local part U_L
+    pub local: Vec<EcpTerm>,
+    /// Semilocal parts U_l − U_L, indexed by l = 0 … L−1
+    pub semilocal: Vec<Vec<EcpTerm>>,
 }
 
 /// Read basis set for a given element
@@ -46,6 +70,119 @@ fn sto3g_hydrogen() -> BasisSet {
                 ],
             },
         ],
+        ecp: None,
     }
 }
 
+/// Parse ECPs in Gaussian (.gbs / Gaussian94) format
+///
+/// This is the format distributed by the Basis Set Exchange for
+/// def2, Stuttgart and LANL2DZ ECPs:
+///
+/// ```text
+/// RB-ECP     3     28        <- symbol, L, core electrons
+/// f potential                <- U_L
+///   1
+/// 2      1.0000000    0.0000000
+/// s-f potential              <- U_0 − U_L
+///   ...
+/// ```
+///
+/// Returns ECPs keyed by element symbol ("Rb").
+pub fn parse_ecp_gaussian(text: &str) -> Result<HashMap<String, Ecp>, String> {
+    let mut ecps = HashMap::new();
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('!'));
+
+    while let Some(line) = lines.next() {
+        let parts: Vec<_> = line.split_whitespace().collect();
+
+        let header = parts
+            .first()
+            .filter(|p| parts.len() == 3 && p.to_uppercase().ends_with("-ECP"));
+
+        let Some(label) = header else {
+            continue;
+        };
+
+        let symbol = normalize_symbol(&label[..label.len() - 4]);
+        let lmax: usize = parts[1]
+            .parse()
+            .map_err(|_| format!("Bad ECP L for {}", symbol))?;
+        let n_core: usize = parts[2]
+            .parse()
+            .map_err(|_| format!("Bad ECP core count for {}", symbol))?;
+
+        let mut blocks = Vec::with_capacity(lmax + 1);
+
+        for _ in 0..=lmax {
+            // title ("f potential", "s-f potential", ...)
+            lines.next().ok_or(format!("Truncated ECP for {}", symbol))?;
+
+            let nterms: usize = lines
+                .next()
+                .ok_or(format!("Truncated ECP for {}", symbol))?
+                .parse()
+                .map_err(|_| format!("Bad ECP term count for {}", symbol))?;
+
+            let mut terms = Vec::with_capacity(nterms);
+            for _ in 0..nterms {
+                let t: Vec<_> = lines
+                    .next()
+                    .ok_or(format!("Truncated ECP for {}", symbol))?
+                    .split_whitespace()
+                    .collect();
+
+                if t.len() != 3 {
+                    return Err(format!("Bad ECP term for {}", symbol));
+                }
+
+                terms.push(EcpTerm {
+                    power: t[0].parse().map_err(|_| "Bad ECP power")?,
+                    exponent: parse_fortran_f64(t[1])?,
+                    coefficient: parse_fortran_f64(t[2])?,
+                });
+            }
+
+            blocks.push(terms);
+        }
+
+        let local = blocks.remove(0);
+
+        ecps.insert(
+            symbol,
+            Ecp {
+                n_core,
+                local,
+                semilocal: blocks,
+            },
+        );
+    }
+
+    Ok(ecps)
+}
+
+/// Read a Gaussian-format ECP file
+pub fn read_ecp_file(path: &str) -> Result<HashMap<String, Ecp>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_ecp_gaussian(&text)
+}
+
+/// "RB" / "rb" → "Rb"
+fn normalize_symbol(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        Some(f) => f.to_uppercase().chain(c.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+/// Accept Fortran exponents (1.0D+01)
+fn parse_fortran_f64(s: &str) -> Result<f64, String> {
+    s.replace(['D', 'd'], "E")
+        .parse()
+        .map_err(|_| format!("Bad number {}", s))
+}
+
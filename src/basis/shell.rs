@@ -2,6 +2,9 @@
 
 use crate::basis::primitive::Primitive;
 
+/// AO values φ_μ, gradients ∇φ_μ and Hessians ∂²φ_μ/∂r_j∂r_k at a point
+pub type AoHessians = (Vec<f64>, Vec<[f64; 3]>, Vec<[[f64; 3]; 3]>);
+
 /// One contracted shell (s, p, d, ...)
 #[derive(Clone, Debug)]
 pub struct Shell {
@@ -34,22 +37,6 @@ impl Shell {
         }
     }
 
-    pub fn num_orbitals(&self) -> usize {
-        // Para un shell con momento angular `l`:
-        // s (l=0): 1 orbital
-        // p (l=1): 3 orbitales  
-        // d (l=2): 6 orbitales (cartesianas) o 5 (esféricas)
-        // f (l=3): 10 orbitales (cartesianas) o 7 (esféricas)
-        
-        match self.ang {
-            0 => 1,   // s
-            1 => 3,   // p
-            2 => 6,   // d (cartesianas, cambia a 5 si usas esféricas)
-            3 => 10,  // f (cartesianas, cambia a 7 si usas esféricas)
-            l => ((l + 1) * (l + 2)) / 2, // fórmula general para cartesianas
-        }
-    }
-
     /// Number of Cartesian atomic orbitals in this shell
     pub fn n_orbitals(&self) -> usize {
         let l = self.ang[0] + self.ang[1] + self.ang[2];
@@ -71,5 +58,221 @@ impl Shell {
 
         comps
     }
+
+    /// Values and gradients of the Cartesian AOs at point r
+    ///
+    /// Same components, contraction and normalization as the integrals
+    /// (`integrals::hermite::expand_shell`):
+    ///   φ_l(r) = x^lx y^ly z^lz Σ_p c_p N_p exp(−α_p |r − A|²)
+    pub fn values_and_gradients(&self, r: [f64; 3]) -> (Vec<f64>, Vec<[f64; 3]>) {
+        let d = [
+            r[0] - self.center[0],
+            r[1] - self.center[1],
+            r[2] - self.center[2],
+        ];
+        let r2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+
+        // Parte radial y su derivada respecto de r² (×2)
+        let mut radial = 0.0;
+        let mut d_radial = 0.0;
+        for p in &self.primitives {
+            let g = p.coefficient() * p.norm() * (-p.exponent() * r2).exp();
+            radial += g;
+            d_radial -= 2.0 * p.exponent() * g;
+        }
+
+        let components = self.cartesian_components();
+        let mut values = Vec::with_capacity(components.len());
+        let mut gradients = Vec::with_capacity(components.len());
+
+        for l in components {
+            let pow = |k: usize, n: usize| d[k].powi(n as i32);
+            let poly = pow(0, l[0]) * pow(1, l[1]) * pow(2, l[2]);
+
+            let mut grad = [0.0; 3];
+            for k in 0..3 {
+                let (k1, k2) = ((k + 1) % 3, (k + 2) % 3);
+                let d_poly = if l[k] > 0 {
+                    l[k] as f64 * pow(k, l[k] - 1) * pow(k1, l[k1]) * pow(k2, l[k2])
+                } else {
+                    0.0
+                };
+                grad[k] = d_poly * radial + poly * d[k] * d_radial;
+            }
+
+            values.push(poly * radial);
+            gradients.push(grad);
+        }
+
+        (values, gradients)
+    }
+
+    /// Values, gradients and Hessians of the Cartesian AOs at point r
+    ///
+    /// Same conventions as `values_and_gradients`; `hessians[μ][j][k]`
+    /// is ∂²φ_μ/∂r_j∂r_k.
+    pub fn values_gradients_hessians(&self, r: [f64; 3]) -> AoHessians {
+        let d = [
+            r[0] - self.center[0],
+            r[1] - self.center[1],
+            r[2] - self.center[2],
+        ];
+        let r2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+
+        // Parte radial R(r²) y sus derivadas: ∂_k R = d_k R',
+        // ∂_j∂_k R = δ_jk R' + d_j d_k R''
+        let (mut radial, mut d_radial, mut d2_radial) = (0.0, 0.0, 0.0);
+        for p in &self.primitives {
+            let a = p.exponent();
+            let g = p.coefficient() * p.norm() * (-a * r2).exp();
+            radial += g;
+            d_radial -= 2.0 * a * g;
+            d2_radial += 4.0 * a * a * g;
+        }
+
+        let components = self.cartesian_components();
+        let mut values = Vec::with_capacity(components.len());
+        let mut gradients = Vec::with_capacity(components.len());
+        let mut hessians = Vec::with_capacity(components.len());
+
+        for l in components {
+            // ∂^e (x^lx y^ly z^lz)
+            let poly = |e: [usize; 3]| -> f64 {
+                (0..3)
+                    .map(|k| {
+                        if e[k] > l[k] {
+                            return 0.0;
+                        }
+                        let falling: usize = (0..e[k]).map(|n| l[k] - n).product();
+                        falling as f64 * d[k].powi((l[k] - e[k]) as i32)
+                    })
+                    .product()
+            };
+            let unit = |k: usize| {
+                let mut e = [0; 3];
+                e[k] = 1;
+                e
+            };
+
+            let p0 = poly([0; 3]);
+            let p1: [f64; 3] = std::array::from_fn(|k| poly(unit(k)));
+
+            let mut grad = [0.0; 3];
+            let mut hess = [[0.0; 3]; 3];
+            for j in 0..3 {
+                grad[j] = p1[j] * radial + p0 * d[j] * d_radial;
+
+                for k in 0..3 {
+                    let mut e = unit(j);
+                    e[k] += 1;
+                    let delta = if j == k { d_radial } else { 0.0 };
+
+                    hess[j][k] = poly(e) * radial
+                        + (p1[k] * d[j] + p1[j] * d[k]) * d_radial
+                        + p0 * (delta + d[j] * d[k] * d2_radial);
+                }
+            }
+
+            values.push(p0 * radial);
+            gradients.push(grad);
+            hessians.push(hess);
+        }
+
+        (values, gradients, hessians)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ao_gradients_match_finite_differences() {
+        let center = [0.2, -0.1, 0.4];
+        let ang = [2, 0, 0];
+        let shell = Shell::new(
+            vec![
+                Primitive::new(1.3, 0.6, center, ang),
+                Primitive::new(0.35, 0.5, center, ang),
+            ],
+            ang,
+            center,
+            0,
+        );
+
+        let r = [0.7, 0.3, -0.5];
+        let (values, gradients) = shell.values_and_gradients(r);
+        assert_eq!(values.len(), shell.n_orbitals());
+
+        let h = 1e-5;
+        for k in 0..3 {
+            let mut rp = r;
+            let mut rm = r;
+            rp[k] += h;
+            rm[k] -= h;
+            let (vp, _) = shell.values_and_gradients(rp);
+            let (vm, _) = shell.values_and_gradients(rm);
+
+            for mu in 0..values.len() {
+                let fd = (vp[mu] - vm[mu]) / (2.0 * h);
+                assert!((gradients[mu][k] - fd).abs() < 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn ao_hessians_match_finite_differences() {
+        let center = [0.1, 0.3, -0.2];
+        let ang = [1, 1, 0];
+        let shell = Shell::new(
+            vec![
+                Primitive::new(0.9, 0.7, center, ang),
+                Primitive::new(0.25, 0.4, center, ang),
+            ],
+            ang,
+            center,
+            0,
+        );
+
+        let r = [0.6, -0.2, 0.4];
+        let (values, gradients, hessians) = shell.values_gradients_hessians(r);
+        let (v0, g0) = shell.values_and_gradients(r);
+        for mu in 0..values.len() {
+            assert!((values[mu] - v0[mu]).abs() < 1e-14);
+            for k in 0..3 {
+                assert!((gradients[mu][k] - g0[mu][k]).abs() < 1e-14);
+            }
+        }
+
+        let h = 1e-5;
+        for k in 0..3 {
+            let mut rp = r;
+            let mut rm = r;
+            rp[k] += h;
+            rm[k] -= h;
+            let (_, gp) = shell.values_and_gradients(rp);
+            let (_, gm) = shell.values_and_gradients(rm);
+
+            for mu in 0..hessians.len() {
+                for j in 0..3 {
+                    let fd = (gp[mu][j] - gm[mu][j]) / (2.0 * h);
+                    assert!((hessians[mu][j][k] - fd).abs() < 1e-8);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn s_values_match_the_primitives() {
+        let center = [0.0, 0.5, -0.3];
+        let prims = vec![
+            Primitive::new(2.0, 0.4, center, [0, 0, 0]),
+            Primitive::new(0.5, 0.7, center, [0, 0, 0]),
+        ];
+        let shell = Shell::new(prims.clone(), [0, 0, 0], center, 0);
+
+        let r = [0.3, 0.1, 0.2];
+        let want: f64 = prims.iter().map(|p| p.value(r)).sum();
+        assert!((shell.values_and_gradients(r).0[0] - want).abs() < 1e-14);
+    }
+}
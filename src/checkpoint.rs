@@ -0,0 +1,437 @@
+//! Checkpoint / restart file
+//!
+//! One YAML document (serde) with a format version:
+//!
+//! - geometry (bohr), charge and multiplicity
+//! - basis definition (shells with exponents and contraction coefficients)
+//! - SCF state: MO coefficients, orbital energies, occupations, densities
+//! - geometry optimization progress (point, gradient, BFGS / L-BFGS memory)
+//! - finite-difference Hessian progress (completed gradient columns)
+//!
+//! Files are written to `<path>.tmp` and renamed, so a job killed while
+//! saving leaves the previous checkpoint intact.
+//!
+//! SCF restart goes through the projection guess, which also covers a
+//! different basis or geometry:
+//!
+//!   options.guess = InitialGuess::Projected(checkpoint.projection_source(&molecule)?)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::basis::primitive::Primitive;
+use crate::basis::shell::Shell;
+use crate::integrals::deriv::shell_atom_indices;
+use crate::system::atom::Atom;
+use crate::system::molecule::Molecule;
+use crate::scf::guess::ProjectionSource;
+use crate::scf::scf_cycle::{ScfResult, SpinOrbitals};
+use crate::scf::utils::vec2d_ref_to_dmatrix;
+
+/// Current format; older files are read, newer ones rejected
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    pub geometry: GeometryRecord,
+    pub basis: Vec<ShellRecord>,
+    pub scf: Option<ScfRecord>,
+    pub optimization: Option<OptimizationRecord>,
+    pub hessian: Option<HessianProgress>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AtomRecord {
+    pub symbol: String,
+    pub atomic_number: usize,
+    /// bohr
+    pub position: [f64; 3],
+    pub core_electrons: usize,
+    pub nuclear_exponent: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GeometryRecord {
+    pub atoms: Vec<AtomRecord>,
+    pub charge: i32,
+    pub multiplicity: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShellRecord {
+    pub ang: [usize; 3],
+    pub center: [f64; 3],
+    pub offset: usize,
+    pub exponents: Vec<f64>,
+    pub coefficients: Vec<f64>,
+}
+
+/// One spin channel (or the spatial orbitals of a restricted run)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrbitalRecord {
+    /// C (AO × MO)
+    pub coefficients: Vec<Vec<f64>>,
+    pub energies: Vec<f64>,
+    pub occupations: Vec<f64>,
+    pub density: Vec<Vec<f64>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScfRecord {
+    /// `ScfMethod` name (RHF, UKS, ...)
+    pub method: String,
+    pub energy: f64,
+    pub converged: bool,
+    pub alpha: OrbitalRecord,
+    /// None for restricted runs
+    pub beta: Option<OrbitalRecord>,
+}
+
+/// Geometry optimizer state after `iteration` accepted steps
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OptimizationRecord {
+    pub iteration: usize,
+    pub x: Vec<f64>,
+    pub energy: f64,
+    pub gradient: Vec<f64>,
+    /// BFGS inverse Hessian
+    pub inverse_hessian: Option<Vec<Vec<f64>>>,
+    /// L-BFGS (s, y) pairs, oldest first
+    pub lbfgs_s: Vec<Vec<f64>>,
+    pub lbfgs_y: Vec<Vec<f64>>,
+}
+
+/// Central-difference Hessian: column i is (g(x + h e_i) − g(x − h e_i)) / 2h
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HessianProgress {
+    pub x: Vec<f64>,
+    pub step: f64,
+    pub columns: Vec<Option<Vec<f64>>>,
+}
+
+impl HessianProgress {
+    pub fn new(x: &[f64], step: f64) -> Self {
+        Self {
+            x: x.to_vec(),
+            step,
+            columns: vec![None; x.len()],
+        }
+    }
+
+    pub fn completed(&self) -> usize {
+        self.columns.iter().filter(|c| c.is_some()).count()
+    }
+}
+
+impl Checkpoint {
+    pub fn new(molecule: &Molecule, shells: &[Shell]) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            geometry: GeometryRecord::from_molecule(molecule),
+            basis: shells.iter().map(ShellRecord::from_shell).collect(),
+            scf: None,
+            optimization: None,
+            hessian: None,
+        }
+    }
+
+    pub fn set_scf(&mut self, result: &ScfResult) {
+        self.scf = Some(ScfRecord::from_result(result));
+    }
+
+    /// Basis of the checkpoint
+    pub fn shells(&self) -> Vec<Shell> {
+        self.basis.iter().map(ShellRecord::to_shell).collect()
+    }
+
+    /// Orbitals and basis of the stored SCF, for `InitialGuess::Projected`
+    ///
+    /// Each stored shell follows its atom (matched by index) to the
+    /// position it has in `molecule`, so a restart after a geometry
+    /// step projects from functions on the current nuclei.
+    pub fn projection_source(&self, molecule: &Molecule) -> Result<ProjectionSource, String> {
+        let scf = self.scf.as_ref().ok_or("Checkpoint has no SCF state")?;
+
+        let stored = self.geometry.to_molecule();
+        if stored.atoms.len() != molecule.atoms.len() {
+            return Err(format!(
+                "Checkpoint has {} atoms, the molecule {}",
+                stored.atoms.len(),
+                molecule.atoms.len()
+            ));
+        }
+
+        let mut shells = self.shells();
+        let centers: Vec<[f64; 3]> = shells.iter().map(|s| s.center).collect();
+        for (shell, a) in shells.iter_mut().zip(shell_atom_indices(&centers, &stored.atoms)) {
+            let center = molecule.atoms[a].position;
+            shell.center = center;
+            for p in &mut shell.primitives {
+                *p = Primitive::new(p.exponent(), p.coefficient(), center, p.ang());
+            }
+        }
+
+        let alpha = vec2d_ref_to_dmatrix(&scf.alpha.coefficients);
+        let beta = match &scf.beta {
+            Some(beta) => vec2d_ref_to_dmatrix(&beta.coefficients),
+            None => alpha.clone(),
+        };
+
+        Ok(ProjectionSource { shells, alpha, beta })
+    }
+
+    /// Write atomically (temporary file + rename)
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_yaml::to_string(self)
+            .map_err(|e| format!("Cannot serialize checkpoint: {}", e))?;
+
+        // path + ".tmp": set_extension would map run.chk and run.yaml to the same run.tmp
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+
+        fs::write(&tmp, text)
+            .map_err(|e| format!("Cannot write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, path)
+            .map_err(|e| format!("Cannot move checkpoint to {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let checkpoint: Self = serde_yaml::from_str(&text)
+            .map_err(|e| format!("Invalid checkpoint {}: {}", path.display(), e))?;
+
+        if checkpoint.version > CHECKPOINT_VERSION {
+            return Err(format!(
+                "Checkpoint version {} is newer than supported ({})",
+                checkpoint.version, CHECKPOINT_VERSION
+            ));
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// Existing checkpoint or a fresh one for `molecule` / `shells`
+    pub fn load_or_new(path: &Path, molecule: &Molecule, shells: &[Shell]) -> Result<Self, String> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::new(molecule, shells))
+        }
+    }
+}
+
+impl GeometryRecord {
+    pub fn from_molecule(molecule: &Molecule) -> Self {
+        Self {
+            atoms: molecule
+                .atoms
+                .iter()
+                .map(|a| AtomRecord {
+                    symbol: a.symbol.clone(),
+                    atomic_number: a.atomic_number,
+                    position: a.position,
+                    core_electrons: a.core_electrons,
+                    nuclear_exponent: a.nuclear_exponent,
+                })
+                .collect(),
+            charge: molecule.charge,
+            multiplicity: molecule.multiplicity,
+        }
+    }
+
+    pub fn to_molecule(&self) -> Molecule {
+        Molecule {
+            atoms: self
+                .atoms
+                .iter()
+                .map(|a| Atom {
+                    symbol: a.symbol.clone(),
+                    atomic_number: a.atomic_number,
+                    position: a.position,
+                    core_electrons: a.core_electrons,
+                    nuclear_exponent: a.nuclear_exponent,
+                })
+                .collect(),
+            charge: self.charge,
+            multiplicity: self.multiplicity,
+        }
+    }
+
+    /// Flattened coordinates [x1, y1, z1, x2, ...] (bohr)
+    pub fn coordinates(&self) -> Vec<f64> {
+        self.atoms.iter().flat_map(|a| a.position).collect()
+    }
+
+    pub fn set_coordinates(&mut self, x: &[f64]) {
+        assert_eq!(x.len(), 3 * self.atoms.len(), "Coordinate vector length");
+        for (atom, r) in self.atoms.iter_mut().zip(x.chunks(3)) {
+            atom.position = [r[0], r[1], r[2]];
+        }
+    }
+}
+
+impl ShellRecord {
+    pub fn from_shell(shell: &Shell) -> Self {
+        Self {
+            ang: shell.ang,
+            center: shell.center,
+            offset: shell.offset,
+            exponents: shell.primitives.iter().map(|p| p.exponent()).collect(),
+            coefficients: shell.primitives.iter().map(|p| p.coefficient()).collect(),
+        }
+    }
+
+    pub fn to_shell(&self) -> Shell {
+        let primitives = self
+            .exponents
+            .iter()
+            .zip(&self.coefficients)
+            .map(|(&e, &c)| Primitive::new(e, c, self.center, self.ang))
+            .collect();
+
+        Shell::new(primitives, self.ang, self.center, self.offset)
+    }
+}
+
+impl OrbitalRecord {
+    fn from_orbitals(orbitals: &SpinOrbitals) -> Self {
+        Self {
+            coefficients: orbitals.coefficients.clone(),
+            energies: orbitals.energies.clone(),
+            occupations: orbitals.occupations.clone(),
+            density: orbitals.density.clone(),
+        }
+    }
+}
+
+impl ScfRecord {
+    pub fn from_result(result: &ScfResult) -> Self {
+        Self {
+            method: format!("{:?}", result.method),
+            energy: result.energy,
+            converged: result.converged,
+            alpha: OrbitalRecord::from_orbitals(&result.alpha),
+            beta: result.beta.as_ref().map(OrbitalRecord::from_orbitals),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+    use crate::scf::guess::InitialGuess;
+    use crate::scf::scf_cycle::{run_scf, ScfOptions};
+
+    /// Empty scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("checkpoint-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn save_keeps_files_with_the_same_stem() {
+        let dir = scratch_dir("stem");
+        let (molecule, shells, _) = h2();
+        let sibling = dir.join("run.tmp");
+        fs::write(&sibling, "keep").unwrap();
+
+        let path = dir.join("run.chk");
+        Checkpoint::new(&molecule, &shells).save(&path).unwrap();
+
+        assert_eq!(fs::read_to_string(&sibling).unwrap(), "keep");
+        assert!(!dir.join("run.chk.tmp").exists());
+        let loaded = Checkpoint::load(&path).unwrap();
+        assert_eq!(loaded.basis.len(), shells.len());
+        assert_eq!(loaded.geometry.to_molecule().atoms[1].position, molecule.atoms[1].position);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scf_restarts_from_the_saved_orbitals() {
+        let dir = scratch_dir("restart");
+        let path = dir.join("h2.chk");
+        let (molecule, shells, centers) = h2();
+        let options = quiet_options();
+
+        let first = run_scf(&molecule, &shells, &centers, &options);
+        let mut checkpoint = Checkpoint::new(&molecule, &shells);
+        checkpoint.set_scf(&first);
+        checkpoint.save(&path).unwrap();
+
+        let source = Checkpoint::load(&path).unwrap().projection_source(&molecule).unwrap();
+        let restart = run_scf(
+            &molecule,
+            &shells,
+            &centers,
+            &ScfOptions { guess: InitialGuess::Projected(source), ..options },
+        );
+
+        assert!(restart.converged);
+        assert!(restart.iterations <= 2);
+        assert!((restart.energy - first.energy).abs() < 1e-10);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scf_restarts_after_a_geometry_step() {
+        let dir = scratch_dir("geometry");
+        let path = dir.join("h2.chk");
+        let (molecule, shells, centers) = h2();
+        let options = quiet_options();
+
+        let first = run_scf(&molecule, &shells, &centers, &options);
+        let mut checkpoint = Checkpoint::new(&molecule, &shells);
+        checkpoint.set_scf(&first);
+        checkpoint.save(&path).unwrap();
+
+        let (moved, moved_shells, moved_centers) =
+            hydrogens(&[[0.0, 0.0, 0.1], [0.0, 0.0, 1.6]], 0, 1);
+        let source = Checkpoint::load(&path).unwrap().projection_source(&moved).unwrap();
+
+        // Las funciones guardadas siguen a sus átomos
+        for (stored, current) in source.shells.iter().zip(&moved_shells) {
+            assert_eq!(stored.center, current.center);
+            assert_eq!(stored.primitives[0].center(), current.center);
+        }
+
+        let fresh = run_scf(&moved, &moved_shells, &moved_centers, &options);
+        let restart = run_scf(
+            &moved,
+            &moved_shells,
+            &moved_centers,
+            &ScfOptions { guess: InitialGuess::Projected(source), ..options },
+        );
+
+        assert!(restart.converged);
+        assert!((restart.energy - fresh.energy).abs() < 1e-8);
+        assert!(restart.iterations < fresh.iterations);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let dir = scratch_dir("version");
+        let path = dir.join("future.chk");
+        let (molecule, shells, _) = h2();
+
+        let mut checkpoint = Checkpoint::new(&molecule, &shells);
+        checkpoint.version = CHECKPOINT_VERSION + 1;
+        checkpoint.save(&path).unwrap();
+
+        let error = Checkpoint::load(&path).unwrap_err();
+        assert!(error.contains("newer than supported"), "{}", error);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,7 +4,7 @@
 //! - rho(r) and ∇rho(r) for closed-shell DFT
 //! - rho_alpha(r), rho_beta(r) for spin-polarized DFT
 
-use crate::basis::shell::Shell;
+use crate::basis::shell::{AoHessians, Shell};
 use nalgebra::DMatrix;
 
 /// Density at a grid point (closed-shell)
@@ -22,48 +22,65 @@ pub struct SpinDensityPoint {
 }
 
 // ========================================================
-// Closed-shell density
+// AO basis on a point
 // ========================================================
 
-pub fn density_at_point(
+/// φ_μ(r) and ∇φ_μ(r) for every AO, in basis order
+pub fn basis_at_point(
     shells: &[Shell],
-    shell_centers: &[[f64; 3]],
-    density: &DMatrix<f64>,
     r: [f64; 3],
-) -> DensityPoint {
+) -> (Vec<f64>, Vec<[f64; 3]>) {
 
-    let nao = density.nrows();
-    let mut rho = 0.0;
-    let mut grad = [0.0; 3];
+    let mut phi = Vec::new();
+    let mut grad_phi = Vec::new();
 
-    for (si, ci) in shells.iter().zip(shell_centers.iter()) {
-        let off_i = si.offset;
+    for shell in shells {
+        let (values, gradients) = shell.values_and_gradients(r);
+        phi.extend(values);
+        grad_phi.extend(gradients);
+    }
 
-        for mu in 0..si.orbitals.len() {
-            let phi_mu = si.orbitals[mu].value(*ci, r);
-            let grad_mu = si.orbitals[mu].gradient(*ci, r);
+    (phi, grad_phi)
+}
 
-            let i = off_i + mu;
+/// φ_μ(r), ∇φ_μ(r) and ∂²φ_μ/∂r_j∂r_k for every AO, in basis order
+pub fn basis_hessians_at_point(shells: &[Shell], r: [f64; 3]) -> AoHessians {
 
-            for (sj, cj) in shells.iter().zip(shell_centers.iter()) {
-                let off_j = sj.offset;
+    let mut phi = Vec::new();
+    let mut grad_phi = Vec::new();
+    let mut hess_phi = Vec::new();
 
-                for nu in 0..sj.orbitals.len() {
-                    let phi_nu = sj.orbitals[nu].value(*cj, r);
-                    let grad_nu = sj.orbitals[nu].gradient(*cj, r);
+    for shell in shells {
+        let (values, gradients, hessians) = shell.values_gradients_hessians(r);
+        phi.extend(values);
+        grad_phi.extend(gradients);
+        hess_phi.extend(hessians);
+    }
+
+    (phi, grad_phi, hess_phi)
+}
+
+/// ρ = Σ P_μν φ_μ φ_ν and its gradient from AO values on the point
+pub fn contract_density(
+    density: impl Fn(usize, usize) -> f64,
+    phi: &[f64],
+    grad_phi: &[[f64; 3]],
+) -> DensityPoint {
+
+    let mut rho = 0.0;
+    let mut grad = [0.0; 3];
 
-                    let j = off_j + nu;
-                    let pij = density[(i, j)];
+    for (i, (phi_mu, grad_mu)) in phi.iter().zip(grad_phi).enumerate() {
+        for (j, (phi_nu, grad_nu)) in phi.iter().zip(grad_phi).enumerate() {
+            let pij = density(i, j);
+            if pij == 0.0 {
+                continue;
+            }
 
-                    rho += pij * phi_mu * phi_nu;
+            rho += pij * phi_mu * phi_nu;
 
-                    for k in 0..3 {
-                        grad[k] += pij * (
-                            grad_mu[k] * phi_nu
-                          + phi_mu * grad_nu[k]
-                        );
-                    }
-                }
+            for k in 0..3 {
+                grad[k] += pij * (grad_mu[k] * phi_nu + phi_mu * grad_nu[k]);
             }
         }
     }
@@ -71,20 +88,36 @@ pub fn density_at_point(
     DensityPoint { rho, grad }
 }
 
+// ========================================================
+// Closed-shell density
+// ========================================================
+
+pub fn density_at_point(
+    shells: &[Shell],
+    _shell_centers: &[[f64; 3]],
+    density: &DMatrix<f64>,
+    r: [f64; 3],
+) -> DensityPoint {
+
+    let (phi, grad_phi) = basis_at_point(shells, r);
+    contract_density(|i, j| density[(i, j)], &phi, &grad_phi)
+}
+
 // ========================================================
 // Spin-polarized density (UDFT)
 // ========================================================
 
 pub fn spin_density_at_point(
     shells: &[Shell],
-    shell_centers: &[[f64; 3]],
+    _shell_centers: &[[f64; 3]],
     density_alpha: &DMatrix<f64>,
     density_beta: &DMatrix<f64>,
     r: [f64; 3],
 ) -> SpinDensityPoint {
 
-    let da = density_at_point(shells, shell_centers, density_alpha, r);
-    let db = density_at_point(shells, shell_centers, density_beta, r);
+    let (phi, grad_phi) = basis_at_point(shells, r);
+    let da = contract_density(|i, j| density_alpha[(i, j)], &phi, &grad_phi);
+    let db = contract_density(|i, j| density_beta[(i, j)], &phi, &grad_phi);
 
     SpinDensityPoint {
         rho_a: da.rho,
@@ -93,4 +126,3 @@ pub fn spin_density_at_point(
         grad_b: db.grad,
     }
 }
-
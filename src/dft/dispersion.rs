@@ -0,0 +1,152 @@
+//! Empirical dispersion of ωB97X-D (Chai & Head-Gordon 2008)
+//!
+//!   E_disp = − Σ_{A<B} C6_AB / R_AB⁶ · f(R_AB)
+//!   f(R)   = 1 / (1 + a (R / R_r)⁻¹²),  a = 6
+//!
+//! with C6_AB = √(C6_A C6_B) and R_r = R0_A + R0_B from Grimme's D2
+//! parameters (J. Comput. Chem. 27, 1787 (2006)); no global s6 scaling.
+//! The term depends only on the nuclear positions.
+
+use crate::system::atom::Atom;
+
+/// Damping steepness a
+const DAMPING: f64 = 6.0;
+
+/// J nm⁶ mol⁻¹ → Eh bohr⁶
+const C6_TO_AU: f64 = 17.345_277_58;
+
+/// Å → bohr
+const ANGSTROM_TO_BOHR: f64 = 1.0 / 0.529_177_210_9;
+
+/// Grimme D2 (C6 [J nm⁶ mol⁻¹], R0 [Å]) for Z = 1..=54
+const D2_PARAMETERS: [(f64, f64); 54] = [
+    (0.14, 1.001), (0.08, 1.012),
+    (1.61, 0.825), (1.61, 1.408), (3.13, 1.485), (1.75, 1.452),
+    (1.23, 1.397), (0.70, 1.342), (0.75, 1.287), (0.63, 1.243),
+    (5.71, 1.144), (5.71, 1.364), (10.79, 1.639), (9.23, 1.716),
+    (7.84, 1.705), (5.57, 1.683), (5.07, 1.639), (4.61, 1.595),
+    (10.80, 1.485), (10.80, 1.474),
+    (10.80, 1.562), (10.80, 1.562), (10.80, 1.562), (10.80, 1.562), (10.80, 1.562),
+    (10.80, 1.562), (10.80, 1.562), (10.80, 1.562), (10.80, 1.562), (10.80, 1.562),
+    (16.99, 1.649), (17.10, 1.727), (16.37, 1.760), (12.64, 1.771),
+    (12.47, 1.749), (12.36, 1.727),
+    (24.67, 1.628), (24.67, 1.606),
+    (24.67, 1.639), (24.67, 1.639), (24.67, 1.639), (24.67, 1.639), (24.67, 1.639),
+    (24.67, 1.639), (24.67, 1.639), (24.67, 1.639), (24.67, 1.639), (24.67, 1.639),
+    (37.32, 1.672), (38.71, 1.804), (38.44, 1.881), (31.74, 1.892),
+    (31.50, 1.892), (29.99, 1.881),
+];
+
+/// (C6 [Eh bohr⁶], R0 [bohr]) of an element
+fn d2_parameters(z: usize) -> (f64, f64) {
+    let (c6, r0) = *D2_PARAMETERS
+        .get(z.wrapping_sub(1))
+        .unwrap_or_else(|| panic!("no D2 dispersion parameters for Z = {}", z));
+
+    (c6 * C6_TO_AU, r0 * ANGSTROM_TO_BOHR)
+}
+
+/// (E_AB, dE_AB/dR) of one atom pair at distance r
+fn pair_term(za: usize, zb: usize, r: f64) -> (f64, f64) {
+    let (c6_a, r0_a) = d2_parameters(za);
+    let (c6_b, r0_b) = d2_parameters(zb);
+
+    let c6 = (c6_a * c6_b).sqrt();
+    let r_r = r0_a + r0_b;
+
+    let x = DAMPING * (r / r_r).powi(-12);
+    let damp = 1.0 / (1.0 + x);
+    let r6 = r.powi(6);
+
+    let energy = -c6 / r6 * damp;
+
+    // d/dR [R⁻⁶ f] = −6 R⁻⁷ f + R⁻⁶ · 12 x / R · f²
+    let d_energy = -c6 * (-6.0 / (r6 * r) * damp + 12.0 * x / (r6 * r) * damp * damp);
+
+    (energy, d_energy)
+}
+
+/// E_disp
+pub fn chg_dispersion_energy(atoms: &[Atom]) -> f64 {
+    let mut energy = 0.0;
+
+    for (a, atom_a) in atoms.iter().enumerate() {
+        for atom_b in &atoms[..a] {
+            let r = distance(atom_a.position, atom_b.position);
+            energy += pair_term(atom_a.atomic_number, atom_b.atomic_number, r).0;
+        }
+    }
+
+    energy
+}
+
+/// ∂E_disp/∂R_A
+pub fn chg_dispersion_gradient(atoms: &[Atom]) -> Vec<[f64; 3]> {
+    let mut grad = vec![[0.0; 3]; atoms.len()];
+
+    for a in 0..atoms.len() {
+        for b in 0..a {
+            let (pa, pb) = (atoms[a].position, atoms[b].position);
+            let r = distance(pa, pb);
+            let (_, d_energy) = pair_term(atoms[a].atomic_number, atoms[b].atomic_number, r);
+
+            for k in 0..3 {
+                let g = d_energy * (pa[k] - pb[k]) / r;
+                grad[a][k] += g;
+                grad[b][k] -= g;
+            }
+        }
+    }
+
+    grad
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atoms(positions: &[(&str, usize, [f64; 3])]) -> Vec<Atom> {
+        positions
+            .iter()
+            .map(|(s, z, r)| Atom::new(s.to_string(), *z, *r))
+            .collect()
+    }
+
+    #[test]
+    fn energy_is_attractive_and_damped_at_short_range() {
+        let far = chg_dispersion_energy(&atoms(&[("C", 6, [0.0; 3]), ("C", 6, [0.0, 0.0, 7.0])]));
+        let near = chg_dispersion_energy(&atoms(&[("C", 6, [0.0; 3]), ("C", 6, [0.0, 0.0, 0.5])]));
+
+        assert!(far < 0.0);
+        // C6/R⁶ alone would be ~10⁴ times larger at 0.5 bohr
+        assert!(near.abs() < 1e-3);
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() {
+        let base = [
+            ("O", 8, [0.0, 0.0, 0.0]),
+            ("H", 1, [1.8, 0.2, -0.1]),
+            ("C", 6, [-0.5, 3.1, 1.2]),
+        ];
+        let grad = chg_dispersion_gradient(&atoms(&base));
+
+        let h = 1e-4;
+        for (a, g) in grad.iter().enumerate() {
+            for (k, &g_k) in g.iter().enumerate() {
+                let mut plus = base;
+                let mut minus = base;
+                plus[a].2[k] += h;
+                minus[a].2[k] -= h;
+                let fd = (chg_dispersion_energy(&atoms(&plus))
+                    - chg_dispersion_energy(&atoms(&minus)))
+                    / (2.0 * h);
+                assert!((g_k - fd).abs() < 1e-9, "{} vs {}", g_k, fd);
+            }
+        }
+    }
+}
@@ -10,6 +10,8 @@ use crate::system::atom::Atom;
 pub struct GridPoint {
     pub r: [f64; 3],
     pub weight: f64,
+    /// Atom whose atomic grid holds the point (it moves with it)
+    pub atom: usize,
 }
 
 /// Complete molecular grid
@@ -22,8 +24,8 @@ impl DftGrid {
     pub fn new(atoms: &[Atom], radial: usize, angular: usize) -> Self {
         let mut points = Vec::new();
 
-        for atom in atoms {
-            let atomic = atomic_grid(atom, radial, angular);
+        for (a, atom) in atoms.iter().enumerate() {
+            let atomic = atomic_grid(atom, a, radial, angular);
             points.extend(atomic);
         }
 
@@ -32,7 +34,7 @@ impl DftGrid {
 }
 
 /// Build an atomic-centered grid
-fn atomic_grid(atom: &Atom, n_radial: usize, n_ang: usize) -> Vec<GridPoint> {
+fn atomic_grid(atom: &Atom, index: usize, n_radial: usize, n_ang: usize) -> Vec<GridPoint> {
     let mut pts = Vec::new();
 
     let r_max = 10.0; // bohr, enough for valence density
@@ -64,6 +66,7 @@ fn atomic_grid(atom: &Atom, n_radial: usize, n_ang: usize) -> Vec<GridPoint> {
                         atom.position[2] + z,
                     ],
                     weight: w_r * w_ang,
+                    atom: index,
                 });
             }
         }
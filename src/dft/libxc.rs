@@ -20,6 +20,19 @@ struct xc_func_type {
     _private: [u8; 0],
 }
 
+#[repr(C)]
+struct xc_func_info_type {
+    _private: [u8; 0],
+}
+
+// XC_FAMILY_* (the HYB_* values only exist before libxc 5)
+const XC_FAMILY_LDA: c_int = 1;
+const XC_FAMILY_GGA: c_int = 2;
+const XC_FAMILY_MGGA: c_int = 4;
+const XC_FAMILY_HYB_GGA: c_int = 32;
+const XC_FAMILY_HYB_MGGA: c_int = 64;
+
+#[link(name = "xc")]
 extern "C" {
     fn xc_func_init(
         p: *mut *mut xc_func_type,
@@ -29,6 +42,10 @@ extern "C" {
 
     fn xc_func_end(p: *mut xc_func_type);
 
+    fn xc_func_get_info(p: *const xc_func_type) -> *const xc_func_info_type;
+
+    fn xc_func_info_get_family(info: *const xc_func_info_type) -> c_int;
+
     fn xc_lda_exc(
         p: *const xc_func_type,
         n: c_int,
@@ -103,6 +120,15 @@ extern "C" {
 pub struct LibXC {
     func: *mut xc_func_type,
     spin: bool,
+    family: Family,
+}
+
+/// Ingredients a functional depends on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Family {
+    Lda,
+    Gga,
+    MetaGga,
 }
 
 pub struct XcHessian {
@@ -119,7 +145,8 @@ pub struct XcHessian {
 impl LibXC {
     pub fn new(func_id: i32, spin: bool) -> Self {
         let mut ptr: *mut xc_func_type = ptr::null_mut();
-        let spin_flag = if spin { 1 } else { 0 };
+        // XC_UNPOLARIZED = 1, XC_POLARIZED = 2
+        let spin_flag = if spin { 2 } else { 1 };
 
         unsafe {
             let ret = xc_func_init(&mut ptr, func_id, spin_flag);
@@ -128,7 +155,18 @@ impl LibXC {
             }
         }
 
-        LibXC { func: ptr, spin }
+        let family = match unsafe { xc_func_info_get_family(xc_func_get_info(ptr)) } {
+            XC_FAMILY_LDA => Family::Lda,
+            XC_FAMILY_GGA | XC_FAMILY_HYB_GGA => Family::Gga,
+            XC_FAMILY_MGGA | XC_FAMILY_HYB_MGGA => Family::MetaGga,
+            other => panic!("libxc functional {} has unsupported family {}", func_id, other),
+        };
+
+        LibXC { func: ptr, spin, family }
+    }
+
+    pub fn family(&self) -> Family {
+        self.family
     }
 }
 
@@ -221,6 +259,49 @@ impl LibXC {
     }
 }
 
+// ==================================================
+// Single spin-polarized point (noncollinear DFT)
+// ==================================================
+
+impl LibXC {
+    /// ε, (v_ρα, v_ρβ), (v_σαα, v_σαβ, v_σββ) at one point
+    ///
+    /// Requires `spin = true`; with `gga = false` the functional is
+    /// evaluated as LDA and σ is ignored.
+    pub fn eval_polarized_point(
+        &self,
+        rho: [f64; 2],
+        sigma: [f64; 3],
+        gga: bool,
+    ) -> (f64, [f64; 2], [f64; 3]) {
+
+        assert!(self.spin, "eval_polarized_point requires a spin-polarized functional");
+
+        let mut exc = [0.0; 1];
+        let mut vrho = [0.0; 2];
+        let mut vsigma = [0.0; 3];
+
+        unsafe {
+            if gga {
+                xc_gga_exc_vxc(
+                    self.func,
+                    1,
+                    rho.as_ptr(),
+                    sigma.as_ptr(),
+                    exc.as_mut_ptr(),
+                    vrho.as_mut_ptr(),
+                    vsigma.as_mut_ptr(),
+                );
+            } else {
+                xc_lda_exc(self.func, 1, rho.as_ptr(), exc.as_mut_ptr());
+                xc_lda_vxc(self.func, 1, rho.as_ptr(), vrho.as_mut_ptr());
+            }
+        }
+
+        (exc[0], vrho, vsigma)
+    }
+}
+
 // ==================================================
 // meta-GGA
 // ==================================================
@@ -297,3 +378,86 @@ impl LibXC {
     }
 }
 
+
+// ==================================================
+// Any family
+// ==================================================
+
+impl LibXC {
+    /// ε, v_ρ, v_σ, v_τ for the functional's own family
+    ///
+    /// Derivatives the family does not have are zero. τ is broadcast
+    /// as in `eval_mgga`.
+    pub fn eval_all(
+        &self,
+        rho: &[f64],
+        sigma: &[f64],
+        tau: f64,
+    ) -> (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>) {
+
+        match self.family {
+            Family::Lda => {
+                let (exc, vrho) = self.eval_lda(rho);
+                (exc, vrho, vec![0.0; sigma.len()], vec![0.0; rho.len()])
+            }
+            Family::Gga => {
+                let (exc, vrho, vsigma) = self.eval_gga(rho, sigma);
+                (exc, vrho, vsigma, vec![0.0; rho.len()])
+            }
+            Family::MetaGga => self.eval_mgga(rho, sigma, tau),
+        }
+    }
+
+    /// Spin-polarized `eval_all`
+    ///
+    /// Per point: ρ = (ρα, ρβ), σ = (σαα, σαβ, σββ), τ = (τα, τβ).
+    /// Returns ε (1 per point), v_ρ (2), v_σ (3), v_τ (2).
+    pub fn eval_all_spin(
+        &self,
+        rho: &[f64],
+        sigma: &[f64],
+        tau: &[f64],
+    ) -> (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>) {
+
+        assert!(self.spin, "eval_all_spin requires a spin-polarized functional");
+
+        let np = rho.len() / 2;
+        let n = np as c_int;
+
+        let mut exc = vec![0.0; np];
+        let mut vrho = vec![0.0; 2 * np];
+        let mut vsigma = vec![0.0; 3 * np];
+        let mut vtau = vec![0.0; 2 * np];
+
+        unsafe {
+            match self.family {
+                Family::Lda => {
+                    xc_lda_exc(self.func, n, rho.as_ptr(), exc.as_mut_ptr());
+                    xc_lda_vxc(self.func, n, rho.as_ptr(), vrho.as_mut_ptr());
+                }
+                Family::Gga => xc_gga_exc_vxc(
+                    self.func,
+                    n,
+                    rho.as_ptr(),
+                    sigma.as_ptr(),
+                    exc.as_mut_ptr(),
+                    vrho.as_mut_ptr(),
+                    vsigma.as_mut_ptr(),
+                ),
+                Family::MetaGga => xc_mgga_exc_vxc(
+                    self.func,
+                    n,
+                    rho.as_ptr(),
+                    sigma.as_ptr(),
+                    tau.as_ptr(),
+                    exc.as_mut_ptr(),
+                    vrho.as_mut_ptr(),
+                    vsigma.as_mut_ptr(),
+                    vtau.as_mut_ptr(),
+                ),
+            }
+        }
+
+        (exc, vrho, vsigma, vtau)
+    }
+}
@@ -3,3 +3,4 @@ pub mod grid;
 pub mod density;
 pub mod vxc;
 pub mod tau;
+pub mod dispersion;
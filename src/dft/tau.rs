@@ -1,11 +1,12 @@
 //! Kinetic energy density τ and its nuclear derivative helpers
 
 use crate::basis::shell::Shell;
+use crate::dft::density::basis_at_point;
 
 /// τ(r) from occupied orbitals (RHF/DFT)
 pub fn tau_at_point(
     shells: &[Shell],
-    centers: &[[f64;3]],
+    _centers: &[[f64;3]],
     coeff: &Vec<Vec<f64>>,   // C_{μi}
     n_occ: usize,
     r: [f64;3],
@@ -13,12 +14,7 @@ pub fn tau_at_point(
     let nao = coeff.len();
 
     // AO gradients
-    let mut grad_phi = Vec::with_capacity(nao);
-    for (sh, c) in shells.iter().zip(centers.iter()) {
-        for ao in &sh.orbitals {
-            grad_phi.push(ao.gradient(*c, r));
-        }
-    }
+    let (_, grad_phi) = basis_at_point(shells, r);
 
     let mut tau = 0.0;
     for i in 0..n_occ {
@@ -33,43 +29,34 @@ pub fn tau_at_point(
     tau
 }
 
-/// ∂τ/∂R_A (AO-based; Pulay XC included implicitly)
+/// Basis-function part of ∂τ/∂R_A for τ = ½ Σ P_μν ∇φ_μ·∇φ_ν
+///
+/// Only the AOs centered on A move:
+///   ∂τ/∂A_k = −Σ_{μ∈A} Σ_ν P_μν Σ_j ∂_k∂_jφ_μ ∂_jφ_ν
+/// The motion of the grid point itself is left to the caller.
+/// Returns one gradient per atom; `ao_atom` maps AOs to atoms.
 pub fn dtau_dra(
-    shells: &[Shell],
-    centers: &[[f64;3]],
-    coeff: &Vec<Vec<f64>>,
-    n_occ: usize,
-    atom_idx: usize,
-    r: [f64;3],
-) -> [f64;3] {
-    let nao = coeff.len();
-
-    let mut grad_phi = Vec::with_capacity(nao);
-    let mut hess_phi = Vec::with_capacity(nao); // second derivatives wrt nuclear motion proxy
-
-    for (sh, c) in shells.iter().zip(centers.iter()) {
-        for ao in &sh.orbitals {
-            grad_phi.push(ao.gradient(*c, r));
-            hess_phi.push(ao.hessian(*c, r)); // assume available (or finite diff)
-        }
-    }
-
-    let mut d = [0.0;3];
-    for i in 0..n_occ {
-        let mut gpsi = [0.0;3];
-        let mut dgpsi = [0.0;3];
-
-        for mu in 0..nao {
-            for k in 0..3 {
-                gpsi[k] += coeff[mu][i] * grad_phi[mu][k];
-                dgpsi[k] += coeff[mu][i] * hess_phi[mu][k];
+    density: &[Vec<f64>],
+    grad_phi: &[[f64; 3]],
+    hess_phi: &[[[f64; 3]; 3]],
+    ao_atom: &[usize],
+    natoms: usize,
+) -> Vec<[f64; 3]> {
+    let mut d = vec![[0.0; 3]; natoms];
+
+    for (mu, row) in density.iter().enumerate() {
+        // Σ_ν P_μν ∇φ_ν
+        let mut g = [0.0; 3];
+        for (p, grad_nu) in row.iter().zip(grad_phi) {
+            for j in 0..3 {
+                g[j] += p * grad_nu[j];
             }
         }
 
         for k in 0..3 {
-            d[k] += 2.0 * gpsi[k] * dgpsi[k];
+            d[ao_atom[mu]][k] -= (0..3).map(|j| hess_phi[mu][k][j] * g[j]).sum::<f64>();
         }
     }
+
     d
 }
-
@@ -5,14 +5,17 @@
 //! - GGA (PBE) / spin-GGA
 //! - meta-GGA (SCAN) / spin-meta-GGA
 //! - Hybrids (PBE0, B3LYP, hybrid meta-GGA)
+//! - Range-separated hybrids (CAM-B3LYP, ωB97X, ωB97X-D, LC-ωPBE)
+//! - The −D dispersion of ωB97X-D (`dft::dispersion`)
 //!
 //! Returns AO-space Vxc and energy corrections:
 //!   Exc − ∫ rho * vxc
 
 use crate::basis::shell::Shell;
 use crate::dft::grid::{DftGrid, GridPoint};
-use crate::dft::density::{density_at_point, spin_density_at_point};
+use crate::dft::density::{basis_at_point, contract_density};
 use crate::dft::tau::tau_at_point;
+use crate::dft::dispersion::{chg_dispersion_energy, chg_dispersion_gradient};
 use crate::dft::libxc::LibXC;
 use crate::system::atom::Atom;
 
@@ -39,12 +42,159 @@ impl Hybrid {
     }
 }
 
+/// Range-separated hybrids
+///
+/// Exact exchange is split with erf(ωr)/r:
+///   1/r = (α + β erf(ωr)) / r  +  (1 − α − β erf(ωr)) / r
+///          \_ HF exchange _/       \_____ DFT (libxc) _____/
+#[derive(Clone, Copy)]
+pub enum RangeSeparated {
+    CAMB3LYP, // α = 0.19,     β = 0.46,     ω = 0.33
+    WB97X,    // α = 0.157706, β = 0.842294, ω = 0.3
+    /// With the damped D2 dispersion (`XcMethod::dispersion_energy`)
+    WB97XD,   // α = 0.222036, β = 0.777964, ω = 0.2
+    LCWPBE,   // α = 0,        β = 1,        ω = 0.4
+}
+
+impl RangeSeparated {
+    /// (α, β, ω)
+    pub fn parameters(&self) -> (f64, f64, f64) {
+        match self {
+            RangeSeparated::CAMB3LYP => (0.19, 0.46, 0.33),
+            RangeSeparated::WB97X => (0.157706, 0.842294, 0.3),
+            RangeSeparated::WB97XD => (0.222036, 0.777964, 0.2),
+            RangeSeparated::LCWPBE => (0.0, 1.0, 0.4),
+        }
+    }
+
+    /// libxc functional id (short-range DFT exchange + correlation)
+    pub fn libxc_id(&self) -> i32 {
+        match self {
+            RangeSeparated::CAMB3LYP => 433,
+            RangeSeparated::WB97X => 464,
+            RangeSeparated::WB97XD => 471,
+            RangeSeparated::LCWPBE => 478,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum XcMethod {
     LDA,
     GGA,
     MetaGGA,
     Hybrid { base: Box<XcMethod>, hyb: Hybrid },
+    RangeSeparated(RangeSeparated),
+}
+
+/// Exact-exchange mixing in the Fock matrix
+///
+///   K_eff = full · K + long_range · K^{lr}(ω)
+#[derive(Clone, Copy, Debug)]
+pub struct ExchangeMixing {
+    pub full: f64,
+    pub long_range: f64,
+    pub omega: f64,
+}
+
+impl ExchangeMixing {
+    /// Pure Hartree–Fock exchange
+    pub fn hartree_fock() -> Self {
+        ExchangeMixing { full: 1.0, long_range: 0.0, omega: 0.0 }
+    }
+
+    pub fn has_long_range(&self) -> bool {
+        self.long_range != 0.0
+    }
+}
+
+impl XcMethod {
+    /// Parse the input functional name (LDA, PBE, SCAN, PBE0, CAM-B3LYP,
+    /// wB97X, wB97X-D, LC-wPBE)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let hybrid = |hyb| XcMethod::Hybrid { base: Box::new(XcMethod::GGA), hyb };
+        match name.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "lda" | "svwn" => Some(XcMethod::LDA),
+            "pbe" => Some(XcMethod::GGA),
+            "scan" => Some(XcMethod::MetaGGA),
+            "pbe0" => Some(hybrid(Hybrid::PBE0)),
+            "camb3lyp" => Some(XcMethod::RangeSeparated(RangeSeparated::CAMB3LYP)),
+            "wb97x" => Some(XcMethod::RangeSeparated(RangeSeparated::WB97X)),
+            "wb97xd" => Some(XcMethod::RangeSeparated(RangeSeparated::WB97XD)),
+            "lcwpbe" => Some(XcMethod::RangeSeparated(RangeSeparated::LCWPBE)),
+            _ => None,
+        }
+    }
+
+    pub fn exchange_mixing(&self) -> ExchangeMixing {
+        match self {
+            XcMethod::Hybrid { hyb, .. } => ExchangeMixing {
+                full: hyb.hf_fraction(),
+                long_range: 0.0,
+                omega: 0.0,
+            },
+            XcMethod::RangeSeparated(rsh) => {
+                let (alpha, beta, omega) = rsh.parameters();
+                ExchangeMixing { full: alpha, long_range: beta, omega }
+            }
+            _ => ExchangeMixing { full: 0.0, long_range: 0.0, omega: 0.0 },
+        }
+    }
+
+    /// Empirical dispersion energy of the method (0 without −D)
+    pub fn dispersion_energy(&self, atoms: &[Atom]) -> f64 {
+        match self {
+            XcMethod::RangeSeparated(RangeSeparated::WB97XD) => chg_dispersion_energy(atoms),
+            _ => 0.0,
+        }
+    }
+
+    /// ∂E_disp/∂R_A (None without −D)
+    pub fn dispersion_gradient(&self, atoms: &[Atom]) -> Option<Vec<[f64; 3]>> {
+        match self {
+            XcMethod::RangeSeparated(RangeSeparated::WB97XD) => {
+                Some(chg_dispersion_gradient(atoms))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// libxc functionals of a method, whether τ is needed, and the weight
+/// of the DFT part
+///
+/// Range-separated hybrids are complete XC functionals in libxc
+/// (short-range exchange already attenuated), so their weight is 1.
+pub(crate) fn xc_functionals(method: XcMethod, spin: bool) -> (Vec<LibXC>, bool, f64) {
+    match method {
+        XcMethod::LDA => (
+            vec![LibXC::new(1, spin), LibXC::new(7, spin)],
+            false,
+            1.0,
+        ),
+        XcMethod::GGA => (
+            vec![LibXC::new(101, spin), LibXC::new(130, spin)],
+            false,
+            1.0,
+        ),
+        XcMethod::MetaGGA => (
+            vec![
+                LibXC::new(263, spin), // SCAN_X
+                LibXC::new(267, spin), // SCAN_C
+            ],
+            true,
+            1.0,
+        ),
+        XcMethod::Hybrid { base, hyb } => {
+            let (funcs, is_meta, _) = xc_functionals(*base, spin);
+            (funcs, is_meta, 1.0 - hyb.hf_fraction())
+        }
+        XcMethod::RangeSeparated(rsh) => (
+            vec![LibXC::new(rsh.libxc_id(), spin)],
+            false,
+            1.0,
+        ),
+    }
 }
 
 //
@@ -77,50 +227,20 @@ pub fn build_vxc(
     let nao = density.len();
     let mut vxc = vec![vec![0.0; nao]; nao];
 
-    let (xc_base, hf_frac) = match method {
-        XcMethod::Hybrid { base, hyb } => (*base, hyb.hf_fraction()),
-        other => (other, 0.0),
-    };
-
-    let (fx, fc, is_meta) = match xc_base {
-        XcMethod::LDA => (
-            LibXC::new(1, false),
-            LibXC::new(7, false),
-            false,
-        ),
-        XcMethod::GGA => (
-            LibXC::new(101, false),
-            LibXC::new(130, false),
-            false,
-        ),
-        XcMethod::MetaGGA => (
-            LibXC::new(263, false), // SCAN_X
-            LibXC::new(267, false), // SCAN_C
-            true,
-        ),
-        _ => unreachable!(),
-    };
+    let (funcs, is_meta, dft_scale) = xc_functionals(method, false);
 
     let grid = DftGrid::new(atoms, 30, 14);
 
     let mut exc = 0.0;
     let mut int_rho_vxc = 0.0;
 
-    for GridPoint { r, weight } in grid.points {
-        let dp = density_at_point(shells, shell_centers, density, r);
-        if dp.rho < 1e-12 {
-            continue;
-        }
-
+    for GridPoint { r, weight, .. } in grid.points {
         // AO values and gradients
-        let mut phi = Vec::with_capacity(nao);
-        let mut grad_phi = Vec::with_capacity(nao);
+        let (phi, grad_phi) = basis_at_point(shells, r);
 
-        for (sh, c) in shells.iter().zip(shell_centers.iter()) {
-            for ao in &sh.orbitals {
-                phi.push(ao.value(*c, r));
-                grad_phi.push(ao.gradient(*c, r));
-            }
+        let dp = contract_density(|i, j| density[i][j], &phi, &grad_phi);
+        if dp.rho < 1e-12 {
+            continue;
         }
 
         // basic invariants
@@ -139,13 +259,18 @@ pub fn build_vxc(
         };
 
         // libxc evaluation
-        let (eps_x, vrx, vsx, vtx_x) = fx.eval_all(&rho, &sigma, tau);
-        let (eps_c, vrc, vsc, vtx_c) = fc.eval_all(&rho, &sigma, tau);
-
-        let eps = (eps_x[0] + eps_c[0]) * (1.0 - hf_frac);
-        let vrho = (vrx[0] + vrc[0]) * (1.0 - hf_frac);
-        let vsig = (vsx[0] + vsc[0]) * (1.0 - hf_frac);
-        let vtau = (vtx_x[0] + vtx_c[0]) * (1.0 - hf_frac);
+        let mut eps = 0.0;
+        let mut vrho = 0.0;
+        let mut vsig = 0.0;
+        let mut vtau = 0.0;
+
+        for f in &funcs {
+            let (e, vr, vs, vt) = f.eval_all(&rho, &sigma, tau);
+            eps += e[0] * dft_scale;
+            vrho += vr[0] * dft_scale;
+            vsig += vs[0] * dft_scale;
+            vtau += vt[0] * dft_scale;
+        }
 
         exc += weight * dp.rho * eps;
         int_rho_vxc += weight * dp.rho * vrho;
@@ -162,9 +287,9 @@ pub fn build_vxc(
 
                 val += 2.0 * vsig * grad_dot;
 
-                // meta-GGA
+                // meta-GGA (τ = ½ Σ P ∇φ·∇φ)
                 if is_meta {
-                    val += vtau *
+                    val += 0.5 * vtau *
                         (grad_phi[mu][0]*grad_phi[nu][0] +
                          grad_phi[mu][1]*grad_phi[nu][1] +
                          grad_phi[mu][2]*grad_phi[nu][2]);
@@ -207,91 +332,59 @@ pub fn build_vxc_udft(
     let mut vxa = vec![vec![0.0; nao]; nao];
     let mut vxb = vec![vec![0.0; nao]; nao];
 
-    let (xc_base, hf_frac) = match method {
-        XcMethod::Hybrid { base, hyb } => (*base, hyb.hf_fraction()),
-        other => (other, 0.0),
-    };
-
-    let (fx, fc, is_meta) = match xc_base {
-        XcMethod::LDA => (
-            LibXC::new(1, true),
-            LibXC::new(7, true),
-            false,
-        ),
-        XcMethod::GGA => (
-            LibXC::new(101, true),
-            LibXC::new(130, true),
-            false,
-        ),
-        XcMethod::MetaGGA => (
-            LibXC::new(263, true),
-            LibXC::new(267, true),
-            true,
-        ),
-        _ => unreachable!(),
-    };
+    let (funcs, is_meta, dft_scale) = xc_functionals(method, true);
 
     let grid = DftGrid::new(atoms, 30, 14);
 
     let mut exc = 0.0;
     let mut int_rho_vxc = 0.0;
 
-    for GridPoint { r, weight } in grid.points {
-        let dp = spin_density_at_point(
-            shells,
-            shell_centers,
-            p_alpha,
-            p_beta,
-            r,
-        );
-
-        if dp.rho_a + dp.rho_b < 1e-12 {
-            continue;
-        }
-
+    for GridPoint { r, weight, .. } in grid.points {
         // AO basis
-        let mut phi = Vec::with_capacity(nao);
-        let mut grad_phi = Vec::with_capacity(nao);
+        let (phi, grad_phi) = basis_at_point(shells, r);
 
-        for (sh, c) in shells.iter().zip(shell_centers.iter()) {
-            for ao in &sh.orbitals {
-                phi.push(ao.value(*c, r));
-                grad_phi.push(ao.gradient(*c, r));
-            }
+        let da = contract_density(|i, j| p_alpha[i][j], &phi, &grad_phi);
+        let db = contract_density(|i, j| p_beta[i][j], &phi, &grad_phi);
+
+        if da.rho + db.rho < 1e-12 {
+            continue;
         }
 
-        let rho = vec![dp.rho_a, dp.rho_b];
+        let rho = vec![da.rho, db.rho];
         let sigma = vec![
-            dp.grad_a[0]*dp.grad_a[0] + dp.grad_a[1]*dp.grad_a[1] + dp.grad_a[2]*dp.grad_a[2],
-            dp.grad_a[0]*dp.grad_b[0] + dp.grad_a[1]*dp.grad_b[1] + dp.grad_a[2]*dp.grad_b[2],
-            dp.grad_b[0]*dp.grad_b[0] + dp.grad_b[1]*dp.grad_b[1] + dp.grad_b[2]*dp.grad_b[2],
+            da.grad[0]*da.grad[0] + da.grad[1]*da.grad[1] + da.grad[2]*da.grad[2],
+            da.grad[0]*db.grad[0] + da.grad[1]*db.grad[1] + da.grad[2]*db.grad[2],
+            db.grad[0]*db.grad[0] + db.grad[1]*db.grad[1] + db.grad[2]*db.grad[2],
         ];
 
+        // τ^σ = ½ Σ_i |∇ψ_i^σ|² (libxc convention)
         let tau_a = if is_meta {
-            tau_at_point(shells, shell_centers, coeff_a.unwrap(), n_occ_a.unwrap(), r)
+            0.5 * tau_at_point(shells, shell_centers, coeff_a.unwrap(), n_occ_a.unwrap(), r)
         } else { 0.0 };
         let tau_b = if is_meta {
-            tau_at_point(shells, shell_centers, coeff_b.unwrap(), n_occ_b.unwrap(), r)
+            0.5 * tau_at_point(shells, shell_centers, coeff_b.unwrap(), n_occ_b.unwrap(), r)
         } else { 0.0 };
 
         let tau = vec![tau_a, tau_b];
 
-        let (eps_x, vrx, vsx, vtx_x) = fx.eval_all_spin(&rho, &sigma, &tau);
-        let (eps_c, vrc, vsc, vtx_c) = fc.eval_all_spin(&rho, &sigma, &tau);
-
-        let v_ra = (vrx[0] + vrc[0]) * (1.0 - hf_frac);
-        let v_rb = (vrx[1] + vrc[1]) * (1.0 - hf_frac);
-
-        let v_saa = (vsx[0] + vsc[0]) * (1.0 - hf_frac);
-        let v_sab = (vsx[1] + vsc[1]) * (1.0 - hf_frac);
-        let v_sbb = (vsx[2] + vsc[2]) * (1.0 - hf_frac);
-
-        let v_tau_a = (vtx_x[0] + vtx_c[0]) * (1.0 - hf_frac);
-        let v_tau_b = (vtx_x[1] + vtx_c[1]) * (1.0 - hf_frac);
+        let mut eps = 0.0;
+        let (mut v_ra, mut v_rb) = (0.0, 0.0);
+        let (mut v_saa, mut v_sab, mut v_sbb) = (0.0, 0.0, 0.0);
+        let (mut v_tau_a, mut v_tau_b) = (0.0, 0.0);
+
+        for f in &funcs {
+            let (e, vr, vs, vt) = f.eval_all_spin(&rho, &sigma, &tau);
+            eps += e[0] * dft_scale;
+            v_ra += vr[0] * dft_scale;
+            v_rb += vr[1] * dft_scale;
+            v_saa += vs[0] * dft_scale;
+            v_sab += vs[1] * dft_scale;
+            v_sbb += vs[2] * dft_scale;
+            v_tau_a += vt[0] * dft_scale;
+            v_tau_b += vt[1] * dft_scale;
+        }
 
-        exc += weight *
-            (rho[0] + rho[1]) *
-            (eps_x[0] + eps_c[0]) * (1.0 - hf_frac);
+        exc += weight * (rho[0] + rho[1]) * eps;
 
         int_rho_vxc += weight * (
             rho[0] * v_ra + rho[1] * v_rb
@@ -299,19 +392,24 @@ pub fn build_vxc_udft(
 
         for mu in 0..nao {
             for nu in 0..nao {
-                let mut val_a =
-                    v_ra * phi[mu] * phi[nu]
-                    + 2.0 * v_saa *
-                        dp.grad_a.iter().zip(&grad_phi[mu]).map(|(a,b)| a*b).sum::<f64>()
-                    + v_tau_a *
-                        grad_phi[mu].iter().zip(&grad_phi[nu]).map(|(a,b)| a*b).sum::<f64>();
-
-                let mut val_b =
-                    v_rb * phi[mu] * phi[nu]
-                    + 2.0 * v_sbb *
-                        dp.grad_b.iter().zip(&grad_phi[mu]).map(|(a,b)| a*b).sum::<f64>()
-                    + v_tau_b *
-                        grad_phi[mu].iter().zip(&grad_phi[nu]).map(|(a,b)| a*b).sum::<f64>();
+                let pp = phi[mu] * phi[nu];
+                let dpp: [f64; 3] = std::array::from_fn(|k| {
+                    grad_phi[mu][k] * phi[nu] + phi[mu] * grad_phi[nu][k]
+                });
+                let tt: f64 = (0..3).map(|k| grad_phi[mu][k] * grad_phi[nu][k]).sum();
+
+                // ∂f/∂∇ρ^α = 2 v_σαα ∇ρ^α + v_σαβ ∇ρ^β (y simétrico para β)
+                let val_a = v_ra * pp
+                    + (0..3)
+                        .map(|k| (2.0 * v_saa * da.grad[k] + v_sab * db.grad[k]) * dpp[k])
+                        .sum::<f64>()
+                    + 0.5 * v_tau_a * tt;
+
+                let val_b = v_rb * pp
+                    + (0..3)
+                        .map(|k| (2.0 * v_sbb * db.grad[k] + v_sab * da.grad[k]) * dpp[k])
+                        .sum::<f64>()
+                    + 0.5 * v_tau_b * tt;
 
                 vxa[mu][nu] += weight * val_a;
                 vxb[mu][nu] += weight * val_b;
@@ -328,3 +426,233 @@ pub fn build_vxc_udft(
         },
     )
 }
+
+//
+// ======================================================================
+// GKS (noncollinear) Vxc
+// ======================================================================
+//
+
+/// Spin blocks of the noncollinear Vxc (AO)
+pub struct NoncollinearVxc {
+    pub aa: Vec<Vec<f64>>,
+    pub bb: Vec<Vec<f64>>,
+    /// V^{αβ} = Re + i Im;  V^{βα} = (V^{αβ})†
+    pub ab_re: Vec<Vec<f64>>,
+    pub ab_im: Vec<Vec<f64>>,
+}
+
+/// Noncollinear Vxc from the spin blocks of a GHF/GKS density
+///
+/// Local spin frame at every point:
+///   n = ρ_αα + ρ_ββ,  m = (2 Re ρ_αβ, −2 Im ρ_αβ, ρ_αα − ρ_ββ)
+///   ρ± = ½ (n ± |m|),  ∇ρ± = ½ (∇n ± m̂·∇m)
+/// The collinear spin-polarized functional of (ρ+, ρ−) gives
+///   V = ½ (v+ + v−) 1 + ½ (v+ − v−) m̂·σ
+/// (projected-gradient GGA; ∇m̂ is neglected). Meta-GGAs are not
+/// supported here.
+pub fn build_vxc_noncollinear(
+    shells: &[Shell],
+    p_aa: &Vec<Vec<f64>>,
+    p_bb: &Vec<Vec<f64>>,
+    p_ab_re: &Vec<Vec<f64>>,
+    p_ab_im: &Vec<Vec<f64>>,
+    atoms: &[Atom],
+    method: XcMethod,
+) -> (NoncollinearVxc, DftEnergy) {
+
+    let nao = p_aa.len();
+    let mut v = NoncollinearVxc {
+        aa: vec![vec![0.0; nao]; nao],
+        bb: vec![vec![0.0; nao]; nao],
+        ab_re: vec![vec![0.0; nao]; nao],
+        ab_im: vec![vec![0.0; nao]; nao],
+    };
+
+    let gga = match &method {
+        XcMethod::LDA => false,
+        XcMethod::Hybrid { base, .. } if matches!(**base, XcMethod::LDA) => false,
+        XcMethod::MetaGGA => panic!("noncollinear meta-GGA is not implemented"),
+        XcMethod::Hybrid { base, .. } if matches!(**base, XcMethod::MetaGGA) => {
+            panic!("noncollinear meta-GGA is not implemented")
+        }
+        _ => true,
+    };
+
+    let (funcs, _, dft_scale) = xc_functionals(method, true);
+
+    let grid = DftGrid::new(atoms, 30, 14);
+
+    let mut exc = 0.0;
+    let mut int_rho_vxc = 0.0;
+
+    let dot = |a: &[f64; 3], b: &[f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+
+    for GridPoint { r, weight, .. } in grid.points {
+
+        let (phi, grad_phi) = basis_at_point(shells, r);
+
+        // ρ_στ(r) and ∇ρ_στ(r) for aa, bb, Re ab, Im ab
+        let blocks = [p_aa, p_bb, p_ab_re, p_ab_im]
+            .map(|p| contract_density(|i, j| p[i][j], &phi, &grad_phi));
+        let val = blocks.each_ref().map(|d| d.rho);
+        let grad = blocks.each_ref().map(|d| d.grad);
+
+        let n = val[0] + val[1];
+        if n < 1e-12 {
+            continue;
+        }
+
+        // m and its local direction
+        let m = [2.0 * val[2], -2.0 * val[3], val[0] - val[1]];
+        let grad_m = [
+            grad[2].map(|g| 2.0 * g),
+            grad[3].map(|g| -2.0 * g),
+            [grad[0][0] - grad[1][0], grad[0][1] - grad[1][1], grad[0][2] - grad[1][2]],
+        ];
+        let m_norm = dot(&m, &m).sqrt();
+        let m_hat = if m_norm > 1e-14 {
+            m.map(|x| x / m_norm)
+        } else {
+            [0.0, 0.0, 1.0]
+        };
+
+        let grad_n = [
+            grad[0][0] + grad[1][0],
+            grad[0][1] + grad[1][1],
+            grad[0][2] + grad[1][2],
+        ];
+        let grad_mpar: [f64; 3] = std::array::from_fn(|k| {
+            (0..3).map(|c| m_hat[c] * grad_m[c][k]).sum()
+        });
+
+        let rho = [0.5 * (n + m_norm), (0.5 * (n - m_norm)).max(0.0)];
+        let grad_p: [f64; 3] = std::array::from_fn(|k| 0.5 * (grad_n[k] + grad_mpar[k]));
+        let grad_q: [f64; 3] = std::array::from_fn(|k| 0.5 * (grad_n[k] - grad_mpar[k]));
+        let sigma = [dot(&grad_p, &grad_p), dot(&grad_p, &grad_q), dot(&grad_q, &grad_q)];
+
+        let mut eps = 0.0;
+        let mut vr = [0.0; 2];
+        let mut vs = [0.0; 3];
+
+        for f in &funcs {
+            let (e, r_, s_) = f.eval_polarized_point(rho, sigma, gga);
+            eps += e * dft_scale;
+            for k in 0..2 { vr[k] += r_[k] * dft_scale; }
+            for k in 0..3 { vs[k] += s_[k] * dft_scale; }
+        }
+
+        exc += weight * (rho[0] + rho[1]) * eps;
+        int_rho_vxc += weight * (rho[0] * vr[0] + rho[1] * vr[1]);
+
+        // Local-frame potential → spin blocks
+        let v_n = 0.5 * (vr[0] + vr[1]);
+        let v_m = 0.5 * (vr[0] - vr[1]);
+
+        let g_p: [f64; 3] = std::array::from_fn(|k| 2.0 * vs[0] * grad_p[k] + vs[1] * grad_q[k]);
+        let g_q: [f64; 3] = std::array::from_fn(|k| 2.0 * vs[2] * grad_q[k] + vs[1] * grad_p[k]);
+        let g_n: [f64; 3] = std::array::from_fn(|k| 0.5 * (g_p[k] + g_q[k]));
+        let g_m: [f64; 3] = std::array::from_fn(|k| 0.5 * (g_p[k] - g_q[k]));
+
+        for mu in 0..nao {
+            for nu in 0..nao {
+                let pp = phi[mu] * phi[nu];
+                let dpp = [
+                    grad_phi[mu][0] * phi[nu] + phi[mu] * grad_phi[nu][0],
+                    grad_phi[mu][1] * phi[nu] + phi[mu] * grad_phi[nu][1],
+                    grad_phi[mu][2] * phi[nu] + phi[mu] * grad_phi[nu][2],
+                ];
+
+                let scalar = v_n * pp + dot(&g_n, &dpp);
+                let magnetic = v_m * pp + dot(&g_m, &dpp);
+
+                v.aa[mu][nu] += weight * (scalar + m_hat[2] * magnetic);
+                v.bb[mu][nu] += weight * (scalar - m_hat[2] * magnetic);
+                v.ab_re[mu][nu] += weight * m_hat[0] * magnetic;
+                v.ab_im[mu][nu] -= weight * m_hat[1] * magnetic;
+            }
+        }
+    }
+
+    (
+        v,
+        DftEnergy {
+            exc,
+            int_rho_vxc,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::h2;
+
+    /// c cᵀ
+    fn outer(c: &[f64]) -> Vec<Vec<f64>> {
+        c.iter().map(|x| c.iter().map(|y| x * y).collect()).collect()
+    }
+
+    fn combine(a: &[Vec<f64>], b: &[Vec<f64>], sa: f64, sb: f64) -> Vec<Vec<f64>> {
+        a.iter()
+            .zip(b)
+            .map(|(ra, rb)| ra.iter().zip(rb).map(|(x, y)| sa * x + sb * y).collect())
+            .collect()
+    }
+
+    fn max_diff(a: &[Vec<f64>], b: &[Vec<f64>]) -> f64 {
+        a.iter()
+            .flatten()
+            .zip(b.iter().flatten())
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0, f64::max)
+    }
+
+    fn spin_densities() -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        (outer(&[0.4, 0.3, 0.2, 0.1]), outer(&[0.1, 0.25, 0.3, 0.2]))
+    }
+
+    #[test]
+    fn collinear_density_reproduces_udft() {
+        let (molecule, shells, centers) = h2();
+        let (pa, pb) = spin_densities();
+        let zero = vec![vec![0.0; 4]; 4];
+
+        let (vxa, vxb, collinear) = build_vxc_udft(
+            &shells, &centers, &pa, &pb, None, None, None, None, &molecule.atoms, XcMethod::LDA,
+        );
+        let (v, noncollinear) =
+            build_vxc_noncollinear(&shells, &pa, &pb, &zero, &zero, &molecule.atoms, XcMethod::LDA);
+
+        assert!((noncollinear.exc - collinear.exc).abs() < 1e-12);
+        assert!(max_diff(&v.aa, &vxa) < 1e-12);
+        assert!(max_diff(&v.bb, &vxb) < 1e-12);
+        assert!(max_diff(&v.ab_re, &zero) < 1e-12);
+    }
+
+    #[test]
+    fn rotating_the_magnetization_leaves_exc_unchanged() {
+        let (molecule, shells, _) = h2();
+        let (pa, pb) = spin_densities();
+        let zero = vec![vec![0.0; 4]; 4];
+        let half_n = combine(&pa, &pb, 0.5, 0.5);
+        let half_m = combine(&pa, &pb, 0.5, -0.5);
+        let minus_half_m = combine(&pa, &pb, -0.5, 0.5);
+
+        type Block = Vec<Vec<f64>>;
+        let exc = |aa: &Block, bb: &Block, re: &Block, im: &Block| {
+            let (_, energy) =
+                build_vxc_noncollinear(&shells, aa, bb, re, im, &molecule.atoms, XcMethod::LDA);
+            energy.exc
+        };
+
+        let along_z = exc(&pa, &pb, &zero, &zero);
+        // m along x: Re ρ_αβ = ½ (ρ_α − ρ_β);  along y: Im ρ_αβ = −½ (ρ_α − ρ_β)
+        let along_x = exc(&half_n, &half_n, &half_m, &zero);
+        let along_y = exc(&half_n, &half_n, &zero, &minus_half_m);
+
+        assert!(along_z < 0.0);
+        assert!((along_x - along_z).abs() < 1e-12);
+        assert!((along_y - along_z).abs() < 1e-12);
+    }
+}
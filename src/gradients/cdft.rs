@@ -0,0 +1,120 @@
+//! Constraint contribution to CDFT nuclear gradients
+//!
+//! At convergence of both loops the gradient of L[P, V] is the usual
+//! one, evaluated with the constrained Fock matrices (so the Pulay term
+//! −Σ W S^x already carries V_c W_c), plus
+//!
+//!   Σ_c V_c Σ_σ s_c^σ Σ_μν P^σ_μν ∂(W_c)_μν/∂R
+//!
+//! ∂W_c/∂R includes basis-function, grid and partition motion and is
+//! taken by central differences of the weight matrices.
+
+use crate::basis::shell::Shell;
+use crate::system::atom::Atom;
+use crate::scf::cdft::{constraint_weights, CdftOptions};
+use crate::scf::scf_cycle::ScfResult;
+use crate::scf::utils::{build_overlap_matrix, vec2d_ref_to_dmatrix};
+
+/// Nuclear displacement of the central differences (bohr)
+const DISPLACEMENT: f64 = 1e-4;
+
+/// Constraint term to add to the total gradient of a CDFT `ScfResult`
+pub fn cdft_gradient(
+    shells: &[Shell],
+    atoms: &[Atom],
+    result: &ScfResult,
+    options: &CdftOptions,
+) -> Vec<[f64; 3]> {
+
+    let solution = result
+        .cdft
+        .as_ref()
+        .expect("cdft_gradient requires a CDFT result");
+
+    let (p_a, p_b) = result.spin_densities();
+    let p_a = vec2d_ref_to_dmatrix(&p_a);
+    let p_b = vec2d_ref_to_dmatrix(&p_b);
+
+    // Σ_c V_c s_c^σ aplicado a P^σ
+    let lagrangian = |shells: &[Shell], atoms: &[Atom]| {
+        let overlap = build_overlap_matrix(shells);
+        let weights = constraint_weights(
+            shells,
+            atoms,
+            &overlap,
+            options,
+            &solution.promolecule,
+        );
+
+        options
+            .constraints
+            .iter()
+            .zip(&weights)
+            .zip(&solution.multipliers)
+            .map(|((c, w), v)| {
+                let (s_a, s_b) = c.kind.spin_signs();
+                v * (s_a * p_a.dot(w) + s_b * p_b.dot(w))
+            })
+            .sum::<f64>()
+    };
+
+    let mut grad = vec![[0.0; 3]; atoms.len()];
+
+    for (a, (atom, g)) in atoms.iter().zip(grad.iter_mut()).enumerate() {
+        for (k, g_k) in g.iter_mut().enumerate() {
+            let mut value = [0.0; 2];
+
+            for (side, sign) in [1.0, -1.0].into_iter().enumerate() {
+                let mut moved_atoms = atoms.to_vec();
+                moved_atoms[a].position[k] += sign * DISPLACEMENT;
+
+                let moved_shells: Vec<Shell> = shells
+                    .iter()
+                    .map(|shell| {
+                        let mut shell = shell.clone();
+                        if shell.center == atom.position {
+                            shell.center[k] += sign * DISPLACEMENT;
+                        }
+                        shell
+                    })
+                    .collect();
+
+                value[side] = lagrangian(&moved_shells, &moved_atoms);
+            }
+
+            *g_k = (value[0] - value[1]) / (2.0 * DISPLACEMENT);
+        }
+    }
+
+    grad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::cdft::Constraint;
+    use crate::scf::fixtures::{h2, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, ScfOptions};
+
+    #[test]
+    fn constraint_gradient_is_translationally_invariant() {
+        let (molecule, shells, centers) = h2();
+        let cdft = CdftOptions {
+            constraints: vec![Constraint::charge(vec![0], 0.2)],
+            ..CdftOptions::default()
+        };
+        let result = run_scf(
+            &molecule,
+            &shells,
+            &centers,
+            &ScfOptions { cdft: Some(cdft.clone()), ..quiet_options() },
+        );
+
+        let grad = cdft_gradient(&shells, &molecule.atoms, &result, &cdft);
+
+        assert!(grad[0][2].abs() > 1e-4);
+        for k in 0..3 {
+            assert!((grad[0][k] + grad[1][k]).abs() < 1e-6, "{:?}", grad);
+        }
+    }
+}
@@ -1,90 +1,193 @@
 //! DFT exchange-correlation gradients (explicit part)
 //!
-//! Provides nuclear gradients of XC energy:
+//! Provides nuclear gradients of the XC energy at fixed density:
 //!   ∂E_xc / ∂R_A
 //!
-//! Covers:
-//! - LDA
-//! - GGA
+//! Covers LDA, GGA and meta-GGA (τ), spin-restricted (P) or
+//! spin-polarized (P^α, P^β), with the DFT weight of hybrids and
+//! range-separated hybrids (`vxc::xc_functionals`).
+//!
+//! E_xc = Σ_g w_g f(ρ, σ, τ)(r_g) on the grid of `dft::grid`, whose
+//! points move rigidly with their atom G. For every density ingredient
+//! q at a point:
+//!   dq/dR_A = q_A − δ_AG Σ_B q_B
+//! with q_A the part from the AOs on A (∂φ_μ/∂A = −∇φ_μ); the second
+//! term is the point motion, since Σ_B q_B = −∇q. This is the exact
+//! derivative of the grid energy.
 //!
 //! Orbital-response terms are handled via CPHF and must NOT be here.
 
-use nalgebra::DMatrix;
-
 use crate::basis::shell::Shell;
+use crate::dft::density::{basis_at_point, basis_hessians_at_point, contract_density};
+use crate::dft::grid::{DftGrid, GridPoint};
+use crate::dft::libxc::Family;
+use crate::dft::tau::dtau_dra;
+use crate::dft::vxc::{xc_functionals, XcMethod};
+use crate::integrals::deriv::shell_atom_indices;
 use crate::system::atom::Atom;
-use crate::dft::grid::DftGrid;
-use crate::dft::density::density_at_point;
-use crate::dft::libxc::LibXC;
 
-/// Compute explicit XC gradient for LDA / GGA
+/// ∂E_xc/∂R_A at fixed density
 ///
-/// Returns AO matrix contribution to ∂F/∂R_Ai
-pub fn grad_xc_lda_gga(
+/// `densities` holds the total P (restricted) or (P^α, P^β).
+pub fn grad_xc(
     shells: &[Shell],
     shell_centers: &[[f64; 3]],
-    density: &DMatrix<f64>,
     atoms: &[Atom],
-    atom: usize,
-    axis: usize,
-    is_gga: bool,
-) -> DMatrix<f64> {
+    densities: &[&Vec<Vec<f64>>],
+    method: &XcMethod,
+) -> Vec<[f64; 3]> {
+
+    assert!(
+        matches!(densities.len(), 1 | 2),
+        "grad_xc takes P or (P^α, P^β)"
+    );
+    let spin = densities.len() == 2;
+
+    let (funcs, _, dft_scale) = xc_functionals(method.clone(), spin);
+    let is_gga = funcs.iter().any(|f| f.family() != Family::Lda);
+    let is_meta = funcs.iter().any(|f| f.family() == Family::MetaGga);
 
-    let nao = density.nrows();
-    let mut grad = DMatrix::zeros(nao, nao);
+    let natoms = atoms.len();
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+    let ao_atom: Vec<usize> = shells
+        .iter()
+        .zip(shell_atoms.iter())
+        .flat_map(|(s, &a)| std::iter::repeat_n(a, s.n_orbitals()))
+        .collect();
 
-    // Select libxc functionals (example: LDA_X + LDA_C_PZ)
-    let fx = LibXC::new(if is_gga { 101 } else { 1 }, false);
-    let fc = LibXC::new(if is_gga { 130 } else { 7 }, false);
+    let grid = DftGrid::new(atoms, 30, 14);
+    let mut grad = vec![[0.0; 3]; natoms];
 
-    let grid = DftGrid::new(atoms, 30, 86);
+    for GridPoint { r, weight, atom } in grid.points {
+        // Hessians of the AOs only enter through ∇ρ and τ
+        let (phi, grad_phi, hess_phi) = if is_gga {
+            basis_hessians_at_point(shells, r)
+        } else {
+            let (phi, grad_phi) = basis_at_point(shells, r);
+            (phi, grad_phi, Vec::new())
+        };
 
-    for pt in &grid.points {
-        let rho_pt =
-            density_at_point(shells, shell_centers, density, pt.r);
+        let points: Vec<_> = densities
+            .iter()
+            .map(|p| contract_density(|i, j| p[i][j], &phi, &grad_phi))
+            .collect();
 
-        if rho_pt.rho < 1e-12 {
+        if points.iter().map(|d| d.rho).sum::<f64>() < 1e-12 {
             continue;
         }
 
-        let rho = vec![rho_pt.rho];
-        let sigma = vec![
-            rho_pt.grad[0]*rho_pt.grad[0]
-          + rho_pt.grad[1]*rho_pt.grad[1]
-          + rho_pt.grad[2]*rho_pt.grad[2]
-        ];
-
-        let (_, vrho, vsigma) =
-            fx.eval_gga(&rho, &sigma);
-
-        let (_, vcrho, vcsigma) =
-            fc.eval_gga(&rho, &sigma);
-
-        let vr = vrho[0] + vcrho[0];
-        let vs = if is_gga { vsigma[0] + vcsigma[0] } else { 0.0 };
-
-        // AO loop
-        for (si, ci) in shells.iter().zip(shell_centers.iter()) {
-            for (sj, cj) in shells.iter().zip(shell_centers.iter()) {
-                let off_i = si.offset;
-                let off_j = sj.offset;
-
-                for mu in 0..si.orbitals.len() {
-                    let phi_mu = si.orbitals[mu].value(*ci, pt.r);
-                    let dphi_mu = si.orbitals[mu].gradient(*ci, pt.r)[axis];
-
-                    for nu in 0..sj.orbitals.len() {
-                        let phi_nu = sj.orbitals[nu].value(*cj, pt.r);
-                        let dphi_nu = sj.orbitals[nu].gradient(*cj, pt.r)[axis];
-
-                        grad[(off_i+mu, off_j+nu)] +=
-                            pt.weight * (
-                                vr * (dphi_mu * phi_nu + phi_mu * dphi_nu)
-                              + 2.0 * vs * rho_pt.grad[axis]
-                                * (phi_mu * phi_nu)
-                            );
+        // τ = ½ Σ P_μν ∇φ_μ·∇φ_ν per channel
+        let tau: Vec<f64> = densities
+            .iter()
+            .map(|p| {
+                if !is_meta {
+                    return 0.0;
+                }
+                let mut t = 0.0;
+                for (mu, row) in p.iter().enumerate() {
+                    for (nu, p_mn) in row.iter().enumerate() {
+                        t += p_mn * (0..3).map(|k| grad_phi[mu][k] * grad_phi[nu][k]).sum::<f64>();
                     }
                 }
+                0.5 * t
+            })
+            .collect();
+
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+
+        // v_ρ, ∂f/∂∇ρ and v_τ per channel
+        let mut v_rho = vec![0.0; densities.len()];
+        let mut v_grad = vec![[0.0; 3]; densities.len()];
+        let mut v_tau = vec![0.0; densities.len()];
+
+        if spin {
+            let (ga, gb) = (points[0].grad, points[1].grad);
+            let rho = [points[0].rho, points[1].rho];
+            let sigma = [dot(ga, ga), dot(ga, gb), dot(gb, gb)];
+
+            let mut vs = [0.0; 3];
+            for f in &funcs {
+                let (_, vr, vsig, vt) = f.eval_all_spin(&rho, &sigma, &tau);
+                for s in 0..2 {
+                    v_rho[s] += vr[s] * dft_scale;
+                    v_tau[s] += vt[s] * dft_scale;
+                }
+                for (v, x) in vs.iter_mut().zip(&vsig) {
+                    *v += x * dft_scale;
+                }
+            }
+
+            // ∂f/∂∇ρ^α = 2 v_σαα ∇ρ^α + v_σαβ ∇ρ^β (likewise for β)
+            for k in 0..3 {
+                v_grad[0][k] = 2.0 * vs[0] * ga[k] + vs[1] * gb[k];
+                v_grad[1][k] = 2.0 * vs[2] * gb[k] + vs[1] * ga[k];
+            }
+        } else {
+            let g = points[0].grad;
+            let rho = [points[0].rho];
+            let sigma = [dot(g, g)];
+
+            let mut vs = 0.0;
+            for f in &funcs {
+                let (_, vr, vsig, vt) = f.eval_all(&rho, &sigma, tau[0]);
+                v_rho[0] += vr[0] * dft_scale;
+                vs += vsig[0] * dft_scale;
+                v_tau[0] += vt[0] * dft_scale;
+            }
+
+            for k in 0..3 {
+                v_grad[0][k] = 2.0 * vs * g[k];
+            }
+        }
+
+        // Basis-function part, per atom
+        let mut basis = vec![[0.0; 3]; natoms];
+
+        for (s, p) in densities.iter().enumerate() {
+            for (mu, row) in p.iter().enumerate() {
+                // Σ_ν P_μν φ_ν and Σ_ν P_μν ∇φ_ν
+                let mut x = 0.0;
+                let mut g = [0.0; 3];
+                for (nu, p_mn) in row.iter().enumerate() {
+                    x += p_mn * phi[nu];
+                    if is_gga {
+                        for j in 0..3 {
+                            g[j] += p_mn * grad_phi[nu][j];
+                        }
+                    }
+                }
+
+                let b = &mut basis[ao_atom[mu]];
+                for k in 0..3 {
+                    // ∂ρ/∂A_k = −2 Σ ∂_kφ_μ P_μν φ_ν
+                    let mut val = v_rho[s] * grad_phi[mu][k] * x;
+
+                    // ∂(∂_jρ)/∂A_k = −2 Σ (∂_k∂_jφ_μ P_μν φ_ν + ∂_kφ_μ P_μν ∂_jφ_ν)
+                    if is_gga {
+                        for j in 0..3 {
+                            val += v_grad[s][j] * (hess_phi[mu][k][j] * x + grad_phi[mu][k] * g[j]);
+                        }
+                    }
+
+                    b[k] -= 2.0 * val;
+                }
+            }
+
+            if is_meta {
+                let dtau = dtau_dra(p, &grad_phi, &hess_phi, &ao_atom, natoms);
+                for (b, dt) in basis.iter_mut().zip(&dtau) {
+                    for k in 0..3 {
+                        b[k] += v_tau[s] * dt[k];
+                    }
+                }
+            }
+        }
+
+        // The point moves with its atom: −Σ_B q_B
+        for a in 0..natoms {
+            for k in 0..3 {
+                grad[a][k] += weight * basis[a][k];
+                grad[atom][k] -= weight * basis[a][k];
             }
         }
     }
@@ -92,3 +195,109 @@ pub fn grad_xc_lda_gga(
     grad
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::dft::vxc::{build_vxc, build_vxc_udft};
+
+    fn geometry(positions: [[f64; 3]; 2]) -> (Vec<Atom>, Vec<Shell>, Vec<[f64; 3]>) {
+        let atoms: Vec<Atom> =
+            positions.iter().map(|&r| Atom::new("H".to_string(), 1, r)).collect();
+
+        let mut shells = Vec::new();
+        let mut offset = 0;
+        for (atom, ang, alpha) in [(0, [0, 0, 0], 0.8), (0, [0, 1, 0], 0.7), (1, [0, 0, 0], 1.0)] {
+            let center = positions[atom];
+            let primitive = Primitive::new(alpha, 1.0, center, ang);
+            let shell = Shell::new(vec![primitive], ang, center, offset);
+            offset += shell.n_orbitals();
+            shells.push(shell);
+        }
+
+        let centers = shells.iter().map(|s| s.center).collect();
+        (atoms, shells, centers)
+    }
+
+    /// C_μi of `n` test orbitals
+    fn orbitals(nao: usize, n: usize) -> Vec<Vec<f64>> {
+        (0..nao)
+            .map(|mu| (0..n).map(|i| 0.4 + 0.3 * ((mu + 2 * i) as f64).cos()).collect())
+            .collect()
+    }
+
+    /// f Σ_i C_μi C_νi
+    fn density(c: &[Vec<f64>], f: f64) -> Vec<Vec<f64>> {
+        c.iter()
+            .map(|ci| {
+                c.iter().map(|cj| f * ci.iter().zip(cj).map(|(a, b)| a * b).sum::<f64>()).collect()
+            })
+            .collect()
+    }
+
+    fn check(
+        base: [[f64; 3]; 2],
+        grad: &[[f64; 3]],
+        energy: impl Fn([[f64; 3]; 2]) -> f64,
+        label: &str,
+    ) {
+        let h = 1e-4;
+        for a in 0..2 {
+            for k in 0..3 {
+                let (mut plus, mut minus) = (base, base);
+                plus[a][k] += h;
+                minus[a][k] -= h;
+                let fd = (energy(plus) - energy(minus)) / (2.0 * h);
+                assert!(
+                    (grad[a][k] - fd).abs() < 1e-6,
+                    "{} {} {}: {} vs {}", label, a, k, grad[a][k], fd
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn restricted_xc_gradient_matches_finite_differences() {
+        let base = [[0.0, 0.0, 0.0], [0.3, 0.5, 1.4]];
+
+        for (label, method) in
+            [("LDA", XcMethod::LDA), ("GGA", XcMethod::GGA), ("meta-GGA", XcMethod::MetaGGA)]
+        {
+            let (atoms, shells, centers) = geometry(base);
+            let c = orbitals(5, 1);
+            let p = density(&c, 2.0);
+
+            let grad = grad_xc(&shells, &centers, &atoms, &[&p], &method);
+
+            check(base, &grad, |positions| {
+                let (atoms, shells, centers) = geometry(positions);
+                build_vxc(&shells, &centers, &p, Some(&c), Some(1), &atoms, method.clone()).1.exc
+            }, label);
+        }
+    }
+
+    #[test]
+    fn spin_polarized_xc_gradient_matches_finite_differences() {
+        let base = [[0.0, 0.0, 0.0], [0.3, 0.5, 1.4]];
+
+        for (label, method) in
+            [("LDA", XcMethod::LDA), ("GGA", XcMethod::GGA), ("meta-GGA", XcMethod::MetaGGA)]
+        {
+            let (atoms, shells, centers) = geometry(base);
+            let (ca, cb) = (orbitals(5, 2), orbitals(5, 1));
+            let (pa, pb) = (density(&ca, 1.0), density(&cb, 1.0));
+
+            let grad = grad_xc(&shells, &centers, &atoms, &[&pa, &pb], &method);
+
+            check(base, &grad, |positions| {
+                let (atoms, shells, centers) = geometry(positions);
+                build_vxc_udft(
+                    &shells, &centers, &pa, &pb, Some(&ca), Some(&cb), Some(2), Some(1),
+                    &atoms, method.clone(),
+                )
+                .2
+                .exc
+            }, label);
+        }
+    }
+}
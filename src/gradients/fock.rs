@@ -1,154 +1,129 @@
 //! First nuclear derivatives of the Fock matrix
 //!
-//! Computes the *explicit* derivative:
+//! Computes the *explicit* derivative (density held fixed):
 //!   ∂F = ∂H_core + ∂J − ∂K + ∂V_xc
+//! as a central difference of the AO Fock matrix with atom A and its
+//! shells displaced, like the other finite-difference derivatives of
+//! the code (relativistic, CDFT).
 //!
 //! Orbital-response terms are NOT included here.
 
 use nalgebra::DMatrix;
 
 use crate::basis::shell::Shell;
-use crate::system::atom::Atom;
-use crate::gradients::overlap::overlap_derivative;
 use crate::dft::vxc::build_vxc;
+use crate::integrals::deriv::shell_atom_indices;
+use crate::scf::jk::build_jk_set;
+use crate::scf::scf_cycle::{core_hamiltonian, ScfOptions, ScfResult};
+use crate::scf::utils::vec2d_ref_to_dmatrix;
+use crate::system::atom::Atom;
+
+/// Displacement (bohr) of the central differences
+const FOCK_STEP: f64 = 1e-4;
 
 /// Compute explicit AO Fock derivative ∂F/∂R_Ai
+///
+/// `result` must come from a spin-restricted SCF (RHF / RKS) run with
+/// `options`; its density (and, for meta-GGA, its orbitals) is kept
+/// fixed.
 pub fn fock_derivative(
     shells: &[Shell],
-    shell_centers: &[[f64;3]],
-    density: &DMatrix<f64>,
-    fock: &DMatrix<f64>,
+    shell_centers: &[[f64; 3]],
     atoms: &[Atom],
+    result: &ScfResult,
+    options: &ScfOptions,
     atom: usize,
     axis: usize,
-    is_dft: bool,
 ) -> DMatrix<f64> {
 
-    let nao = fock.nrows();
-    let mut dF = DMatrix::zeros(nao, nao);
-
-    // ==================================================
-    // 1. One-electron part: T + V_nuc
-    // ==================================================
-    for (si, ci) in shells.iter().zip(shell_centers.iter()) {
-        let off_i = si.offset;
-        let ni = si.orbitals.len();
-
-        for (sj, cj) in shells.iter().zip(shell_centers.iter()) {
-            let off_j = sj.offset;
-            let nj = sj.orbitals.len();
-
-            let dT =
-                si.second_deriv_kinetic(sj, *ci, *cj, atoms);
-
-            let dV =
-                si.second_deriv_nuclear_attraction(sj, *ci, *cj, atoms);
-
-            for mu in 0..ni {
-                for nu in 0..nj {
-                    dF[(off_i+mu, off_j+nu)] +=
-                        dT[atom][atom][axis][axis][mu][nu]
-                      + dV[atom][atom][axis][axis][mu][nu];
-                }
+    assert!(
+        result.beta.is_none(),
+        "fock_derivative requires a spin-restricted result"
+    );
+
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+    let density = &result.density;
+    let mixing = options.exchange_mixing();
+    let omega = mixing.has_long_range().then_some(mixing.omega);
+
+    let fock = |step: f64| -> DMatrix<f64> {
+        let mut sh = shells.to_vec();
+        for (s, &owner) in sh.iter_mut().zip(shell_atoms.iter()) {
+            if owner == atom {
+                s.center[axis] += step;
             }
         }
-    }
+        let centers: Vec<[f64; 3]> = sh.iter().map(|s| s.center).collect();
 
-    // ==================================================
-    // 2. Coulomb (J) term
-    // ==================================================
-    for si in shells {
-        for sj in shells {
-            for sk in shells {
-                for sl in shells {
-
-                    let dERI =
-                        si.first_deriv_eri(sj, sk, sl, atoms.len());
-
-                    for mu in 0..si.orbitals.len() {
-                        for nu in 0..sj.orbitals.len() {
-                            let i = si.offset + mu;
-                            let j = sj.offset + nu;
-
-                            let mut val = 0.0;
-                            for la in 0..sk.orbitals.len() {
-                                for si2 in 0..sl.orbitals.len() {
-                                    let k = sk.offset + la;
-                                    let l = sl.offset + si2;
-
-                                    val += density[(k,l)]
-                                        * dERI[atom][mu][nu][la][si2][axis];
-                                }
-                            }
-                            dF[(i,j)] += 2.0 * val;
-                        }
-                    }
-                }
-            }
-        }
-    }
+        let mut at = atoms.to_vec();
+        at[atom].position[axis] += step;
 
-    // ==================================================
-    // 3. Exchange (K) term
-    // ==================================================
-    for si in shells {
-        for sj in shells {
-            for sk in shells {
-                for sl in shells {
-
-                    let dERI =
-                        si.first_deriv_eri(sj, sk, sl, atoms.len());
-
-                    for mu in 0..si.orbitals.len() {
-                        for nu in 0..sj.orbitals.len() {
-                            let i = si.offset + mu;
-                            let j = sj.offset + nu;
-
-                            let mut val = 0.0;
-                            for la in 0..sk.orbitals.len() {
-                                for si2 in 0..sl.orbitals.len() {
-                                    let k = sk.offset + la;
-                                    let l = sl.offset + si2;
-
-                                    val += density[(k,l)]
-                                        * dERI[atom][mu][si2][la][nu][axis];
-                                }
-                            }
-                            dF[(i,j)] -= val;
-                        }
-                    }
-                }
-            }
+        // F = H_core + J − ½ K_eff (K[P^σ] = ½ K[P])
+        let jk = build_jk_set(&sh, Some(density), &[density], omega);
+        let mut f = core_hamiltonian(&sh, &at, options) + vec2d_ref_to_dmatrix(&jk.j)
+            - vec2d_ref_to_dmatrix(&jk.k[0]) * (0.5 * mixing.full);
+
+        if let Some(k_lr) = jk.k_lr.first() {
+            f -= vec2d_ref_to_dmatrix(k_lr) * (0.5 * mixing.long_range);
         }
-    }
 
-    // ==================================================
-    // 4. XC contribution (DFT only)
-    // ==================================================
-    if is_dft {
-        let dvxc =
-            build_vxc_derivative(
-                shells,
-                shell_centers,
+        if let Some(xc) = &options.xc_method {
+            let (vxc, _) = build_vxc(
+                &sh,
+                &centers,
                 density,
-                atoms,
-                atom,
-                axis,
+                Some(&result.alpha.coefficients),
+                Some(result.alpha.n_occupied()),
+                &at,
+                xc.clone(),
             );
-        dF += dvxc;
-    }
-
-    // ==================================================
-    // 5. Symmetrize
-    // ==================================================
-    for i in 0..nao {
-        for j in 0..i {
-            let avg = 0.5 * (dF[(i,j)] + dF[(j,i)]);
-            dF[(i,j)] = avg;
-            dF[(j,i)] = avg;
+            f += vec2d_ref_to_dmatrix(&vxc);
         }
-    }
 
-    dF
+        f
+    };
+
+    (fock(FOCK_STEP) - fock(-FOCK_STEP)) / (2.0 * FOCK_STEP)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dft::vxc::ExchangeMixing;
+    use crate::gradients::one_electron::grad_one_electron;
+    use crate::gradients::two_electron::grad_two_electron;
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+    use crate::scf::scf_cycle::run_scf;
+
+    /// Σ P ∂F = Σ P ∂H_core + Σ P ∂G(P): the one-electron gradient plus
+    /// twice the two-electron one (E_2 = ½ Σ P G(P))
+    #[test]
+    fn density_contraction_matches_the_energy_gradients() {
+        let (molecule, shells, centers) =
+            hydrogens(&[[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]], 1, 1);
+        let options = quiet_options();
+        let result = run_scf(&molecule, &shells, &centers, &options);
+
+        let p = &result.density;
+        let half: Vec<Vec<f64>> =
+            p.iter().map(|row| row.iter().map(|x| 0.5 * x).collect()).collect();
+        let g1 = grad_one_electron(&shells, &centers, p, &molecule.atoms);
+        let g2 = grad_two_electron(
+            &shells, &centers, &molecule.atoms, &half, &half, &ExchangeMixing::hartree_fock(),
+        );
+
+        let nao = p.len();
+        for a in 0..3 {
+            for k in 0..3 {
+                let df =
+                    fock_derivative(&shells, &centers, &molecule.atoms, &result, &options, a, k);
+                let contraction: f64 = (0..nao)
+                    .flat_map(|i| (0..nao).map(move |j| (i, j)))
+                    .map(|(i, j)| p[i][j] * df[(i, j)])
+                    .sum();
+                let expected = g1[a][k] + 2.0 * g2[a][k];
+                assert!((contraction - expected).abs() < 1e-6, "{} vs {}", contraction, expected);
+            }
+        }
+    }
+}
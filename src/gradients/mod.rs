@@ -7,6 +7,6 @@ pub mod total;
 pub mod overlap;
 pub mod fock;
 pub mod dft_xc;
-pub mod dft_xc_meta;
-pub mod dft_xc_spin;
 
+pub mod rohf;
+pub mod cdft;
@@ -1,6 +1,9 @@
 use crate::system::atom::Atom;
+use crate::system::nuclear_model::nuclear_pair_function;
 
 /// ∂E_nn / ∂R_A
+///
+/// E_nn = Σ_{A<B} Z_A Z_B f(R_AB), f = 1/R (point) or erf(μR)/R (Gaussian)
 pub fn grad_nuclear_repulsion(
     atoms: &[Atom],
 ) -> Vec<[f64; 3]> {
@@ -11,23 +14,21 @@ pub fn grad_nuclear_repulsion(
         for b in 0..n {
             if a == b { continue; }
 
-            let za = atoms[a].atomic_number as f64;
-            let zb = atoms[b].atomic_number as f64;
+            let za = atoms[a].nuclear_charge();
+            let zb = atoms[b].nuclear_charge();
 
             let ra = atoms[a].position;
             let rb = atoms[b].position;
 
-            let dx = ra[0] - rb[0];
-            let dy = ra[1] - rb[1];
-            let dz = ra[2] - rb[2];
+            let d = [ra[0] - rb[0], ra[1] - rb[1], ra[2] - rb[2]];
+            let r = (d[0]*d[0] + d[1]*d[1] + d[2]*d[2]).sqrt();
 
-            let r2 = dx*dx + dy*dy + dz*dz;
-            let r = r2.sqrt();
-            let pref = za * zb / (r2 * r);
+            let [_, df, _] = nuclear_pair_function(&atoms[a], &atoms[b], r);
+            let pref = za * zb * df / r;
 
-            grad[a][0] += pref * dx;
-            grad[a][1] += pref * dy;
-            grad[a][2] += pref * dz;
+            for k in 0..3 {
+                grad[a][k] += pref * d[k];
+            }
         }
     }
     grad
@@ -1,7 +1,19 @@
+use std::collections::HashMap;
+
+use crate::basis::reader::Ecp;
 use crate::basis::shell::Shell;
+use crate::integrals::deriv::{
+    center_derivative, kinetic_first_deriv, nuclear_attraction_first_deriv,
+    scatter_center_gradients, shell_atom_indices,
+};
+use crate::integrals::ecp::{ao_functions, ecp_center_block, GaussianSum};
 use crate::system::atom::Atom;
 
-/// ⟨μ|∂(T+V)|ν⟩ contraction
+/// Σ_{μν} P_{μν} ∂⟨μ|T + V_nuc|ν⟩/∂R_A
+///
+/// Kinetic and nuclear-attraction derivatives from the Hermite kernels
+/// of `integrals::deriv`, contracted shell pair by shell pair; the
+/// operator center of V_nuc follows from translational invariance.
 pub fn grad_one_electron(
     shells: &[Shell],
     shell_centers: &[[f64; 3]],
@@ -11,29 +23,224 @@ pub fn grad_one_electron(
 
     let mut grad = vec![[0.0; 3]; atoms.len()];
 
-    for (a, atom) in atoms.iter().enumerate() {
-        let center = atom.position;
-
-        for (i, shell_i) in shells.iter().enumerate() {
-            for (j, shell_j) in shells.iter().enumerate() {
-
-                let d_h = shell_i.grad_kinetic_nuclear(
-                    &shell_j,
-                    shell_centers[i],
-                    shell_centers[j],
-                    center,
-                );
-
-                for mu in 0..shell_i.orbitals.len() {
-                    for nu in 0..shell_j.orbitals.len() {
-                        let p = density[shell_i.offset + mu][shell_j.offset + nu];
-                        for k in 0..3 {
-                            grad[a][k] += p * d_h[k];
-                        }
-                    }
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+
+    for (i, si) in shells.iter().enumerate() {
+        let off_i = si.offset;
+        let ni = si.n_orbitals();
+
+        for (j, sj) in shells.iter().enumerate() {
+            let off_j = sj.offset;
+            let nj = sj.n_orbitals();
+
+            let mut p = vec![0.0; ni * nj];
+            for mu in 0..ni {
+                for nu in 0..nj {
+                    p[mu * nj + nu] = density[off_i + mu][off_j + nu];
                 }
             }
+
+            if p.iter().all(|x| x.abs() < 1e-14) {
+                continue;
+            }
+
+            let dt = kinetic_first_deriv(si, sj, &p);
+            scatter_center_gradients(&mut grad, &dt, &[shell_atoms[i], shell_atoms[j]]);
+
+            for (c, atom) in atoms.iter().enumerate() {
+                let dv = nuclear_attraction_first_deriv(si, sj, &p, atom);
+                scatter_center_gradients(&mut grad, &dv, &[shell_atoms[i], shell_atoms[j], c]);
+            }
         }
     }
+
     grad
 }
+
+/// ECP gradient Σ_{μν} P_{μν} ∂⟨μ|U|ν⟩/∂R_A
+///
+/// Basis-function centers are differentiated analytically
+/// (∂G/∂A_k = 2α G₊ − l_k G₋); the ECP center follows from
+/// translational invariance.
+pub fn grad_ecp(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+    ecps: &HashMap<String, Ecp>,
+) -> Vec<[f64; 3]> {
+
+    let mut grad = vec![[0.0; 3]; atoms.len()];
+
+    let shell_centers: Vec<[f64; 3]> = shells.iter().map(|s| s.center).collect();
+    let shell_atoms = shell_atom_indices(&shell_centers, atoms);
+
+    // AO → atom
+    let ao_atom: Vec<usize> = shells
+        .iter()
+        .zip(shell_atoms.iter())
+        .flat_map(|(s, &a)| std::iter::repeat_n(a, s.n_orbitals()))
+        .collect();
+
+    let aos = ao_functions(shells);
+    let nao = aos.len();
+
+    // ∂χ_μ/∂A_k, stored at k * nao + μ
+    let mut d_aos: Vec<GaussianSum> = Vec::with_capacity(3 * nao);
+    for k in 0..3 {
+        for f in &aos {
+            d_aos.push(
+                f.iter()
+                    .flat_map(|(c, g)| {
+                        center_derivative(g, k)
+                            .into_iter()
+                            .map(move |(cd, gd)| (c * cd, gd))
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    for (c, atom) in atoms.iter().enumerate() {
+        let Some(ecp) = ecps.get(&atom.symbol) else {
+            continue;
+        };
+
+        let block = ecp_center_block(&d_aos, &aos, atom.position, ecp);
+
+        for k in 0..3 {
+            for mu in 0..nao {
+                let mut g = 0.0;
+                for nu in 0..nao {
+                    g += density[mu][nu] * block[k * nao + mu][nu];
+                }
+
+                // bra and ket derivatives are equal for symmetric P
+                grad[ao_atom[mu]][k] += 2.0 * g;
+                grad[c][k] -= 2.0 * g;
+            }
+        }
+    }
+
+    grad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::basis::reader::EcpTerm;
+    use crate::integrals::ecp::ecp_matrix;
+    use crate::scf::utils::build_one_electron_matrix;
+
+    fn geometry(positions: [[f64; 3]; 2]) -> (Vec<Atom>, Vec<Shell>) {
+        let atoms = vec![
+            Atom::new("Na".to_string(), 11, positions[0]),
+            Atom::new("H".to_string(), 1, positions[1]),
+        ];
+
+        let mut shells = Vec::new();
+        let mut offset = 0;
+        for (center, ang, alpha) in [
+            (positions[0], [0, 0, 0], 0.5),
+            (positions[0], [1, 0, 0], 0.4),
+            (positions[1], [0, 0, 0], 0.8),
+        ] {
+            let primitive = Primitive::new(alpha, 1.0, center, ang);
+            let shell = Shell::new(vec![primitive], ang, center, offset);
+            offset += shell.n_orbitals();
+            shells.push(shell);
+        }
+
+        (atoms, shells)
+    }
+
+    fn ecps() -> HashMap<String, Ecp> {
+        let term = |power, exponent, coefficient| EcpTerm { power, exponent, coefficient };
+        let ecp = Ecp {
+            n_core: 10,
+            local: vec![term(2, 1.1, -0.8)],
+            semilocal: vec![vec![term(2, 0.9, 3.0)], vec![term(2, 0.7, -1.5)]],
+        };
+        HashMap::from([("Na".to_string(), ecp)])
+    }
+
+    #[test]
+    fn one_electron_gradient_matches_finite_differences() {
+        let base = [[0.0, 0.0, 0.0], [0.4, -0.3, 2.6]];
+
+        for gaussian_nuclei in [false, true] {
+            let with_model = |positions| {
+                let (mut atoms, shells) = geometry(positions);
+                if gaussian_nuclei {
+                    atoms[0].nuclear_exponent = Some(0.9);
+                    atoms[1].nuclear_exponent = Some(1.7);
+                }
+                (atoms, shells)
+            };
+
+            let (atoms, shells) = with_model(base);
+            let centers: Vec<[f64; 3]> = shells.iter().map(|s| s.center).collect();
+            let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
+            let p: Vec<Vec<f64>> = (0..nao)
+                .map(|i| (0..nao).map(|j| 0.3 / (1.0 + (i + j) as f64)).collect())
+                .collect();
+
+            let grad = grad_one_electron(&shells, &centers, &p, &atoms);
+
+            let energy = |positions| {
+                let (atoms, shells) = with_model(positions);
+                let h = build_one_electron_matrix(&shells, &atoms);
+                (0..nao)
+                    .flat_map(|i| (0..nao).map(move |j| (i, j)))
+                    .map(|(i, j)| p[i][j] * h[(i, j)])
+                    .sum::<f64>()
+            };
+
+            let h = 1e-4;
+            for a in 0..2 {
+                for k in 0..3 {
+                    let (mut plus, mut minus) = (base, base);
+                    plus[a][k] += h;
+                    minus[a][k] -= h;
+                    let fd = (energy(plus) - energy(minus)) / (2.0 * h);
+                    let g = grad[a][k];
+                    assert!((g - fd).abs() < 1e-6, "{} {}: {} vs {}", a, k, g, fd);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ecp_gradient_matches_finite_differences() {
+        let base = [[0.0, 0.0, 0.0], [0.4, -0.3, 2.6]];
+        let ecps = ecps();
+
+        let (atoms, shells) = geometry(base);
+        let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
+        let p: Vec<Vec<f64>> = (0..nao)
+            .map(|i| (0..nao).map(|j| 0.3 / (1.0 + (i + j) as f64)).collect())
+            .collect();
+
+        let grad = grad_ecp(&shells, &p, &atoms, &ecps);
+
+        let energy = |positions| {
+            let (atoms, shells) = geometry(positions);
+            let u = ecp_matrix(&shells, &atoms, &ecps);
+            (0..nao)
+                .flat_map(|i| (0..nao).map(move |j| (i, j)))
+                .map(|(i, j)| p[i][j] * u[i][j])
+                .sum::<f64>()
+        };
+
+        let h = 1e-4;
+        for a in 0..2 {
+            for k in 0..3 {
+                let (mut plus, mut minus) = (base, base);
+                plus[a][k] += h;
+                minus[a][k] -= h;
+                let fd = (energy(plus) - energy(minus)) / (2.0 * h);
+                assert!((grad[a][k] - fd).abs() < 1e-6, "{} {}: {} vs {}", a, k, grad[a][k], fd);
+            }
+        }
+    }
+}
@@ -4,53 +4,101 @@
 //!   (∂S_{μν} / ∂R_Ai)
 
 use nalgebra::DMatrix;
+
 use crate::basis::shell::Shell;
+use crate::integrals::deriv::{center_derivative, shell_atom_indices};
+use crate::integrals::hermite::{expand_shell, overlap};
+use crate::system::atom::Atom;
 
 /// Compute AO overlap derivative with respect to atom A and Cartesian axis
 ///
 /// Returns AO matrix dS/dR_Ai
 pub fn overlap_derivative(
     shells: &[Shell],
-    shell_centers: &[[f64;3]],
-    natoms: usize,
+    shell_centers: &[[f64; 3]],
+    atoms: &[Atom],
     atom: usize,
     axis: usize,
 ) -> DMatrix<f64> {
 
-    let nao = shells.last().unwrap().offset
-            + shells.last().unwrap().orbitals.len();
-
+    let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
     let mut ds = DMatrix::zeros(nao, nao);
 
-    for (si, ci) in shells.iter().zip(shell_centers.iter()) {
-        let off_i = si.offset;
-        let ni = si.orbitals.len();
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+    let expanded: Vec<_> = shells.iter().map(expand_shell).collect();
 
-        for (sj, cj) in shells.iter().zip(shell_centers.iter()) {
-            let off_j = sj.offset;
-            let nj = sj.orbitals.len();
+    // ⟨∂μ/∂A_i|ν⟩ for μ on A; the ket derivative is its transpose
+    for (si, sh_i) in shells.iter().enumerate() {
+        if shell_atoms[si] != atom {
+            continue;
+        }
 
-            let dS =
-                si.first_deriv_overlap(sj, *ci, *cj, natoms);
+        for (sj, sh_j) in shells.iter().enumerate() {
+            for (mu, prims_mu) in expanded[si].iter().enumerate() {
+                for (nu, prims_nu) in expanded[sj].iter().enumerate() {
+                    let mut val = 0.0;
+                    for (c_mu, g_mu) in prims_mu {
+                        for (c_nu, g_nu) in prims_nu {
+                            for (c_d, g_d) in center_derivative(g_mu, axis) {
+                                val += c_mu * c_nu * c_d * overlap(&g_d, g_nu);
+                            }
+                        }
+                    }
 
-            for mu in 0..ni {
-                for nu in 0..nj {
-                    ds[(off_i+mu, off_j+nu)] =
-                        dS[atom][mu][nu][axis];
+                    let (i, j) = (sh_i.offset + mu, sh_j.offset + nu);
+                    ds[(i, j)] += val;
+                    ds[(j, i)] += val;
                 }
             }
         }
     }
 
-    // Ensure symmetry
-    for i in 0..nao {
-        for j in 0..i {
-            let avg = 0.5 * (ds[(i,j)] + ds[(j,i)]);
-            ds[(i,j)] = avg;
-            ds[(j,i)] = avg;
+    ds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::scf::utils::build_overlap_matrix;
+
+    fn system(r: [[f64; 3]; 2]) -> (Vec<Shell>, Vec<[f64; 3]>, Vec<Atom>) {
+        let mut shells = Vec::new();
+        let mut offset = 0;
+        for (center, ang, alpha) in [
+            (r[0], [0, 0, 0], 1.3),
+            (r[0], [0, 1, 0], 0.6),
+            (r[1], [0, 0, 0], 0.8),
+        ] {
+            let primitive = Primitive::new(alpha, 1.0, center, ang);
+            let shell = Shell::new(vec![primitive], ang, center, offset);
+            offset += shell.n_orbitals();
+            shells.push(shell);
         }
+        let centers = shells.iter().map(|s| s.center).collect();
+        let atoms = vec![Atom::new("O".into(), 8, r[0]), Atom::new("H".into(), 1, r[1])];
+        (shells, centers, atoms)
     }
 
-    ds
-}
+    #[test]
+    fn overlap_derivative_matches_finite_differences() {
+        let base = [[0.1, 0.0, -0.2], [0.4, 1.2, 0.9]];
+        let (shells, centers, atoms) = system(base);
+
+        let h = 1e-5;
+        for a in 0..2 {
+            for k in 0..3 {
+                let ds = overlap_derivative(&shells, &centers, &atoms, a, k);
 
+                let (mut plus, mut minus) = (base, base);
+                plus[a][k] += h;
+                minus[a][k] -= h;
+                let fd = (build_overlap_matrix(&system(plus).0)
+                    - build_overlap_matrix(&system(minus).0))
+                    / (2.0 * h);
+
+                assert!((ds - fd).amax() < 1e-8);
+            }
+        }
+    }
+}
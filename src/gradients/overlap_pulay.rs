@@ -1,29 +1,50 @@
+//! Pulay (overlap) term of the nuclear gradient
+//!
+//!   −Σ_{μν} W_{μν} ∂S_{μν}/∂R_A
+//!
+//! with the energy-weighted density W (Σ_i n_i ε_i C_i C_iᵀ, or
+//! Σ_σ P^σ F^σ P^σ in general).
+
 use crate::basis::shell::Shell;
+use crate::integrals::deriv::{overlap_first_deriv, scatter_center_gradients, shell_atom_indices};
+use crate::system::atom::Atom;
 
-/// −Σ F_μν ∂S_μν/∂R_A
+/// −Σ W_μν ∂S_μν/∂R_A
 pub fn grad_overlap_pulay(
     shells: &[Shell],
-    fock: &Vec<Vec<f64>>,
-    natoms: usize,
-) -> Vec<[f64;3]> {
-
-    let mut grad = vec![[0.0;3]; natoms];
-
-    for shell_i in shells {
-        for shell_j in shells {
-            let dS = shell_i.grad_overlap(&shell_j);
-
-            for mu in 0..shell_i.orbitals.len() {
-                for nu in 0..shell_j.orbitals.len() {
-                    let f = fock[shell_i.offset + mu][shell_j.offset + nu];
-                    for a in 0..natoms {
-                        for k in 0..3 {
-                            grad[a][k] -= f * dS[a][k];
-                        }
-                    }
+    shell_centers: &[[f64; 3]],
+    energy_weighted: &[Vec<f64>],
+    atoms: &[Atom],
+) -> Vec<[f64; 3]> {
+
+    let mut grad = vec![[0.0; 3]; atoms.len()];
+
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+
+    for (i, si) in shells.iter().enumerate() {
+        let off_i = si.offset;
+        let ni = si.n_orbitals();
+
+        for (j, sj) in shells.iter().enumerate() {
+            // Both shells on one atom: ∂S/∂A + ∂S/∂B = 0
+            if shell_atoms[i] == shell_atoms[j] {
+                continue;
+            }
+
+            let off_j = sj.offset;
+            let nj = sj.n_orbitals();
+
+            let mut w = vec![0.0; ni * nj];
+            for mu in 0..ni {
+                for nu in 0..nj {
+                    w[mu * nj + nu] = -energy_weighted[off_i + mu][off_j + nu];
                 }
             }
+
+            let ds = overlap_first_deriv(si, sj, &w);
+            scatter_center_gradients(&mut grad, &ds, &[shell_atoms[i], shell_atoms[j]]);
         }
     }
+
     grad
 }
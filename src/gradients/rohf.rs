@@ -0,0 +1,119 @@
+//! ROHF / ROKS nuclear gradients
+//!
+//! The ROHF energy is the unrestricted functional of (P^α, P^β) built
+//! from one set of spatial orbitals:
+//!
+//!   dE/dR = E_nn^x + Σ P h^x + ½ Σ P_μν P_λσ (μν|λσ)^x
+//!         − ½ α Σ_σ Σ P^σ_μλ P^σ_νσ (μν|λσ)^x − Σ W S^x + E_xc^x
+//!
+//! with W = Σ_σ P^σ F^σ P^σ. At convergence the off-diagonal blocks of
+//! the effective Fock vanish and W equals the symmetric ROHF Lagrangian
+//! C ε Cᵀ, so no orbital response is needed.
+
+use crate::basis::shell::Shell;
+use crate::gradients::total::total_gradient;
+use crate::scf::scf_cycle::{ScfMethod, ScfOptions, ScfResult};
+use crate::system::atom::Atom;
+
+/// Total ROHF / ROKS gradient from a converged `ScfResult`
+///
+/// The ROHF energy is the unrestricted functional, so this is
+/// `total_gradient` on the spin densities and spin Fock matrices of
+/// `result` (two-electron term from the screened quartet loop of
+/// `gradients::two_electron`, range-separated exchange included).
+pub fn rohf_gradient(
+    shells: &[Shell],
+    shell_centers: &[[f64; 3]],
+    atoms: &[Atom],
+    result: &ScfResult,
+    options: &ScfOptions,
+) -> Vec<[f64; 3]> {
+
+    assert!(
+        matches!(result.method, ScfMethod::ROHF | ScfMethod::ROKS),
+        "rohf_gradient requires an ROHF / ROKS result"
+    );
+
+    total_gradient(shells, shell_centers, atoms, result, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradients::total::energy_weighted_density;
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+    use crate::scf::rohf::RohfCoupling;
+    use crate::scf::scf_cycle::{run_scf, Reference, ScfOptions};
+
+    const H3: [[f64; 3]; 3] = [[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]];
+
+    fn lagrangian(result: &ScfResult) -> Vec<Vec<f64>> {
+        let beta = result.beta.as_ref().unwrap();
+        energy_weighted_density(
+            &[&result.alpha.density, &beta.density],
+            &[&result.alpha.fock, &beta.fock],
+        )
+    }
+
+    #[test]
+    fn pulay_weights_do_not_depend_on_the_coupling() {
+        let (molecule, shells, centers) = hydrogens(&H3, 0, 2);
+        let run = |rohf_coupling| {
+            let options = ScfOptions {
+                reference: Reference::RestrictedOpenShell,
+                rohf_coupling,
+                ..quiet_options()
+            };
+            lagrangian(&run_scf(&molecule, &shells, &centers, &options))
+        };
+
+        let w_gs = run(RohfCoupling::GuestSaunders);
+        let w_r = run(RohfCoupling::Roothaan);
+
+        for (row_gs, row_r) in w_gs.iter().zip(&w_r) {
+            for (a, b) in row_gs.iter().zip(row_r) {
+                assert!((a - b).abs() < 1e-5, "{} vs {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn rohf_gradient_matches_finite_differences() {
+        let options = ScfOptions { reference: Reference::RestrictedOpenShell, ..quiet_options() };
+        let direction = [[0.3, -0.5, 0.2], [-0.1, 0.4, 0.6], [0.5, 0.2, -0.4]];
+
+        let (molecule, shells, centers) = hydrogens(&H3, 0, 2);
+        let result = run_scf(&molecule, &shells, &centers, &options);
+        let grad = rohf_gradient(&shells, &centers, &molecule.atoms, &result, &options);
+
+        let energy = |t: f64| {
+            let mut positions = H3;
+            for (r, d) in positions.iter_mut().zip(&direction) {
+                for k in 0..3 {
+                    r[k] += t * d[k];
+                }
+            }
+            let (molecule, shells, centers) = hydrogens(&positions, 0, 2);
+            run_scf(&molecule, &shells, &centers, &options).energy
+        };
+
+        let h = 1e-4;
+        let fd = (energy(h) - energy(-h)) / (2.0 * h);
+        let analytic: f64 = grad
+            .iter()
+            .zip(&direction)
+            .map(|(g, d)| (0..3).map(|k| g[k] * d[k]).sum::<f64>())
+            .sum();
+        assert!((analytic - fd).abs() < 1e-6, "{} vs {}", analytic, fd);
+    }
+
+    #[test]
+    #[should_panic(expected = "rohf_gradient requires an ROHF / ROKS result")]
+    fn unrestricted_results_are_rejected() {
+        let (molecule, shells, centers) = hydrogens(&H3, 0, 2);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+        let uhf = run_scf(&molecule, &shells, &centers, &options);
+
+        rohf_gradient(&shells, &centers, &molecule.atoms, &uhf, &options);
+    }
+}
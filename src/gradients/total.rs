@@ -2,140 +2,233 @@
 //!
 //! Includes:
 //! - Nuclear repulsion
-//! - One-electron gradients (T + V)
+//! - One-electron gradients (T + V, or X2C / DKH2)
 //! - Two-electron ERI gradients
 //! - Pulay overlap term
 //! - XC gradients:
 //!     * LDA / GGA
 //!     * meta-GGA (τ)
 //!     * Spin-polarized (UDFT)
+//! - ECP gradients
+//! - CDFT constraint term
+//! - Empirical dispersion (−D of ωB97X-D)
+//! - External potentials (fields, point charges, ...)
 
 use crate::basis::shell::Shell;
 use crate::system::atom::Atom;
 
 // HF components
 use crate::gradients::nuclear_repulsion::grad_nuclear_repulsion;
-use crate::gradients::one_electron::grad_one_electron;
+use crate::gradients::one_electron::{grad_ecp, grad_one_electron};
 use crate::gradients::two_electron::grad_two_electron;
 use crate::gradients::overlap_pulay::grad_overlap_pulay;
+use crate::gradients::cdft::cdft_gradient;
+use crate::integrals::relativistic::{relativistic_gradient, RelativisticHamiltonian};
 
-// DFT XC gradients
-use crate::gradients::dft_xc::grad_xc_lda_gga;
-use crate::gradients::dft_xc_spin::grad_xc_udft;
-use crate::gradients::dft_xc_meta::grad_xc_meta;
+// DFT XC gradients (LDA / GGA / meta-GGA, restricted or spin)
+use crate::gradients::dft_xc::grad_xc;
 
-// XC selector
-use crate::dft::vxc::XcMethod;
+// External potentials
+use crate::scf::external::external_gradient;
+use crate::scf::scf_cycle::{ScfOptions, ScfResult};
+use crate::scf::utils::vec2d_ref_to_dmatrix;
 
-/// Compute total nuclear gradient
+/// Compute total nuclear gradient of a converged SCF
 ///
-/// Usage:
-/// - RHF / DFT: provide `density`
-/// - UDFT: provide `density_alpha` and `density_beta`
-/// - meta-GGA: provide `coeff` and `n_occ`
+/// `options` must be the ones of the SCF run: they select the XC
+/// functional, the exact-exchange mixing, the one-electron Hamiltonian,
+/// the ECPs, the external potentials and the CDFT constraints.
+///
+/// The energy is a functional of (P^α, P^β) (P/2 each when
+/// restricted) and the Pulay term uses W = Σ_σ P^σ F^σ P^σ, which
+/// equals Σ_i n_i ε_i C_i C_iᵀ at convergence for RHF / UHF and the
+/// symmetric Lagrangian for ROHF. The Fock matrices of `result` include
+/// external and constraint potentials, as their energies do.
 pub fn total_gradient(
     shells: &[Shell],
     shell_centers: &[[f64; 3]],
     atoms: &[Atom],
-
-    // Densities
-    density: Option<&Vec<Vec<f64>>>,              // RHF / DFT
-    density_alpha: Option<&Vec<Vec<f64>>>,        // UDFT
-    density_beta: Option<&Vec<Vec<f64>>>,
-
-    // Orbitals (needed for meta-GGA τ)
-    coeff: Option<&Vec<Vec<f64>>>,
-    n_occ: Option<usize>,
-
-    // Common
-    fock: &Vec<Vec<f64>>,
-    eri_grad: &dyn Fn(usize, usize, usize, usize, usize) -> [f64; 3],
-    xc: Option<XcMethod>,
+    result: &ScfResult,
+    options: &ScfOptions,
 ) -> Vec<[f64; 3]> {
 
-    let natoms = atoms.len();
     let mut grad = grad_nuclear_repulsion(atoms);
 
+    let p = &result.density;
+    let (p_a, p_b) = result.spin_densities();
+    let f_b = result.beta.as_ref().map_or(&result.alpha.fock, |beta| &beta.fock);
+
     // ==================================================
-    // RHF / DFT / meta-GGA (spin-restricted)
+    // Empirical dispersion (geometry only)
     // ==================================================
-    if let Some(p) = density {
-        let g1 = grad_one_electron(shells, shell_centers, p, atoms);
-        let g2 = grad_two_electron(shells, p, eri_grad, natoms);
-        let gp = grad_overlap_pulay(shells, fock, natoms);
-
-        for a in 0..natoms {
-            for k in 0..3 {
-                grad[a][k] += g1[a][k] + g2[a][k] + gp[a][k];
-            }
+    if let Some(gdisp) = options.xc_method.as_ref().and_then(|m| m.dispersion_gradient(atoms)) {
+        add(&mut grad, &gdisp);
+    }
+
+    // ==================================================
+    // One-electron (T + V, or X2C / DKH2) and ECP
+    // ==================================================
+    let g1 = match options.relativistic {
+        RelativisticHamiltonian::NonRelativistic => {
+            grad_one_electron(shells, shell_centers, p, atoms)
         }
+        method => relativistic_gradient(shells, p, atoms, method),
+    };
+    add(&mut grad, &g1);
 
-        if let Some(method) = xc {
-            match method.as_ref() {
-                XcMethod::LDA | XcMethod::GGA
-                | XcMethod::Hybrid { base: XcMethod::LDA, .. }
-                | XcMethod::Hybrid { base: XcMethod::GGA, .. } => {
-
-                    let gxc = grad_xc_lda_gga(
-                        shells,
-                        shell_centers,
-                        p,
-                        atoms,
-                        method,
-                    );
-
-                    for a in 0..natoms {
-                        for k in 0..3 {
-                            grad[a][k] += gxc[a][k];
-                        }
-                    }
-                }
+    if !options.ecps.is_empty() {
+        add(&mut grad, &grad_ecp(shells, p, atoms, &options.ecps));
+    }
 
-                XcMethod::MetaGGA
-                | XcMethod::Hybrid { base: XcMethod::MetaGGA, .. } => {
-
-                    let gmeta = grad_xc_meta(
-                        shells,
-                        shell_centers,
-                        p,
-                        coeff.expect("meta-GGA requires coeff"),
-                        n_occ.expect("meta-GGA requires n_occ"),
-                        atoms,
-                        method,
-                    );
-
-                    for a in 0..natoms {
-                        for k in 0..3 {
-                            grad[a][k] += gmeta[a][k];
-                        }
-                    }
-                }
-            }
-        }
+    // ==================================================
+    // Coulomb and exact exchange
+    // ==================================================
+    let g2 = grad_two_electron(
+        shells,
+        shell_centers,
+        atoms,
+        &p_a,
+        &p_b,
+        &options.exchange_mixing(),
+    );
+    add(&mut grad, &g2);
+
+    // ==================================================
+    // Pulay: W = Σ_σ P^σ F^σ P^σ
+    // ==================================================
+    let w = energy_weighted_density(&[&p_a, &p_b], &[&result.alpha.fock, f_b]);
+    add(&mut grad, &grad_overlap_pulay(shells, shell_centers, &w, atoms));
+
+    // ==================================================
+    // XC: restricted on P, spin-polarized on (P^α, P^β)
+    // ==================================================
+    if let Some(method) = &options.xc_method {
+        let gxc = if result.beta.is_some() {
+            grad_xc(shells, shell_centers, atoms, &[&p_a, &p_b], method)
+        } else {
+            grad_xc(shells, shell_centers, atoms, &[p], method)
+        };
+        add(&mut grad, &gxc);
     }
 
     // ==================================================
-    // UDFT (spin-polarized)
+    // External potentials (on P^α + P^β)
+    // ==================================================
+    if !options.external.is_empty() {
+        let gext = external_gradient(&options.external, shells, atoms, &vec2d_ref_to_dmatrix(p));
+        add(&mut grad, &gext);
+    }
+
     // ==================================================
-    if let (Some(pa), Some(pb), Some(method)) =
-        (density_alpha, density_beta, xc)
-    {
-        let gxc_spin = grad_xc_udft(
-            shells,
-            shell_centers,
-            pa,
-            pb,
-            atoms,
-            method,
-        );
-
-        for a in 0..natoms {
-            for k in 0..3 {
-                grad[a][k] += gxc_spin[a][k];
+    // CDFT constraints (Σ_c V_c Σ_σ s_c^σ P^σ ∂W_c)
+    // ==================================================
+    if let Some(cdft) = &options.cdft {
+        add(&mut grad, &cdft_gradient(shells, atoms, result, cdft));
+    }
+
+    grad
+}
+
+/// W = Σ_σ P^σ F^σ P^σ
+pub(crate) fn energy_weighted_density(
+    densities: &[&Vec<Vec<f64>>],
+    focks: &[&Vec<Vec<f64>>],
+) -> Vec<Vec<f64>> {
+    let nao = densities[0].len();
+    let mut w = vec![vec![0.0; nao]; nao];
+
+    for (p, f) in densities.iter().zip(focks.iter()) {
+        // PF
+        let mut pf = vec![vec![0.0; nao]; nao];
+        for i in 0..nao {
+            for k in 0..nao {
+                if p[i][k] == 0.0 { continue; }
+                for j in 0..nao {
+                    pf[i][j] += p[i][k] * f[k][j];
+                }
+            }
+        }
+
+        // (PF) P
+        for i in 0..nao {
+            for k in 0..nao {
+                for j in 0..nao {
+                    w[i][j] += pf[i][k] * p[k][j];
+                }
             }
         }
     }
 
-    grad
+    w
 }
 
+fn add(grad: &mut [[f64; 3]], other: &[[f64; 3]]) {
+    for (g, o) in grad.iter_mut().zip(other.iter()) {
+        for k in 0..3 {
+            g[k] += o[k];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dft::vxc::{Hybrid, XcMethod};
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, Reference};
+
+    const H3: [[f64; 3]; 3] = [[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]];
+
+    /// Displacement direction mixing every coordinate
+    const DIRECTION: [[f64; 3]; 3] = [[0.3, -0.5, 0.2], [-0.1, 0.4, 0.6], [0.5, 0.2, -0.4]];
+
+    /// |g·d − central difference of the SCF energy along d|
+    fn directional_error(charge: i32, multiplicity: usize, options: &ScfOptions) -> f64 {
+        let (molecule, shells, centers) = hydrogens(&H3, charge, multiplicity);
+        let result = run_scf(&molecule, &shells, &centers, options);
+        let grad = total_gradient(&shells, &centers, &molecule.atoms, &result, options);
+
+        let energy = |t: f64| {
+            let mut positions = H3;
+            for (r, d) in positions.iter_mut().zip(&DIRECTION) {
+                for k in 0..3 {
+                    r[k] += t * d[k];
+                }
+            }
+            let (molecule, shells, centers) = hydrogens(&positions, charge, multiplicity);
+            run_scf(&molecule, &shells, &centers, options).energy
+        };
+
+        let h = 1e-4;
+        let fd = (energy(h) - energy(-h)) / (2.0 * h);
+        let analytic: f64 = grad
+            .iter()
+            .zip(&DIRECTION)
+            .map(|(g, d)| (0..3).map(|k| g[k] * d[k]).sum::<f64>())
+            .sum();
+        (analytic - fd).abs()
+    }
+
+    #[test]
+    fn hartree_fock_gradients_match_finite_differences() {
+        let rhf = quiet_options();
+        let uhf = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+
+        assert!(directional_error(1, 1, &rhf) < 1e-6);
+        assert!(directional_error(0, 2, &uhf) < 1e-6);
+    }
+
+    #[test]
+    fn dft_gradients_match_finite_differences() {
+        let dft = |xc_method, reference| ScfOptions {
+            xc_method: Some(xc_method),
+            reference,
+            ..quiet_options()
+        };
+        let pbe0 = XcMethod::Hybrid { base: Box::new(XcMethod::GGA), hyb: Hybrid::PBE0 };
+
+        assert!(directional_error(1, 1, &dft(XcMethod::GGA, Reference::Restricted)) < 1e-6);
+        assert!(directional_error(1, 1, &dft(pbe0, Reference::Restricted)) < 1e-6);
+        assert!(directional_error(0, 2, &dft(XcMethod::MetaGGA, Reference::Unrestricted)) < 1e-6);
+    }
+}
@@ -1,5 +1,11 @@
 use crate::system::atom::Atom;
+use crate::system::nuclear_model::nuclear_pair_function;
 
+/// ∂²E_nn / ∂R_A∂R_B  (3N × 3N)
+///
+/// For a pair with d = R_A − R_B, R = |d|:
+///   ∂²/∂A_i∂A_j = Z_A Z_B [ f'' d_i d_j / R² + f' (δ_ij / R − d_i d_j / R³) ]
+///   ∂²/∂A_i∂B_j = −∂²/∂A_i∂A_j
 pub fn hess_nuclear_repulsion(atoms: &[Atom]) -> Vec<Vec<f64>> {
     let n = atoms.len();
     let dim = 3*n;
@@ -9,36 +15,28 @@ pub fn hess_nuclear_repulsion(atoms: &[Atom]) -> Vec<Vec<f64>> {
         for b in 0..n {
             if a == b { continue; }
 
-            let za = atoms[a].atomic_number as f64;
-            let zb = atoms[b].atomic_number as f64;
+            let za = atoms[a].nuclear_charge();
+            let zb = atoms[b].nuclear_charge();
 
             let ra = atoms[a].position;
             let rb = atoms[b].position;
 
-            let dx = ra[0]-rb[0];
-            let dy = ra[1]-rb[1];
-            let dz = ra[2]-rb[2];
-
-            let r2 = dx*dx + dy*dy + dz*dz;
+            let d = [ra[0]-rb[0], ra[1]-rb[1], ra[2]-rb[2]];
+            let r2 = d[0]*d[0] + d[1]*d[1] + d[2]*d[2];
             let r = r2.sqrt();
-            let pref = za*zb/(r2*r);
+
+            let [_, df, d2f] = nuclear_pair_function(&atoms[a], &atoms[b], r);
 
             for i in 0..3 {
                 for j in 0..3 {
-                    let val = pref * match (i,j) {
-                        (0,0) => 3.0*dx*dx/r2 - 1.0,
-                        (1,1) => 3.0*dy*dy/r2 - 1.0,
-                        (2,2) => 3.0*dz*dz/r2 - 1.0,
-                        (0,1)|(1,0) => 3.0*dx*dy/r2,
-                        (0,2)|(2,0) => 3.0*dx*dz/r2,
-                        (1,2)|(2,1) => 3.0*dy*dz/r2,
-                        _ => 0.0,
-                    };
-
-                    let ia = 3*a+i;
-                    let ib = 3*b+j;
-
-                    h[ia][ib] += val;
+                    let delta = if i == j { 1.0 } else { 0.0 };
+                    let val = za * zb * (
+                        d2f * d[i] * d[j] / r2
+                      + df * (delta / r - d[i] * d[j] / (r2 * r))
+                    );
+
+                    h[3*a+i][3*a+j] += val;
+                    h[3*a+i][3*b+j] -= val;
                 }
             }
         }
@@ -46,4 +44,3 @@ pub fn hess_nuclear_repulsion(atoms: &[Atom]) -> Vec<Vec<f64>> {
 
     h
 }
-
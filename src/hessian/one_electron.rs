@@ -5,12 +5,19 @@
 //!
 //! Includes:
 //! - kinetic energy second derivatives
-//! - nuclear attraction second derivatives
+//! - nuclear attraction second derivatives, including the
+//!   derivative of the operator center
 //!
 //! Contracted with AO density matrix
 
 use crate::basis::shell::Shell;
 use crate::system::atom::Atom;
+use crate::integrals::deriv::{
+    kinetic_second_deriv,
+    nuclear_attraction_second_deriv,
+    scatter_center_blocks,
+    shell_atom_indices,
+};
 
 /// One-electron Hessian contribution
 ///
@@ -31,51 +38,50 @@ pub fn hess_one_electron(
     let dim = 3 * natoms;
     let mut hess = vec![vec![0.0; dim]; dim];
 
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+
     // Loop over shell pairs
-    for (si, ci) in shells.iter().zip(shell_centers.iter()) {
+    for (i, si) in shells.iter().enumerate() {
         let off_i = si.offset;
-        let ni = si.orbitals.len();
+        let ni = si.n_orbitals();
 
-        for (sj, cj) in shells.iter().zip(shell_centers.iter()) {
+        for (j, sj) in shells.iter().enumerate() {
             let off_j = sj.offset;
-            let nj = sj.orbitals.len();
+            let nj = sj.n_orbitals();
+
+            // Density block (contracted on the fly by the kernels)
+            let mut p = vec![0.0; ni * nj];
+            for mu in 0..ni {
+                for nu in 0..nj {
+                    p[mu * nj + nu] = density[off_i + mu][off_j + nu];
+                }
+            }
+
+            if p.iter().all(|x| x.abs() < 1e-14) {
+                continue;
+            }
 
             // --------------------------------------------------
-            // Second derivatives of kinetic integrals
-            // d²T/dR_A dR_B  -> [A][B][i][j][μ][ν]
+            // Kinetic: centers (A, B)
             // --------------------------------------------------
-            let d2t = si.second_deriv_kinetic(sj, *ci, *cj, atoms);
+            let d2t = kinetic_second_deriv(si, sj, &p);
+            scatter_center_blocks(
+                &mut hess,
+                &d2t,
+                &[shell_atoms[i], shell_atoms[j]],
+            );
 
             // --------------------------------------------------
-            // Second derivatives of nuclear attraction
-            // d²V/dR_A dR_B
+            // Nuclear attraction: centers (A, B, C)
+            // C carries the operator derivative
             // --------------------------------------------------
-            let d2v = si.second_deriv_nuclear_attraction(sj, *ci, *cj, atoms);
-
-            for mu in 0..ni {
-                for nu in 0..nj {
-                    let p = density[off_i + mu][off_j + nu];
-                    if p.abs() < 1e-14 {
-                        continue;
-                    }
-
-                    for a in 0..natoms {
-                        for b in 0..natoms {
-                            for iaxis in 0..3 {
-                                for jaxis in 0..3 {
-                                    let idx_a = 3 * a + iaxis;
-                                    let idx_b = 3 * b + jaxis;
-
-                                    hess[idx_a][idx_b] +=
-                                        p * (
-                                            d2t[a][b][iaxis][jaxis][mu][nu]
-                                          + d2v[a][b][iaxis][jaxis][mu][nu]
-                                        );
-                                }
-                            }
-                        }
-                    }
-                }
+            for (c, atom) in atoms.iter().enumerate() {
+                let d2v = nuclear_attraction_second_deriv(si, sj, &p, atom);
+                scatter_center_blocks(
+                    &mut hess,
+                    &d2v,
+                    &[shell_atoms[i], shell_atoms[j], c],
+                );
             }
         }
     }
@@ -91,4 +97,3 @@ pub fn hess_one_electron(
 
     hess
 }
-
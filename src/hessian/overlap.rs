@@ -1,21 +1,22 @@
 //! Second-order Pulay (overlap) contribution to the nuclear Hessian
 //!
-//! Implements the explicit overlap Hessian terms (no orbital response):
+//! Implements the explicit overlap Hessian term (no orbital response):
 //!
-//! H_AB^{ij} +=
-//!   - Σ_{μν} P_{μν} ∂²S_{μν}/∂R_Ai∂R_Bj * F_{νμ}
-//!   - 2 Σ_{μν} ∂S_{μν}/∂R_Ai * ∂F_{νμ}/∂R_Bj
+//! H_AB^{ij} += − Σ_{μν} W_{μν} ∂²S_{μν}/∂R_Ai∂R_Bj
 //!
-//! Notes:
-//! - The second term couples with Fock derivatives; this implementation
-//!   provides the explicit (non-response) part.
-//! - Orbital-response (Z-vector) corrections are handled in hessian/cphf.rs
+//! with the energy-weighted density W = ½ P F P (closed shell).
 //!
-//! Assumptions:
-//! - Shell provides first/second derivatives of overlap integrals
-//! - Shells know AO offsets
+//! Notes:
+//! - The first-derivative coupling −2 Σ ∂S/∂R_A · ∂F/∂R_B belongs to the
+//!   orbital response and is handled in hessian/cphf.rs
 
 use crate::basis::shell::Shell;
+use crate::system::atom::Atom;
+use crate::integrals::deriv::{
+    overlap_second_deriv,
+    scatter_center_blocks,
+    shell_atom_indices,
+};
 
 /// Pulay second-order Hessian contribution
 ///
@@ -23,7 +24,7 @@ use crate::basis::shell::Shell;
 /// shell_centers : coordinates of shell centers
 /// density       : AO density matrix
 /// fock          : AO Fock matrix
-/// natoms        : number of nuclei
+/// atoms         : molecular atoms
 ///
 /// Returns Hessian matrix (3N x 3N)
 pub fn hess_overlap_pulay(
@@ -31,80 +32,72 @@ pub fn hess_overlap_pulay(
     shell_centers: &[[f64; 3]],
     density: &Vec<Vec<f64>>,
     fock: &Vec<Vec<f64>>,
-    natoms: usize,
+    atoms: &[Atom],
 ) -> Vec<Vec<f64>> {
 
+    let natoms = atoms.len();
     let dim = 3 * natoms;
+    let nao = density.len();
     let mut hess = vec![vec![0.0; dim]; dim];
 
+    // --------------------------------------------------
+    // Energy-weighted density W = ½ P F P
+    // --------------------------------------------------
+    let mut pf = vec![vec![0.0; nao]; nao];
+    for i in 0..nao {
+        for k in 0..nao {
+            let p = density[i][k];
+            if p == 0.0 {
+                continue;
+            }
+            for j in 0..nao {
+                pf[i][j] += p * fock[k][j];
+            }
+        }
+    }
+
+    let mut w = vec![vec![0.0; nao]; nao];
+    for i in 0..nao {
+        for k in 0..nao {
+            let x = pf[i][k];
+            if x == 0.0 {
+                continue;
+            }
+            for j in 0..nao {
+                w[i][j] += 0.5 * x * density[k][j];
+            }
+        }
+    }
+
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+
     // Loop over shell pairs
-    for (si, ci) in shells.iter().zip(shell_centers.iter()) {
+    for (i, si) in shells.iter().enumerate() {
         let off_i = si.offset;
-        let ni = si.orbitals.len();
+        let ni = si.n_orbitals();
 
-        for (sj, cj) in shells.iter().zip(shell_centers.iter()) {
+        for (j, sj) in shells.iter().enumerate() {
             let off_j = sj.offset;
-            let nj = sj.orbitals.len();
-
-            // --------------------------------------------------
-            // First derivatives of overlap
-            // dS/dR_A  -> [A][i][μ][ν]
-            // --------------------------------------------------
-            let ds =
-                si.first_deriv_overlap(sj, *ci, *cj, natoms);
-
-            // --------------------------------------------------
-            // Second derivatives of overlap
-            // d²S/dR_A dR_B -> [A][B][i][j][μ][ν]
-            // --------------------------------------------------
-            let d2s =
-                si.second_deriv_overlap(sj, *ci, *cj, natoms);
+            let nj = sj.n_orbitals();
 
+            // −W block, contracted on the fly with ∂²S
+            let mut wb = vec![0.0; ni * nj];
             for mu in 0..ni {
-                let i = off_i + mu;
-
                 for nu in 0..nj {
-                    let j = off_j + nu;
-
-                    let p = density[i][j];
-                    let f = fock[j][i]; // note transpose
-
-                    if p.abs() < 1e-14 && f.abs() < 1e-14 {
-                        continue;
-                    }
-
-                    // ------------------------------------------
-                    // Term 1: -P * F * d²S
-                    // ------------------------------------------
-                    for a in 0..natoms {
-                        for b in 0..natoms {
-                            for iaxis in 0..3 {
-                                for jaxis in 0..3 {
-
-                                    let idx_a = 3*a + iaxis;
-                                    let idx_b = 3*b + jaxis;
-
-                                    hess[idx_a][idx_b] -=
-                                        p * f *
-                                        d2s[a][b][iaxis][jaxis][mu][nu];
-                                }
-                            }
-                        }
-                    }
-
-                    // ------------------------------------------
-                    // Term 2: -2 * (dS/dR_A) * (dF/dR_B)
-                    // ------------------------------------------
-                    // The explicit non-response part uses:
-                    //   dF ≈ 0  (orbital response neglected here)
-                    // This term is added via CPHF/Z-vector later.
-                    //
-                    // We keep the structure here for completeness.
-                    //
-                    // If you later include explicit dF/dR, add here.
-                    let _ = &ds; // explicitly unused until response is added
+                    wb[mu * nj + nu] = -w[off_i + mu][off_j + nu];
                 }
             }
+
+            if wb.iter().all(|x| x.abs() < 1e-14) {
+                continue;
+            }
+
+            let d2s = overlap_second_deriv(si, sj, &wb);
+            scatter_center_blocks(
+                &mut hess,
+                &d2s,
+                &[shell_atoms[i], shell_atoms[j]],
+            );
         }
     }
 
@@ -119,4 +112,3 @@ pub fn hess_overlap_pulay(
 
     hess
 }
-
@@ -1,109 +1,118 @@
 //! Second nuclear derivatives of two-electron integrals (ERI Hessian)
 //!
 //! Computes:
-//!   H_AB^{ij} = 1/2 Σ_{μνλσ} P_{μν} P_{λσ}
+//!   H_AB^{ij} = Σ_{μνλσ} [ ½ P_{μν} P_{λσ} − ¼ c_x P_{μλ} P_{νσ} ]
 //!                ∂²(μν|λσ)/∂R_Ai∂R_Bj
 //!
+//! c_x is the exact-exchange fraction (1 for HF, 0 for pure DFT).
+//!
 //! This file implements ONLY the explicit (non-response) part.
 //! Orbital response terms are handled via CPHF/Z-vector.
-//
-//! Assumes:
-//! - ERI second derivatives are available at shell level
-//! - Shells know their AO offsets
+//!
+//! Derivative ERIs are contracted quartet by quartet
+//! (see integrals::eri::eri_hess); nothing larger than the density
+//! and the 3N × 3N Hessian is stored.
+//!
+//! Only symmetry-unique shell quartets (ab|cd), a ≥ b, c ≥ d, ab ≥ cd
+//! are computed, with the weight averaged over the 8 permutations and
+//! the degeneracy factor applied. Quartets are dropped when the Schwarz
+//! bound Q_ab Q_cd times the largest density weight is negligible, or
+//! when all four shells sit on one atom (translational invariance).
 
 use crate::basis::shell::Shell;
+use crate::system::atom::Atom;
+use crate::integrals::deriv::{scatter_center_blocks, shell_atom_indices};
+use crate::integrals::eri::eri_hess::eri_second_deriv;
+use crate::integrals::schwarz::{density_bounds, schwarz_factors};
+
+/// Screening threshold on Q_ab Q_cd × max density weight
+const SCREEN_CUTOFF: f64 = 1e-12;
 
 /// Two-electron Hessian contribution (explicit ERI term)
 ///
-/// shells  : AO shells
-/// density : AO density matrix
-/// natoms  : number of nuclei
+/// shells         : AO shells
+/// shell_centers  : coordinates of shell centers
+/// density        : AO density matrix (closed shell, total)
+/// atoms          : molecular atoms
+/// exchange_scale : exact-exchange fraction c_x
 ///
 /// Returns Hessian matrix (3N x 3N)
 pub fn hess_two_electron(
     shells: &[Shell],
+    shell_centers: &[[f64; 3]],
     density: &Vec<Vec<f64>>,
-    natoms: usize,
+    atoms: &[Atom],
+    exchange_scale: f64,
 ) -> Vec<Vec<f64>> {
 
+    let natoms = atoms.len();
     let dim = 3 * natoms;
-    let nao = density.len();
     let mut hess = vec![vec![0.0; dim]; dim];
 
-    // Loop over shell quartets
-    for sh_mu in shells {
-        let off_mu = sh_mu.offset;
-        let nmu = sh_mu.orbitals.len();
-
-        for sh_nu in shells {
-            let off_nu = sh_nu.offset;
-            let nnu = sh_nu.orbitals.len();
-
-            for sh_la in shells {
-                let off_la = sh_la.offset;
-                let nla = sh_la.orbitals.len();
-
-                for sh_si in shells {
-                    let off_si = sh_si.offset;
-                    let nsi = sh_si.orbitals.len();
-
-                    // --------------------------------------------------
-                    // Second derivatives of ERIs
-                    // d²(μν|λσ)/dR_A dR_B
-                    //
-                    // Tensor layout:
-                    //   d2eri[A][B][iaxis][jaxis][μ][ν][λ][σ]
-                    // --------------------------------------------------
-                    let d2eri =
-                        sh_mu.second_deriv_eri(
-                            sh_nu,
-                            sh_la,
-                            sh_si,
-                            natoms,
-                        );
-
-                    // Contract with density matrices
-                    for mu in 0..nmu {
-                        let i = off_mu + mu;
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
+    let nshells = shells.len();
+
+    let schwarz = schwarz_factors(shells);
+    let dmax = density_bounds(shells, density);
 
-                        for nu in 0..nnu {
-                            let j = off_nu + nu;
-                            let p_ij = density[i][j];
-                            if p_ij.abs() < 1e-14 {
-                                continue;
-                            }
-
-                            for la in 0..nla {
-                                let k = off_la + la;
-
-                                for si in 0..nsi {
-                                    let l = off_si + si;
-                                    let p_kl = density[k][l];
-                                    if p_kl.abs() < 1e-14 {
-                                        continue;
-                                    }
-
-                                    let pref = 0.5 * p_ij * p_kl;
-
-                                    for a in 0..natoms {
-                                        for b in 0..natoms {
-                                            for iaxis in 0..3 {
-                                                for jaxis in 0..3 {
-
-                                                    let idx_a = 3*a + iaxis;
-                                                    let idx_b = 3*b + jaxis;
-
-                                                    hess[idx_a][idx_b] += pref *
-                                                        d2eri[a][b][iaxis][jaxis]
-                                                             [mu][nu][la][si];
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    // Loop over symmetry-unique shell quartets
+    for a in 0..nshells {
+        for b in 0..=a {
+            let ab = a * (a + 1) / 2 + b;
+
+            for c in 0..=a {
+                let d_top = if c == a { b } else { c };
+
+                for d in 0..=d_top {
+                    let cd = c * (c + 1) / 2 + d;
+
+                    let centers = [shell_atoms[a], shell_atoms[b], shell_atoms[c], shell_atoms[d]];
+                    if centers.iter().all(|&x| x == centers[0]) {
+                        continue;
                     }
+
+                    let w_max = 0.5 * dmax[a][b] * dmax[c][d]
+                        + 0.125 * exchange_scale.abs()
+                            * (dmax[a][c] * dmax[b][d] + dmax[a][d] * dmax[b][c]);
+                    if schwarz[a][b] * schwarz[c][d] * w_max < SCREEN_CUTOFF {
+                        continue;
+                    }
+
+                    // (ab|cd) stands for its 8 permutations
+                    let mut degeneracy = 1.0;
+                    if a != b {
+                        degeneracy *= 2.0;
+                    }
+                    if c != d {
+                        degeneracy *= 2.0;
+                    }
+                    if ab != cd {
+                        degeneracy *= 2.0;
+                    }
+
+                    let (sh_mu, sh_nu, sh_la, sh_si) =
+                        (&shells[a], &shells[b], &shells[c], &shells[d]);
+                    let (off_mu, off_nu, off_la, off_si) =
+                        (sh_mu.offset, sh_nu.offset, sh_la.offset, sh_si.offset);
+
+                    // Quartet weight (Coulomb + exchange), averaged over
+                    // the permutations of (μν|λσ)
+                    let weight = |mu: usize, nu: usize, la: usize, si: usize| -> f64 {
+                        let i = off_mu + mu;
+                        let j = off_nu + nu;
+                        let k = off_la + la;
+                        let l = off_si + si;
+
+                        degeneracy
+                            * (0.5 * density[i][j] * density[k][l]
+                                - 0.125 * exchange_scale
+                                    * (density[i][k] * density[j][l]
+                                        + density[i][l] * density[j][k]))
+                    };
+
+                    let d2eri = eri_second_deriv(sh_mu, sh_nu, sh_la, sh_si, &weight);
+
+                    scatter_center_blocks(&mut hess, &d2eri, &centers);
                 }
             }
         }
@@ -121,3 +130,109 @@ pub fn hess_two_electron(
     hess
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::integrals::hermite::{eri, expand_shell};
+
+    fn shell(alpha: f64, ang: [usize; 3], center: [f64; 3], offset: usize) -> Shell {
+        Shell::new(vec![Primitive::new(alpha, 1.0, center, ang)], ang, center, offset)
+    }
+
+    /// s + p on the first atom, s on the second, at the given positions
+    fn system(r: &[[f64; 3]]) -> (Vec<Shell>, Vec<[f64; 3]>, Vec<Atom>) {
+        let shells = vec![
+            shell(1.1, [0, 0, 0], r[0], 0),
+            shell(0.7, [1, 0, 0], r[0], 1),
+            shell(0.9, [0, 0, 0], r[1], 4),
+        ];
+        let centers = shells.iter().map(|s| s.center).collect();
+        let atoms = vec![
+            Atom::new("N".into(), 7, r[0]),
+            Atom::new("H".into(), 1, r[1]),
+        ];
+        (shells, centers, atoms)
+    }
+
+    fn density() -> Vec<Vec<f64>> {
+        let n = 5;
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| 0.3 / (1.0 + (i + j) as f64) + if i == j { 0.5 } else { 0.0 })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// ½ Σ P P (μν|λσ) − ¼ c_x Σ P P (μλ|νσ) at fixed density
+    fn two_electron_energy(shells: &[Shell], p: &[Vec<f64>], c_x: f64) -> f64 {
+        let aos: Vec<_> = shells.iter().flat_map(expand_shell).collect();
+        let n = aos.len();
+        let mut e = 0.0;
+
+        for i in 0..n {
+            for j in 0..n {
+                for k in 0..n {
+                    for l in 0..n {
+                        let (ci, gi) = aos[i][0];
+                        let (cj, gj) = aos[j][0];
+                        let (ck, gk) = aos[k][0];
+                        let (cl, gl) = aos[l][0];
+                        let v = ci * cj * ck * cl * eri(&gi, &gj, &gk, &gl);
+                        e += (0.5 * p[i][j] * p[k][l] - 0.25 * c_x * p[i][k] * p[j][l]) * v;
+                    }
+                }
+            }
+        }
+
+        e
+    }
+
+    const GEOMETRY: [[f64; 3]; 2] = [[0.0, 0.1, -0.2], [0.3, -0.4, 1.5]];
+
+    #[test]
+    fn hessian_is_translationally_invariant() {
+        let (shells, centers, atoms) = system(&GEOMETRY);
+        let hess = hess_two_electron(&shells, &centers, &density(), &atoms, 1.0);
+
+        for row in &hess {
+            for j in 0..3 {
+                let sum: f64 = (0..atoms.len()).map(|b| row[3 * b + j]).sum();
+                assert!(sum.abs() < 1e-10, "Σ_B H = {sum}");
+            }
+        }
+    }
+
+    #[test]
+    fn hessian_matches_finite_differences() {
+        let p = density();
+        let c_x = 0.6;
+        let (shells, centers, atoms) = system(&GEOMETRY);
+        let hess = hess_two_electron(&shells, &centers, &p, &atoms, c_x);
+
+        let h = 1e-3;
+        let energy = |moves: &[(usize, f64)]| {
+            let mut r = GEOMETRY;
+            for &(q, d) in moves {
+                r[q / 3][q % 3] += d;
+            }
+            two_electron_energy(&system(&r).0, &p, c_x)
+        };
+
+        for (q1, row) in hess.iter().enumerate() {
+            for (q2, &h_12) in row.iter().enumerate() {
+                let fd = (energy(&[(q1, h), (q2, h)]) - energy(&[(q1, h), (q2, -h)])
+                    - energy(&[(q1, -h), (q2, h)])
+                    + energy(&[(q1, -h), (q2, -h)]))
+                    / (4.0 * h * h);
+
+                assert!(
+                    (h_12 - fd).abs() < 1e-5,
+                    "H[{q1}][{q2}] = {h_12} vs {fd}"
+                );
+            }
+        }
+    }
+}
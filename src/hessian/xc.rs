@@ -2,194 +2,74 @@
 //!
 //! Implements the *explicit* XC Hessian terms:
 //!   ∂²E_xc / ∂R_Ai ∂R_Bj
+//! at fixed density, for every functional of `gradients::dft_xc`.
 //!
-//! Covered:
-//! - LDA
-//! - GGA
-//! - meta-GGA (τ)
+//! Each row is a central difference of the analytic XC gradient with
+//! atom A (its shells and its grid) displaced, as for the other
+//! finite-difference derivatives of the code (relativistic, CDFT).
 //!
 //! Not included here:
 //! - Orbital response (CPHF / Z-vector)
 //!   → handled in hessian/cphf.rs
-//!
-//! This matches the standard decomposition used in Psi4 / Q-Chem.
 
 use crate::basis::shell::Shell;
-use crate::system::atom::Atom;
-use crate::dft::grid::{DftGrid, GridPoint};
-use crate::dft::density::density_at_point;
-use crate::dft::tau::{tau_at_point, dtau_dra};
-use crate::dft::libxc::LibXC;
 use crate::dft::vxc::XcMethod;
+use crate::gradients::dft_xc::grad_xc;
+use crate::integrals::deriv::shell_atom_indices;
+use crate::system::atom::Atom;
+
+/// Displacement (bohr) of the central differences
+const HESSIAN_STEP: f64 = 1e-4;
 
 /// Explicit XC Hessian contribution
 ///
+/// `densities` as in `grad_xc`: P or (P^α, P^β).
+///
 /// Returns Hessian matrix (3N x 3N)
 pub fn hess_xc(
     shells: &[Shell],
-    shell_centers: &[[f64;3]],
-    density: &Vec<Vec<f64>>,
-    coeff: Option<&Vec<Vec<f64>>>,   // needed for meta-GGA
-    n_occ: Option<usize>,            // needed for meta-GGA
+    shell_centers: &[[f64; 3]],
+    densities: &[&Vec<Vec<f64>>],
     atoms: &[Atom],
-    method: XcMethod,
+    method: &XcMethod,
 ) -> Vec<Vec<f64>> {
 
     let natoms = atoms.len();
     let dim = 3 * natoms;
-    let nao = density.len();
-
     let mut hess = vec![vec![0.0; dim]; dim];
 
-    // -------------------------------------------
-    // Resolve hybrid scaling
-    // -------------------------------------------
-    let (xc_base, hf_frac) = match method {
-        XcMethod::Hybrid { base, hyb } => (*base, hyb.hf_fraction()),
-        other => (other, 0.0),
-    };
-
-    // -------------------------------------------
-    // Select libxc functionals
-    // -------------------------------------------
-    let (fx, fc, is_meta) = match xc_base {
-        XcMethod::LDA => (
-            LibXC::new(1, false),
-            LibXC::new(7, false),
-            false,
-        ),
-        XcMethod::GGA => (
-            LibXC::new(101, false),
-            LibXC::new(130, false),
-            false,
-        ),
-        XcMethod::MetaGGA => (
-            LibXC::new(263, false), // SCAN_X
-            LibXC::new(267, false), // SCAN_C
-            true,
-        ),
-        _ => unreachable!(),
-    };
-
-    // -------------------------------------------
-    // Grid
-    // -------------------------------------------
-    let grid = DftGrid::new(atoms, 30, 14);
-
-    for GridPoint { r, weight } in grid.points {
-
-        let dp = density_at_point(shells, shell_centers, density, r);
-        if dp.rho < 1e-12 {
-            continue;
-        }
-
-        // AO values and gradients
-        let mut phi = Vec::with_capacity(nao);
-        let mut grad_phi = Vec::with_capacity(nao);
+    let shell_atoms = shell_atom_indices(shell_centers, atoms);
 
-        for (sh, c) in shells.iter().zip(shell_centers.iter()) {
-            for ao in &sh.orbitals {
-                phi.push(ao.value(*c, r));
-                grad_phi.push(ao.gradient(*c, r));
+    let gradient = |a: usize, k: usize, step: f64| {
+        let mut sh = shells.to_vec();
+        for (s, &owner) in sh.iter_mut().zip(shell_atoms.iter()) {
+            if owner == a {
+                s.center[k] += step;
             }
         }
+        let centers: Vec<[f64; 3]> = sh.iter().map(|s| s.center).collect();
 
-        // -------------------------------------------
-        // Build invariants
-        // -------------------------------------------
-        let rho = vec![dp.rho];
-        let sigma = vec![
-            dp.grad[0]*dp.grad[0] +
-            dp.grad[1]*dp.grad[1] +
-            dp.grad[2]*dp.grad[2]
-        ];
-
-        let tau = if is_meta {
-            tau_at_point(
-                shells,
-                shell_centers,
-                coeff.expect("meta-GGA requires coeff"),
-                n_occ.expect("meta-GGA requires n_occ"),
-                r,
-            )
-        } else { 0.0 };
-
-        // -------------------------------------------
-        // XC second derivatives from libxc
-        // -------------------------------------------
-        // libxc returns:
-        //   d²f/dρ², d²f/dρdσ, d²f/dσ², d²f/dτ², ...
-        let xc2 = fx.eval_xc_hessian(&rho, &sigma, tau);
-        let cc2 = fc.eval_xc_hessian(&rho, &sigma, tau);
-
-        let vrr = (xc2.vrr[0] + cc2.vrr[0]) * (1.0 - hf_frac);
-        let vrs = (xc2.vrs[0] + cc2.vrs[0]) * (1.0 - hf_frac);
-        let vss = (xc2.vss[0] + cc2.vss[0]) * (1.0 - hf_frac);
-        let vtt = if is_meta {
-            (xc2.vtt[0] + cc2.vtt[0]) * (1.0 - hf_frac)
-        } else { 0.0 };
-
-        // -------------------------------------------
-        // Nuclear derivatives
-        // -------------------------------------------
-        for a in 0..natoms {
-            for b in 0..natoms {
+        let mut at = atoms.to_vec();
+        at[a].position[k] += step;
 
-                let mut d2rho = [0.0;3];
-                let mut d2sig = [0.0;3];
-                let mut d2tau = [0.0;3];
-
-                for mu in 0..nao {
-                    for nu in 0..nao {
-                        let p = density[mu][nu];
-                        if p.abs() < 1e-14 { continue; }
-
-                        let dphi =
-                            [ grad_phi[mu][0]*phi[nu] + phi[mu]*grad_phi[nu][0],
-                              grad_phi[mu][1]*phi[nu] + phi[mu]*grad_phi[nu][1],
-                              grad_phi[mu][2]*phi[nu] + phi[mu]*grad_phi[nu][2] ];
-
-                        for k in 0..3 {
-                            d2rho[k] += p * dphi[k];
-                            d2sig[k] += 2.0 * dp.grad[k] * p * dphi[k];
-                        }
-                    }
-                }
+        grad_xc(&sh, &centers, &at, densities, method)
+    };
 
-                if is_meta {
-                    let dt = dtau_dra(
-                        shells,
-                        shell_centers,
-                        coeff.unwrap(),
-                        n_occ.unwrap(),
-                        b,
-                        r,
-                    );
-                    for k in 0..3 {
-                        d2tau[k] = dt[k];
-                    }
-                }
+    for a in 0..natoms {
+        for i in 0..3 {
+            let plus = gradient(a, i, HESSIAN_STEP);
+            let minus = gradient(a, i, -HESSIAN_STEP);
 
-                for ia in 0..3 {
-                    let idx_a = 3*a + ia;
-                    for ib in 0..3 {
-                        let idx_b = 3*b + ib;
-
-                        hess[idx_a][idx_b] += weight * (
-                            vrr * d2rho[ia] * d2rho[ib] +
-                            2.0 * vrs * d2rho[ia] * d2sig[ib] +
-                            vss * d2sig[ia] * d2sig[ib] +
-                            vtt * d2tau[ia] * d2tau[ib]
-                        );
-                    }
+            for b in 0..natoms {
+                for j in 0..3 {
+                    hess[3 * a + i][3 * b + j] =
+                        (plus[b][j] - minus[b][j]) / (2.0 * HESSIAN_STEP);
                 }
             }
         }
     }
 
-    // -------------------------------------------
     // Symmetrize
-    // -------------------------------------------
     for i in 0..dim {
         for j in 0..i {
             let avg = 0.5 * (hess[i][j] + hess[j][i]);
@@ -201,3 +81,57 @@ pub fn hess_xc(
     hess
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::dft::vxc::build_vxc;
+
+    fn geometry(positions: [[f64; 3]; 2]) -> (Vec<Atom>, Vec<Shell>, Vec<[f64; 3]>) {
+        let atoms: Vec<Atom> =
+            positions.iter().map(|&r| Atom::new("H".to_string(), 1, r)).collect();
+
+        let mut shells = Vec::new();
+        for (atom, ang, alpha) in [(0, [0, 0, 0], 0.8), (0, [0, 0, 1], 0.7), (1, [0, 0, 0], 1.0)] {
+            let center = positions[atom];
+            let offset = shells.iter().map(Shell::n_orbitals).sum();
+            let primitive = Primitive::new(alpha, 1.0, center, ang);
+            shells.push(Shell::new(vec![primitive], ang, center, offset));
+        }
+
+        let centers = shells.iter().map(|s| s.center).collect();
+        (atoms, shells, centers)
+    }
+
+    #[test]
+    fn lda_hessian_is_translationally_invariant_and_matches_the_energy() {
+        let base = [[0.0, 0.0, 0.0], [0.2, -0.3, 1.5]];
+        // Two doubly occupied orbitals: ρ ≥ 0 everywhere
+        let c = [[0.6, 0.3, -0.2, 0.1, 0.5], [0.1, -0.4, 0.3, 0.2, -0.3]];
+        let p: Vec<Vec<f64>> = (0..5)
+            .map(|i| (0..5).map(|j| 2.0 * c.iter().map(|o| o[i] * o[j]).sum::<f64>()).collect())
+            .collect();
+
+        let (atoms, shells, centers) = geometry(base);
+        let hess = hess_xc(&shells, &centers, &[&p], &atoms, &XcMethod::LDA);
+
+        // Σ_B ∂²E/∂A_i∂B_j = 0
+        for row in &hess {
+            for j in 0..3 {
+                let sum = row[j] + row[3 + j];
+                assert!(sum.abs() < 1e-5, "{}", sum);
+            }
+        }
+
+        // ∂²E/∂B_z² from the energy
+        let energy = |dz: f64| {
+            let mut positions = base;
+            positions[1][2] += dz;
+            let (atoms, shells, centers) = geometry(positions);
+            build_vxc(&shells, &centers, &p, None, None, &atoms, XcMethod::LDA).1.exc
+        };
+        let h = 1e-3;
+        let fd = (energy(h) - 2.0 * energy(0.0) + energy(-h)) / (h * h);
+        assert!((hess[5][5] - fd).abs() < 1e-4, "{} vs {}", hess[5][5], fd);
+    }
+}
@@ -0,0 +1,433 @@
+//! YAML input file
+//!
+//! Maps the keys of `input.yaml` onto the task flags, the molecule
+//! settings and `ScfOptions`. Every key except `basis` is optional and
+//! falls back to the `ScfOptions` default; unknown keys are rejected so
+//! a misspelled option is never silently ignored.
+
+use serde_yaml::{Mapping, Value};
+
+use crate::dft::vxc::XcMethod;
+use crate::integrals::relativistic::RelativisticHamiltonian;
+use crate::scf::cdft::{CdftOptions, Constraint, Partition};
+use crate::scf::diis::Accelerator;
+use crate::scf::guess::InitialGuess;
+use crate::scf::mom::{MomOptions, MomVariant, OccupationChange, SpinChannel};
+use crate::scf::rohf::RohfCoupling;
+use crate::scf::scf_cycle::{NonConvergence, Reference, ScfOptions};
+use crate::scf::soscf::{OrbitalHessian, SecondOrder};
+use crate::scf::stability::StabilityMode;
+use crate::scf::stabilizers::{Damping, DampingTarget, Smearing, SmearingKind};
+use crate::system::nuclear_model::NuclearModel;
+
+/// What to compute after the SCF
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Task {
+    pub compute_gradients: bool,
+    pub compute_hessian: bool,
+    pub compute_frequencies: bool,
+}
+
+pub struct Input {
+    pub task: Task,
+    pub basis: String,
+    pub charge: i32,
+    pub multiplicity: usize,
+    pub nuclear_model: NuclearModel,
+    pub scf: ScfOptions,
+}
+
+impl Input {
+    pub fn from_yaml(text: &str) -> Result<Self, String> {
+        let root: Value = serde_yaml::from_str(text).map_err(|e| format!("input: {}", e))?;
+        let root = Section::new(&root, "input".to_string())?;
+        root.check_keys(&[
+            "task", "charge", "multiplicity", "method", "dft", "basis", "scf",
+            "relativistic", "nuclear_model",
+        ])?;
+
+        let mut task = Task::default();
+        if let Some(s) = root.section("task")? {
+            s.check_keys(&["compute_gradients", "compute_hessian", "compute_frequencies"])?;
+            task.compute_gradients = s.bool("compute_gradients")?.unwrap_or(false);
+            task.compute_hessian = s.bool("compute_hessian")?.unwrap_or(false);
+            task.compute_frequencies = s.bool("compute_frequencies")?.unwrap_or(false);
+        }
+
+        let basis = root
+            .string("basis")?
+            .ok_or_else(|| root.missing("basis"))?
+            .to_string();
+
+        let mut scf = ScfOptions {
+            xc_method: method(&root)?,
+            ..ScfOptions::default()
+        };
+        if let Some(r) = root.keyword("relativistic", RelativisticHamiltonian::from_name)? {
+            scf.relativistic = r;
+        }
+        if let Some(s) = root.section("scf")? {
+            scf_section(&s, &mut scf)?;
+        }
+
+        Ok(Self {
+            task,
+            basis,
+            charge: root.integer("charge")?.unwrap_or(0) as i32,
+            multiplicity: root.integer("multiplicity")?.unwrap_or(1) as usize,
+            nuclear_model: root
+                .keyword("nuclear_model", NuclearModel::from_name)?
+                .unwrap_or(NuclearModel::PointCharge),
+            scf,
+        })
+    }
+}
+
+/// `method: HF | DFT` and `dft.functional`
+fn method(root: &Section) -> Result<Option<XcMethod>, String> {
+    let dft = root.section("dft")?;
+    if let Some(d) = &dft {
+        d.check_keys(&["functional"])?;
+    }
+
+    match root.string("method")?.map(|m| m.to_ascii_lowercase()).as_deref() {
+        None | Some("hf") => Ok(None),
+        Some("dft") => {
+            let functional = dft
+                .as_ref()
+                .map(|d| d.keyword("functional", XcMethod::from_name))
+                .transpose()?
+                .flatten()
+                .ok_or_else(|| "input.dft.functional: missing".to_string())?;
+            Ok(Some(functional))
+        }
+        Some(m) => Err(format!("input.method: unknown keyword '{}'", m)),
+    }
+}
+
+fn scf_section(s: &Section, scf: &mut ScfOptions) -> Result<(), String> {
+    s.check_keys(&[
+        "max_iter", "conv_energy", "conv_density", "conv_density_max", "conv_commutator",
+        "on_max_iter", "guess", "reference", "rohf_coupling", "diis_size", "accelerator",
+        "diis_switch", "level_shift", "damping", "damping_factor", "smearing",
+        "smearing_temperature", "stabilizers_off", "soscf", "soscf_hessian", "mom", "stability",
+        "guess_rotation", "cdft",
+    ])?;
+
+    if let Some(v) = s.integer("max_iter")? {
+        scf.max_iter = v as usize;
+    }
+
+    // Convergence
+    if let Some(v) = s.float("conv_energy")? {
+        scf.convergence.energy = v;
+    }
+    if let Some(v) = s.float("conv_density")? {
+        scf.convergence.density_rms = v;
+    }
+    if let Some(v) = s.float("conv_density_max")? {
+        scf.convergence.density_max = v;
+    }
+    if let Some(v) = s.float("conv_commutator")? {
+        scf.convergence.commutator = v;
+    }
+    if let Some(v) = s.keyword("on_max_iter", NonConvergence::from_name)? {
+        scf.on_max_iter = v;
+    }
+
+    // Reference and guess
+    if let Some(v) = s.keyword("reference", Reference::from_name)? {
+        scf.reference = v;
+    }
+    if let Some(v) = s.keyword("rohf_coupling", RohfCoupling::from_name)? {
+        scf.rohf_coupling = v;
+    }
+    if let Some(v) = s.keyword("guess", InitialGuess::from_name)? {
+        scf.guess = v;
+    }
+    if let Some(v) = s.float("guess_rotation")? {
+        scf.guess_rotation = v;
+    }
+
+    // DIIS
+    if let Some(v) = s.integer("diis_size")? {
+        scf.diis_size = v as usize;
+    }
+    if let Some(v) = s.keyword("accelerator", Accelerator::from_name)? {
+        scf.accelerator = v;
+    }
+    if let Some(v) = s.float("diis_switch")? {
+        scf.diis_switch = v;
+    }
+
+    // Stabilizers
+    let stabilizers = &mut scf.stabilizers;
+    if let Some(v) = s.float("level_shift")? {
+        stabilizers.level_shift = v;
+    }
+    if let Some(target) = s.optional_keyword("damping", DampingTarget::from_name)? {
+        let factor = s.float("damping_factor")?.unwrap_or(0.5);
+        stabilizers.damping = Some(Damping::adaptive(target, factor));
+    }
+    if let Some(kind) = s.optional_keyword("smearing", SmearingKind::from_name)? {
+        let temperature = s.float("smearing_temperature")?.unwrap_or(0.01);
+        stabilizers.smearing = Some(Smearing::annealed(kind, temperature));
+    }
+    if let Some(v) = s.float("stabilizers_off")? {
+        stabilizers.off_below = v;
+    }
+
+    // Second order, stability
+    if let Some(v) = s.keyword("soscf", SecondOrder::from_name)? {
+        scf.soscf.mode = v;
+    }
+    if let Some(v) = s.keyword("soscf_hessian", OrbitalHessian::from_name)? {
+        scf.soscf.hessian = v;
+    }
+    if let Some(v) = s.keyword("stability", StabilityMode::from_name)? {
+        scf.stability.mode = v;
+    }
+
+    if let Some(m) = s.optional_section("mom")? {
+        scf.mom = Some(mom(&m)?);
+    }
+    if let Some(c) = s.optional_section("cdft")? {
+        scf.cdft = Some(cdft(&c)?);
+    }
+
+    Ok(())
+}
+
+/// `mom: {variant, spin, from, to}` or `{variant, spin, ionize}`
+fn mom(s: &Section) -> Result<MomOptions, String> {
+    s.check_keys(&["variant", "spin", "from", "to", "ionize"])?;
+
+    let variant = s.keyword("variant", MomVariant::from_name)?.unwrap_or(MomVariant::Imom);
+    let spin = s.keyword("spin", SpinChannel::from_name)?.unwrap_or(SpinChannel::Alpha);
+
+    let change = match s.integer("ionize")? {
+        Some(orbital) => OccupationChange::Ionization { spin, orbital: orbital as usize },
+        None => {
+            let from = s.integer("from")?.ok_or_else(|| s.missing("from"))?;
+            let to = s.integer("to")?.ok_or_else(|| s.missing("to"))?;
+            OccupationChange::Excitation { spin, from: from as usize, to: to as usize }
+        }
+    };
+
+    Ok(MomOptions { variant, changes: vec![change] })
+}
+
+/// `cdft: {partition, tolerance, max_iter, constraints: [{atoms, charge | spin}]}`
+fn cdft(s: &Section) -> Result<CdftOptions, String> {
+    s.check_keys(&["partition", "tolerance", "max_iter", "constraints"])?;
+
+    let mut options = CdftOptions::default();
+    if let Some(v) = s.keyword("partition", Partition::from_name)? {
+        options.partition = v;
+    }
+    if let Some(v) = s.float("tolerance")? {
+        options.tolerance = v;
+    }
+    if let Some(v) = s.integer("max_iter")? {
+        options.max_iter = v as usize;
+    }
+
+    let list = s
+        .value("constraints")
+        .and_then(Value::as_sequence)
+        .ok_or_else(|| s.missing("constraints"))?;
+
+    for (i, c) in list.iter().enumerate() {
+        let c = Section::new(c, format!("{}.constraints[{}]", s.path, i))?;
+        c.check_keys(&["atoms", "charge", "spin"])?;
+
+        let atoms = c
+            .value("atoms")
+            .and_then(Value::as_sequence)
+            .ok_or_else(|| c.missing("atoms"))?
+            .iter()
+            .map(|a| a.as_u64().map(|a| a as usize))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(|| format!("{}.atoms: expected atom indices", c.path))?;
+
+        let constraint = match (c.float("charge")?, c.float("spin")?) {
+            (Some(q), None) => Constraint::charge(atoms, q),
+            (None, Some(s)) => Constraint::spin(atoms, s),
+            _ => return Err(format!("{}: expected exactly one of charge | spin", c.path)),
+        };
+        options.constraints.push(constraint);
+    }
+
+    Ok(options)
+}
+
+/// A YAML mapping with its dotted path (for error messages)
+struct Section<'a> {
+    map: &'a Mapping,
+    path: String,
+}
+
+impl<'a> Section<'a> {
+    fn new(value: &'a Value, path: String) -> Result<Self, String> {
+        match value.as_mapping() {
+            Some(map) => Ok(Self { map, path }),
+            None => Err(format!("{}: expected a mapping", path)),
+        }
+    }
+
+    fn check_keys(&self, known: &[&str]) -> Result<(), String> {
+        for key in self.map.keys() {
+            match key.as_str() {
+                Some(k) if known.contains(&k) => {}
+                _ => return Err(format!("{}: unknown key {:?}", self.path, key)),
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&self, key: &str) -> Option<&'a Value> {
+        self.map.get(key)
+    }
+
+    fn key_path(&self, key: &str) -> String {
+        format!("{}.{}", self.path, key)
+    }
+
+    fn missing(&self, key: &str) -> String {
+        format!("{}: missing", self.key_path(key))
+    }
+
+    fn expected<T>(&self, key: &str, value: Option<T>, what: &str) -> Result<Option<T>, String> {
+        match (self.value(key), value) {
+            (None, _) => Ok(None),
+            (Some(_), Some(v)) => Ok(Some(v)),
+            (Some(_), None) => Err(format!("{}: expected {}", self.key_path(key), what)),
+        }
+    }
+
+    fn section(&self, key: &str) -> Result<Option<Section<'a>>, String> {
+        self.value(key).map(|v| Section::new(v, self.key_path(key))).transpose()
+    }
+
+    /// `none` or a mapping
+    fn optional_section(&self, key: &str) -> Result<Option<Section<'a>>, String> {
+        match self.value(key) {
+            Some(Value::String(s)) if s.eq_ignore_ascii_case("none") => Ok(None),
+            _ => self.section(key),
+        }
+    }
+
+    fn bool(&self, key: &str) -> Result<Option<bool>, String> {
+        self.expected(key, self.value(key).and_then(Value::as_bool), "true | false")
+    }
+
+    fn integer(&self, key: &str) -> Result<Option<i64>, String> {
+        self.expected(key, self.value(key).and_then(Value::as_i64), "an integer")
+    }
+
+    fn float(&self, key: &str) -> Result<Option<f64>, String> {
+        self.expected(key, self.value(key).and_then(Value::as_f64), "a number")
+    }
+
+    fn string(&self, key: &str) -> Result<Option<&'a str>, String> {
+        self.expected(key, self.value(key).and_then(Value::as_str), "a string")
+    }
+
+    fn keyword<T>(&self, key: &str, parse: fn(&str) -> Option<T>) -> Result<Option<T>, String> {
+        match self.string(key)? {
+            None => Ok(None),
+            Some(name) => parse(name)
+                .map(Some)
+                .ok_or_else(|| format!("{}: unknown keyword '{}'", self.key_path(key), name)),
+        }
+    }
+
+    /// `none` or a keyword
+    fn optional_keyword<T>(
+        &self,
+        key: &str,
+        parse: fn(&str) -> Option<T>,
+    ) -> Result<Option<T>, String> {
+        match self.string(key)? {
+            Some(name) if name.eq_ignore_ascii_case("none") => Ok(None),
+            _ => self.keyword(key, parse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_input_maps_onto_the_options() {
+        let input = Input::from_yaml(include_str!("input.yaml")).unwrap();
+
+        assert!(input.task.compute_gradients);
+        assert_eq!(input.basis, "def2-svp");
+        assert_eq!((input.charge, input.multiplicity), (0, 1));
+        assert_eq!(input.nuclear_model, NuclearModel::PointCharge);
+        assert!(matches!(input.scf.xc_method, Some(XcMethod::GGA)));
+        assert_eq!(input.scf.convergence.density_max, 1e-5);
+        assert!(matches!(input.scf.guess, InitialGuess::Sad));
+        assert_eq!(input.scf.rohf_coupling, RohfCoupling::GuestSaunders);
+        assert!(input.scf.mom.is_none() && input.scf.cdft.is_none());
+    }
+
+    #[test]
+    fn non_default_options_are_parsed() {
+        let text = "
+basis: sto-3g
+method: HF
+relativistic: x2c
+nuclear_model: gaussian
+scf:
+  max_iter: 20
+  reference: unrestricted
+  damping: fock
+  damping_factor: 0.3
+  smearing: gaussian
+  mom: {variant: mom, spin: beta, from: 2, to: 4}
+  cdft:
+    partition: lowdin
+    constraints:
+      - {atoms: [0, 1], charge: 0.5}
+      - {atoms: [2], spin: 1.0}
+";
+        let input = Input::from_yaml(text).unwrap();
+        let scf = &input.scf;
+
+        assert!(scf.xc_method.is_none());
+        assert_eq!(scf.max_iter, 20);
+        assert_eq!(scf.relativistic, RelativisticHamiltonian::X2C);
+        assert_eq!(input.nuclear_model, NuclearModel::Gaussian);
+        assert_eq!(scf.reference, Reference::Unrestricted);
+
+        let damping = scf.stabilizers.damping.unwrap();
+        assert_eq!((damping.target, damping.factor), (DampingTarget::Fock, 0.3));
+        assert_eq!(scf.stabilizers.smearing.unwrap().kind, SmearingKind::Gaussian);
+
+        let mom = scf.mom.as_ref().unwrap();
+        assert_eq!(mom.variant, MomVariant::Mom);
+        assert_eq!(
+            mom.changes,
+            vec![OccupationChange::Excitation { spin: SpinChannel::Beta, from: 2, to: 4 }]
+        );
+
+        let cdft = scf.cdft.as_ref().unwrap();
+        assert_eq!(cdft.partition, Partition::Lowdin);
+        assert_eq!(cdft.constraints.len(), 2);
+        assert_eq!(cdft.constraints[1].atoms, vec![2]);
+    }
+
+    #[test]
+    fn unknown_keys_and_keywords_are_rejected() {
+        let err = Input::from_yaml("basis: sto-3g\nscf:\n  max_iters: 10\n").err().unwrap();
+        assert!(err.contains("input.scf"), "{}", err);
+
+        let err = Input::from_yaml("basis: sto-3g\nrelativistic: dkh3\n").err().unwrap();
+        assert!(err.contains("input.relativistic"), "{}", err);
+
+        assert!(Input::from_yaml("method: DFT\nbasis: sto-3g\n").is_err());
+    }
+}
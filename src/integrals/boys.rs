@@ -3,20 +3,26 @@
 //! F_n(T) = ∫₀¹ t^(2n) exp(-T t²) dt
 //!
 //! Stable for:
-//! - small T
-//! - large T
-//! - n up to ~10–15 (suficiente para d/f shells)
+//! - small T      (Taylor series)
+//! - moderate T   (positive series, no cancellation)
+//! - large T      (erf + upward recurrence)
+//! - n up to ~16 (suficiente para segundas derivadas de shells d/f)
 
 use std::f64::consts::PI;
 
 /// Threshold below which we use the series expansion
 const T_SMALL: f64 = 1e-8;
 
+/// Above this T the upward recurrence is numerically safe
+const T_LARGE: f64 = 30.0;
+
 /// Compute the Boys function F_n(T)
 #[inline]
 pub fn boys(n: usize, t: f64) -> f64 {
     if t < T_SMALL {
         boys_small_t(n, t)
+    } else if t < T_LARGE {
+        boys_series(n, t)
     } else {
         boys_general(n, t)
     }
@@ -51,6 +57,32 @@ fn boys_small_t(n: usize, t: f64) -> f64 {
     sum
 }
 
+// =======================================================
+// Moderate-T positive series
+// =======================================================
+
+#[inline]
+fn boys_series(n: usize, t: f64) -> f64 {
+    // F_n(T) = exp(-T) Σ_k (2T)^k / [(2n+1)(2n+3)…(2n+2k+1)]
+    // All terms are positive: no cancellation, unlike the upward
+    // recurrence for small/moderate T.
+    let mut term = 1.0 / (2 * n + 1) as f64;
+    let mut sum = term;
+    let mut k = 0usize;
+
+    loop {
+        k += 1;
+        term *= 2.0 * t / (2 * n + 2 * k + 1) as f64;
+        sum += term;
+
+        if term < 1e-17 * sum {
+            break;
+        }
+    }
+
+    (-t).exp() * sum
+}
+
 // =======================================================
 // General evaluation using erf + recurrence
 // =======================================================
@@ -59,7 +91,7 @@ fn boys_small_t(n: usize, t: f64) -> f64 {
 fn boys_general(n: usize, t: f64) -> f64 {
     // Compute F_0(T) analytically
     let sqrt_t = t.sqrt();
-    let mut f0 = 0.5 * (PI / t).sqrt() * erf(sqrt_t);
+    let mut f0 = 0.5 * (PI / t).sqrt() * libm::erf(sqrt_t);
 
     if n == 0 {
         return f0;
@@ -77,33 +109,6 @@ fn boys_general(n: usize, t: f64) -> f64 {
     f0
 }
 
-// =======================================================
-// Error function (erf)
-// =======================================================
-
-/// Error function erf(x)
-///
-/// Rational approximation (Abramowitz & Stegun 7.1.26)
-#[inline]
-fn erf(x: f64) -> f64 {
-    // constants
-    let a1 = 0.254829592;
-    let a2 = -0.284496736;
-    let a3 = 1.421413741;
-    let a4 = -1.453152027;
-    let a5 = 1.061405429;
-    let p  = 0.3275911;
-
-    let sign = if x < 0.0 { -1.0 } else { 1.0 };
-    let x = x.abs();
-
-    let t = 1.0 / (1.0 + p * x);
-    let y = 1.0 - (((((a5 * t + a4) * t + a3) * t + a2) * t + a1)
-                    * t * (-x * x).exp());
-
-    sign * y
-}
-
 /// Boys function F_0
 
 pub fn boys0(t: f64) -> f64 {
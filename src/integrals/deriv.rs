@@ -0,0 +1,422 @@
+//! Nuclear first- and second-derivative kernels for AO integrals
+//!
+//! Derivatives are taken analytically on the Gaussians themselves:
+//!   ∂G_l/∂A_k = 2α G_{l+1_k} − l_k G_{l−1_k}
+//! so every derivative integral is a short sum of ordinary integrals
+//! from `integrals::hermite`.
+//!
+//! Kernels never store derivative integrals. Each shell pair / quartet
+//! is contracted immediately with caller-provided AO weights (densities)
+//! and only the small per-center block (∂/∂X_i or ∂²/∂X_i∂Y_j) is
+//! returned.
+//!
+//! Centers that are not differentiated explicitly (operator center C,
+//! fourth ERI center D) follow from translational invariance:
+//!   Σ_X ∂/∂X = 0
+
+use crate::basis::shell::Shell;
+use crate::integrals::hermite::{
+    expand_shell, gaussian_charge_potential, kinetic, overlap, CartGaussian,
+};
+use crate::system::atom::Atom;
+
+/// ∂²/∂X_i∂Y_j blocks: `blocks[x][y][i][j]`
+pub type CenterBlocks = Vec<Vec<[[f64; 3]; 3]>>;
+
+/// ∂/∂X_i per center: `grads[x][i]`
+pub type CenterGradients = Vec<[f64; 3]>;
+
+/// Contraction threshold for AO weights
+const WEIGHT_CUTOFF: f64 = 1e-14;
+
+// ======================================================
+// Derivatives of a single Gaussian
+// ======================================================
+
+/// ∂/∂A_k of a Gaussian centered at A
+pub fn center_derivative(g: &CartGaussian, k: usize) -> Vec<(f64, CartGaussian)> {
+    let mut terms = Vec::with_capacity(2);
+
+    let mut up = *g;
+    up.l[k] += 1;
+    terms.push((2.0 * g.alpha, up));
+
+    if g.l[k] > 0 {
+        let mut down = *g;
+        down.l[k] -= 1;
+        terms.push((-(g.l[k] as f64), down));
+    }
+
+    terms
+}
+
+/// ∂²/∂A_k∂A_m of a Gaussian centered at A
+pub fn center_second_derivative(
+    g: &CartGaussian,
+    k: usize,
+    m: usize,
+) -> Vec<(f64, CartGaussian)> {
+    let mut terms = Vec::with_capacity(4);
+
+    for (c1, g1) in center_derivative(g, k) {
+        for (c2, g2) in center_derivative(&g1, m) {
+            terms.push((c1 * c2, g2));
+        }
+    }
+
+    terms
+}
+
+// ======================================================
+// Generic N-center kernels
+// ======================================================
+
+/// First derivatives of op(g_0, …, g_{n-1}) w.r.t. the centers of all
+/// Gaussians, for one set of primitives.
+pub(crate) fn primitive_first_derivative<F>(
+    gs: &[CartGaussian],
+    coef: f64,
+    op: &F,
+    grads: &mut CenterGradients,
+) where
+    F: Fn(&[CartGaussian]) -> f64,
+{
+    let mut work = gs.to_vec();
+
+    for (x, (grad, g_x)) in grads.iter_mut().zip(gs).enumerate() {
+        for (i, grad_i) in grad.iter_mut().enumerate() {
+            let mut val = 0.0;
+            for (c, g) in center_derivative(g_x, i) {
+                work[x] = g;
+                val += c * op(&work);
+            }
+            work[x] = *g_x;
+
+            *grad_i += coef * val;
+        }
+    }
+}
+
+/// Second derivatives of op(g_0, …, g_{n-1}) w.r.t. the centers of all
+/// Gaussians, for one set of primitives.
+pub(crate) fn primitive_second_derivative<F>(
+    gs: &[CartGaussian],
+    coef: f64,
+    op: &F,
+    blocks: &mut CenterBlocks,
+) where
+    F: Fn(&[CartGaussian]) -> f64,
+{
+    let n = gs.len();
+    let mut work = gs.to_vec();
+
+    for x in 0..n {
+        for y in x..n {
+            let mut block = [[0.0; 3]; 3];
+
+            for (i, row) in block.iter_mut().enumerate() {
+                for (j, b) in row.iter_mut().enumerate() {
+                    let mut val = 0.0;
+
+                    if x == y {
+                        for (c, g) in center_second_derivative(&gs[x], i, j) {
+                            work[x] = g;
+                            val += c * op(&work);
+                        }
+                        work[x] = gs[x];
+                    } else {
+                        for (cx, gx) in center_derivative(&gs[x], i) {
+                            work[x] = gx;
+                            for (cy, gy) in center_derivative(&gs[y], j) {
+                                work[y] = gy;
+                                val += cx * cy * op(&work);
+                            }
+                            work[y] = gs[y];
+                        }
+                        work[x] = gs[x];
+                    }
+
+                    *b = coef * val;
+                }
+            }
+
+            for (i, row) in block.iter().enumerate() {
+                for (j, &b) in row.iter().enumerate() {
+                    blocks[x][y][i][j] += b;
+                    if x != y {
+                        blocks[y][x][j][i] += b;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Append one center whose derivative is minus the sum of all others
+///
+/// Used for the operator center of nuclear attraction and the fourth
+/// ERI center.
+pub fn add_translational_center(blocks: &CenterBlocks) -> CenterBlocks {
+    let n = blocks.len();
+    let mut out = vec![vec![[[0.0; 3]; 3]; n + 1]; n + 1];
+
+    for x in 0..n {
+        for y in 0..n {
+            for i in 0..3 {
+                for j in 0..3 {
+                    let v = blocks[x][y][i][j];
+
+                    out[x][y][i][j] += v;
+                    // ∂X ∂Z = −Σ_Y ∂X ∂Y
+                    out[x][n][i][j] -= v;
+                    out[n][y][i][j] -= v;
+                    // ∂Z ∂Z = Σ_XY ∂X ∂Y
+                    out[n][n][i][j] += v;
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// First-derivative counterpart of `add_translational_center`
+pub fn add_translational_center_gradient(grads: &CenterGradients) -> CenterGradients {
+    let mut out = grads.clone();
+    let mut last = [0.0; 3];
+
+    for g in grads {
+        for i in 0..3 {
+            last[i] -= g[i];
+        }
+    }
+
+    out.push(last);
+    out
+}
+
+pub(crate) fn zero_blocks(n: usize) -> CenterBlocks {
+    vec![vec![[[0.0; 3]; 3]; n]; n]
+}
+
+// ======================================================
+// One-electron kernels
+// ======================================================
+
+/// Shell-pair first derivative of a one-electron operator
+///
+/// `weights` is the (na × nb) AO weight block, row-major.
+/// Returns the gradients of centers (A, B).
+fn pair_first_derivative<F>(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+    op: F,
+) -> CenterGradients
+where
+    F: Fn(&CartGaussian, &CartGaussian) -> f64,
+{
+    let ga = expand_shell(shell_a);
+    let gb = expand_shell(shell_b);
+    let nb = gb.len();
+
+    let mut grads = vec![[0.0; 3]; 2];
+    let op2 = |g: &[CartGaussian]| op(&g[0], &g[1]);
+
+    for (i, prims_a) in ga.iter().enumerate() {
+        for (j, prims_b) in gb.iter().enumerate() {
+            let w = weights[i * nb + j];
+            if w.abs() < WEIGHT_CUTOFF {
+                continue;
+            }
+
+            for (ca, pa) in prims_a {
+                for (cb, pb) in prims_b {
+                    primitive_first_derivative(&[*pa, *pb], w * ca * cb, &op2, &mut grads);
+                }
+            }
+        }
+    }
+
+    grads
+}
+
+/// Σ_{μν} W_{μν} ∂S_{μν}  — centers (A, B)
+pub fn overlap_first_deriv(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+) -> CenterGradients {
+    pair_first_derivative(shell_a, shell_b, weights, overlap)
+}
+
+/// Σ_{μν} P_{μν} ∂T_{μν}  — centers (A, B)
+pub fn kinetic_first_deriv(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+) -> CenterGradients {
+    pair_first_derivative(shell_a, shell_b, weights, kinetic)
+}
+
+/// Σ_{μν} P_{μν} ∂⟨μ| −Z_C/|r−C| |ν⟩  — centers (A, B, C)
+///
+/// As in `nuclear_attraction_second_deriv`, the operator center follows
+/// from translational invariance.
+pub fn nuclear_attraction_first_deriv(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+    atom: &Atom,
+) -> CenterGradients {
+    let z = atom.nuclear_charge();
+    let c = atom.position;
+    let zeta = atom.nuclear_exponent;
+
+    let ab = pair_first_derivative(shell_a, shell_b, weights, |a, b| {
+        -z * gaussian_charge_potential(a, b, c, zeta)
+    });
+
+    add_translational_center_gradient(&ab)
+}
+
+/// Shell-pair second derivative of a one-electron operator
+///
+/// `weights` is the (na × nb) AO weight block, row-major.
+/// Returns 2×2 center blocks (A, B).
+fn pair_second_derivative<F>(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+    op: F,
+) -> CenterBlocks
+where
+    F: Fn(&CartGaussian, &CartGaussian) -> f64,
+{
+    let ga = expand_shell(shell_a);
+    let gb = expand_shell(shell_b);
+    let nb = gb.len();
+
+    let mut blocks = zero_blocks(2);
+    let op2 = |g: &[CartGaussian]| op(&g[0], &g[1]);
+
+    for (i, prims_a) in ga.iter().enumerate() {
+        for (j, prims_b) in gb.iter().enumerate() {
+            let w = weights[i * nb + j];
+            if w.abs() < WEIGHT_CUTOFF {
+                continue;
+            }
+
+            for (ca, pa) in prims_a {
+                for (cb, pb) in prims_b {
+                    primitive_second_derivative(
+                        &[*pa, *pb],
+                        w * ca * cb,
+                        &op2,
+                        &mut blocks,
+                    );
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Σ_{μν} W_{μν} ∂²S_{μν}  — centers (A, B)
+pub fn overlap_second_deriv(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+) -> CenterBlocks {
+    pair_second_derivative(shell_a, shell_b, weights, overlap)
+}
+
+/// Σ_{μν} P_{μν} ∂²T_{μν}  — centers (A, B)
+pub fn kinetic_second_deriv(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+) -> CenterBlocks {
+    pair_second_derivative(shell_a, shell_b, weights, kinetic)
+}
+
+/// Σ_{μν} P_{μν} ∂²⟨μ| −Z_C/|r−C| |ν⟩  — centers (A, B, C)
+///
+/// The third center is the nucleus carrying the operator (point or
+/// Gaussian); its derivatives (operator derivative) come from
+/// translational invariance.
+pub fn nuclear_attraction_second_deriv(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    weights: &[f64],
+    atom: &Atom,
+) -> CenterBlocks {
+    let z = atom.nuclear_charge();
+    let c = atom.position;
+    let zeta = atom.nuclear_exponent;
+
+    let ab = pair_second_derivative(shell_a, shell_b, weights, |a, b| {
+        -z * gaussian_charge_potential(a, b, c, zeta)
+    });
+
+    add_translational_center(&ab)
+}
+
+// ======================================================
+// Helpers for Hessian drivers
+// ======================================================
+
+/// Atom index of every shell (matched by center position)
+pub fn shell_atom_indices(
+    shell_centers: &[[f64; 3]],
+    atoms: &[Atom],
+) -> Vec<usize> {
+    shell_centers
+        .iter()
+        .map(|c| {
+            atoms
+                .iter()
+                .enumerate()
+                .map(|(a, atom)| {
+                    let dx = atom.position[0] - c[0];
+                    let dy = atom.position[1] - c[1];
+                    let dz = atom.position[2] - c[2];
+                    (a, dx * dx + dy * dy + dz * dz)
+                })
+                .min_by(|x, y| x.1.total_cmp(&y.1))
+                .map(|(a, _)| a)
+                .expect("shell_atom_indices: no atoms")
+        })
+        .collect()
+}
+
+/// Scatter center gradients into per-atom gradients
+pub fn scatter_center_gradients(
+    grad: &mut [[f64; 3]],
+    grads: &CenterGradients,
+    center_atoms: &[usize],
+) {
+    for (x, &ax) in center_atoms.iter().enumerate() {
+        for i in 0..3 {
+            grad[ax][i] += grads[x][i];
+        }
+    }
+}
+
+/// Scatter center blocks into a 3N × 3N Hessian
+pub fn scatter_center_blocks(
+    hess: &mut [Vec<f64>],
+    blocks: &CenterBlocks,
+    center_atoms: &[usize],
+) {
+    for (x, &ax) in center_atoms.iter().enumerate() {
+        for (y, &ay) in center_atoms.iter().enumerate() {
+            for i in 0..3 {
+                for j in 0..3 {
+                    hess[3 * ax + i][3 * ay + j] += blocks[x][y][i][j];
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+//! Effective core potential (ECP) integrals
+//!
+//!   U_C = U_L(r) + Σ_{l<L} Σ_m |lm⟩ (U_l(r) − U_L(r)) ⟨lm|
+//!   U(r) = Σ_k d_k r^{n_k−2} exp(−ζ_k r²)      (r measured from C)
+//!
+//! Semi-numerical evaluation around every ECP center:
+//! - radial Gauss–Legendre quadrature on [0, r_max], r = r_max t²
+//! - angular product quadrature (Gauss–Legendre in cos θ × trapezoid in φ)
+//!
+//! The semilocal projectors act through real spherical harmonics:
+//!   ⟨μ|ΔU_l P_l|ν⟩ = ∫ r² ΔU_l(r) Σ_m c^μ_lm(r) c^ν_lm(r) dr
+//!   c^μ_lm(r)      = ∫ dΩ χ_μ(C + rΩ) Y_lm(Ω)
+//!
+//! Nuclear gradients (`gradients::one_electron::grad_ecp`) reuse the
+//! same quadrature with differentiated basis functions.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::basis::reader::{Ecp, EcpTerm};
+use crate::basis::shell::Shell;
+use crate::integrals::hermite::{expand_shell, CartGaussian};
+use crate::system::atom::Atom;
+
+/// Radial quadrature points
+const N_RADIAL: usize = 128;
+/// Gauss–Legendre points in cos θ (φ uses twice as many)
+const N_THETA: usize = 32;
+/// ζ_min r_max² — exp(−40) is below double precision relevance
+const RADIAL_EXTENT: f64 = 40.0;
+/// Function values below this are skipped on a radial shell
+const VALUE_CUTOFF: f64 = 1e-14;
+
+/// AO-like function as a sum of Cartesian Gaussians
+pub(crate) type GaussianSum = Vec<(f64, CartGaussian)>;
+
+// ======================================================
+// Quadrature
+// ======================================================
+
+/// Gauss–Legendre nodes and weights on [−1, 1]
+fn gauss_legendre(n: usize) -> Vec<(f64, f64)> {
+    let mut pts = Vec::with_capacity(n);
+
+    for i in 0..n {
+        // Chebyshev initial guess, Newton refinement
+        let mut x = (PI * (i as f64 + 0.75) / (n as f64 + 0.5)).cos();
+        let mut dp = 0.0;
+
+        for _ in 0..100 {
+            let mut p0 = 1.0;
+            let mut p1 = x;
+            for k in 2..=n {
+                let p2 = ((2 * k - 1) as f64 * x * p1 - (k - 1) as f64 * p0) / k as f64;
+                p0 = p1;
+                p1 = p2;
+            }
+            dp = n as f64 * (x * p1 - p0) / (x * x - 1.0);
+
+            let dx = p1 / dp;
+            x -= dx;
+            if dx.abs() < 1e-15 {
+                break;
+            }
+        }
+
+        pts.push((x, 2.0 / ((1.0 - x * x) * dp * dp)));
+    }
+
+    pts
+}
+
+/// Real spherical harmonics Y_lm(u), l < lmax, l-major with m = −l … l
+fn real_spherical_harmonics(lmax: usize, u: [f64; 3]) -> Vec<f64> {
+    let mut ylm = Vec::with_capacity(lmax * lmax);
+    if lmax == 0 {
+        return ylm;
+    }
+
+    let x = u[2];
+    let s = (1.0 - x * x).max(0.0).sqrt();
+    let phi = u[1].atan2(u[0]);
+
+    // Associated Legendre P_l^m(x), without Condon–Shortley phase
+    let mut plm = vec![vec![0.0; lmax]; lmax];
+    let mut pmm = 1.0;
+    for m in 0..lmax {
+        if m > 0 {
+            pmm *= (2 * m - 1) as f64 * s;
+        }
+        plm[m][m] = pmm;
+        if m + 1 < lmax {
+            plm[m + 1][m] = x * (2 * m + 1) as f64 * pmm;
+        }
+        for l in (m + 2)..lmax {
+            plm[l][m] = ((2 * l - 1) as f64 * x * plm[l - 1][m]
+                - (l + m - 1) as f64 * plm[l - 2][m])
+                / (l - m) as f64;
+        }
+    }
+
+    for (l, plm_l) in plm.iter().enumerate() {
+        for m in -(l as i32)..=(l as i32) {
+            let am = m.unsigned_abs() as usize;
+
+            // (l−|m|)! / (l+|m|)!
+            let mut ratio = 1.0;
+            for k in (l - am + 1)..=(l + am) {
+                ratio /= k as f64;
+            }
+            let n = ((2 * l + 1) as f64 / (4.0 * PI) * ratio).sqrt();
+
+            let y = match m.cmp(&0) {
+                std::cmp::Ordering::Equal => n * plm_l[0],
+                std::cmp::Ordering::Greater => {
+                    2f64.sqrt() * n * plm_l[am] * (am as f64 * phi).cos()
+                }
+                std::cmp::Ordering::Less => {
+                    2f64.sqrt() * n * plm_l[am] * (am as f64 * phi).sin()
+                }
+            };
+
+            ylm.push(y);
+        }
+    }
+
+    ylm
+}
+
+/// Σ_k d_k r^{n_k−2} exp(−ζ_k r²)
+fn radial_potential(terms: &[EcpTerm], r: f64) -> f64 {
+    terms
+        .iter()
+        .map(|t| t.coefficient * r.powi(t.power - 2) * (-t.exponent * r * r).exp())
+        .sum()
+}
+
+/// Value of an unnormalized Cartesian Gaussian at a point
+fn gaussian_value(g: &CartGaussian, r: [f64; 3]) -> f64 {
+    let d = [r[0] - g.center[0], r[1] - g.center[1], r[2] - g.center[2]];
+    let r2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+
+    d[0].powi(g.l[0] as i32)
+        * d[1].powi(g.l[1] as i32)
+        * d[2].powi(g.l[2] as i32)
+        * (-g.alpha * r2).exp()
+}
+
+// ======================================================
+// One ECP center
+// ======================================================
+
+/// ⟨f_i| U_C |g_j⟩ for two sets of Gaussian sums
+pub(crate) fn ecp_center_block(
+    fa: &[GaussianSum],
+    fb: &[GaussianSum],
+    center: [f64; 3],
+    ecp: &Ecp,
+) -> Vec<Vec<f64>> {
+
+    let mut block = vec![vec![0.0; fb.len()]; fa.len()];
+
+    let zeta_min = ecp
+        .semilocal
+        .iter()
+        .flatten()
+        .chain(ecp.local.iter())
+        .map(|t| t.exponent)
+        .fold(f64::INFINITY, f64::min);
+
+    if !zeta_min.is_finite() {
+        return block;
+    }
+
+    let lmax = ecp.semilocal.len();
+    let r_max = (RADIAL_EXTENT / zeta_min).sqrt();
+
+    // --------------------------------------------------
+    // Angular grid and Y_lm
+    // --------------------------------------------------
+    let n_phi = 2 * N_THETA;
+    let mut angular = Vec::with_capacity(N_THETA * n_phi);
+    for (ct, wt) in gauss_legendre(N_THETA) {
+        let st = (1.0 - ct * ct).sqrt();
+        for k in 0..n_phi {
+            let phi = 2.0 * PI * k as f64 / n_phi as f64;
+            let u = [st * phi.cos(), st * phi.sin(), ct];
+            angular.push((u, wt * 2.0 * PI / n_phi as f64));
+        }
+    }
+
+    let ylm: Vec<Vec<f64>> = angular
+        .iter()
+        .map(|(u, _)| real_spherical_harmonics(lmax, *u))
+        .collect();
+
+    // --------------------------------------------------
+    // Radial shells
+    // --------------------------------------------------
+    let mut va = vec![vec![0.0; angular.len()]; fa.len()];
+    let mut vb = vec![vec![0.0; angular.len()]; fb.len()];
+
+    for (x, wx) in gauss_legendre(N_RADIAL) {
+        let t = 0.5 * (1.0 + x);
+        let r = r_max * t * t;
+        // dr = r_max t dx, times r² from the volume element
+        let wr = wx * r_max * t * r * r;
+
+        let u_local = radial_potential(&ecp.local, r);
+        let u_semi: Vec<f64> = ecp
+            .semilocal
+            .iter()
+            .map(|terms| radial_potential(terms, r))
+            .collect();
+
+        let points: Vec<[f64; 3]> = angular
+            .iter()
+            .map(|(u, _)| {
+                [
+                    center[0] + r * u[0],
+                    center[1] + r * u[1],
+                    center[2] + r * u[2],
+                ]
+            })
+            .collect();
+
+        let active_a = evaluate(fa, &points, &mut va);
+        let active_b = evaluate(fb, &points, &mut vb);
+
+        // Local part: ∫ dΩ f g U_L
+        if u_local != 0.0 {
+            for &i in &active_a {
+                for &j in &active_b {
+                    let s: f64 = angular
+                        .iter()
+                        .enumerate()
+                        .map(|(p, (_, w))| w * va[i][p] * vb[j][p])
+                        .sum();
+                    block[i][j] += wr * u_local * s;
+                }
+            }
+        }
+
+        // Semilocal part: Σ_m c_lm(f) c_lm(g) ΔU_l
+        if lmax > 0 {
+            let project = |v: &Vec<Vec<f64>>, active: &[usize]| {
+                let mut c = vec![vec![0.0; lmax * lmax]; v.len()];
+                for &i in active {
+                    for (p, (_, w)) in angular.iter().enumerate() {
+                        let wv = w * v[i][p];
+                        for (lm, y) in ylm[p].iter().enumerate() {
+                            c[i][lm] += wv * y;
+                        }
+                    }
+                }
+                c
+            };
+
+            let ca = project(&va, &active_a);
+            let cb = project(&vb, &active_b);
+
+            for &i in &active_a {
+                for &j in &active_b {
+                    let mut s = 0.0;
+                    for (l, ul) in u_semi.iter().enumerate() {
+                        let lm0 = l * l;
+                        let proj: f64 = (lm0..lm0 + 2 * l + 1)
+                            .map(|lm| ca[i][lm] * cb[j][lm])
+                            .sum();
+                        s += ul * proj;
+                    }
+                    block[i][j] += wr * s;
+                }
+            }
+        }
+    }
+
+    block
+}
+
+/// Evaluate functions on one radial shell; returns the non-negligible ones
+fn evaluate(
+    funcs: &[GaussianSum],
+    points: &[[f64; 3]],
+    values: &mut [Vec<f64>],
+) -> Vec<usize> {
+    let mut active = Vec::new();
+
+    for (i, f) in funcs.iter().enumerate() {
+        let mut vmax: f64 = 0.0;
+        for (p, r) in points.iter().enumerate() {
+            let v: f64 = f.iter().map(|(c, g)| c * gaussian_value(g, *r)).sum();
+            values[i][p] = v;
+            vmax = vmax.max(v.abs());
+        }
+        if vmax > VALUE_CUTOFF {
+            active.push(i);
+        }
+    }
+
+    active
+}
+
+/// All AOs as Gaussian sums, in global AO order
+pub(crate) fn ao_functions(shells: &[Shell]) -> Vec<GaussianSum> {
+    shells.iter().flat_map(expand_shell).collect()
+}
+
+// ======================================================
+// Public drivers
+// ======================================================
+
+/// AO matrix of all ECPs  Σ_C ⟨μ|U_C|ν⟩
+///
+/// `ecps` is keyed by element symbol; atoms without an entry are
+/// all-electron.
+pub fn ecp_matrix(
+    shells: &[Shell],
+    atoms: &[Atom],
+    ecps: &HashMap<String, Ecp>,
+) -> Vec<Vec<f64>> {
+
+    let aos = ao_functions(shells);
+    let nao = aos.len();
+    let mut u = vec![vec![0.0; nao]; nao];
+
+    for atom in atoms {
+        let Some(ecp) = ecps.get(&atom.symbol) else {
+            continue;
+        };
+
+        let block = ecp_center_block(&aos, &aos, atom.position, ecp);
+
+        for mu in 0..nao {
+            for nu in 0..nao {
+                u[mu][nu] += block[mu][nu];
+            }
+        }
+    }
+
+    u
+}
+
+/// H_core += Σ_C U_C
+pub fn add_ecp_to_hcore(
+    h_core: &mut [Vec<f64>],
+    shells: &[Shell],
+    atoms: &[Atom],
+    ecps: &HashMap<String, Ecp>,
+) {
+    let u = ecp_matrix(shells, atoms, ecps);
+
+    for (h_row, u_row) in h_core.iter_mut().zip(u.iter()) {
+        for (h, v) in h_row.iter_mut().zip(u_row.iter()) {
+            *h += v;
+        }
+    }
+}
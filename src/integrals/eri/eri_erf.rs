@@ -0,0 +1,65 @@
+//! Range-separated (erf-attenuated) electron repulsion integrals
+//!
+//!   (μν| erf(ω r12) / r12 |λσ)
+//!
+//! Same shell-quartet layout as `eri_shell::eri_shell_shell_shell_shell`:
+//!   ((μ * nb + ν) * nc + λ) * nd + σ
+//!
+//! The short-range part follows as (μν|λσ) − (μν|erf|λσ).
+
+use crate::basis::shell::Shell;
+use crate::integrals::eri::eri_shell::eri_shell_quartet_scaled;
+
+/// Long-range 4-shell ERI block
+///
+/// Only the Boys argument changes: with α = pq/(p+q) and
+/// κ = ω² / (ω² + α), F_n(T) → κ^{n+½} F_n(κT) (see `hermite::eri_erf`).
+pub fn eri_erf_shell_quartet(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    shell_c: &Shell,
+    shell_d: &Shell,
+    omega: f64,
+) -> Vec<f64> {
+    let w2 = omega * omega;
+    eri_shell_quartet_scaled(shell_a, shell_b, shell_c, shell_d, &|p, q| {
+        w2 / (w2 + p * q / (p + q))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::integrals::hermite::{eri_erf, expand_shell};
+
+    #[test]
+    fn quartet_matches_primitive_erf_eris() {
+        let shell = |alphas: &[f64], ang: [usize; 3], center: [f64; 3]| {
+            let primitives = alphas.iter().map(|&a| Primitive::new(a, 0.7, center, ang)).collect();
+            Shell::new(primitives, ang, center, 0)
+        };
+        let a = shell(&[1.2, 0.4], [0, 1, 0], [0.0, 0.0, 0.0]);
+        let b = shell(&[0.9], [0, 0, 0], [0.6, 0.1, -0.2]);
+        let omega = 0.33;
+
+        let block = eri_erf_shell_quartet(&a, &b, &b, &a, omega);
+
+        let (ga, gb) = (expand_shell(&a), expand_shell(&b));
+        for (i, ai) in ga.iter().enumerate() {
+            for (l, al) in ga.iter().enumerate() {
+                let mut want = 0.0;
+                for (w1, p1) in ai {
+                    for (w2, p2) in &gb[0] {
+                        for (w3, p3) in &gb[0] {
+                            for (w4, p4) in al {
+                                want += w1 * w2 * w3 * w4 * eri_erf(p1, p2, p3, p4, omega);
+                            }
+                        }
+                    }
+                }
+                assert!((block[i * 3 + l] - want).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! First nuclear derivatives of electron–electron repulsion integrals
+//!
+//! Computes, for one shell quartet,
+//!   Σ_{μνλσ} W_{μνλσ} ∂(μν|λσ) / ∂X_i
+//! with X ∈ {A, B, C, D}, contracted on the fly as in `eri_hess`.
+//! Centers A, B, C are differentiated explicitly (Hermite / MD),
+//! center D follows from translational invariance.
+
+use crate::basis::shell::Shell;
+use crate::integrals::deriv::{
+    add_translational_center_gradient, primitive_first_derivative, CenterGradients,
+};
+use crate::integrals::hermite::{
+    eri_tabulated, expand_shell, CartGaussian, HermitePair, HermiteQuartet,
+};
+
+/// Contraction threshold for quartet weights
+const WEIGHT_CUTOFF: f64 = 1e-14;
+
+/// First nuclear derivative of ERIs for a shell quartet
+///
+/// `weight(μ, ν, λ, σ)` receives shell-local AO indices.
+///
+/// Returns the gradients of centers (A, B, C, D).
+pub fn eri_first_deriv(
+    sh_mu: &Shell,
+    sh_nu: &Shell,
+    sh_la: &Shell,
+    sh_si: &Shell,
+    weight: &dyn Fn(usize, usize, usize, usize) -> f64,
+) -> CenterGradients {
+    eri_first_deriv_scaled(sh_mu, sh_nu, sh_la, sh_si, weight, &|_, _| 1.0)
+}
+
+/// `eri_first_deriv` with an attenuated Boys argument
+///
+/// `scale(p, q)` as in `eri_shell::eri_shell_quartet_scaled`.
+pub fn eri_first_deriv_scaled(
+    sh_mu: &Shell,
+    sh_nu: &Shell,
+    sh_la: &Shell,
+    sh_si: &Shell,
+    weight: &dyn Fn(usize, usize, usize, usize) -> f64,
+    scale: &dyn Fn(f64, f64) -> f64,
+) -> CenterGradients {
+
+    let g_mu = expand_shell(sh_mu);
+    let g_nu = expand_shell(sh_nu);
+    let g_la = expand_shell(sh_la);
+    let g_si = expand_shell(sh_si);
+
+    // AO quartets that survive the weight cutoff
+    let mut quartets = Vec::new();
+    for mu in 0..g_mu.len() {
+        for nu in 0..g_nu.len() {
+            for la in 0..g_la.len() {
+                for si in 0..g_si.len() {
+                    let w = weight(mu, nu, la, si);
+                    if w.abs() >= WEIGHT_CUTOFF {
+                        quartets.push((mu, nu, la, si, w));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut grads = vec![[0.0; 3]; 3];
+
+    if quartets.is_empty() {
+        return add_translational_center_gradient(&grads);
+    }
+
+    // First derivatives raise A, B or C by one unit
+    let l = [sh_mu, sh_nu, sh_la, sh_si].map(|sh| sh.ang.iter().sum::<usize>());
+    let lmax = l.iter().sum::<usize>() + 1;
+
+    // Every AO of a shell shares the primitive exponents
+    for (pm, (_, prim_mu)) in g_mu[0].iter().enumerate() {
+        for (pn, (_, prim_nu)) in g_nu[0].iter().enumerate() {
+            let bra = HermitePair::new(prim_mu, prim_nu, l[0] + 1, l[1] + 1);
+
+            for (pl, (_, prim_la)) in g_la[0].iter().enumerate() {
+                for (ps, (_, prim_si)) in g_si[0].iter().enumerate() {
+                    // σ is held fixed; its derivative comes by invariance
+                    let ket = HermitePair::new(prim_la, prim_si, l[2] + 1, l[3]);
+                    let quartet = HermiteQuartet::new(&bra, &ket, lmax, scale(bra.p, ket.p));
+
+                    for &(mu, nu, la, si, w) in &quartets {
+                        let (c_mu, p_mu) = g_mu[mu][pm];
+                        let (c_nu, p_nu) = g_nu[nu][pn];
+                        let (c_la, p_la) = g_la[la][pl];
+                        let (c_si, p_si) = g_si[si][ps];
+
+                        let op = |g: &[CartGaussian]| {
+                            eri_tabulated(&bra, &ket, &quartet, [g[0].l, g[1].l, g[2].l, p_si.l])
+                        };
+
+                        primitive_first_derivative(
+                            &[p_mu, p_nu, p_la],
+                            w * c_mu * c_nu * c_la * c_si,
+                            &op,
+                            &mut grads,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    add_translational_center_gradient(&grads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::integrals::eri::eri_erf::eri_erf_shell_quartet;
+
+    fn shells(r: [[f64; 3]; 4]) -> [Shell; 4] {
+        let shell = |alphas: &[f64], ang: [usize; 3], center: [f64; 3]| {
+            let primitives = alphas.iter().map(|&a| Primitive::new(a, 0.6, center, ang)).collect();
+            Shell::new(primitives, ang, center, 0)
+        };
+        [
+            shell(&[1.1, 0.4], [1, 0, 0], r[0]),
+            shell(&[0.8], [0, 0, 0], r[1]),
+            shell(&[0.9], [0, 1, 1], r[2]),
+            shell(&[1.3, 0.5], [0, 0, 1], r[3]),
+        ]
+    }
+
+    #[test]
+    fn erf_gradient_matches_finite_differences() {
+        let base = [[0.0, 0.1, -0.2], [0.5, -0.3, 0.4], [-0.4, 0.6, 0.8], [0.3, 0.2, -0.6]];
+        let omega = 0.4;
+        let scale = |p: f64, q: f64| omega * omega / (omega * omega + p * q / (p + q));
+
+        let sh = shells(base);
+        let weight =
+            |i: usize, j: usize, k: usize, l: usize| 0.3 + 0.1 * (i + 2 * j + k + 3 * l) as f64;
+        let grads = eri_first_deriv_scaled(&sh[0], &sh[1], &sh[2], &sh[3], &weight, &scale);
+
+        let energy = |r| {
+            let sh = shells(r);
+            let block = eri_erf_shell_quartet(&sh[0], &sh[1], &sh[2], &sh[3], omega);
+            let n = sh.each_ref().map(|s| s.n_orbitals());
+            let mut e = 0.0;
+            for i in 0..n[0] {
+                for j in 0..n[1] {
+                    for k in 0..n[2] {
+                        for l in 0..n[3] {
+                            e += weight(i, j, k, l) * block[((i * n[1] + j) * n[2] + k) * n[3] + l];
+                        }
+                    }
+                }
+            }
+            e
+        };
+
+        let h = 1e-5;
+        for x in 0..4 {
+            for k in 0..3 {
+                let (mut plus, mut minus) = (base, base);
+                plus[x][k] += h;
+                minus[x][k] -= h;
+                let fd = (energy(plus) - energy(minus)) / (2.0 * h);
+                assert!((grads[x][k] - fd).abs() < 1e-7, "{} {}: {} vs {}", x, k, grads[x][k], fd);
+            }
+        }
+    }
+}
@@ -1,172 +1,104 @@
 //! Second nuclear derivatives of electron–electron repulsion integrals
 //!
-//! Computes:
-//!   d²(μν|λσ) / dR_Ai dR_Bj
+//! Computes, for one shell quartet,
+//!   Σ_{μνλσ} W_{μνλσ} ∂²(μν|λσ) / ∂X_i ∂Y_j
+//! with X, Y ∈ {A, B, C, D}.
 //!
-//! This module is a low-level AO kernel provider.
-//! It must NOT know about density matrices or Hessians.
+//! The derivative integrals are contracted on the fly with the
+//! caller's weights, so no 8-index tensor is ever allocated.
+//! Centers A, B, C are differentiated explicitly (Hermite / MD),
+//! center D follows from translational invariance.
 //!
-//! Mathematical framework:
-//! - Obara–Saika / VRR + HRR
-//! - Extension of first-derivative ERI kernels
-//!
-//! Tensor layout returned:
-//!   d2ERI[A][B][i][j][μ][ν][λ][σ]
+//! Per primitive quartet the Hermite E coefficients of both pairs and
+//! the R_{tuv} table are built once, two angular momenta above the
+//! shells, and reused by every Cartesian component and derivative term.
 
 use crate::basis::shell::Shell;
-use crate::integrals::eri::{eri_ssss};
-use crate::integrals::eri_grad::{eri_first_deriv};
+use crate::integrals::deriv::{
+    add_translational_center, primitive_second_derivative, zero_blocks, CenterBlocks,
+};
+use crate::integrals::hermite::{
+    eri_tabulated, expand_shell, CartGaussian, HermitePair, HermiteQuartet,
+};
+
+/// Contraction threshold for quartet weights
+const WEIGHT_CUTOFF: f64 = 1e-14;
 
 /// Second nuclear derivative of ERIs for a shell quartet
 ///
-/// sh_mu, sh_nu, sh_la, sh_si : shells
-/// natoms                    : number of nuclei
+/// `weight(μ, ν, λ, σ)` receives shell-local AO indices.
 ///
-/// Returns:
-///   d2ERI[A][B][i][j][μ][ν][λ][σ]
+/// Returns 4×4 center blocks (A, B, C, D).
 pub fn eri_second_deriv(
     sh_mu: &Shell,
     sh_nu: &Shell,
     sh_la: &Shell,
     sh_si: &Shell,
-    natoms: usize,
-) -> Vec<Vec<Vec<Vec<Vec<Vec<f64>>>>>> {
-
-    let nmu = sh_mu.orbitals.len();
-    let nnu = sh_nu.orbitals.len();
-    let nla = sh_la.orbitals.len();
-    let nsi = sh_si.orbitals.len();
-
-    // Allocate tensor
-    let mut d2eri = vec![
-        vec![
-            vec![
-                vec![
-                    vec![vec![0.0; nsi]; nla];
-                    nnu
-                ];
-                nmu
-            ];
-            3
-        ];
-        natoms
-    ];
-
-    // --------------------------------------------------
-    // Base case: ssss
-    // --------------------------------------------------
-    if sh_mu.angular_momentum == 0 &&
-       sh_nu.angular_momentum == 0 &&
-       sh_la.angular_momentum == 0 &&
-       sh_si.angular_momentum == 0 {
-
-        let d1 = eri_first_deriv(sh_mu, sh_nu, sh_la, sh_si, natoms);
-
-        for a in 0..natoms {
-            for b in 0..natoms {
-                for ia in 0..3 {
-                    for ib in 0..3 {
-                        let val =
-                            eri_ssss::second_deriv(
-                                sh_mu, sh_nu, sh_la, sh_si,
-                                a, b, ia, ib
-                            );
-
-                        for mu in 0..nmu {
-                            for nu in 0..nnu {
-                                for la in 0..nla {
-                                    for si in 0..nsi {
-                                        d2eri[a][b][ia][ib][mu][nu][la][si] = val;
-                                    }
-                                }
-                            }
-                        }
+    weight: &dyn Fn(usize, usize, usize, usize) -> f64,
+) -> CenterBlocks {
+
+    let g_mu = expand_shell(sh_mu);
+    let g_nu = expand_shell(sh_nu);
+    let g_la = expand_shell(sh_la);
+    let g_si = expand_shell(sh_si);
+
+    // AO quartets that survive the weight cutoff
+    let mut quartets = Vec::new();
+    for mu in 0..g_mu.len() {
+        for nu in 0..g_nu.len() {
+            for la in 0..g_la.len() {
+                for si in 0..g_si.len() {
+                    let w = weight(mu, nu, la, si);
+                    if w.abs() >= WEIGHT_CUTOFF {
+                        quartets.push((mu, nu, la, si, w));
                     }
                 }
             }
         }
-        return d2eri;
     }
 
-    // --------------------------------------------------
-    // General VRR/HRR recursion
-    // --------------------------------------------------
+    let mut blocks = zero_blocks(3);
 
-    for a in 0..natoms {
-        for b in 0..natoms {
-            for ia in 0..3 {
-                for ib in 0..3 {
-
-                    // VRR: raise angular momentum on bra/ket
-                    let vrr =
-                        eri_vrr_second(
-                            sh_mu,
-                            sh_nu,
-                            sh_la,
-                            sh_si,
-                            a,
-                            b,
-                            ia,
-                            ib,
-                        );
+    if quartets.is_empty() {
+        return add_translational_center(&blocks);
+    }
 
-                    // HRR redistribution
-                    let hrr =
-                        eri_hrr_second(
-                            sh_mu,
-                            sh_nu,
-                            sh_la,
-                            sh_si,
-                            vrr,
+    // Second derivatives raise A, B or C by up to two units
+    let l = [sh_mu, sh_nu, sh_la, sh_si].map(|sh| sh.ang.iter().sum::<usize>());
+    let lmax = l.iter().sum::<usize>() + 2;
+
+    // Every AO of a shell shares the primitive exponents
+    for (pm, (_, prim_mu)) in g_mu[0].iter().enumerate() {
+        for (pn, (_, prim_nu)) in g_nu[0].iter().enumerate() {
+            let bra = HermitePair::new(prim_mu, prim_nu, l[0] + 2, l[1] + 2);
+
+            for (pl, (_, prim_la)) in g_la[0].iter().enumerate() {
+                for (ps, (_, prim_si)) in g_si[0].iter().enumerate() {
+                    // σ is held fixed; its derivatives come by invariance
+                    let ket = HermitePair::new(prim_la, prim_si, l[2] + 2, l[3]);
+                    let quartet = HermiteQuartet::new(&bra, &ket, lmax, 1.0);
+
+                    for &(mu, nu, la, si, w) in &quartets {
+                        let (c_mu, p_mu) = g_mu[mu][pm];
+                        let (c_nu, p_nu) = g_nu[nu][pn];
+                        let (c_la, p_la) = g_la[la][pl];
+                        let (c_si, p_si) = g_si[si][ps];
+
+                        let op = |g: &[CartGaussian]| {
+                            eri_tabulated(&bra, &ket, &quartet, [g[0].l, g[1].l, g[2].l, p_si.l])
+                        };
+
+                        primitive_second_derivative(
+                            &[p_mu, p_nu, p_la],
+                            w * c_mu * c_nu * c_la * c_si,
+                            &op,
+                            &mut blocks,
                         );
-
-                    // Store
-                    for mu in 0..nmu {
-                        for nu in 0..nnu {
-                            for la in 0..nla {
-                                for si in 0..nsi {
-                                    d2eri[a][b][ia][ib][mu][nu][la][si] =
-                                        hrr[mu][nu][la][si];
-                                }
-                            }
-                        }
                     }
                 }
             }
         }
     }
 
-    d2eri
+    add_translational_center(&blocks)
 }
-
-// ======================================================
-// Internal helpers (to be implemented in eri_vrr.rs etc.)
-// ======================================================
-
-/// Second-derivative VRR kernel
-///
-/// Returns primitive ERIs for given angular momentum combination
-fn eri_vrr_second(
-    _sh_mu: &Shell,
-    _sh_nu: &Shell,
-    _sh_la: &Shell,
-    _sh_si: &Shell,
-    _atom_a: usize,
-    _atom_b: usize,
-    _ia: usize,
-    _ib: usize,
-) -> Vec<Vec<Vec<Vec<f64>>>> {
-    unimplemented!("Second-derivative VRR kernel not yet implemented");
-}
-
-/// Second-derivative HRR redistribution
-fn eri_hrr_second(
-    _sh_mu: &Shell,
-    _sh_nu: &Shell,
-    _sh_la: &Shell,
-    _sh_si: &Shell,
-    _vrr: Vec<Vec<Vec<Vec<f64>>>>,
-) -> Vec<Vec<Vec<Vec<f64>>>> {
-    unimplemented!("Second-derivative HRR kernel not yet implemented");
-}
-
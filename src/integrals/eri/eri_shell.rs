@@ -1,15 +1,15 @@
 //! Shell–shell electron repulsion integrals (ERI)
 //!
-//! Devuelve el bloque (μν|λσ) para dos shells
+//! Devuelve el bloque (μν|λσ) para dos o cuatro shells, evaluado con
+//! las integrales de McMurchie–Davidson (`integrals::hermite`) con la
+//! misma contracción y normalización que `hermite::expand_shell`.
 
 use crate::basis::shell::Shell;
-use crate::basis::contracted::Contracted;
-use crate::integrals::eri::eri_contracted::eri_contracted;
-use crate::integrals::schwarz::schwarz_shell_pair;
+use crate::integrals::hermite::{eri_tabulated, CartGaussian, HermitePair, HermiteQuartet};
 
 /// ERI block between two shells
 ///
-/// (μν|λσ) where μ,ν ∈ shell A and λ,σ ∈ shell B
+/// (μν|μν) where μ ∈ shell A and ν ∈ shell B
 pub fn eri_shell_shell(
     shell_a: &Shell,
     shell_b: &Shell,
@@ -18,65 +18,87 @@ pub fn eri_shell_shell(
     let na = shell_a.n_orbitals();
     let nb = shell_b.n_orbitals();
 
-    let mut eri = vec![vec![0.0_f64; nb]; na];
+    let block = eri_shell_shell_shell_shell(shell_a, shell_b, shell_a, shell_b);
 
-    // Cada AO cartesiano comparte el mismo conjunto de primitivas
-    let ca = Contracted::new(shell_a.primitives.clone());
-    let cb = Contracted::new(shell_b.primitives.clone());
-
-    for i in 0..na {
-        for j in 0..nb {
-            eri[i][j] = eri_contracted(&ca, &cb, &ca, &cb);
-        }
-    }
-
-    eri
+    (0..na)
+        .map(|i| (0..nb).map(|j| block[((i * nb + j) * na + i) * nb + j]).collect())
+        .collect()
 }
 
 /// 4-shell ERI block (μν|λσ)
 ///
 /// Devuelve el tensor aplanado:
 /// ((μ * nb + ν) * nc + λ) * nd + σ
+///
+/// Sin screening: los llamadores (J/K) aplican Schwarz por cuarteto.
 pub fn eri_shell_shell_shell_shell(
     shell_a: &Shell,
     shell_b: &Shell,
     shell_c: &Shell,
     shell_d: &Shell,
 ) -> Vec<f64> {
+    eri_shell_quartet_scaled(shell_a, shell_b, shell_c, shell_d, &|_, _| 1.0)
+}
 
-    let na = shell_a.n_orbitals();
-    let nb = shell_b.n_orbitals();
-    let nc = shell_c.n_orbitals();
-    let nd = shell_d.n_orbitals();
-
-    let mut eri = vec![0.0_f64; na * nb * nc * nd];
-
-    // Schwarz screening (correcto)
-    let bound_ab = schwarz_shell_pair(shell_a, shell_b);
-    let bound_cd = schwarz_shell_pair(shell_c, shell_d);
-    let cutoff = 1e-12;
+/// 4-shell ERI block with an attenuated Boys argument
+///
+/// `scale(p, q)` gives κ of every primitive quartet from the bra and
+/// ket exponents (see `hermite::eri_scaled`); κ = 1 is (μν|λσ).
+pub(crate) fn eri_shell_quartet_scaled(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    shell_c: &Shell,
+    shell_d: &Shell,
+    scale: &dyn Fn(f64, f64) -> f64,
+) -> Vec<f64> {
 
-    if bound_ab * bound_cd < cutoff {
-        return eri;
-    }
+    let shells = [shell_a, shell_b, shell_c, shell_d];
+    let comps = shells.map(|s| s.cartesian_components());
+    let l = shells.map(|s| s.ang.iter().sum::<usize>());
+    let [na, nb, nc, nd] = comps.each_ref().map(|c| c.len());
 
-    let ao_a = Contracted::new(shell_a.primitives.clone());
-    let ao_b = Contracted::new(shell_b.primitives.clone());
-    let ao_c = Contracted::new(shell_c.primitives.clone());
-    let ao_d = Contracted::new(shell_d.primitives.clone());
+    let mut eri = vec![0.0_f64; na * nb * nc * nd];
 
     let idx = |i, j, k, l| ((i * nb + j) * nc + k) * nd + l;
 
-    for i in 0..na {
-        for j in 0..nb {
-            for k in 0..nc {
-                for l in 0..nd {
-                    eri[idx(i, j, k, l)] = eri_contracted(
-                        &ao_a,
-                        &ao_b,
-                        &ao_c,
-                        &ao_d,
-                    );
+    // Primitivas sin normalizar (componente s) y sus pesos c·N
+    let gaussians = shells.map(|s| {
+        s.primitives
+            .iter()
+            .map(|p| {
+                (
+                    p.coefficient() * p.norm(),
+                    CartGaussian::new(p.exponent(), [0, 0, 0], s.center),
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for (wa, ga) in &gaussians[0] {
+        for (wb, gb) in &gaussians[1] {
+            let bra = HermitePair::new(ga, gb, l[0], l[1]);
+
+            for (wc, gc) in &gaussians[2] {
+                for (wd, gd) in &gaussians[3] {
+                    let ket = HermitePair::new(gc, gd, l[2], l[3]);
+                    let kappa = scale(bra.p, ket.p);
+                    let quartet = HermiteQuartet::new(&bra, &ket, l.iter().sum(), kappa);
+                    let w = wa * wb * wc * wd;
+
+                    for (i, la) in comps[0].iter().enumerate() {
+                        for (j, lb) in comps[1].iter().enumerate() {
+                            for (k, lc) in comps[2].iter().enumerate() {
+                                for (m, ld) in comps[3].iter().enumerate() {
+                                    eri[idx(i, j, k, m)] += w * eri_tabulated(
+                                        &bra,
+                                        &ket,
+                                        &quartet,
+                                        [*la, *lb, *lc, *ld],
+                                    );
+                                }
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -85,3 +107,52 @@ pub fn eri_shell_shell_shell_shell(
     eri
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::integrals::hermite::{eri, expand_shell};
+
+    fn shell(alphas: &[f64], ang: [usize; 3], center: [f64; 3]) -> Shell {
+        let primitives = alphas
+            .iter()
+            .map(|&a| Primitive::new(a, 0.5, center, ang))
+            .collect();
+        Shell::new(primitives, ang, center, 0)
+    }
+
+    #[test]
+    fn quartet_matches_primitive_eris() {
+        let shells = [
+            shell(&[1.1, 0.3], [1, 0, 0], [0.0, 0.0, 0.0]),
+            shell(&[0.8], [0, 0, 0], [0.4, -0.2, 0.3]),
+            shell(&[0.6, 1.9], [0, 2, 0], [-0.3, 0.5, 0.9]),
+            shell(&[1.4], [0, 0, 1], [0.2, 0.1, -0.5]),
+        ];
+        let block = eri_shell_shell_shell_shell(&shells[0], &shells[1], &shells[2], &shells[3]);
+
+        let g = shells.each_ref().map(expand_shell);
+        let mut n = 0;
+        for a in &g[0] {
+            for b in &g[1] {
+                for c in &g[2] {
+                    for d in &g[3] {
+                        let mut want = 0.0;
+                        for (wa, pa) in a {
+                            for (wb, pb) in b {
+                                for (wc, pc) in c {
+                                    for (wd, pd) in d {
+                                        want += wa * wb * wc * wd * eri(pa, pb, pc, pd);
+                                    }
+                                }
+                            }
+                        }
+                        assert!((block[n] - want).abs() < 1e-12);
+                        n += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(n, block.len());
+    }
+}
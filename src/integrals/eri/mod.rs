@@ -4,3 +4,6 @@ pub mod eri_hrr;
 pub mod eri_contracted;
 pub mod eri_ssss;
 pub mod eri_shell;
+pub mod eri_hess;
+pub mod eri_grad;
+pub mod eri_erf;
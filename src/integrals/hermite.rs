@@ -0,0 +1,625 @@
+//! McMurchie–Davidson integrals over Cartesian Gaussians
+//!
+//! Works on *unnormalized* primitives
+//!   G(r) = (x-Ax)^l (y-Ay)^m (z-Az)^n exp(-α |r-A|²)
+//! of arbitrary angular momentum and provides:
+//! - overlap            ⟨a|b⟩
+//! - kinetic            ⟨a| -½ ∇² |b⟩
+//! - point potential    ⟨a| 1/|r-C| |b⟩
+//! - Gaussian-charge potential ⟨a| erf(√ζ|r-C|)/|r-C| |b⟩
+//! - electron repulsion (ab|cd)
+//! - long-range electron repulsion (ab| erf(ω r12)/r12 |cd)
+//!
+//! Normalization and contraction coefficients are applied by callers
+//! (see `expand_shell`).
+
+use std::f64::consts::PI;
+
+use crate::basis::shell::Shell;
+use crate::integrals::boys::boys;
+
+/// One unnormalized Cartesian Gaussian
+#[derive(Clone, Copy, Debug)]
+pub struct CartGaussian {
+    pub alpha: f64,
+    pub l: [usize; 3],
+    pub center: [f64; 3],
+}
+
+impl CartGaussian {
+    pub fn new(alpha: f64, l: [usize; 3], center: [f64; 3]) -> Self {
+        Self { alpha, l, center }
+    }
+
+    #[inline]
+    pub fn l_total(&self) -> usize {
+        self.l[0] + self.l[1] + self.l[2]
+    }
+}
+
+/// Expand a shell into its Cartesian components
+///
+/// Returns, for every AO of the shell, the list of
+/// (contraction weight, primitive) pairs.
+pub fn expand_shell(shell: &Shell) -> Vec<Vec<(f64, CartGaussian)>> {
+    shell
+        .cartesian_components()
+        .into_iter()
+        .map(|l| {
+            shell
+                .primitives
+                .iter()
+                .map(|p| {
+                    (
+                        p.coefficient() * p.norm(),
+                        CartGaussian::new(p.exponent(), l, shell.center),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Contract a primitive kernel over a shell pair
+///
+/// Returns the AO block K_{μν} = Σ c_μ c_ν kernel(a, b) of the pair.
+pub fn shell_pair_block<F>(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    kernel: F,
+) -> Vec<Vec<f64>>
+where
+    F: Fn(&CartGaussian, &CartGaussian) -> f64,
+{
+    let ga = expand_shell(shell_a);
+    let gb = expand_shell(shell_b);
+
+    let mut block = vec![vec![0.0; gb.len()]; ga.len()];
+
+    for (i, prims_a) in ga.iter().enumerate() {
+        for (j, prims_b) in gb.iter().enumerate() {
+            let mut val = 0.0;
+            for (ca, pa) in prims_a {
+                for (cb, pb) in prims_b {
+                    val += ca * cb * kernel(pa, pb);
+                }
+            }
+            block[i][j] = val;
+        }
+    }
+
+    block
+}
+
+// ======================================================
+// Hermite expansion coefficients
+// ======================================================
+
+/// Hermite expansion coefficients E^{ij}_t of one primitive pair along
+/// one Cartesian direction, for all 0 ≤ i ≤ imax, 0 ≤ j ≤ jmax
+///
+/// Built bottom-up with the usual two-term recurrences, so every
+/// coefficient costs O(1) once the table exists.
+#[derive(Clone, Debug)]
+pub struct HermiteTable {
+    jmax: usize,
+    tdim: usize,
+    e: Vec<f64>,
+}
+
+impl HermiteTable {
+    /// qx = A_x − B_x
+    pub fn new(imax: usize, jmax: usize, qx: f64, a: f64, b: f64) -> Self {
+        let p = a + b;
+        let mu = a * b / p;
+        let tdim = imax + jmax + 1;
+
+        let mut table = Self {
+            jmax,
+            tdim,
+            e: vec![0.0; (imax + 1) * (jmax + 1) * tdim],
+        };
+
+        let k00 = table.index(0, 0, 0);
+        table.e[k00] = (-mu * qx * qx).exp();
+
+        for i in 0..=imax {
+            for j in 0..=jmax {
+                if i == 0 && j == 0 {
+                    continue;
+                }
+
+                for t in 0..=(i + j) {
+                    let val = if j == 0 {
+                        // decrement i
+                        0.5 / p * table.at(i - 1, j, t as isize - 1)
+                            - mu * qx / a * table.at(i - 1, j, t as isize)
+                            + (t + 1) as f64 * table.at(i - 1, j, t as isize + 1)
+                    } else {
+                        // decrement j
+                        0.5 / p * table.at(i, j - 1, t as isize - 1)
+                            + mu * qx / b * table.at(i, j - 1, t as isize)
+                            + (t + 1) as f64 * table.at(i, j - 1, t as isize + 1)
+                    };
+
+                    let k = table.index(i, j, t);
+                    table.e[k] = val;
+                }
+            }
+        }
+
+        table
+    }
+
+    #[inline]
+    fn index(&self, i: usize, j: usize, t: usize) -> usize {
+        (i * (self.jmax + 1) + j) * self.tdim + t
+    }
+
+    #[inline]
+    fn at(&self, i: usize, j: usize, t: isize) -> f64 {
+        if t < 0 || t as usize > i + j {
+            0.0
+        } else {
+            self.e[self.index(i, j, t as usize)]
+        }
+    }
+
+    /// E^{ij}_t (zero outside 0 ≤ t ≤ i + j)
+    #[inline]
+    pub fn get(&self, i: usize, j: usize, t: usize) -> f64 {
+        self.at(i, j, t as isize)
+    }
+
+    /// E^{ij}_t for t = 0..=i+j
+    #[inline]
+    pub fn row(&self, i: usize, j: usize) -> &[f64] {
+        let start = self.index(i, j, 0);
+        &self.e[start..=start + i + j]
+    }
+}
+
+/// Hermite expansion coefficient E^{ij}_t
+///
+/// qx = A_x − B_x
+pub fn hermite_e(i: i32, j: i32, t: i32, qx: f64, a: f64, b: f64) -> f64 {
+    if t < 0 || t > i + j || i < 0 || j < 0 {
+        return 0.0;
+    }
+
+    let (i, j) = (i as usize, j as usize);
+    HermiteTable::new(i, j, qx, a, b).get(i, j, t as usize)
+}
+
+/// All E^{ij}_t for one Cartesian direction, t = 0..=i+j
+fn hermite_e_row(i: usize, j: usize, qx: f64, a: f64, b: f64) -> Vec<f64> {
+    HermiteTable::new(i, j, qx, a, b).row(i, j).to_vec()
+}
+
+/// Hermite data of one primitive pair (exponents and centers only)
+///
+/// Tabulates E^{ij}_t in x, y, z up to (imax, jmax), so every Cartesian
+/// component of the pair — and every derivative Gaussian raised from it —
+/// is expanded without recomputing coefficients.
+#[derive(Clone, Debug)]
+pub struct HermitePair {
+    /// p = α_a + α_b
+    pub p: f64,
+    /// Gaussian product center P
+    pub center: [f64; 3],
+    tables: [HermiteTable; 3],
+}
+
+impl HermitePair {
+    pub fn new(a: &CartGaussian, b: &CartGaussian, imax: usize, jmax: usize) -> Self {
+        let p = a.alpha + b.alpha;
+        let center = [0, 1, 2].map(|k| (a.alpha * a.center[k] + b.alpha * b.center[k]) / p);
+        let tables = [0, 1, 2].map(|k| {
+            HermiteTable::new(imax, jmax, a.center[k] - b.center[k], a.alpha, b.alpha)
+        });
+
+        Self { p, center, tables }
+    }
+
+    /// E^{i_k j_k}_t rows in x, y, z
+    #[inline]
+    fn rows(&self, la: [usize; 3], lb: [usize; 3]) -> [&[f64]; 3] {
+        [0, 1, 2].map(|k| self.tables[k].row(la[k], lb[k]))
+    }
+}
+
+// ======================================================
+// Hermite Coulomb integrals R_{tuv}
+// ======================================================
+
+/// Table of Hermite Coulomb integrals R^0_{tuv}, t+u+v ≤ lmax
+///
+/// `alpha`  : reduced exponent of the Coulomb kernel
+/// `pc`     : P − C (or P − Q for ERIs)
+/// `scale`  : attenuation factor κ ∈ (0, 1]; κ = 1 is the bare 1/r.
+///            The argument becomes κ·T and every order n picks up κ^n.
+///
+/// Layout: index (t * dim + u) * dim + v with dim = lmax + 1
+pub fn hermite_r_table(
+    lmax: usize,
+    alpha: f64,
+    pc: [f64; 3],
+    scale: f64,
+) -> Vec<f64> {
+    let dim = lmax + 1;
+    let idx = |t: usize, u: usize, v: usize| (t * dim + u) * dim + v;
+
+    let rpc2 = pc[0] * pc[0] + pc[1] * pc[1] + pc[2] * pc[2];
+    let x = scale * alpha * rpc2;
+
+    let mut prev = vec![0.0_f64; dim * dim * dim];
+
+    for n in (0..=lmax).rev() {
+        let mut cur = vec![0.0_f64; dim * dim * dim];
+        let lrem = lmax - n;
+
+        for t in 0..=lrem {
+            for u in 0..=(lrem - t) {
+                for v in 0..=(lrem - t - u) {
+                    let val = if t > 0 {
+                        let mut r = pc[0] * prev[idx(t - 1, u, v)];
+                        if t > 1 {
+                            r += (t - 1) as f64 * prev[idx(t - 2, u, v)];
+                        }
+                        r
+                    } else if u > 0 {
+                        let mut r = pc[1] * prev[idx(t, u - 1, v)];
+                        if u > 1 {
+                            r += (u - 1) as f64 * prev[idx(t, u - 2, v)];
+                        }
+                        r
+                    } else if v > 0 {
+                        let mut r = pc[2] * prev[idx(t, u, v - 1)];
+                        if v > 1 {
+                            r += (v - 1) as f64 * prev[idx(t, u, v - 2)];
+                        }
+                        r
+                    } else {
+                        (-2.0 * scale * alpha).powi(n as i32) * boys(n, x)
+                    };
+
+                    cur[idx(t, u, v)] = val;
+                }
+            }
+        }
+
+        prev = cur;
+    }
+
+    prev
+}
+
+// ======================================================
+// One-electron primitives
+// ======================================================
+
+/// Primitive overlap ⟨a|b⟩
+pub fn overlap(a: &CartGaussian, b: &CartGaussian) -> f64 {
+    let p = a.alpha + b.alpha;
+    let mut s = (PI / p).powf(1.5);
+
+    for k in 0..3 {
+        s *= hermite_e(
+            a.l[k] as i32,
+            b.l[k] as i32,
+            0,
+            a.center[k] - b.center[k],
+            a.alpha,
+            b.alpha,
+        );
+    }
+
+    s
+}
+
+/// One-dimensional overlap factor E^{ij}_0 √(π/p)
+#[inline]
+pub fn overlap_1d(i: i32, j: i32, qx: f64, a: f64, b: f64) -> f64 {
+    if i < 0 || j < 0 {
+        return 0.0;
+    }
+    hermite_e(i, j, 0, qx, a, b) * (PI / (a + b)).sqrt()
+}
+
+/// Primitive kinetic energy ⟨a| -½ ∇² |b⟩
+pub fn kinetic(a: &CartGaussian, b: &CartGaussian) -> f64 {
+    let beta = b.alpha;
+
+    let mut s = [0.0; 3];
+    let mut t = [0.0; 3];
+
+    for k in 0..3 {
+        let i = a.l[k] as i32;
+        let j = b.l[k] as i32;
+        let qx = a.center[k] - b.center[k];
+
+        s[k] = overlap_1d(i, j, qx, a.alpha, beta);
+
+        t[k] = beta * (2 * j + 1) as f64 * s[k]
+            - 2.0 * beta * beta * overlap_1d(i, j + 2, qx, a.alpha, beta)
+            - 0.5 * (j * (j - 1)) as f64 * overlap_1d(i, j - 2, qx, a.alpha, beta);
+    }
+
+    t[0] * s[1] * s[2] + s[0] * t[1] * s[2] + s[0] * s[1] * t[2]
+}
+
+/// Primitive Coulomb potential of a unit point charge ⟨a| 1/|r-C| |b⟩
+pub fn potential(a: &CartGaussian, b: &CartGaussian, c: [f64; 3]) -> f64 {
+    potential_scaled(a, b, c, 1.0)
+}
+
+/// Attenuated point potential
+///
+/// With κ = ω² / (ω² + p) this is ⟨a| erf(ω|r-C|) / |r-C| |b⟩.
+/// κ = 1 reproduces `potential`.
+pub fn potential_scaled(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    c: [f64; 3],
+    scale: f64,
+) -> f64 {
+    potential_kernel(a, b, c, scale, [0, 0, 0])
+}
+
+/// Potential of a unit Gaussian charge (ζ/π)^{3/2} exp(−ζ|r−C|²)
+///
+///   ⟨a| erf(√ζ |r-C|) / |r-C| |b⟩,   κ = ζ / (ζ + p)
+///
+/// `zeta = None` is a point charge.
+pub fn gaussian_charge_potential(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    c: [f64; 3],
+    zeta: Option<f64>,
+) -> f64 {
+    match zeta {
+        None => potential(a, b, c),
+        Some(z) => potential_scaled(a, b, c, z / (z + a.alpha + b.alpha)),
+    }
+}
+
+/// Derivative of the point potential with respect to the point C
+///
+///   ∂^{dx+dy+dz} / ∂C_x^dx ∂C_y^dy ∂C_z^dz  ⟨a| 1/|r-C| |b⟩
+///
+/// First derivatives give the electric-field integrals
+/// ⟨a| (r-C)_k / |r-C|³ |b⟩, second derivatives the field gradient.
+pub fn potential_derivative(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    c: [f64; 3],
+    d: [usize; 3],
+) -> f64 {
+    potential_kernel(a, b, c, 1.0, d)
+}
+
+fn potential_kernel(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    c: [f64; 3],
+    scale: f64,
+    d: [usize; 3],
+) -> f64 {
+    let p = a.alpha + b.alpha;
+
+    let pc = [
+        (a.alpha * a.center[0] + b.alpha * b.center[0]) / p - c[0],
+        (a.alpha * a.center[1] + b.alpha * b.center[1]) / p - c[1],
+        (a.alpha * a.center[2] + b.alpha * b.center[2]) / p - c[2],
+    ];
+
+    let ex = hermite_e_row(a.l[0], b.l[0], a.center[0] - b.center[0], a.alpha, b.alpha);
+    let ey = hermite_e_row(a.l[1], b.l[1], a.center[1] - b.center[1], a.alpha, b.alpha);
+    let ez = hermite_e_row(a.l[2], b.l[2], a.center[2] - b.center[2], a.alpha, b.alpha);
+
+    let nd = d[0] + d[1] + d[2];
+    let lmax = a.l_total() + b.l_total() + nd;
+    let dim = lmax + 1;
+    let r = hermite_r_table(lmax, p, pc, scale);
+
+    let mut val = 0.0;
+    for (t, et) in ex.iter().enumerate() {
+        for (u, eu) in ey.iter().enumerate() {
+            for (v, ev) in ez.iter().enumerate() {
+                val += et * eu * ev
+                    * r[((t + d[0]) * dim + (u + d[1])) * dim + (v + d[2])];
+            }
+        }
+    }
+
+    // ∂/∂C_k R_{tuv}(P − C) = −R_{t+1_k}
+    let sign = if nd.is_multiple_of(2) { 1.0 } else { -1.0 };
+
+    sign * 2.0 * PI / p * scale.sqrt() * val
+}
+
+// ======================================================
+// Two-electron primitives
+// ======================================================
+
+/// Primitive electron repulsion integral (ab|cd)
+pub fn eri(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    c: &CartGaussian,
+    d: &CartGaussian,
+) -> f64 {
+    eri_scaled(a, b, c, d, 1.0)
+}
+
+/// Primitive ERI with an attenuated Boys argument
+///
+/// κ = 1 is the bare Coulomb operator.
+pub fn eri_scaled(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    c: &CartGaussian,
+    d: &CartGaussian,
+    scale: f64,
+) -> f64 {
+    let bra = HermitePair::new(a, b, a.l_total(), b.l_total());
+    let ket = HermitePair::new(c, d, c.l_total(), d.l_total());
+
+    let lmax = a.l_total() + b.l_total() + c.l_total() + d.l_total();
+    let quartet = HermiteQuartet::new(&bra, &ket, lmax, scale);
+
+    eri_tabulated(&bra, &ket, &quartet, [a.l, b.l, c.l, d.l])
+}
+
+/// Hermite Coulomb integrals and prefactor of one primitive quartet
+///
+/// Shared by every ERI of the quartet with total angular momentum
+/// ≤ `lmax` (Cartesian components and center derivatives alike).
+#[derive(Clone, Debug)]
+pub struct HermiteQuartet {
+    r: Vec<f64>,
+    dim: usize,
+    prefactor: f64,
+}
+
+impl HermiteQuartet {
+    pub fn new(bra: &HermitePair, ket: &HermitePair, lmax: usize, scale: f64) -> Self {
+        let (p, q) = (bra.p, ket.p);
+        let alpha = p * q / (p + q);
+        let pq = [0, 1, 2].map(|k| bra.center[k] - ket.center[k]);
+
+        Self {
+            r: hermite_r_table(lmax, alpha, pq, scale),
+            dim: lmax + 1,
+            prefactor: 2.0 * PI.powf(2.5) / (p * q * (p + q).sqrt()) * scale.sqrt(),
+        }
+    }
+}
+
+/// (ab|cd) from tabulated pairs; `l` = [l_a, l_b, l_c, l_d]
+///
+/// The pairs must be tabulated up to l_a, l_b (bra) and l_c, l_d (ket),
+/// and the quartet up to their total angular momentum.
+pub fn eri_tabulated(
+    bra: &HermitePair,
+    ket: &HermitePair,
+    quartet: &HermiteQuartet,
+    l: [[usize; 3]; 4],
+) -> f64 {
+    let eab = bra.rows(l[0], l[1]);
+    let ecd = ket.rows(l[2], l[3]);
+    let dim = quartet.dim;
+    let r = &quartet.r;
+
+    let mut val = 0.0;
+    for (t, et) in eab[0].iter().enumerate() {
+        for (u, eu) in eab[1].iter().enumerate() {
+            for (v, ev) in eab[2].iter().enumerate() {
+                let e_bra = et * eu * ev;
+                if e_bra == 0.0 {
+                    continue;
+                }
+
+                for (tau, ft) in ecd[0].iter().enumerate() {
+                    for (nu, fu) in ecd[1].iter().enumerate() {
+                        for (phi, fv) in ecd[2].iter().enumerate() {
+                            let sign = if (tau + nu + phi) % 2 == 0 { 1.0 } else { -1.0 };
+                            val += e_bra * sign * ft * fu * fv
+                                * r[((t + tau) * dim + (u + nu)) * dim + (v + phi)];
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    quartet.prefactor * val
+}
+
+/// Primitive long-range ERI (ab| erf(ω r12) / r12 |cd)
+///
+/// Only the Boys argument changes: with α = pq/(p+q) and
+/// κ = ω² / (ω² + α), F_n(T) → κ^{n+½} F_n(κT).
+pub fn eri_erf(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    c: &CartGaussian,
+    d: &CartGaussian,
+    omega: f64,
+) -> f64 {
+    let p = a.alpha + b.alpha;
+    let q = c.alpha + d.alpha;
+    let alpha = p * q / (p + q);
+    let w2 = omega * omega;
+
+    eri_scaled(a, b, c, d, w2 / (w2 + alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plain recursive E^{ij}_t
+    fn reference_e(i: i32, j: i32, t: i32, qx: f64, a: f64, b: f64) -> f64 {
+        if t < 0 || t > i + j || i < 0 || j < 0 {
+            return 0.0;
+        }
+        let p = a + b;
+        let mu = a * b / p;
+        if i == 0 && j == 0 {
+            return (-mu * qx * qx).exp();
+        }
+        if j == 0 {
+            0.5 / p * reference_e(i - 1, j, t - 1, qx, a, b)
+                - mu * qx / a * reference_e(i - 1, j, t, qx, a, b)
+                + (t + 1) as f64 * reference_e(i - 1, j, t + 1, qx, a, b)
+        } else {
+            0.5 / p * reference_e(i, j - 1, t - 1, qx, a, b)
+                + mu * qx / b * reference_e(i, j - 1, t, qx, a, b)
+                + (t + 1) as f64 * reference_e(i, j - 1, t + 1, qx, a, b)
+        }
+    }
+
+    #[test]
+    fn hermite_table_matches_recursion() {
+        let (qx, a, b) = (0.37, 1.3, 0.45);
+        let table = HermiteTable::new(4, 3, qx, a, b);
+
+        for i in 0..=4 {
+            for j in 0..=3 {
+                for t in 0..=(i + j + 1) {
+                    let want = reference_e(i as i32, j as i32, t as i32, qx, a, b);
+                    assert!((table.get(i, j, t) - want).abs() < 1e-13 * want.abs().max(1.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eri_has_permutational_symmetry() {
+        let a = CartGaussian::new(0.8, [1, 0, 0], [0.0, 0.0, 0.0]);
+        let b = CartGaussian::new(1.2, [0, 1, 1], [0.5, -0.3, 0.2]);
+        let c = CartGaussian::new(0.6, [0, 0, 1], [-0.4, 0.7, 1.0]);
+        let d = CartGaussian::new(1.5, [2, 0, 0], [0.1, 0.2, -0.6]);
+
+        let abcd = eri(&a, &b, &c, &d);
+        for other in [eri(&b, &a, &c, &d), eri(&a, &b, &d, &c), eri(&c, &d, &a, &b)] {
+            assert!((abcd - other).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn raised_tables_give_the_same_eri() {
+        let a = CartGaussian::new(0.8, [1, 0, 0], [0.0, 0.0, 0.0]);
+        let b = CartGaussian::new(1.2, [0, 1, 0], [0.5, -0.3, 0.2]);
+        let c = CartGaussian::new(0.6, [0, 0, 1], [-0.4, 0.7, 1.0]);
+        let d = CartGaussian::new(1.5, [0, 0, 0], [0.1, 0.2, -0.6]);
+
+        let bra = HermitePair::new(&a, &b, 3, 3);
+        let ket = HermitePair::new(&c, &d, 3, 2);
+        let quartet = HermiteQuartet::new(&bra, &ket, 7, 1.0);
+
+        let tabulated = eri_tabulated(&bra, &ket, &quartet, [a.l, b.l, c.l, d.l]);
+        assert!((tabulated - eri(&a, &b, &c, &d)).abs() < 1e-13);
+    }
+}
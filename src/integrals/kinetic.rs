@@ -11,6 +11,7 @@ use std::f64::consts::PI;
 use crate::basis::primitive::Primitive;
 use crate::basis::contracted::Contracted;
 use crate::basis::shell::Shell;
+use crate::integrals::hermite::{kinetic, shell_pair_block};
 
 /// |A - B|²
 #[inline]
@@ -71,31 +72,11 @@ pub fn kinetic_contracted(
 
 /// Kinetic energy block between two shells
 ///
-/// Devuelve la matriz T_{μν}
+/// Devuelve la matriz T_{μν} (McMurchie–Davidson, cualquier l)
 pub fn kinetic_shell_shell(
     shell_a: &Shell,
     shell_b: &Shell,
 ) -> Vec<Vec<f64>> {
-
-    let comps_a = shell_a.cartesian_components();
-    let comps_b = shell_b.cartesian_components();
-
-    let na = comps_a.len();
-    let nb = comps_b.len();
-
-    let mut t = vec![vec![0.0; nb]; na];
-
-    for (i, _) in comps_a.iter().enumerate() {
-        for (j, _) in comps_b.iter().enumerate() {
-
-            // Cada AO cartesiano comparte el mismo conjunto de primitivas
-            let ca = Contracted::new(shell_a.primitives.clone());
-            let cb = Contracted::new(shell_b.primitives.clone());
-
-            t[i][j] = kinetic_contracted(&ca, &cb);
-        }
-    }
-
-    t
+    shell_pair_block(shell_a, shell_b, kinetic)
 }
 
@@ -6,3 +6,8 @@ pub mod nuclear_attraction;
 pub mod schwarz;
 pub mod eri;
 pub mod boys;
+pub mod hermite;
+pub mod deriv;
+pub mod multipole;
+pub mod ecp;
+pub mod relativistic;
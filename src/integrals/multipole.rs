@@ -0,0 +1,265 @@
+//! Cartesian multipole integrals ⟨χ_μ | (x-Ox)^i (y-Oy)^j (z-Oz)^k | χ_ν⟩
+//!
+//! Any origin O is supported. Each 1D factor is reduced to overlaps by
+//! re-expanding around the ket center B:
+//!   (x-Ox)^e = Σ_k C(e,k) (Bx-Ox)^{e-k} (x-Bx)^k
+//!
+//! Provides:
+//! - dipole     (3 components)
+//! - quadrupole (6 components: xx xy xz yy yz zz)
+//! - octupole   (10 components: xxx xxy xxz xyy xyz xzz yyy yyz yzz zzz)
+
+use crate::basis::shell::Shell;
+use crate::integrals::hermite::{expand_shell, overlap_1d, CartGaussian};
+use crate::system::atom::Atom;
+use crate::system::units::atomic_mass;
+
+/// Origin used for multipole expansions
+#[derive(Clone, Copy, Debug)]
+pub enum MultipoleOrigin {
+    /// Cartesian origin (0, 0, 0)
+    Origin,
+    /// Center of mass
+    CenterOfMass,
+    /// Center of nuclear charge
+    NuclearCharge,
+    /// User-defined point (bohr)
+    Point([f64; 3]),
+}
+
+impl MultipoleOrigin {
+    /// Resolve to a point in bohr
+    pub fn resolve(&self, atoms: &[Atom]) -> [f64; 3] {
+        match self {
+            MultipoleOrigin::Origin => [0.0; 3],
+            MultipoleOrigin::Point(p) => *p,
+            MultipoleOrigin::CenterOfMass => {
+                weighted_center(atoms, |a| atomic_mass(&a.symbol))
+            }
+            MultipoleOrigin::NuclearCharge => {
+                weighted_center(atoms, |a| a.atomic_number as f64)
+            }
+        }
+    }
+}
+
+fn weighted_center(atoms: &[Atom], w: impl Fn(&Atom) -> f64) -> [f64; 3] {
+    let mut c = [0.0; 3];
+    let mut tot = 0.0;
+
+    for a in atoms {
+        let wa = w(a);
+        tot += wa;
+        for (ck, xk) in c.iter_mut().zip(a.position) {
+            *ck += wa * xk;
+        }
+    }
+
+    c.map(|ck| ck / tot)
+}
+
+/// Cartesian exponents of all components of order `order`
+///
+/// Lexicographic, x-major: xx xy xz yy yz zz for order 2.
+pub fn multipole_components(order: usize) -> Vec<[usize; 3]> {
+    let mut comps = Vec::new();
+    for ex in (0..=order).rev() {
+        for ey in (0..=(order - ex)).rev() {
+            comps.push([ex, ey, order - ex - ey]);
+        }
+    }
+    comps
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    let mut b = 1.0;
+    for i in 0..k {
+        b *= (n - i) as f64 / (i + 1) as f64;
+    }
+    b
+}
+
+/// Primitive multipole integral ⟨a| Π_k (r_k - O_k)^{e_k} |b⟩
+pub fn multipole_primitive(
+    a: &CartGaussian,
+    b: &CartGaussian,
+    origin: [f64; 3],
+    e: [usize; 3],
+) -> f64 {
+    let mut val = 1.0;
+
+    for k in 0..3 {
+        let qx = a.center[k] - b.center[k];
+        let bo = b.center[k] - origin[k];
+        let i = a.l[k] as i32;
+        let j = b.l[k] as i32;
+
+        let mut m = 0.0;
+        for s in 0..=e[k] {
+            m += binomial(e[k], s)
+                * bo.powi((e[k] - s) as i32)
+                * overlap_1d(i, j + s as i32, qx, a.alpha, b.alpha);
+        }
+
+        val *= m;
+        if val == 0.0 {
+            break;
+        }
+    }
+
+    val
+}
+
+/// AO matrix of one multipole component
+pub fn multipole_matrix(
+    shells: &[Shell],
+    origin: [f64; 3],
+    e: [usize; 3],
+) -> Vec<Vec<f64>> {
+
+    let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
+    let mut m = vec![vec![0.0; nao]; nao];
+
+    let expanded: Vec<_> = shells.iter().map(expand_shell).collect();
+
+    for (si, ga) in shells.iter().zip(expanded.iter()) {
+        for (sj, gb) in shells.iter().zip(expanded.iter()) {
+            for (i, prims_a) in ga.iter().enumerate() {
+                for (j, prims_b) in gb.iter().enumerate() {
+
+                    let mut val = 0.0;
+                    for (ca, pa) in prims_a {
+                        for (cb, pb) in prims_b {
+                            val += ca * cb * multipole_primitive(pa, pb, origin, e);
+                        }
+                    }
+
+                    m[si.offset + i][sj.offset + j] = val;
+                }
+            }
+        }
+    }
+
+    m
+}
+
+/// All AO multipole matrices of a given order
+///
+/// Returned in `multipole_components(order)` ordering.
+pub fn multipole_integrals(
+    shells: &[Shell],
+    origin: [f64; 3],
+    order: usize,
+) -> Vec<Vec<Vec<f64>>> {
+    multipole_components(order)
+        .into_iter()
+        .map(|e| multipole_matrix(shells, origin, e))
+        .collect()
+}
+
+/// Dipole integrals ⟨μ| r − O |ν⟩ (x, y, z)
+pub fn dipole_integrals(
+    shells: &[Shell],
+    origin: [f64; 3],
+) -> Vec<Vec<Vec<f64>>> {
+    multipole_integrals(shells, origin, 1)
+}
+
+/// Second-moment integrals (xx, xy, xz, yy, yz, zz)
+pub fn quadrupole_integrals(
+    shells: &[Shell],
+    origin: [f64; 3],
+) -> Vec<Vec<Vec<f64>>> {
+    multipole_integrals(shells, origin, 2)
+}
+
+/// Third-moment integrals (xxx, xxy, …, zzz)
+pub fn octupole_integrals(
+    shells: &[Shell],
+    origin: [f64; 3],
+) -> Vec<Vec<Vec<f64>>> {
+    multipole_integrals(shells, origin, 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+
+    fn shells() -> Vec<Shell> {
+        let a = [0.1, -0.2, 0.3];
+        let b = [0.4, 0.5, -0.6];
+        vec![
+            Shell::new(vec![Primitive::new(0.8, 1.0, a, [0, 0, 0])], [0, 0, 0], a, 0),
+            Shell::new(vec![Primitive::new(0.5, 1.0, b, [0, 0, 1])], [0, 0, 1], b, 1),
+        ]
+    }
+
+    #[test]
+    fn order_zero_is_the_normalized_overlap() {
+        let m = multipole_matrix(&shells(), [1.0, 2.0, 3.0], [0, 0, 0]);
+
+        for (mu, row) in m.iter().enumerate() {
+            assert!((row[mu] - 1.0).abs() < 1e-12);
+            for (nu, m_munu) in row.iter().enumerate() {
+                assert!((m_munu - m[nu][mu]).abs() < 1e-14);
+            }
+        }
+        // s on A against p_x, p_y, p_z on B
+        assert!(m[0][1].abs() > 1e-3 && m[0][3].abs() > 1e-3);
+    }
+
+    #[test]
+    fn second_moment_of_an_s_gaussian() {
+        let alpha = 0.8;
+        let center = [0.1, -0.2, 0.3];
+        let primitive = Primitive::new(alpha, 1.0, center, [0, 0, 0]);
+        let shell = Shell::new(vec![primitive], [0, 0, 0], center, 0);
+
+        let dipole = dipole_integrals(std::slice::from_ref(&shell), center);
+        let second = quadrupole_integrals(std::slice::from_ref(&shell), center);
+
+        // ⟨(x − A)⟩ = 0, ⟨(x − A)²⟩ = 1/(4α), ⟨(x − A)(y − A)⟩ = 0
+        for d in &dipole {
+            assert!(d[0][0].abs() < 1e-12);
+        }
+        for (c, e) in multipole_components(2).iter().enumerate() {
+            let expected = if e.contains(&2) { 0.25 / alpha } else { 0.0 };
+            assert!((second[c][0][0] - expected).abs() < 1e-12, "{:?}", e);
+        }
+    }
+
+    #[test]
+    fn moments_follow_the_binomial_origin_shift() {
+        let shells = shells();
+        let (o, o2) = ([0.0; 3], [0.3, -0.7, 1.1]);
+        let s = multipole_matrix(&shells, o, [0, 0, 0]);
+        let d1 = dipole_integrals(&shells, o);
+        let d2 = dipole_integrals(&shells, o2);
+        let q1 = multipole_matrix(&shells, o, [0, 0, 2]);
+        let q2 = multipole_matrix(&shells, o2, [0, 0, 2]);
+        let shift = o[2] - o2[2];
+
+        for mu in 0..4 {
+            for nu in 0..4 {
+                // z − O' = (z − O) + (O − O')
+                assert!((d2[2][mu][nu] - d1[2][mu][nu] - shift * s[mu][nu]).abs() < 1e-12);
+                let expanded = q1[mu][nu] + 2.0 * shift * d1[2][mu][nu] + shift * shift * s[mu][nu];
+                assert!((q2[mu][nu] - expanded).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn origins_resolve_to_weighted_centers() {
+        let atoms = vec![
+            Atom::new("H".to_string(), 1, [0.0, 0.0, 0.0]),
+            Atom::new("He".to_string(), 2, [0.0, 0.0, 3.0]),
+        ];
+
+        assert_eq!(MultipoleOrigin::NuclearCharge.resolve(&atoms), [0.0, 0.0, 2.0]);
+        assert_eq!(MultipoleOrigin::Point([1.0, 2.0, 3.0]).resolve(&atoms), [1.0, 2.0, 3.0]);
+        let com = MultipoleOrigin::CenterOfMass.resolve(&atoms);
+        assert!(com[2] > 2.0 && com[2] < 3.0);
+    }
+}
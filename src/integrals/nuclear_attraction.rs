@@ -5,6 +5,11 @@
 //! - nuclear_attraction_contracted
 //! - nuclear_attraction_shell_shell
 //!
+//! Generalizado a puntos arbitrarios (no atómicos):
+//! - point_potential_shell_shell : ⟨μ| 1/|r-C| |ν⟩
+//! - point_field_shell_shell     : ⟨μ| (r-C)_k / |r-C|³ |ν⟩
+//! - point_charges_shell_shell   : Σ_C ⟨μ| -q_C/|r-C| |ν⟩
+//!
 //! Usa Primitive encapsulado (getters) y Shell sin orbitales explícitos.
 
 use std::f64::consts::PI;
@@ -14,6 +19,20 @@ use crate::basis::contracted::Contracted;
 use crate::basis::shell::Shell;
 use crate::system::atom::Atom;
 use crate::integrals::boys::boys0;
+use crate::integrals::hermite::{
+    gaussian_charge_potential, potential, potential_derivative, shell_pair_block,
+};
+
+/// Classical point (or Gaussian-smeared) charge (bohr, a.u.)
+///
+/// Not an atom: carries no basis functions and no mass.
+#[derive(Clone, Copy, Debug)]
+pub struct PointCharge {
+    pub position: [f64; 3],
+    pub charge: f64,
+    /// Gaussian width ζ of a smeared charge (None = point)
+    pub exponent: Option<f64>,
+}
 
 /// |A - B|²
 #[inline]
@@ -40,7 +59,7 @@ pub fn nuclear_attraction_primitive(
     let B = b.center();
     let C = atom.position;
 
-    let z = atom.atomic_number as f64;
+    let z = atom.nuclear_charge();
 
     let zeta = alpha + beta;
 
@@ -79,6 +98,55 @@ pub fn nuclear_attraction_contracted(
     v
 }
 
+// ======================================================
+// Shell-pair blocks at arbitrary points
+// ======================================================
+
+/// Potential of a unit positive charge at C
+///
+/// Devuelve ⟨μ| 1/|r-C| |ν⟩
+pub fn point_potential_shell_shell(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    point: [f64; 3],
+) -> Vec<Vec<f64>> {
+    shell_pair_block(shell_a, shell_b, |a, b| potential(a, b, point))
+}
+
+/// Electric-field integrals at C
+///
+/// Devuelve [x, y, z] con ⟨μ| (r-C)_k / |r-C|³ |ν⟩ = ∂/∂C_k ⟨μ| 1/|r-C| |ν⟩
+pub fn point_field_shell_shell(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    point: [f64; 3],
+) -> [Vec<Vec<f64>>; 3] {
+    let axis = |k: usize| {
+        let mut d = [0usize; 3];
+        d[k] = 1;
+        shell_pair_block(shell_a, shell_b, |a, b| potential_derivative(a, b, point, d))
+    };
+
+    [axis(0), axis(1), axis(2)]
+}
+
+/// Electron–point-charge attraction
+///
+/// Devuelve Σ_C ⟨μ| -q_C / |r-C| |ν⟩
+/// (erf(√ζ|r-C|)/|r-C| para cargas gaussianas)
+pub fn point_charges_shell_shell(
+    shell_a: &Shell,
+    shell_b: &Shell,
+    charges: &[PointCharge],
+) -> Vec<Vec<f64>> {
+    shell_pair_block(shell_a, shell_b, |a, b| {
+        charges
+            .iter()
+            .map(|q| -q.charge * gaussian_charge_potential(a, b, q.position, q.exponent))
+            .sum()
+    })
+}
+
 /// Nuclear attraction block between two shells
 ///
 /// Devuelve matriz V_{μν}
@@ -88,26 +156,81 @@ pub fn nuclear_attraction_shell_shell(
     atoms: &[Atom],
 ) -> Vec<Vec<f64>> {
 
-    let na = shell_a.n_orbitals();
-    let nb = shell_b.n_orbitals();
-
-    let mut vmat = vec![vec![0.0; nb]; na];
+    // Núcleos: cargas +Z puntuales o gaussianas (modelo nuclear)
+    let charges: Vec<PointCharge> = atoms
+        .iter()
+        .map(|atom| PointCharge {
+            position: atom.position,
+            charge: atom.nuclear_charge(),
+            exponent: atom.nuclear_exponent,
+        })
+        .collect();
+
+    point_charges_shell_shell(shell_a, shell_b, &charges)
+}
 
-    // Para cada núcleo
-    for atom in atoms {
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let ca = Contracted::new(shell_a.primitives.clone());
-        let cb = Contracted::new(shell_b.primitives.clone());
+    fn s_shell(alpha: f64, center: [f64; 3]) -> Shell {
+        Shell::new(vec![Primitive::new(alpha, 1.0, center, [0, 0, 0])], [0, 0, 0], center, 0)
+    }
 
-        let val = nuclear_attraction_contracted(&ca, &cb, atom);
+    fn p_shell(alpha: f64, center: [f64; 3]) -> Shell {
+        Shell::new(vec![Primitive::new(alpha, 1.0, center, [1, 0, 0])], [1, 0, 0], center, 1)
+    }
 
-        for i in 0..na {
-            for j in 0..nb {
-                vmat[i][j] += val;
+    #[test]
+    fn field_integrals_are_derivatives_of_the_potential() {
+        let (a, b) = (s_shell(0.9, [0.0, 0.1, -0.2]), p_shell(0.6, [0.3, -0.4, 0.8]));
+        let c = [0.5, 0.7, -0.3];
+        let h = 1e-5;
+
+        let field = point_field_shell_shell(&a, &b, c);
+
+        for k in 0..3 {
+            let (mut cp, mut cm) = (c, c);
+            cp[k] += h;
+            cm[k] -= h;
+            let vp = point_potential_shell_shell(&a, &b, cp);
+            let vm = point_potential_shell_shell(&a, &b, cm);
+            for j in 0..3 {
+                let fd = (vp[0][j] - vm[0][j]) / (2.0 * h);
+                assert!((field[k][0][j] - fd).abs() < 1e-7, "{} {}", k, j);
             }
         }
     }
 
-    vmat
-}
+    #[test]
+    fn distant_point_sees_the_overlap_charge() {
+        let a = s_shell(0.9, [0.0; 3]);
+        let c = [0.0, 0.0, 40.0];
 
+        // normalized charge distribution: ⟨s| 1/|r − C| |s⟩ → 1/|C|
+        let v = point_potential_shell_shell(&a, &a, c);
+        assert!((v[0][0] - 1.0 / 40.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn point_charges_add_up_with_their_sign() {
+        let (a, b) = (s_shell(0.9, [0.0; 3]), s_shell(0.4, [0.0, 0.0, 1.2]));
+        let (c1, c2) = ([0.3, 0.0, 0.5], [-0.6, 0.2, 2.0]);
+        let charges = [
+            PointCharge { position: c1, charge: 0.5, exponent: None },
+            PointCharge { position: c2, charge: -1.5, exponent: None },
+        ];
+
+        let v = point_charges_shell_shell(&a, &b, &charges);
+        let expected = -0.5 * point_potential_shell_shell(&a, &b, c1)[0][0]
+            + 1.5 * point_potential_shell_shell(&a, &b, c2)[0][0];
+        assert!((v[0][0] - expected).abs() < 1e-12);
+
+        // a very narrow Gaussian charge acts as a point charge
+        let smeared = [PointCharge { exponent: Some(1e10), ..charges[0] }];
+        let point = [PointCharge { exponent: None, ..charges[0] }];
+        let vs = point_charges_shell_shell(&a, &b, &smeared)[0][0];
+        let vp = point_charges_shell_shell(&a, &b, &point)[0][0];
+        assert!((vs - vp).abs() < 1e-8);
+    }
+}
@@ -0,0 +1,471 @@
+//! Scalar-relativistic one-electron Hamiltonians
+//!
+//! Both replace T + V in H_core:
+//! - spin-free exact two-component X2C-1e
+//! - second-order Douglas–Kroll–Hess (DKH2)
+//!
+//! Everything is built in the *uncontracted* basis (one function per
+//! distinct primitive) from
+//!   S, T, V = −Σ_A Z_A/|r−A|,  W = ⟨∇χ| V ·|∇χ⟩  (pVp)
+//! and projected back onto the contracted AOs:
+//!   h_AO = Cᵀ h_prim C
+//!
+//! X2C-1e (modified Dirac equation, spin-orbit dropped):
+//!   [ V   T           ] [c_L]     [ S  0       ] [c_L]
+//!   [ T   W/4c² − T   ] [c_S] = E [ 0  T/2c²   ] [c_S]
+//!   X = C_S C_L⁻¹,  S̃ = S + X† T X / 2c²
+//!   R = S^{-½} (S^{-½} S̃ S^{-½})^{-½} S^{½}
+//!   h = R† (V + T X + X† T − X† T X + X† W X / 4c²) R
+//!
+//! DKH2 works in the eigenbasis of p² (T U = S U t, p² = 2t), with
+//! the spin-free reduction σ·p X σ·p → pXp.
+//!
+//! Nuclei follow the molecule's nuclear model (point or Gaussian,
+//! see `system::nuclear_model`); a finite nucleus removes the
+//! point-charge singularity of the small component.
+
+use nalgebra::DMatrix;
+
+use crate::basis::shell::Shell;
+use crate::integrals::deriv::{center_derivative, shell_atom_indices};
+use crate::integrals::hermite::{gaussian_charge_potential, kinetic, overlap, CartGaussian};
+use crate::system::atom::Atom;
+use crate::system::units::SPEED_OF_LIGHT;
+
+/// Two primitives closer than this (exponent, center) are the same
+const PRIMITIVE_TOL: f64 = 1e-10;
+
+/// Step for the semi-numerical X2C gradient (bohr)
+const GRADIENT_STEP: f64 = 1e-4;
+
+/// One-electron Hamiltonian used in H_core
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RelativisticHamiltonian {
+    /// T + V
+    NonRelativistic,
+    /// Spin-free X2C-1e
+    X2C,
+    /// Spin-free DKH2
+    DKH2,
+}
+
+impl RelativisticHamiltonian {
+    /// Parse input keyword ("none", "x2c", "dkh2")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "none" | "nonrelativistic" => Some(Self::NonRelativistic),
+            "x2c" | "sfx2c" | "x2c-1e" | "sfx2c1e" => Some(Self::X2C),
+            "dkh2" | "dkh" => Some(Self::DKH2),
+            _ => None,
+        }
+    }
+}
+
+// ======================================================
+// Uncontracted basis
+// ======================================================
+
+/// Distinct primitives and the contraction matrix (n_prim × n_ao)
+///
+/// Primitives are normalized; identical primitives of different
+/// contractions (general contractions) are merged.
+fn uncontract(shells: &[Shell]) -> (Vec<CartGaussian>, DMatrix<f64>) {
+    let mut prims: Vec<CartGaussian> = Vec::new();
+    let mut entries: Vec<(usize, usize, f64)> = Vec::new();
+
+    let mut ao = 0;
+    for sh in shells {
+        for l in sh.cartesian_components() {
+            for p in &sh.primitives {
+                let g = CartGaussian::new(p.exponent(), l, sh.center);
+                let n = overlap(&g, &g).sqrt().recip();
+
+                let idx = prims
+                    .iter()
+                    .position(|q| same_primitive(q, &g))
+                    .unwrap_or_else(|| {
+                        prims.push(g);
+                        prims.len() - 1
+                    });
+
+                // coefficient relative to the normalized primitive
+                entries.push((idx, ao, p.coefficient() * p.norm() / n));
+            }
+            ao += 1;
+        }
+    }
+
+    let mut c = DMatrix::zeros(prims.len(), ao);
+    for (i, mu, v) in entries {
+        c[(i, mu)] += v;
+    }
+
+    (prims, c)
+}
+
+fn same_primitive(a: &CartGaussian, b: &CartGaussian) -> bool {
+    a.l == b.l
+        && (a.alpha - b.alpha).abs() < PRIMITIVE_TOL * a.alpha
+        && (0..3).all(|k| (a.center[k] - b.center[k]).abs() < PRIMITIVE_TOL)
+}
+
+/// S, T, V, W over normalized primitives
+fn primitive_matrices(
+    prims: &[CartGaussian],
+    atoms: &[Atom],
+) -> (DMatrix<f64>, DMatrix<f64>, DMatrix<f64>, DMatrix<f64>) {
+
+    let n = prims.len();
+    let norms: Vec<f64> = prims.iter().map(|g| overlap(g, g).sqrt().recip()).collect();
+
+    let nuclear = |a: &CartGaussian, b: &CartGaussian| -> f64 {
+        atoms
+            .iter()
+            .map(|at| -at.nuclear_charge()
+                * gaussian_charge_potential(a, b, at.position, at.nuclear_exponent))
+            .sum()
+    };
+
+    let mut s = DMatrix::zeros(n, n);
+    let mut t = DMatrix::zeros(n, n);
+    let mut v = DMatrix::zeros(n, n);
+    let mut w = DMatrix::zeros(n, n);
+
+    for i in 0..n {
+        for j in 0..=i {
+            let (a, b) = (&prims[i], &prims[j]);
+            let nn = norms[i] * norms[j];
+
+            // ∂/∂r = −∂/∂A : signs cancel in ⟨∂χ|V|∂χ⟩
+            let mut pvp = 0.0;
+            for k in 0..3 {
+                for (ca, da) in center_derivative(a, k) {
+                    for (cb, db) in center_derivative(b, k) {
+                        pvp += ca * cb * nuclear(&da, &db);
+                    }
+                }
+            }
+
+            let vals = [
+                overlap(a, b),
+                kinetic(a, b),
+                nuclear(a, b),
+                pvp,
+            ];
+
+            for (m, val) in [&mut s, &mut t, &mut v, &mut w].into_iter().zip(vals) {
+                m[(i, j)] = val * nn;
+                m[(j, i)] = val * nn;
+            }
+        }
+    }
+
+    (s, t, v, w)
+}
+
+// ======================================================
+// Linear algebra helpers
+// ======================================================
+
+/// f(M) for symmetric M
+fn sym_func(m: &DMatrix<f64>, f: impl Fn(f64) -> f64) -> DMatrix<f64> {
+    let eig = m.clone().symmetric_eigen();
+    let d = DMatrix::from_diagonal(&eig.eigenvalues.map(f));
+    &eig.eigenvectors * d * eig.eigenvectors.transpose()
+}
+
+/// Solve H C = M C E (M positive definite), eigenvalues ascending
+fn generalized_eigen(h: &DMatrix<f64>, m: &DMatrix<f64>) -> (Vec<f64>, DMatrix<f64>) {
+    let m_inv_sqrt = sym_func(m, |x| 1.0 / x.sqrt());
+    let h_ortho = &m_inv_sqrt * h * &m_inv_sqrt;
+
+    let eig = h_ortho.symmetric_eigen();
+
+    let mut order: Vec<usize> = (0..eig.eigenvalues.len()).collect();
+    order.sort_by(|&a, &b| eig.eigenvalues[a].total_cmp(&eig.eigenvalues[b]));
+
+    let vecs = &m_inv_sqrt * eig.eigenvectors.select_columns(&order);
+    let vals = order.iter().map(|&i| eig.eigenvalues[i]).collect();
+
+    (vals, vecs)
+}
+
+// ======================================================
+// X2C-1e
+// ======================================================
+
+/// Spin-free X2C-1e Hamiltonian over normalized primitives
+fn x2c_primitive(
+    s: &DMatrix<f64>,
+    t: &DMatrix<f64>,
+    v: &DMatrix<f64>,
+    w: &DMatrix<f64>,
+) -> DMatrix<f64> {
+
+    let n = s.nrows();
+    let c2 = SPEED_OF_LIGHT * SPEED_OF_LIGHT;
+
+    // --------------------------------------------------
+    // Modified Dirac equation
+    // --------------------------------------------------
+    let mut h4 = DMatrix::zeros(2 * n, 2 * n);
+    let mut m4 = DMatrix::zeros(2 * n, 2 * n);
+
+    h4.view_mut((0, 0), (n, n)).copy_from(v);
+    h4.view_mut((0, n), (n, n)).copy_from(t);
+    h4.view_mut((n, 0), (n, n)).copy_from(t);
+    h4.view_mut((n, n), (n, n)).copy_from(&(w * (0.25 / c2) - t));
+
+    m4.view_mut((0, 0), (n, n)).copy_from(s);
+    m4.view_mut((n, n), (n, n)).copy_from(&(t * (0.5 / c2)));
+
+    let (_, c) = generalized_eigen(&h4, &m4);
+
+    // Electronic (positive-energy) solutions: upper half of the spectrum
+    let c_l = c.view((0, n), (n, n)).into_owned();
+    let c_s = c.view((n, n), (n, n)).into_owned();
+
+    // X = C_S C_L⁻¹
+    let x = c_l
+        .transpose()
+        .lu()
+        .solve(&c_s.transpose())
+        .expect("X2C: singular large-component block")
+        .transpose();
+
+    // --------------------------------------------------
+    // Renormalization R
+    // --------------------------------------------------
+    let s_tilde = s + x.transpose() * t * &x * (0.5 / c2);
+
+    let s_sqrt = sym_func(s, f64::sqrt);
+    let s_inv_sqrt = sym_func(s, |e| 1.0 / e.sqrt());
+    let mid = sym_func(&(&s_inv_sqrt * s_tilde * &s_inv_sqrt), |e| 1.0 / e.sqrt());
+    let r = &s_inv_sqrt * mid * &s_sqrt;
+
+    // --------------------------------------------------
+    // Foldy–Wouthuysen Hamiltonian
+    // --------------------------------------------------
+    let tx = t * &x;
+    let h_fw = v + &tx + tx.transpose() - x.transpose() * &tx
+        + x.transpose() * w * &x * (0.25 / c2);
+
+    r.transpose() * h_fw * r
+}
+
+// ======================================================
+// DKH2
+// ======================================================
+
+/// Spin-free DKH2 Hamiltonian over normalized primitives
+fn dkh2_primitive(
+    s: &DMatrix<f64>,
+    t: &DMatrix<f64>,
+    v: &DMatrix<f64>,
+    w: &DMatrix<f64>,
+) -> DMatrix<f64> {
+
+    let n = s.nrows();
+    let c = SPEED_OF_LIGHT;
+    let c2 = c * c;
+
+    // --------------------------------------------------
+    // p² eigenbasis: T U = S U t, Uᵀ S U = 1
+    // --------------------------------------------------
+    let (tk, u) = generalized_eigen(t, s);
+
+    let p2: Vec<f64> = tk.iter().map(|t| 2.0 * t).collect();
+    let e: Vec<f64> = p2.iter().map(|p2| c * (p2 + c2).sqrt()).collect();
+    let a: Vec<f64> = e.iter().map(|e| ((e + c2) / (2.0 * e)).sqrt()).collect();
+    let k: Vec<f64> = e.iter().map(|e| c / (e + c2)).collect();
+
+    let vp = u.transpose() * v * &u;
+    let wp = u.transpose() * w * &u;
+
+    // diag(l) · M1 · diag(mid) · M2 · diag(r)
+    let sandwich = |l: &dyn Fn(usize) -> f64,
+                    m1: &DMatrix<f64>,
+                    mid: &dyn Fn(usize) -> f64,
+                    m2: &DMatrix<f64>,
+                    r: &dyn Fn(usize) -> f64| {
+        let left = DMatrix::from_fn(n, n, |i, j| l(i) * m1[(i, j)] * mid(j));
+        let right = DMatrix::from_fn(n, n, |i, j| m2[(i, j)] * r(j));
+        left * right
+    };
+
+    // --------------------------------------------------
+    // DKH1: E_p − c² + A (V + K pVp K) A
+    // --------------------------------------------------
+    let mut h = DMatrix::from_fn(n, n, |i, j| {
+        a[i] * (vp[(i, j)] + k[i] * wp[(i, j)] * k[j]) * a[j]
+    });
+    for i in 0..n {
+        h[(i, i)] += e[i] - c2;
+    }
+
+    // --------------------------------------------------
+    // DKH2: −½ (W₁O₁ + O₁W₁),  W₁ = O₁ / (E_i + E_j)
+    //
+    //   W₁O₁ = A K (pVp/D) A²K V A
+    //        − A K (pVp/D) (A²/p²) pVp K A
+    //        − A (V/D) K²A²p² V A
+    //        + A (V/D) K A² pVp K A
+    // --------------------------------------------------
+    let vd = DMatrix::from_fn(n, n, |i, j| vp[(i, j)] / (e[i] + e[j]));
+    let wd = DMatrix::from_fn(n, n, |i, j| wp[(i, j)] / (e[i] + e[j]));
+
+    let w1o1 = sandwich(&|i| a[i] * k[i], &wd, &|j| a[j] * a[j] * k[j], &vp, &|j| a[j])
+        - sandwich(&|i| a[i] * k[i], &wd, &|j| a[j] * a[j] / p2[j], &wp, &|j| k[j] * a[j])
+        - sandwich(&|i| a[i], &vd, &|j| k[j] * k[j] * a[j] * a[j] * p2[j], &vp, &|j| a[j])
+        + sandwich(&|i| a[i], &vd, &|j| k[j] * a[j] * a[j], &wp, &|j| k[j] * a[j]);
+
+    h -= (&w1o1 + w1o1.transpose()) * 0.5;
+
+    // back to the primitive basis: h_prim = S U h Uᵀ S
+    let su = s * &u;
+    &su * h * su.transpose()
+}
+
+// ======================================================
+// Public drivers
+// ======================================================
+
+/// Scalar-relativistic replacement for T + V (AO basis)
+///
+/// `NonRelativistic` returns the plain T + V from the same
+/// uncontracted route, so all three options are directly comparable.
+pub fn relativistic_core_hamiltonian(
+    shells: &[Shell],
+    atoms: &[Atom],
+    method: RelativisticHamiltonian,
+) -> Vec<Vec<f64>> {
+
+    let (prims, contr) = uncontract(shells);
+    let (s, t, v, w) = primitive_matrices(&prims, atoms);
+
+    let h_prim = match method {
+        RelativisticHamiltonian::NonRelativistic => t + v,
+        RelativisticHamiltonian::X2C => x2c_primitive(&s, &t, &v, &w),
+        RelativisticHamiltonian::DKH2 => dkh2_primitive(&s, &t, &v, &w),
+    };
+
+    let h = contr.transpose() * h_prim * &contr;
+
+    (0..h.nrows())
+        .map(|i| (0..h.ncols()).map(|j| h[(i, j)]).collect())
+        .collect()
+}
+
+/// Spin-free X2C-1e Hamiltonian (AO basis)
+pub fn x2c_hamiltonian(shells: &[Shell], atoms: &[Atom]) -> Vec<Vec<f64>> {
+    relativistic_core_hamiltonian(shells, atoms, RelativisticHamiltonian::X2C)
+}
+
+/// Spin-free DKH2 Hamiltonian (AO basis)
+pub fn dkh2_hamiltonian(shells: &[Shell], atoms: &[Atom]) -> Vec<Vec<f64>> {
+    relativistic_core_hamiltonian(shells, atoms, RelativisticHamiltonian::DKH2)
+}
+
+/// Σ_{μν} P_{μν} ∂h^{rel}_{μν}/∂R_A
+///
+/// Semi-numerical: central differences of the one-electron
+/// Hamiltonian with each atom (nucleus and its basis functions)
+/// displaced. Replaces the T + V part of the one-electron gradient;
+/// the overlap (Pulay) term is unchanged.
+pub fn relativistic_gradient(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+    method: RelativisticHamiltonian,
+) -> Vec<[f64; 3]> {
+
+    let shell_centers: Vec<[f64; 3]> = shells.iter().map(|s| s.center).collect();
+    let shell_atoms = shell_atom_indices(&shell_centers, atoms);
+
+    let energy = |shells: &[Shell], atoms: &[Atom]| -> f64 {
+        let h = relativistic_core_hamiltonian(shells, atoms, method);
+        h.iter()
+            .zip(density.iter())
+            .map(|(hr, pr)| hr.iter().zip(pr.iter()).map(|(h, p)| h * p).sum::<f64>())
+            .sum()
+    };
+
+    let displaced = |a: usize, k: usize, step: f64| {
+        let mut sh = shells.to_vec();
+        for (s, &owner) in sh.iter_mut().zip(shell_atoms.iter()) {
+            if owner == a {
+                s.center[k] += step;
+            }
+        }
+        let mut at = atoms.to_vec();
+        at[a].position[k] += step;
+        (sh, at)
+    };
+
+    let mut grad = vec![[0.0; 3]; atoms.len()];
+
+    for (a, g) in grad.iter_mut().enumerate() {
+        for (k, g_k) in g.iter_mut().enumerate() {
+            let (sp, ap) = displaced(a, k, GRADIENT_STEP);
+            let (sm, am) = displaced(a, k, -GRADIENT_STEP);
+
+            *g_k = (energy(&sp, &ap) - energy(&sm, &am)) / (2.0 * GRADIENT_STEP);
+        }
+    }
+
+    grad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+
+    fn system(z: usize, alphas: &[f64]) -> (Vec<Atom>, Vec<Shell>, Vec<Vec<f64>>) {
+        let positions = [[0.0, 0.0, 0.0], [0.3, -0.2, 1.7]];
+        let atoms = vec![
+            Atom::new("X".to_string(), z, positions[0]),
+            Atom::new("H".to_string(), 1, positions[1]),
+        ];
+
+        let mut shells = Vec::new();
+        let centers = [positions[0], positions[0], positions[1]];
+        for (i, (&alpha, center)) in alphas.iter().zip(centers).enumerate() {
+            let primitive = Primitive::new(alpha, 1.0, center, [0, 0, 0]);
+            shells.push(Shell::new(vec![primitive], [0, 0, 0], center, i));
+        }
+
+        let p = vec![vec![0.6, 0.2, 0.3], vec![0.2, 0.4, 0.1], vec![0.3, 0.1, 0.5]];
+        (atoms, shells, p)
+    }
+
+    #[test]
+    fn gradients_are_translationally_invariant() {
+        let (atoms, shells, p) = system(30, &[400.0, 2.0, 0.8]);
+
+        for method in [RelativisticHamiltonian::X2C, RelativisticHamiltonian::DKH2] {
+            let grad = relativistic_gradient(&shells, &p, &atoms, method);
+            for (g0, g1) in grad[0].iter().zip(grad[1]) {
+                let total = g0 + g1;
+                assert!(total.abs() < 1e-5 * g0.abs().max(1.0), "{:?}: {}", method, total);
+            }
+        }
+    }
+
+    #[test]
+    fn light_atoms_recover_the_nonrelativistic_gradient() {
+        let (atoms, shells, p) = system(1, &[3.0, 0.5, 0.8]);
+
+        let nr =
+            relativistic_gradient(&shells, &p, &atoms, RelativisticHamiltonian::NonRelativistic);
+        let x2c = relativistic_gradient(&shells, &p, &atoms, RelativisticHamiltonian::X2C);
+
+        for a in 0..2 {
+            for k in 0..3 {
+                // corrections of order (Z/c)² ≈ 5e-5
+                let (g_nr, g_x2c) = (nr[a][k], x2c[a][k]);
+                assert!((g_nr - g_x2c).abs() < 1e-4 * g_nr.abs().max(1.0), "{} vs {}", g_nr, g_x2c);
+            }
+        }
+    }
+}
@@ -3,7 +3,7 @@
 //! |(μν|λσ)| ≤ sqrt( max|(μν|μν)| * max|(λσ|λσ)| )
 
 use crate::basis::shell::Shell;
-use crate::integrals::eri::eri_shell::eri_shell_shell;
+use crate::integrals::eri::eri_shell::{eri_shell_shell, eri_shell_shell_shell_shell};
 
 /// Compute Schwarz bound between two shells
 ///
@@ -53,3 +53,46 @@ pub fn compute_schwarz_bounds(
     bounds
 }
 
+/// Schwarz factors Q_ab = max √|(μν|μν)| over the shell pair
+pub fn schwarz_factors(shells: &[Shell]) -> Vec<Vec<f64>> {
+    let n = shells.len();
+    let mut q = vec![vec![0.0; n]; n];
+
+    for a in 0..n {
+        for b in 0..=a {
+            let (na, nb) = (shells[a].n_orbitals(), shells[b].n_orbitals());
+            let block = eri_shell_shell_shell_shell(&shells[a], &shells[b], &shells[a], &shells[b]);
+
+            let mut q_max: f64 = 0.0;
+            for i in 0..na {
+                for j in 0..nb {
+                    let diag = block[((i * nb + j) * na + i) * nb + j];
+                    q_max = q_max.max(diag.abs().sqrt());
+                }
+            }
+
+            q[a][b] = q_max;
+            q[b][a] = q_max;
+        }
+    }
+
+    q
+}
+
+/// max |P_μν| over every shell pair block
+pub fn density_bounds(shells: &[Shell], density: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = shells.len();
+    let mut dmax = vec![vec![0.0_f64; n]; n];
+
+    for (a, sa) in shells.iter().enumerate() {
+        for (b, sb) in shells.iter().enumerate() {
+            for row in &density[sa.offset..sa.offset + sa.n_orbitals()] {
+                for p in &row[sb.offset..sb.offset + sb.n_orbitals()] {
+                    dmax[a][b] = dmax[a][b].max(p.abs());
+                }
+            }
+        }
+    }
+
+    dmax
+}
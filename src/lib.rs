@@ -7,5 +7,10 @@ pub mod hessian;
 pub mod dft;
 pub mod mo;
 pub mod vibrations;
-//pub mod input;
+pub mod spectroscopy;
+pub mod properties;
+pub mod optimization;
+pub mod checkpoint;
+pub mod observer;
+pub mod input;
 
@@ -2,15 +2,17 @@ use std::env;
 use std::fs::File;
 use std::io::Read;
 
-use nalgebra::DMatrix;
-
 use quantum_engine::input::parser::Input;
 use quantum_engine::system::molecule::Molecule;
-use quantum_engine::basis::reader::load_basis;
+use quantum_engine::system::basis_loader::load_basis;
+use quantum_engine::system::units::atomic_mass;
 use quantum_engine::scf::scf_cycle::run_scf;
-use quantum_engine::gradients::total::compute_gradients;
-use quantum_engine::hessian::total::compute_hessian;
-use quantum_engine::vibrations::frequencies::compute_frequencies;
+use quantum_engine::gradients::total::total_gradient;
+use quantum_engine::vibrations::driver::compute_frequencies;
+use quantum_engine::vibrations::hessian_fd::hessian_fd;
+
+/// Finite-difference step of the Hessian (bohr)
+const HESSIAN_STEP: f64 = 1e-3;
 
 fn main() {
     // -------------------------------------------------
@@ -37,28 +39,25 @@ fn main() {
         .expect("Cannot read input file");
 
     let input = Input::from_yaml(&input_text)
-        .expect("Invalid input file");
+        .unwrap_or_else(|e| panic!("Invalid input file: {}", e));
 
     // -------------------------------------------------
     // 3. Read XYZ → Molecule
     // -------------------------------------------------
-    let molecule =
-        Molecule::from_xyz(xyz_file)
+    let mut molecule =
+        Molecule::from_xyz(xyz_file, input.charge, input.multiplicity)
             .expect("Invalid XYZ file");
+    molecule.set_nuclear_model(input.nuclear_model);
 
     println!("Molecule loaded: {} atoms", molecule.atoms.len());
 
     // -------------------------------------------------
     // 4. Load basis
     // -------------------------------------------------
-    let (shells, shell_centers) =
-        load_basis(
-            &molecule,
-            &input.basis,
-        ).expect("Basis load failed");
+    let shells = load_basis(&molecule, &input.basis);
+    let shell_centers: Vec<[f64; 3]> = shells.iter().map(|s| s.center).collect();
 
-    println!("AO basis size: {}", shells.last().unwrap().offset
-                                      + shells.last().unwrap().orbitals.len());
+    println!("AO basis size: {}", shells.iter().map(|s| s.n_orbitals()).sum::<usize>());
 
     // -------------------------------------------------
     // 5. SCF
@@ -70,7 +69,6 @@ fn main() {
             &shells,
             &shell_centers,
             &input.scf,
-            &input.dft,
         );
 
     println!("SCF converged in {} iterations",
@@ -84,36 +82,62 @@ fn main() {
     if input.task.compute_gradients {
         println!("Computing gradients...");
         let grad =
-            compute_gradients(
-                &molecule,
+            total_gradient(
                 &shells,
                 &shell_centers,
+                &molecule.atoms,
                 &scf_result,
+                &input.scf,
             );
 
-        grad.print();
+        for (atom, g) in molecule.atoms.iter().zip(&grad) {
+            println!("{:>3} {:>16.10} {:>16.10} {:>16.10}", atom.symbol, g[0], g[1], g[2]);
+        }
     }
 
     // -------------------------------------------------
     // 7. Hessian + frequencies (optional)
     // -------------------------------------------------
-    if input.task.compute_hessian {
-        println!("Computing Hessian...");
-        let hess =
-            compute_hessian(
-                &molecule,
-                &shells,
-                &shell_centers,
-                &scf_result,
-            );
+    if input.task.compute_hessian || input.task.compute_frequencies {
+        // Analytic gradient at displaced geometries
+        let gradient = |x: &Vec<f64>| -> Vec<f64> {
+            let mut atoms = molecule.atoms.clone();
+            for (a, atom) in atoms.iter_mut().enumerate() {
+                atom.position.copy_from_slice(&x[3 * a..3 * a + 3]);
+            }
+            let displaced = Molecule {
+                atoms,
+                charge: molecule.charge,
+                multiplicity: molecule.multiplicity,
+            };
+            let shells = load_basis(&displaced, &input.basis);
+            let centers: Vec<[f64; 3]> = shells.iter().map(|s| s.center).collect();
+            let result = run_scf(&displaced, &shells, &centers, &input.scf);
+            total_gradient(&shells, &centers, &displaced.atoms, &result, &input.scf)
+                .into_iter()
+                .flatten()
+                .collect()
+        };
+
+        let coords: Vec<f64> = molecule.atoms.iter().flat_map(|a| a.position).collect();
+
+        if input.task.compute_hessian {
+            println!("Computing Hessian...");
+            let hess = hessian_fd(&coords, &gradient, HESSIAN_STEP);
+            for row in &hess {
+                let line: Vec<String> = row.iter().map(|h| format!("{:>12.6}", h)).collect();
+                println!("{}", line.join(" "));
+            }
+        }
 
         if input.task.compute_frequencies {
-            let freqs =
-                compute_frequencies(&molecule, &hess);
-            freqs.print();
+            println!("Computing frequencies...");
+            let masses: Vec<f64> = molecule.atoms.iter().map(|a| atomic_mass(&a.symbol)).collect();
+            for f in compute_frequencies(&coords, &masses, &gradient) {
+                println!("{:>12.2} cm^-1", f);
+            }
         }
     }
 
     println!("Done.");
 }
-
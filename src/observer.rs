@@ -0,0 +1,189 @@
+//! Progress hooks for embedding the engine
+//!
+//! Drivers call an observer after every step:
+//!
+//! - SCF (`run_scf_observed`): `ScfObserver::on_iteration` with the energy,
+//!   DIIS errors, densities and Fock matrices of the iteration
+//! - geometry optimization (`optimize_observed`): `OptimizationObserver::on_step`
+//! - frequencies (`compute_frequencies_observed`): `FrequencyObserver::on_column`
+//!
+//! Every hook can stop its driver; the SCF hook can also add a potential
+//! to the Fock matrices. Closures of the right signature are observers.
+//!
+//! UHF / UDFT runs are `run_scf_observed` with `Reference::Unrestricted`
+//! (`run_uhf` and `run_udft` are plain wrappers). The GHF and complex
+//! drivers take no observer: their densities and Fock matrices are not a
+//! real (P^α, P^β) pair.
+
+use nalgebra::DMatrix;
+
+use crate::checkpoint::{HessianProgress, OptimizationRecord};
+use crate::scf::scf_cycle::{EnergyComponents, IterationRecord, ScfResult};
+
+/// Answer of the optimizer / frequency hooks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+/// Answer of the SCF hook
+#[derive(Clone, Debug)]
+pub enum ScfAction {
+    Continue,
+    /// Return the current state as a non-converged result
+    Stop,
+    /// Add (V^α, V^β) (AO) to every later Fock build, replacing the previous
+    /// one; its energy Σ_σ Σ P^σ V^σ goes into `one_electron`
+    SetPotential(DMatrix<f64>, DMatrix<f64>),
+}
+
+/// State of one SCF iteration
+///
+/// In ROHF / ROKS the Fock matrices are the effective one (equal for
+/// both spins); in restricted runs P^α = P^β = P/2.
+pub struct ScfIteration<'a> {
+    pub record: &'a IterationRecord,
+    pub components: &'a EnergyComponents,
+    pub p_alpha: &'a DMatrix<f64>,
+    pub p_beta: &'a DMatrix<f64>,
+    /// Fock of (P^α, P^β) before DIIS and stabilizers
+    pub f_alpha: &'a DMatrix<f64>,
+    pub f_beta: &'a DMatrix<f64>,
+    /// FPS − SPF
+    pub error_alpha: &'a DMatrix<f64>,
+    pub error_beta: &'a DMatrix<f64>,
+}
+
+pub trait ScfObserver {
+    /// After the Fock build of every iteration (also for the restarts of
+    /// stability following and the CDFT multiplier loop)
+    fn on_iteration(&mut self, iteration: &ScfIteration) -> ScfAction;
+
+    /// Final result of `run_scf_observed`
+    fn on_finish(&mut self, _result: &ScfResult) {}
+}
+
+impl<F: FnMut(&ScfIteration) -> ScfAction> ScfObserver for F {
+    fn on_iteration(&mut self, iteration: &ScfIteration) -> ScfAction {
+        self(iteration)
+    }
+}
+
+pub trait OptimizationObserver {
+    /// After every accepted step
+    fn on_step(&mut self, record: &OptimizationRecord) -> Control;
+}
+
+impl<F: FnMut(&OptimizationRecord) -> Control> OptimizationObserver for F {
+    fn on_step(&mut self, record: &OptimizationRecord) -> Control {
+        self(record)
+    }
+}
+
+pub trait FrequencyObserver {
+    /// After every finite-difference gradient column
+    fn on_column(&mut self, progress: &HessianProgress) -> Control;
+}
+
+impl<F: FnMut(&HessianProgress) -> Control> FrequencyObserver for F {
+    fn on_column(&mut self, progress: &HessianProgress) -> Control {
+        self(progress)
+    }
+}
+
+/// Observer of an SCF run with the potential it injected
+pub(crate) struct ScfHook<'a> {
+    observer: &'a mut dyn ScfObserver,
+    pub(crate) potential: Option<(DMatrix<f64>, DMatrix<f64>)>,
+    /// The observer answered `Stop` (ends the outer loops too)
+    pub(crate) stopped: bool,
+}
+
+impl<'a> ScfHook<'a> {
+    pub(crate) fn new(observer: &'a mut dyn ScfObserver) -> Self {
+        Self { observer, potential: None, stopped: false }
+    }
+
+    /// Calls the observer; true if it asked to stop
+    pub(crate) fn notify(&mut self, iteration: &ScfIteration) -> bool {
+        match self.observer.on_iteration(iteration) {
+            ScfAction::Continue => false,
+            ScfAction::Stop => {
+                self.stopped = true;
+                true
+            }
+            ScfAction::SetPotential(v_a, v_b) => {
+                self.potential = Some((v_a, v_b));
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, run_scf_observed, Reference, ScfOptions};
+    use crate::scf::uhf::run_uhf;
+    use crate::scf::utils::build_overlap_matrix;
+
+    #[test]
+    fn stop_returns_the_current_state() {
+        let (molecule, shells, centers) = h2();
+        let mut calls = 0;
+        let mut observer = |iteration: &ScfIteration| {
+            calls += 1;
+            if iteration.record.iteration == 3 {
+                ScfAction::Stop
+            } else {
+                ScfAction::Continue
+            }
+        };
+
+        let options = quiet_options();
+        let result = run_scf_observed(&molecule, &shells, &centers, &options, &mut observer);
+
+        assert_eq!(calls, 3);
+        assert_eq!(result.iterations, 3);
+        assert!(!result.converged);
+    }
+
+    #[test]
+    fn injected_potential_shifts_fock_and_energy() {
+        let (molecule, shells, centers) = h2();
+        let options = quiet_options();
+        let shift = 0.1;
+        let v = build_overlap_matrix(&shells) * shift;
+
+        let free = run_scf(&molecule, &shells, &centers, &options);
+        let mut observer = |_: &ScfIteration| ScfAction::SetPotential(v.clone(), v.clone());
+        let shifted = run_scf_observed(&molecule, &shells, &centers, &options, &mut observer);
+
+        // c S moves every ε by c and leaves the orbitals alone: ΔE = c N
+        assert!(shifted.converged);
+        assert!((shifted.energy - free.energy - 2.0 * shift).abs() < 1e-8);
+        for (e, e0) in shifted.alpha.energies.iter().zip(&free.alpha.energies) {
+            assert!((e - e0 - shift).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn unrestricted_observed_run_matches_run_uhf() {
+        let (molecule, shells, centers) =
+            hydrogens(&[[0.0; 3], [0.0, 0.0, 1.4], [0.0, 0.0, 2.8]], 0, 2);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+        let mut iterations = 0;
+        let mut observer = |_: &ScfIteration| {
+            iterations += 1;
+            ScfAction::Continue
+        };
+
+        let observed = run_scf_observed(&molecule, &shells, &centers, &options, &mut observer);
+        let uhf = run_uhf(&molecule, &shells, &centers, &options);
+
+        assert_eq!(iterations, observed.iterations);
+        assert!((observed.energy - uhf.energy).abs() < 1e-12);
+    }
+}
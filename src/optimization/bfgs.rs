@@ -15,6 +15,15 @@ impl Bfgs {
         Self { h_inv }
     }
 
+    /// Restart from a stored inverse Hessian
+    pub fn from_inverse_hessian(h_inv: Vec<Vec<f64>>) -> Self {
+        Self { h_inv }
+    }
+
+    pub fn inverse_hessian(&self) -> &Vec<Vec<f64>> {
+        &self.h_inv
+    }
+
     pub fn step(
         &mut self,
         x: &Vec<f64>,
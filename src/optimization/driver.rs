@@ -1,46 +1,159 @@
 //! Geometry optimization driver
 
+use std::path::Path;
+
+use crate::checkpoint::{Checkpoint, OptimizationRecord};
+use crate::observer::{Control, OptimizationObserver};
 use crate::optimization::{bfgs::Bfgs, lbfgs::Lbfgs};
 
+/// eval(x) → (energy, gradient)
+pub type Objective = dyn Fn(&Vec<f64>) -> (f64, Vec<f64>);
+
 pub enum Optimizer {
     BFGS,
     LBFGS { m: usize },
 }
 
+/// Optimizer with its quasi-Newton memory
+enum State {
+    Bfgs(Bfgs),
+    Lbfgs(Lbfgs),
+}
+
+impl State {
+    fn step(
+        &mut self,
+        x: &Vec<f64>,
+        f: f64,
+        g: &Vec<f64>,
+        eval: &Objective,
+    ) -> (Vec<f64>, f64, Vec<f64>) {
+        match self {
+            State::Bfgs(opt) => opt.step(x, f, g, eval),
+            State::Lbfgs(opt) => opt.step(x, f, g, eval),
+        }
+    }
+}
+
 /// Generic geometry optimizer
 ///
 /// eval(x) must return (energy, gradient)
 pub fn optimize(
-    mut x: Vec<f64>,
-    eval: &dyn Fn(&Vec<f64>) -> (f64, Vec<f64>),
+    x: Vec<f64>,
+    eval: &Objective,
+    optimizer: Optimizer,
+    max_iter: usize,
+    grad_tol: f64,
+) -> (Vec<f64>, f64) {
+
+    optimize_observed(x, eval, optimizer, max_iter, grad_tol, &mut |_: &OptimizationRecord| {
+        Control::Continue
+    })
+}
+
+/// `optimize` reporting every step to `observer`, which may stop it
+/// (the last accepted point is returned)
+pub fn optimize_observed(
+    x: Vec<f64>,
+    eval: &Objective,
     optimizer: Optimizer,
     max_iter: usize,
     grad_tol: f64,
+    observer: &mut dyn OptimizationObserver,
 ) -> (Vec<f64>, f64) {
 
-    let (mut f, mut g) = eval(&x);
+    let (f, g) = eval(&x);
+    let state = match optimizer {
+        Optimizer::BFGS => State::Bfgs(Bfgs::new(x.len())),
+        Optimizer::LBFGS { m } => State::Lbfgs(Lbfgs::new(m)),
+    };
+
+    run(state, (x, f, g), 0, eval, max_iter, grad_tol, observer)
+}
 
-    match optimizer {
-        Optimizer::BFGS => {
-            let mut opt = Bfgs::new(x.len());
-            for _ in 0..max_iter {
-                if norm(&g) < grad_tol {
-                    break;
+/// `optimize` saving its state to `path` after every step
+///
+/// Resumes from `checkpoint.optimization` if present (the starting point
+/// `x` is then ignored); the checkpoint geometry follows the optimizer.
+/// A failed save only warns.
+pub fn optimize_resume(
+    x: Vec<f64>,
+    eval: &Objective,
+    optimizer: Optimizer,
+    max_iter: usize,
+    grad_tol: f64,
+    checkpoint: &mut Checkpoint,
+    path: &Path,
+) -> (Vec<f64>, f64) {
+
+    let (state, x, f, g, iteration) = match checkpoint.optimization.take() {
+        Some(record) => {
+            println!("Resuming optimization at step {}", record.iteration);
+            let state = match optimizer {
+                Optimizer::BFGS => State::Bfgs(match record.inverse_hessian {
+                    Some(h_inv) => Bfgs::from_inverse_hessian(h_inv),
+                    None => Bfgs::new(record.x.len()),
+                }),
+                Optimizer::LBFGS { m } => {
+                    State::Lbfgs(Lbfgs::from_history(m, record.lbfgs_s, record.lbfgs_y))
                 }
-                let (x_new, f_new, g_new) = opt.step(&x, f, &g, eval);
-                x = x_new; f = f_new; g = g_new;
-            }
+            };
+            (state, record.x, record.energy, record.gradient, record.iteration)
         }
+        None => {
+            let (f, g) = eval(&x);
+            let state = match optimizer {
+                Optimizer::BFGS => State::Bfgs(Bfgs::new(x.len())),
+                Optimizer::LBFGS { m } => State::Lbfgs(Lbfgs::new(m)),
+            };
+            (state, x, f, g, 0)
+        }
+    };
 
-        Optimizer::LBFGS { m } => {
-            let mut opt = Lbfgs::new(m);
-            for _ in 0..max_iter {
-                if norm(&g) < grad_tol {
-                    break;
-                }
-                let (x_new, f_new, g_new) = opt.step(&x, f, &g, eval);
-                x = x_new; f = f_new; g = g_new;
-            }
+    run(state, (x, f, g), iteration, eval, max_iter, grad_tol, &mut |record: &OptimizationRecord| {
+        if record.x.len() == 3 * checkpoint.geometry.atoms.len() {
+            checkpoint.geometry.set_coordinates(&record.x);
+        }
+        checkpoint.optimization = Some(record.clone());
+        if let Err(e) = checkpoint.save(path) {
+            eprintln!("WARNING: {}", e);
+        }
+        Control::Continue
+    })
+}
+
+fn run(
+    mut state: State,
+    (mut x, mut f, mut g): (Vec<f64>, f64, Vec<f64>),
+    start: usize,
+    eval: &Objective,
+    max_iter: usize,
+    grad_tol: f64,
+    observer: &mut dyn OptimizationObserver,
+) -> (Vec<f64>, f64) {
+
+    for iteration in start..max_iter {
+        if norm(&g) < grad_tol {
+            break;
+        }
+        let (x_new, f_new, g_new) = state.step(&x, f, &g, eval);
+        x = x_new; f = f_new; g = g_new;
+
+        let (inverse_hessian, (lbfgs_s, lbfgs_y)) = match &state {
+            State::Bfgs(opt) => (Some(opt.inverse_hessian().clone()), (Vec::new(), Vec::new())),
+            State::Lbfgs(opt) => (None, opt.history()),
+        };
+        let record = OptimizationRecord {
+            iteration: iteration + 1,
+            x: x.clone(),
+            energy: f,
+            gradient: g.clone(),
+            inverse_hessian,
+            lbfgs_s,
+            lbfgs_y,
+        };
+        if observer.on_step(&record) == Control::Stop {
+            break;
         }
     }
 
@@ -18,6 +18,23 @@ impl Lbfgs {
         }
     }
 
+    /// Restart from stored (s, y) pairs, oldest first
+    pub fn from_history(m: usize, s: Vec<Vec<f64>>, y: Vec<Vec<f64>>) -> Self {
+        Self {
+            m,
+            s_list: s.into(),
+            y_list: y.into(),
+        }
+    }
+
+    /// (s, y) pairs, oldest first
+    pub fn history(&self) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        (
+            self.s_list.iter().cloned().collect(),
+            self.y_list.iter().cloned().collect(),
+        )
+    }
+
     fn two_loop(&self, g: &Vec<f64>) -> Vec<f64> {
         let mut q = g.clone();
         let mut alpha = Vec::new();
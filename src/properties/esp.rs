@@ -0,0 +1,237 @@
+//! Molecular electrostatic potential (ESP) and electric field
+//!
+//! At a point C:
+//!   φ(C) = Σ_A Z_A / |R_A − C|  −  Σ_{μν} P_{μν} ⟨μ| 1/|r−C| |ν⟩
+//!   E(C) = −∇φ(C)
+//!        = Σ_A Z_A (C − R_A) / |C − R_A|³
+//!        + Σ_{μν} P_{μν} ⟨μ| (r−C) / |r−C|³ |ν⟩
+//!
+//! Points are NOT atoms: they carry no basis functions. When a point
+//! coincides with a nucleus, that nucleus is excluded from the nuclear
+//! sum (potential / field "at the nucleus").
+//!
+//! Building block for ESP-fitted charges, QM/MM embedding, PCM and
+//! electric field gradients.
+
+use crate::basis::shell::Shell;
+use crate::system::atom::Atom;
+use crate::integrals::nuclear_attraction::{
+    point_field_shell_shell,
+    point_potential_shell_shell,
+};
+
+/// Points closer than this to a nucleus are treated as "at" it (bohr)
+const SELF_CUTOFF: f64 = 1e-8;
+
+/// Electronic potential Σ P ⟨μ|1/|r−C||ν⟩ at one point
+fn electronic_potential(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    point: [f64; 3],
+) -> f64 {
+    let mut v = 0.0;
+
+    for si in shells {
+        for sj in shells {
+            let block = point_potential_shell_shell(si, sj, point);
+
+            for (mu, row) in block.iter().enumerate() {
+                for (nu, val) in row.iter().enumerate() {
+                    v += density[si.offset + mu][sj.offset + nu] * val;
+                }
+            }
+        }
+    }
+
+    v
+}
+
+/// Electronic field Σ P ⟨μ|(r−C)/|r−C|³|ν⟩ at one point
+fn electronic_field(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    point: [f64; 3],
+) -> [f64; 3] {
+    let mut e = [0.0; 3];
+
+    for si in shells {
+        for sj in shells {
+            let blocks = point_field_shell_shell(si, sj, point);
+
+            for k in 0..3 {
+                for (mu, row) in blocks[k].iter().enumerate() {
+                    for (nu, val) in row.iter().enumerate() {
+                        e[k] += density[si.offset + mu][sj.offset + nu] * val;
+                    }
+                }
+            }
+        }
+    }
+
+    e
+}
+
+/// Molecular ESP φ(C) at arbitrary points (a.u.)
+///
+/// `density` is the total AO density (P_α + P_β for UHF).
+pub fn electrostatic_potential(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+    points: &[[f64; 3]],
+) -> Vec<f64> {
+    points
+        .iter()
+        .map(|&c| {
+            let mut phi = -electronic_potential(shells, density, c);
+
+            for a in atoms {
+                let r = distance(a.position, c);
+                if r > SELF_CUTOFF {
+                    phi += a.nuclear_charge() / r;
+                }
+            }
+
+            phi
+        })
+        .collect()
+}
+
+/// Molecular electric field E(C) at arbitrary points (a.u.)
+pub fn electric_field(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+    points: &[[f64; 3]],
+) -> Vec<[f64; 3]> {
+    points
+        .iter()
+        .map(|&c| {
+            let mut e = electronic_field(shells, density, c);
+
+            for a in atoms {
+                let r = distance(a.position, c);
+                if r > SELF_CUTOFF {
+                    let z = a.nuclear_charge() / (r * r * r);
+                    for k in 0..3 {
+                        e[k] += z * (c[k] - a.position[k]);
+                    }
+                }
+            }
+
+            e
+        })
+        .collect()
+}
+
+/// ESP at every nucleus (self-interaction excluded)
+pub fn esp_at_nuclei(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+) -> Vec<f64> {
+    let points: Vec<[f64; 3]> = atoms.iter().map(|a| a.position).collect();
+    electrostatic_potential(shells, density, atoms, &points)
+}
+
+/// Electric field at every nucleus (self-interaction excluded)
+pub fn field_at_nuclei(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+) -> Vec<[f64; 3]> {
+    let points: Vec<[f64; 3]> = atoms.iter().map(|a| a.position).collect();
+    electric_field(shells, density, atoms, &points)
+}
+
+#[inline]
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let dx = a[0] - b[0];
+    let dy = a[1] - b[1];
+    let dz = a[2] - b[2];
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::integrals::multipole::multipole_integrals;
+
+    /// H atoms with two s shells each, and a p shell on the first atom
+    fn hydrogens(positions: &[[f64; 3]], with_p: bool) -> (Vec<Atom>, Vec<Shell>) {
+        let atoms = positions.iter().map(|&r| Atom::new("H".to_string(), 1, r)).collect();
+
+        let mut shells: Vec<Shell> = Vec::new();
+        for (a, &center) in positions.iter().enumerate() {
+            let mut kinds = vec![([0, 0, 0], 1.3), ([0, 0, 0], 0.25)];
+            if with_p && a == 0 {
+                kinds.push(([1, 0, 0], 0.6));
+            }
+            for (ang, alpha) in kinds {
+                let offset = shells.iter().map(Shell::n_orbitals).sum();
+                let primitive = Primitive::new(alpha, 1.0, center, ang);
+                shells.push(Shell::new(vec![primitive], ang, center, offset));
+            }
+        }
+
+        (atoms, shells)
+    }
+
+    /// n c cᵀ / (cᵀ S c): `electrons` electrons in one orbital
+    fn density(shells: &[Shell], c: &[f64], electrons: f64) -> Vec<Vec<f64>> {
+        let s = &multipole_integrals(shells, [0.0; 3], 0)[0];
+        let norm: f64 =
+            (0..c.len()).flat_map(|i| (0..c.len()).map(move |j| c[i] * s[i][j] * c[j])).sum();
+        c.iter().map(|x| c.iter().map(|y| electrons * x * y / norm).collect()).collect()
+    }
+
+    #[test]
+    fn field_is_minus_the_gradient_of_the_potential() {
+        let (atoms, shells) = hydrogens(&[[0.0, 0.0, 0.0], [0.0, 0.0, 1.4]], true);
+        let p = density(&shells, &[0.4, 0.3, 0.2, -0.1, 0.05, 0.5, 0.2], 2.0);
+        let c = [0.4, -0.3, 2.1];
+        let h = 1e-5;
+
+        let e = electric_field(&shells, &p, &atoms, &[c])[0];
+
+        for k in 0..3 {
+            let (mut cp, mut cm) = (c, c);
+            cp[k] += h;
+            cm[k] -= h;
+            let phi = electrostatic_potential(&shells, &p, &atoms, &[cp, cm]);
+            let fd = -(phi[0] - phi[1]) / (2.0 * h);
+            assert!((e[k] - fd).abs() < 1e-6, "{}: {} vs {}", k, e[k], fd);
+        }
+    }
+
+    #[test]
+    fn far_potential_is_that_of_the_net_charge() {
+        let far = [0.0, 30.0, 0.0];
+        let (atoms, shells) = hydrogens(&[[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.4, 0.8]], false);
+        let c = [0.3, 0.4, 0.2, 0.5, 0.3, 0.4];
+
+        let neutral = density(&shells, &c, 3.0);
+        let phi = electrostatic_potential(&shells, &neutral, &atoms, &[far]);
+        assert!(phi[0].abs() < 1e-4);
+
+        let cation = density(&shells, &c, 2.0);
+        let phi = electrostatic_potential(&shells, &cation, &atoms, &[far]);
+        assert!((phi[0] * 30.0 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn values_at_nuclei_exclude_their_own_charge() {
+        let (atoms, shells) = hydrogens(&[[0.0, 0.0, 0.0], [0.0, 0.0, 1.4]], false);
+        let p = density(&shells, &[0.5, 0.3, 0.5, 0.3], 2.0);
+
+        let phi = esp_at_nuclei(&shells, &p, &atoms);
+        let field = field_at_nuclei(&shells, &p, &atoms);
+
+        assert!(phi.iter().all(|x| x.is_finite()));
+        // mirror-symmetric nuclei: same potential, opposite fields along z
+        assert!((phi[0] - phi[1]).abs() < 1e-10);
+        assert!((field[0][2] + field[1][2]).abs() < 1e-10);
+        assert!(field[0][0].abs() < 1e-12 && field[0][1].abs() < 1e-12);
+    }
+}
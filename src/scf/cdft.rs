@@ -0,0 +1,605 @@
+//! Constrained DFT (CDFT) with charge and spin constraints
+//!
+//! Each constraint c fixes a population of a fragment F (set of atoms)
+//!
+//!   N_c[P] = Σ_σ s_c^σ tr(P^σ W_c) = N_c^0
+//!
+//! with s^α = s^β = 1 for charge and s^α = −s^β = 1 for spin, and W_c
+//! the AO matrix of the fragment weight w_F(r):
+//!
+//! - Becke:     w_F = Σ_{A∈F} p_A(r), fuzzy Becke cells (k = 3)
+//! - Hirshfeld: w_F = Σ_{A∈F} ρ_A⁰ / Σ_B ρ_B⁰, spherical atomic SAD densities
+//! - Löwdin:    W = S^½ Π_F S^½ (no grid)
+//!
+//! For fixed multipliers V_c the SCF minimizes
+//!
+//!   L[P, V] = E[P] + Σ_c V_c (N_c[P] − N_c^0)
+//!
+//! by adding V^σ = Σ_c V_c s_c^σ W_c to F^σ. The outer loop maximizes L
+//! over V (Newton on N_c − N_c^0 = 0): the first Jacobian is the
+//! uncoupled response
+//!
+//!   ∂N_c/∂V_d = Σ_σ s_c^σ s_d^σ Σ_ia 2 (W_c)_ia (W_d)_ia / (ε_i − ε_a)
+//!
+//! refined by Broyden updates; every SCF restarts from the previous one.
+//! The reported energy is E[P] of the constrained density.
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::basis::shell::Shell;
+use crate::dft::density::basis_at_point;
+use crate::dft::grid::{DftGrid, GridPoint};
+use crate::system::atom::Atom;
+use crate::scf::guess::{atom_shells, promolecule_densities, Guess};
+use crate::scf::scf_cycle::{
+    iterate, iterate_from, NonConvergence, Reference, ScfOptions, ScfResult, ScfSystem,
+    SpinOrbitals,
+};
+use crate::scf::utils::vec2d_ref_to_dmatrix;
+
+/// Same molecular grid as `dft::vxc`
+const GRID_RADIAL: usize = 30;
+const GRID_ANGULAR: usize = 14;
+
+/// Becke cell function iterations
+const BECKE_ITERATIONS: usize = 3;
+
+/// Largest multiplier change per outer iteration (Hartree)
+const MAX_STEP: f64 = 0.5;
+
+/// Fragment weight function
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Partition {
+    Becke,
+    Hirshfeld,
+    Lowdin,
+}
+
+impl Partition {
+    /// Parse the input keyword (becke | hirshfeld | lowdin)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "becke" => Some(Self::Becke),
+            "hirshfeld" => Some(Self::Hirshfeld),
+            "lowdin" | "löwdin" => Some(Self::Lowdin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstraintKind {
+    /// Fragment charge Σ_{A∈F} Z_A − N_F
+    Charge,
+    /// Fragment spin population N_F^α − N_F^β
+    Spin,
+}
+
+impl ConstraintKind {
+    /// (s^α, s^β)
+    pub(crate) fn spin_signs(self) -> (f64, f64) {
+        match self {
+            ConstraintKind::Charge => (1.0, 1.0),
+            ConstraintKind::Spin => (1.0, -1.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Constraint {
+    /// Indices into the molecule's atoms
+    pub atoms: Vec<usize>,
+    pub kind: ConstraintKind,
+    /// Target charge or spin population
+    pub target: f64,
+}
+
+impl Constraint {
+    pub fn charge(atoms: Vec<usize>, charge: f64) -> Self {
+        Self { atoms, kind: ConstraintKind::Charge, target: charge }
+    }
+
+    pub fn spin(atoms: Vec<usize>, spin_population: f64) -> Self {
+        Self { atoms, kind: ConstraintKind::Spin, target: spin_population }
+    }
+
+    /// Target in electrons, N_c^0
+    fn electron_target(&self, atoms: &[Atom]) -> f64 {
+        match self.kind {
+            ConstraintKind::Charge => self.valence(atoms) - self.target,
+            ConstraintKind::Spin => self.target,
+        }
+    }
+
+    /// Reported value (charge or spin population) from N_c
+    fn value(&self, atoms: &[Atom], population: f64) -> f64 {
+        match self.kind {
+            ConstraintKind::Charge => self.valence(atoms) - population,
+            ConstraintKind::Spin => population,
+        }
+    }
+
+    fn valence(&self, atoms: &[Atom]) -> f64 {
+        self.atoms
+            .iter()
+            .map(|&a| (atoms[a].atomic_number - atoms[a].core_electrons) as f64)
+            .sum()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CdftOptions {
+    pub constraints: Vec<Constraint>,
+    pub partition: Partition,
+    /// max |N_c − N_c^0| (electrons)
+    pub tolerance: f64,
+    /// Outer (multiplier) iterations
+    pub max_iter: usize,
+}
+
+impl Default for CdftOptions {
+    fn default() -> Self {
+        Self {
+            constraints: Vec::new(),
+            partition: Partition::Becke,
+            tolerance: 1e-5,
+            max_iter: 20,
+        }
+    }
+}
+
+/// Multipliers and weights of a converged CDFT run
+#[derive(Clone, Debug)]
+pub struct CdftSolution {
+    /// V_c (Hartree)
+    pub multipliers: Vec<f64>,
+    /// Charge or spin population reached per constraint
+    pub values: Vec<f64>,
+    /// W_c (AO)
+    pub weights: Vec<DMatrix<f64>>,
+    pub iterations: usize,
+    pub converged: bool,
+    /// Hirshfeld promolecule (empty for Becke / Löwdin)
+    pub(crate) promolecule: Vec<Option<(DMatrix<f64>, Vec<usize>)>>,
+}
+
+impl CdftSolution {
+    pub fn print(&self, options: &CdftOptions) {
+        println!("\nCDFT constraints ({:?})", options.partition);
+        for ((c, v), value) in options.constraints.iter().zip(&self.multipliers).zip(&self.values) {
+            println!(
+                "  {:?} {:?}  target = {:10.6}  value = {:10.6}  V = {:12.8}",
+                c.kind, c.atoms, c.target, value, v
+            );
+        }
+    }
+}
+
+/// CDFT outer loop (called from `run_scf`)
+pub(crate) fn run_cdft(
+    sys: &mut ScfSystem,
+    options: &ScfOptions,
+    cdft: &CdftOptions,
+) -> ScfResult {
+
+    assert!(!cdft.constraints.is_empty(), "CDFT requires at least one constraint");
+    assert!(
+        options.reference == Reference::Unrestricted
+            || cdft.constraints.iter().all(|c| c.kind == ConstraintKind::Charge),
+        "CDFT spin constraints require an unrestricted reference"
+    );
+    for c in &cdft.constraints {
+        assert!(
+            c.atoms.iter().all(|&a| a < sys.atoms.len()),
+            "CDFT constraint on atoms {:?}: only {} atoms",
+            c.atoms,
+            sys.atoms.len()
+        );
+    }
+
+    let promolecule = match cdft.partition {
+        Partition::Hirshfeld => promolecule_densities(sys, options),
+        _ => Vec::new(),
+    };
+    let weights = constraint_weights(
+        sys.shells,
+        sys.atoms,
+        &sys.overlap,
+        cdft,
+        &promolecule,
+    );
+
+    let targets: Vec<f64> = cdft
+        .constraints
+        .iter()
+        .map(|c| c.electron_target(sys.atoms))
+        .collect();
+    let n_c = targets.len();
+
+    let mut v = DVector::zeros(n_c);
+    let mut result = iterate(sys, options);
+    // (V, N − N⁰) del paso anterior y Jacobiano ∂N/∂V
+    let mut previous: Option<(DVector<f64>, DVector<f64>)> = None;
+    let mut jacobian = DMatrix::zeros(n_c, n_c);
+
+    for iter in 0..cdft.max_iter {
+        let populations = constraint_populations(&result, cdft, &weights);
+        let residual = DVector::from_iterator(
+            n_c,
+            populations.iter().zip(&targets).map(|(n, n0)| n - n0),
+        );
+
+        if options.print_iterations {
+            println!(
+                "CDFT {:3}  V = {:?}  max |N - N0| = {:10.3e}",
+                iter + 1,
+                v.as_slice(),
+                residual.amax()
+            );
+        }
+
+        let converged = residual.amax() < cdft.tolerance;
+        if converged || sys.stopped() || iter + 1 == cdft.max_iter {
+            if !converged && !sys.stopped() {
+                let message = format!(
+                    "CDFT did not converge in {} iterations (max |N - N0| = {:.3e})",
+                    cdft.max_iter,
+                    residual.amax()
+                );
+                match options.on_max_iter {
+                    NonConvergence::Fail => panic!("{}", message),
+                    NonConvergence::Warn => eprintln!("WARNING: {}", message),
+                }
+            }
+
+            let values = cdft
+                .constraints
+                .iter()
+                .zip(&populations)
+                .map(|(c, &n)| c.value(sys.atoms, n))
+                .collect();
+
+            result.converged &= converged;
+            result.cdft = Some(CdftSolution {
+                multipliers: v.iter().copied().collect(),
+                values,
+                weights,
+                iterations: iter + 1,
+                converged,
+                promolecule,
+            });
+            return result;
+        }
+
+        // -----------------------------
+        // Jacobiano: respuesta desacoplada, luego Broyden
+        // -----------------------------
+        jacobian = match &previous {
+            None => uncoupled_response(&result, cdft, &weights),
+            Some((v_old, r_old)) => {
+                let dv = &v - v_old;
+                let dr = &residual - r_old;
+                let norm = dv.norm_squared();
+                if norm > 0.0 {
+                    &jacobian + (dr - &jacobian * &dv) * dv.transpose() / norm
+                } else {
+                    jacobian
+                }
+            }
+        };
+
+        let mut step = jacobian
+            .clone()
+            .lu()
+            .solve(&(-&residual))
+            .unwrap_or_else(|| -&residual);
+        if step.amax() > MAX_STEP {
+            step *= MAX_STEP / step.amax();
+        }
+
+        previous = Some((v.clone(), residual));
+        v += step;
+
+        sys.constraint_potential = Some(constraint_potential(v.as_slice(), cdft, &weights));
+        result = iterate_from(sys, options, restart_guess(&result));
+    }
+
+    unreachable!()
+}
+
+/// N_c = Σ_σ s_c^σ tr(P^σ W_c)
+fn constraint_populations(
+    result: &ScfResult,
+    cdft: &CdftOptions,
+    weights: &[DMatrix<f64>],
+) -> Vec<f64> {
+    let (p_a, p_b) = result.spin_densities();
+    let p_a = vec2d_ref_to_dmatrix(&p_a);
+    let p_b = vec2d_ref_to_dmatrix(&p_b);
+
+    cdft.constraints
+        .iter()
+        .zip(weights)
+        .map(|(c, w)| {
+            let (s_a, s_b) = c.kind.spin_signs();
+            s_a * p_a.dot(w) + s_b * p_b.dot(w)
+        })
+        .collect()
+}
+
+/// (V^α, V^β) = Σ_c V_c s_c^σ W_c
+fn constraint_potential(
+    multipliers: &[f64],
+    cdft: &CdftOptions,
+    weights: &[DMatrix<f64>],
+) -> (DMatrix<f64>, DMatrix<f64>) {
+    let nao = weights[0].nrows();
+    let mut v_a = DMatrix::zeros(nao, nao);
+    let mut v_b = DMatrix::zeros(nao, nao);
+
+    for ((c, w), &v) in cdft.constraints.iter().zip(weights).zip(multipliers) {
+        let (s_a, s_b) = c.kind.spin_signs();
+        v_a += w * (v * s_a);
+        v_b += w * (v * s_b);
+    }
+
+    (v_a, v_b)
+}
+
+/// ∂N_c/∂V_d without orbital relaxation of J / XC
+fn uncoupled_response(
+    result: &ScfResult,
+    cdft: &CdftOptions,
+    weights: &[DMatrix<f64>],
+) -> DMatrix<f64> {
+    let n_c = weights.len();
+    let mut jacobian = DMatrix::zeros(n_c, n_c);
+
+    // (orbitales, umbral de ocupación, índice de espín)
+    let channels: Vec<(&SpinOrbitals, f64, usize)> = match &result.beta {
+        Some(beta) => vec![(&result.alpha, 0.5, 0), (beta, 0.5, 1)],
+        None => vec![(&result.alpha, 1.0, 0), (&result.alpha, 1.0, 1)],
+    };
+
+    for (orbitals, threshold, spin) in channels {
+        let c = vec2d_ref_to_dmatrix(&orbitals.coefficients);
+        let eps = &orbitals.energies;
+        let occupied = |k: &usize| orbitals.occupations[*k] >= threshold;
+        let occ: Vec<usize> = (0..eps.len()).filter(occupied).collect();
+        let virt: Vec<usize> = (0..eps.len()).filter(|k| !occupied(k)).collect();
+
+        let mo: Vec<DMatrix<f64>> = weights.iter().map(|w| c.transpose() * w * &c).collect();
+        let signs: Vec<f64> = cdft
+            .constraints
+            .iter()
+            .map(|k| {
+                let (s_a, s_b) = k.kind.spin_signs();
+                if spin == 0 { s_a } else { s_b }
+            })
+            .collect();
+
+        for x in 0..n_c {
+            for y in 0..n_c {
+                let mut sum = 0.0;
+                for &i in &occ {
+                    for &a in &virt {
+                        sum += 2.0 * mo[x][(i, a)] * mo[y][(i, a)] / (eps[i] - eps[a]);
+                    }
+                }
+                jacobian[(x, y)] += signs[x] * signs[y] * sum;
+            }
+        }
+    }
+
+    jacobian
+}
+
+/// Orbitals and densities of the previous outer iteration
+fn restart_guess(result: &ScfResult) -> Guess {
+    let (p_a, p_b) = result.spin_densities();
+    let c_a = vec2d_ref_to_dmatrix(&result.alpha.coefficients);
+    let c_b = match &result.beta {
+        Some(beta) => vec2d_ref_to_dmatrix(&beta.coefficients),
+        None => c_a.clone(),
+    };
+
+    Guess {
+        p_alpha: vec2d_ref_to_dmatrix(&p_a),
+        p_beta: vec2d_ref_to_dmatrix(&p_b),
+        orbitals: (c_a, c_b),
+    }
+}
+
+// ======================================================
+// Fragment weight matrices
+// ======================================================
+
+/// W_c in the AO basis for the given geometry
+pub(crate) fn constraint_weights(
+    shells: &[Shell],
+    atoms: &[Atom],
+    overlap: &DMatrix<f64>,
+    cdft: &CdftOptions,
+    promolecule: &[Option<(DMatrix<f64>, Vec<usize>)>],
+) -> Vec<DMatrix<f64>> {
+
+    let nao = overlap.nrows();
+    let fragments: Vec<Vec<bool>> = cdft
+        .constraints
+        .iter()
+        .map(|c| (0..atoms.len()).map(|a| c.atoms.contains(&a)).collect())
+        .collect();
+
+    if cdft.partition == Partition::Lowdin {
+        let eig = overlap.clone().symmetric_eigen();
+        let s_half = &eig.eigenvectors
+            * DMatrix::from_diagonal(&eig.eigenvalues.map(f64::sqrt))
+            * eig.eigenvectors.transpose();
+
+        return fragments
+            .iter()
+            .map(|in_fragment| {
+                let mut projector = DMatrix::zeros(nao, nao);
+                for (atom, _) in atoms.iter().zip(in_fragment).filter(|(_, &f)| f) {
+                    for mu in atom_shells(shells, atom).1 {
+                        projector[(mu, mu)] = 1.0;
+                    }
+                }
+                &s_half * projector * &s_half
+            })
+            .collect();
+    }
+
+    let mut weights = vec![DMatrix::zeros(nao, nao); fragments.len()];
+
+    for (center, atom) in atoms.iter().enumerate() {
+        let grid = DftGrid::new(std::slice::from_ref(atom), GRID_RADIAL, GRID_ANGULAR);
+
+        for GridPoint { r, weight, .. } in grid.points {
+            let cells = becke_cells(atoms, r);
+            let quadrature = weight * cells[center];
+            if quadrature.abs() < 1e-14 {
+                continue;
+            }
+
+            let phi = DVector::from_vec(basis_at_point(shells, r).0);
+
+            let atomic = match cdft.partition {
+                Partition::Hirshfeld => hirshfeld_atoms(promolecule, &phi),
+                _ => cells,
+            };
+
+            let outer = &phi * phi.transpose();
+            for (w, in_fragment) in weights.iter_mut().zip(&fragments) {
+                let w_f: f64 = atomic
+                    .iter()
+                    .zip(in_fragment)
+                    .filter(|(_, &f)| f)
+                    .map(|(p, _)| p)
+                    .sum();
+                if w_f != 0.0 {
+                    *w += &outer * (quadrature * w_f);
+                }
+            }
+        }
+    }
+
+    weights
+}
+
+/// Normalized Becke cell functions p_A(r)
+fn becke_cells(atoms: &[Atom], r: [f64; 3]) -> Vec<f64> {
+    let distance = |a: [f64; 3], b: [f64; 3]| {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+    };
+    let dist: Vec<f64> = atoms.iter().map(|a| distance(r, a.position)).collect();
+
+    let mut cells = vec![1.0; atoms.len()];
+    for a in 0..atoms.len() {
+        for b in 0..atoms.len() {
+            if a == b {
+                continue;
+            }
+            let mut mu = (dist[a] - dist[b]) / distance(atoms[a].position, atoms[b].position);
+            for _ in 0..BECKE_ITERATIONS {
+                mu = 1.5 * mu - 0.5 * mu.powi(3);
+            }
+            cells[a] *= 0.5 * (1.0 - mu);
+        }
+    }
+
+    let total: f64 = cells.iter().sum();
+    cells.iter().map(|c| c / total).collect()
+}
+
+/// Hirshfeld atomic weights ρ_A⁰ / Σ_B ρ_B⁰ at one point
+fn hirshfeld_atoms(
+    promolecule: &[Option<(DMatrix<f64>, Vec<usize>)>],
+    phi: &DVector<f64>,
+) -> Vec<f64> {
+    let rho: Vec<f64> = promolecule
+        .iter()
+        .map(|entry| match entry {
+            Some((density, aos)) => {
+                let local = DVector::from_iterator(aos.len(), aos.iter().map(|&mu| phi[mu]));
+                local.dot(&(density * &local)).max(0.0)
+            }
+            None => 0.0,
+        })
+        .collect();
+
+    let total: f64 = rho.iter().sum();
+    if total < 1e-14 {
+        return vec![0.0; rho.len()];
+    }
+    rho.iter().map(|x| x / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, quiet_options};
+    use crate::scf::scf_cycle::run_scf;
+    use crate::scf::utils::build_overlap_matrix;
+
+    fn atom_fragments(partition: Partition) -> CdftOptions {
+        CdftOptions {
+            constraints: vec![Constraint::charge(vec![0], 0.0), Constraint::charge(vec![1], 0.0)],
+            partition,
+            ..CdftOptions::default()
+        }
+    }
+
+    #[test]
+    fn becke_weights_of_mirror_atoms_are_mirrored() {
+        let (molecule, shells, _) = h2();
+        let overlap = build_overlap_matrix(&shells);
+
+        let cdft = atom_fragments(Partition::Becke);
+        let w = constraint_weights(&shells, &molecule.atoms, &overlap, &cdft, &[]);
+
+        // AOs 0, 1 on atom 0 and 2, 3 on atom 1
+        let mirror = [2, 3, 0, 1];
+        for mu in 0..4 {
+            for nu in 0..4 {
+                assert!((w[0][(mu, nu)] - w[1][(mirror[mu], mirror[nu])]).abs() < 1e-10);
+            }
+        }
+        assert!(w[0][(0, 0)] > w[1][(0, 0)]);
+    }
+
+    #[test]
+    fn lowdin_fragments_add_up_to_the_overlap() {
+        let (molecule, shells, _) = h2();
+        let overlap = build_overlap_matrix(&shells);
+
+        let cdft = atom_fragments(Partition::Lowdin);
+        let w = constraint_weights(&shells, &molecule.atoms, &overlap, &cdft, &[]);
+
+        assert!((&w[0] + &w[1] - &overlap).amax() < 1e-12);
+    }
+
+    #[test]
+    fn charge_constraint_reaches_its_target() {
+        let (molecule, shells, centers) = h2();
+        let cdft = CdftOptions {
+            constraints: vec![Constraint::charge(vec![0], 0.2)],
+            ..CdftOptions::default()
+        };
+
+        let free = run_scf(&molecule, &shells, &centers, &quiet_options());
+        let result = run_scf(
+            &molecule,
+            &shells,
+            &centers,
+            &ScfOptions { cdft: Some(cdft), ..quiet_options() },
+        );
+
+        let solution = result.cdft.as_ref().unwrap();
+        assert!(solution.converged);
+        assert!((solution.values[0] - 0.2).abs() < 1e-5);
+        // the constraint pushes charge uphill from the symmetric solution
+        assert!(solution.multipliers[0] > 0.0);
+        assert!(result.energy > free.energy);
+    }
+}
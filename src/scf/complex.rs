@@ -0,0 +1,546 @@
+//! Complex-valued RHF / UHF / RKS / UKS
+//!
+//! Same equations as `scf_cycle` with Hermitian densities
+//! P^σ = Σ_i C_i C_i†:
+//!
+//!   F^σ = H + J[Re P] − K_eff[P^σ] + V_xc^σ[Re P^α, Re P^β]
+//!
+//! J and the collinear V_xc only see the real (symmetric) part of P, the
+//! imaginary (antisymmetric) part enters through exchange alone. Generic
+//! over the scalar: with `f64` this reproduces the real RHF / UHF path
+//! without stabilizers or TRAH; with `Complex<f64>` the orbital rotations
+//! and the DIIS extrapolation are complex.
+//!
+//! A real guess is a stationary point of the complex problem as well, so
+//! `ScfOptions::guess_rotation` mixes HOMO and LUMO with phase i to leave
+//! the real subspace.
+
+use std::time::Instant;
+
+use nalgebra::DMatrix;
+
+use crate::basis::shell::Shell;
+use crate::system::molecule::Molecule;
+use crate::dft::vxc::{build_vxc, build_vxc_udft, ExchangeMixing};
+use crate::scf::diis::{Diis, DiisEntry};
+use crate::scf::external::external_contribution;
+use crate::scf::guess::initial_guess;
+use crate::scf::jk::build_jk_set;
+use crate::scf::scalar::{
+    commutator, from_parts, imag_part, promote, real_part, realify, rotate_pair,
+    solve_hermitian, unrealify, weighted_density, ScfScalar,
+};
+use crate::scf::stability::{follow_complex_instabilities, StabilityMode};
+use crate::scf::scf_cycle::{
+    report_non_convergence, EnergyComponents, IterationRecord, Reference,
+    ScfMethod, ScfOptions, ScfSystem,
+};
+use crate::scf::utils::{dmatrix_to_vec2d, vec2d_ref_to_dmatrix};
+
+/// Orbitales de un canal de espín (complejos o reales)
+pub struct ComplexOrbitals<T: ScfScalar> {
+    /// C (AO × MO)
+    pub coefficients: DMatrix<T>,
+    /// ε en orden ascendente
+    pub energies: Vec<f64>,
+    /// 2/0 en referencia restringida, 1/0 por espín si no
+    pub occupations: Vec<f64>,
+    /// F^σ (AO) sin extrapolar del último ciclo
+    pub fock: DMatrix<T>,
+    /// P = Σ_i n_i C_i C_i†
+    pub density: DMatrix<T>,
+}
+
+/// Resultado SCF complejo
+pub struct ComplexScfResult<T: ScfScalar> {
+    pub method: ScfMethod,
+    /// Energía total (incluye repulsión nuclear)
+    pub energy: f64,
+    pub components: EnergyComponents,
+    /// Densidad total P^α + P^β
+    pub density: DMatrix<T>,
+    /// Orbitales espaciales (restringido) u orbitales α
+    pub alpha: ComplexOrbitals<T>,
+    /// Orbitales β (no restringido)
+    pub beta: Option<ComplexOrbitals<T>>,
+    pub iterations: usize,
+    pub converged: bool,
+    pub history: Vec<IterationRecord>,
+}
+
+impl<T: ScfScalar> ComplexScfResult<T> {
+    /// (P^α, P^β); en referencia restringida P/2 cada una
+    pub fn spin_densities(&self) -> (DMatrix<T>, DMatrix<T>) {
+        match &self.beta {
+            Some(beta) => (self.alpha.density.clone(), beta.density.clone()),
+            None => {
+                let half = &self.density * T::from_real(0.5);
+                (half.clone(), half)
+            }
+        }
+    }
+
+    /// max |Im P_μν|: 0 for a solution that is real up to a phase
+    pub fn max_imaginary_density(&self) -> f64 {
+        imag_part(&self.density).amax()
+    }
+}
+
+/// Punto de entrada SCF complejo: RHF / UHF / RKS / UKS según `options`
+///
+/// `Reference::RestrictedOpenShell` no está soportado; no admite
+/// `ScfObserver` (ver `crate::observer`).
+pub fn run_scf_complex<T: ScfScalar>(
+    molecule: &Molecule,
+    shells: &[Shell],
+    shell_centers: &[[f64; 3]],
+    options: &ScfOptions,
+) -> ComplexScfResult<T> {
+
+    let sys = ScfSystem::new(molecule, shells, shell_centers, options);
+    let (n_alpha, n_beta) = (sys.n_alpha, sys.n_beta);
+
+    if options.reference == Reference::Restricted && n_alpha != n_beta {
+        panic!(
+            "Restricted SCF requires a closed shell (multiplicity {}); \
+             use Reference::Unrestricted",
+            molecule.multiplicity
+        );
+    }
+
+    // -----------------------------
+    // Guess real, opcionalmente rotado HOMO–LUMO
+    // -----------------------------
+    let guess = initial_guess(&sys, options);
+
+    let (p_a, p_b) = if options.guess_rotation != 0.0 {
+        let rotated = |c: &DMatrix<f64>, n_occ: usize| {
+            let mut c: DMatrix<T> = promote(c);
+            if n_occ > 0 && n_occ < c.ncols() {
+                rotate_pair(&mut c, n_occ - 1, n_occ, options.guess_rotation);
+            }
+            weighted_density(&c, &vec![1.0; n_occ])
+        };
+        (
+            rotated(&guess.orbitals.0, n_alpha),
+            rotated(&guess.orbitals.1, n_beta),
+        )
+    } else {
+        (promote(&guess.p_alpha), promote(&guess.p_beta))
+    };
+
+    let result = iterate_complex(&sys, options, p_a, p_b);
+
+    if options.stability.mode == StabilityMode::Off || sys.stopped() {
+        return result;
+    }
+    follow_complex_instabilities(&sys, options, result)
+}
+
+/// Ciclo SCF complejo desde (P^α, P^β) dadas
+///
+/// En referencia restringida se usa solo P^α (= P/2).
+pub(crate) fn iterate_complex<T: ScfScalar>(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+    mut p_a: DMatrix<T>,
+    mut p_b: DMatrix<T>,
+) -> ComplexScfResult<T> {
+
+    assert!(options.max_iter > 0, "SCF max_iter must be positive");
+    assert!(
+        options.reference != Reference::RestrictedOpenShell,
+        "complex SCF supports restricted and unrestricted references only"
+    );
+
+    let nao = sys.h_core.nrows();
+    let restricted = options.reference == Reference::Restricted;
+    let mixing = options.exchange_mixing();
+
+    if restricted {
+        p_b = p_a.clone();
+    }
+
+    let mut diis = Diis::new(options.diis_size);
+    let mut orbitals: Option<(DMatrix<T>, DMatrix<T>)> = None;
+
+    let mut energy_old = 0.0;
+    let mut delta_p = (0.0, 0.0);
+    let mut history: Vec<IterationRecord> = Vec::new();
+    let mut clock = Instant::now();
+
+    if options.print_iterations {
+        IterationRecord::print_header();
+    }
+
+    for iter in 0..options.max_iter {
+
+        let (f_a, f_b, components) =
+            complex_fock(sys, &p_a, &p_b, orbitals.as_ref(), options, &mixing);
+        let energy = components.total();
+
+        let err_a = commutator(&f_a, &p_a, &sys.overlap);
+        let err_b = commutator(&f_b, &p_b, &sys.overlap);
+        let commutator = err_a.camax().max(err_b.camax());
+
+        // Matrices complejas realificadas para el DIIS real
+        if options.diis_size > 0 {
+            diis.push(if restricted {
+                DiisEntry {
+                    focks: vec![realify(&f_a)],
+                    densities: vec![realify(&(&p_a + &p_b))],
+                    errors: vec![realify(&err_a)],
+                    energy,
+                }
+            } else {
+                DiisEntry {
+                    focks: vec![realify(&f_a), realify(&f_b)],
+                    densities: vec![realify(&p_a), realify(&p_b)],
+                    errors: vec![realify(&err_a), realify(&err_b)],
+                    energy,
+                }
+            });
+        }
+
+        let record = IterationRecord {
+            iteration: iter + 1,
+            energy,
+            delta_e: (energy - energy_old).abs(),
+            density_rms: delta_p.0,
+            density_max: delta_p.1,
+            commutator,
+            diis_size: diis.len(),
+            time: clock.elapsed(),
+        };
+        clock = Instant::now();
+
+        if options.print_iterations {
+            record.print();
+        }
+
+        let converged = iter > 0 && options.convergence.is_satisfied(&record);
+        history.push(record);
+
+        if converged || iter + 1 == options.max_iter {
+            if !converged {
+                report_non_convergence(options, history.last().unwrap());
+            }
+            return finalize(sys, options, f_a, f_b, history, converged);
+        }
+
+        // -----------------------------
+        // DIIS / EDIIS / ADIIS
+        // -----------------------------
+        let (f_a, f_b) = match diis.extrapolate_hybrid(options.accelerator, options.diis_switch) {
+            Some(focks) if restricted => {
+                let f: DMatrix<T> = unrealify(&focks[0]);
+                (f.clone(), f)
+            }
+            Some(focks) => (unrealify(&focks[0]), unrealify(&focks[1])),
+            None => (f_a, f_b),
+        };
+
+        let (c_a, _) = solve_hermitian(&f_a, &sys.overlap);
+        let c_b = if restricted { c_a.clone() } else { solve_hermitian(&f_b, &sys.overlap).0 };
+
+        let p_a_new = weighted_density(&c_a, &vec![1.0; sys.n_alpha]);
+        let p_b_new = weighted_density(&c_b, &vec![1.0; sys.n_beta]);
+
+        let d_a = &p_a_new - &p_a;
+        let d_b = &p_b_new - &p_b;
+        delta_p = (
+            d_a.norm().max(d_b.norm()) / nao as f64,
+            d_a.camax().max(d_b.camax()),
+        );
+
+        p_a = p_a_new;
+        p_b = p_b_new;
+        orbitals = Some((c_a, c_b));
+        energy_old = energy;
+    }
+
+    unreachable!()
+}
+
+/// F^α, F^β y componentes de energía para (P^α, P^β) hermíticas
+pub(crate) fn complex_fock<T: ScfScalar>(
+    sys: &ScfSystem,
+    p_a: &DMatrix<T>,
+    p_b: &DMatrix<T>,
+    orbitals: Option<&(DMatrix<T>, DMatrix<T>)>,
+    options: &ScfOptions,
+    mixing: &ExchangeMixing,
+) -> (DMatrix<T>, DMatrix<T>, EnergyComponents) {
+
+    let restricted = options.reference == Reference::Restricted;
+
+    let p_tot = real_part(&(p_a + p_b));
+    let (j, k_a, k_b) = if restricted {
+        let (j, mut k) = coulomb_exchange(sys, &p_tot, &[p_a], mixing);
+        let k_a = k.remove(0);
+        (j, k_a.clone(), k_a)
+    } else {
+        let (j, mut k) = coulomb_exchange(sys, &p_tot, &[p_a, p_b], mixing);
+        let k_b = k.remove(1);
+        (j, k.remove(0), k_b)
+    };
+
+    let hj: DMatrix<T> = promote(&(&sys.h_core + &j));
+    let mut f_a = &hj - &k_a;
+    let mut f_b = &hj - &k_b;
+
+    // Potenciales externos (reales, sobre Re P)
+    let mut e_external = 0.0;
+    if let Some((v, e)) = external_contribution(&options.external, sys.shells, sys.atoms, &p_tot) {
+        let v: DMatrix<T> = promote(&v);
+        f_a += &v;
+        f_b += &v;
+        e_external = e;
+    }
+
+    // -----------------------------
+    // XC colineal: solo Re P^σ
+    // -----------------------------
+    let mut e_xc = 0.0;
+
+    if let Some(xc) = &options.xc_method {
+
+        // τ de orbitales complejos: columnas [Re C_occ | Im C_occ]
+        let split = |c: &DMatrix<T>, n_occ: usize| {
+            let occ = c.columns(0, n_occ).into_owned();
+            let mut parts = DMatrix::zeros(c.nrows(), 2 * n_occ);
+            parts.columns_mut(0, n_occ).copy_from(&real_part(&occ));
+            parts.columns_mut(n_occ, n_occ).copy_from(&imag_part(&occ));
+            dmatrix_to_vec2d(&parts)
+        };
+        let coeffs = orbitals.map(|(c_a, c_b)| (split(c_a, sys.n_alpha), split(c_b, sys.n_beta)));
+
+        if restricted {
+            let (vxc, dft_energy) = build_vxc(
+                sys.shells,
+                sys.shell_centers,
+                &dmatrix_to_vec2d(&p_tot),
+                coeffs.as_ref().map(|(c, _)| c),
+                Some(2 * sys.n_alpha),
+                sys.atoms,
+                xc.clone(),
+            );
+
+            let vxc: DMatrix<T> = promote(&vec2d_ref_to_dmatrix(&vxc));
+            f_a += &vxc;
+            f_b += &vxc;
+            e_xc = dft_energy.exc;
+        } else {
+            let (vxa, vxb, dft_energy) = build_vxc_udft(
+                sys.shells,
+                sys.shell_centers,
+                &dmatrix_to_vec2d(&real_part(p_a)),
+                &dmatrix_to_vec2d(&real_part(p_b)),
+                coeffs.as_ref().map(|(c, _)| c),
+                coeffs.as_ref().map(|(_, c)| c),
+                Some(2 * sys.n_alpha),
+                Some(2 * sys.n_beta),
+                sys.atoms,
+                xc.clone(),
+            );
+
+            f_a += promote::<T>(&vec2d_ref_to_dmatrix(&vxa));
+            f_b += promote::<T>(&vec2d_ref_to_dmatrix(&vxb));
+            e_xc = dft_energy.exc;
+        }
+    }
+
+    // -----------------------------
+    // Energía (tr(K P) es real para K, P hermíticas)
+    // -----------------------------
+    let components = EnergyComponents {
+        nuclear: sys.e_nuc,
+        one_electron: p_tot.dot(&sys.h_core),
+        coulomb: 0.5 * p_tot.dot(&j),
+        exchange: -0.5 * ((&k_a * p_a).trace().real() + (&k_b * p_b).trace().real()),
+        xc: e_xc,
+        external: e_external,
+        dispersion: options.xc_method.as_ref().map_or(0.0, |xc| xc.dispersion_energy(sys.atoms)),
+    };
+
+    (f_a, f_b, components)
+}
+
+/// J[P_J] (real symmetric P_J) and K_eff[X_i] = α K[X_i] + β K^{lr}[X_i]
+/// from one pass over the ERIs, with K[A + iB] = K[A] + i K[B]
+///
+/// The X_i may be non-Hermitian (the α–β block of a GHF density).
+pub(crate) fn coulomb_exchange<T: ScfScalar>(
+    sys: &ScfSystem,
+    p_j: &DMatrix<f64>,
+    xs: &[&DMatrix<T>],
+    mixing: &ExchangeMixing,
+) -> (DMatrix<f64>, Vec<DMatrix<T>>) {
+
+    let n = p_j.nrows();
+    let has_exchange = mixing.full != 0.0 || mixing.has_long_range();
+
+    // Re X_i (e Im X_i si es complejo), en ese orden
+    let mut parts: Vec<Vec<Vec<f64>>> = Vec::new();
+    if has_exchange {
+        for x in xs {
+            parts.push(dmatrix_to_vec2d(&real_part(x)));
+            if T::IS_COMPLEX {
+                parts.push(dmatrix_to_vec2d(&imag_part(x)));
+            }
+        }
+    }
+    let part_refs: Vec<&Vec<Vec<f64>>> = parts.iter().collect();
+
+    let omega = mixing.has_long_range().then_some(mixing.omega);
+    let jk = build_jk_set(sys.shells, Some(&dmatrix_to_vec2d(p_j)), &part_refs, omega);
+
+    let k_eff = |i: usize| -> DMatrix<f64> {
+        let mut k = vec2d_ref_to_dmatrix(&jk.k[i]) * mixing.full;
+        if let Some(k_lr) = jk.k_lr.get(i) {
+            k += vec2d_ref_to_dmatrix(k_lr) * mixing.long_range;
+        }
+        k
+    };
+
+    let stride = if T::IS_COMPLEX { 2 } else { 1 };
+    let ks = (0..xs.len())
+        .map(|i| {
+            if !has_exchange {
+                return DMatrix::zeros(n, n);
+            }
+            let im = if T::IS_COMPLEX { k_eff(stride * i + 1) } else { DMatrix::zeros(n, n) };
+            from_parts(&k_eff(stride * i), &im)
+        })
+        .collect();
+
+    (vec2d_ref_to_dmatrix(&jk.j), ks)
+}
+
+/// Diagonaliza los Fock finales (sin DIIS) y empaqueta el resultado
+///
+/// Como en `scf_cycle`, Fock y energía se recalculan sobre la densidad
+/// de los orbitales devueltos.
+fn finalize<T: ScfScalar>(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+    f_a: DMatrix<T>,
+    f_b: DMatrix<T>,
+    history: Vec<IterationRecord>,
+    converged: bool,
+) -> ComplexScfResult<T> {
+
+    let restricted = options.reference == Reference::Restricted;
+
+    let (c_a, eps_a) = solve_hermitian(&f_a, &sys.overlap);
+    let (c_b, eps_b) = if restricted {
+        (c_a.clone(), eps_a.clone())
+    } else {
+        solve_hermitian(&f_b, &sys.overlap)
+    };
+
+    let aufbau = |n_eps: usize, n_occ: usize, occupation: f64| -> Vec<f64> {
+        (0..n_eps).map(|i| if i < n_occ { occupation } else { 0.0 }).collect()
+    };
+
+    // Fock y energía de la densidad que se devuelve
+    let p_a = weighted_density(&c_a, &aufbau(eps_a.len(), sys.n_alpha, 1.0));
+    let p_b = weighted_density(&c_b, &aufbau(eps_b.len(), sys.n_beta, 1.0));
+    let orbitals = (c_a, c_b);
+    let (f_a, f_b, components) =
+        complex_fock(sys, &p_a, &p_b, Some(&orbitals), options, &options.exchange_mixing());
+    let (c_a, c_b) = orbitals;
+
+    let channel = |c: DMatrix<T>, eps: Vec<f64>, fock: DMatrix<T>, n_occ: usize, occupation: f64| {
+        let occupations = aufbau(eps.len(), n_occ, occupation);
+
+        ComplexOrbitals {
+            density: weighted_density(&c, &occupations),
+            coefficients: c,
+            energies: eps,
+            occupations,
+            fock,
+        }
+    };
+
+    let (alpha, beta) = if restricted {
+        (channel(c_a, eps_a, f_a, sys.n_alpha, 2.0), None)
+    } else {
+        (
+            channel(c_a, eps_a, f_a, sys.n_alpha, 1.0),
+            Some(channel(c_b, eps_b, f_b, sys.n_beta, 1.0)),
+        )
+    };
+
+    let density = match &beta {
+        Some(beta) => &alpha.density + &beta.density,
+        None => alpha.density.clone(),
+    };
+
+    ComplexScfResult {
+        method: options.method(),
+        energy: components.total(),
+        components,
+        density,
+        alpha,
+        beta,
+        iterations: history.len(),
+        converged,
+        history,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Complex;
+
+    use super::*;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+    use crate::scf::scf_cycle::run_scf;
+
+    const H3: [[f64; 3]; 3] = [[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]];
+
+    #[test]
+    fn real_scalar_reproduces_the_real_driver() {
+        let unrestricted = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+
+        for ((molecule, shells, centers), options) in
+            [(h2(), quiet_options()), (hydrogens(&H3, 0, 2), unrestricted)]
+        {
+            let real = run_scf(&molecule, &shells, &centers, &options);
+            let generic = run_scf_complex::<f64>(&molecule, &shells, &centers, &options);
+
+            assert!(generic.converged);
+            assert!((generic.energy - real.energy).abs() < 1e-9);
+            let p = vec2d_ref_to_dmatrix(&real.density);
+            assert!((&generic.density - p).amax() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn complex_rotated_guess_returns_to_the_real_solution() {
+        let (molecule, shells, centers) = h2();
+        let options = ScfOptions { guess_rotation: 0.3, ..quiet_options() };
+
+        let real = run_scf(&molecule, &shells, &centers, &quiet_options());
+        let complex = run_scf_complex::<Complex<f64>>(&molecule, &shells, &centers, &options);
+
+        assert!(complex.converged);
+        assert!((complex.energy - real.energy).abs() < 1e-8);
+        assert!(complex.max_imaginary_density() < 1e-6);
+    }
+
+    #[test]
+    fn following_the_complex_instability_lowers_the_energy() {
+        let (molecule, shells, centers) = hydrogens(&[[0.0; 3], [0.0, 0.0, 5.0]], 0, 1);
+        let options = quiet_options();
+        let mut follow = options.clone();
+        follow.stability.mode = StabilityMode::Follow;
+
+        let plain = run_scf_complex::<Complex<f64>>(&molecule, &shells, &centers, &options);
+        let followed = run_scf_complex::<Complex<f64>>(&molecule, &shells, &centers, &follow);
+
+        assert!(followed.converged);
+        assert!(followed.energy < plain.energy - 1e-4, "{} vs {}", followed.energy, plain.energy);
+    }
+}
@@ -1,128 +1,400 @@
-/// Direct Inversion in the Iterative Subspace (DIIS)
-///
-/// Stores (Fock, error) pairs and extrapolates a new Fock matrix.
+//! Direct Inversion in the Iterative Subspace (DIIS)
+//!
+//! Stores (Fock, density, error, energy) history and extrapolates a
+//! new Fock matrix with one of:
+//! - Pulay (commutator) DIIS
+//! - EDIIS  (Kudin–Scuseria–Cancès, energy interpolation)
+//! - ADIIS  (Hu–Yang, augmented Roothaan–Hall energy)
+//!
+//! Every entry holds one matrix per spin channel: restricted SCF
+//! stores (F, P_total), unrestricted stores (F^α, P^α), (F^β, P^β).
+//! With that convention the spin-summed traces below are exact for
+//! both cases.
+
+use nalgebra::{DMatrix, DVector};
+
+/// Largest subspace used by EDIIS/ADIIS (2^m faces are enumerated)
+const MAX_EDIIS_VECS: usize = 10;
+
+/// Relative singular-value cutoff for the Pulay B matrix
+const SVD_CUTOFF: f64 = 1e-14;
+
+/// Convergence accelerator
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Accelerator {
+    /// Plain Pulay DIIS
+    Pulay,
+    /// EDIIS far from convergence, Pulay below the switch threshold
+    EDiis,
+    /// ADIIS far from convergence, Pulay below the switch threshold
+    ADiis,
+}
+
+impl Accelerator {
+    /// Parse the input keyword (diis | ediis | adiis)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "diis" | "pulay" => Some(Self::Pulay),
+            "ediis" => Some(Self::EDiis),
+            "adiis" => Some(Self::ADiis),
+            _ => None,
+        }
+    }
+}
+
+/// One SCF iterate
+pub struct DiisEntry {
+    pub focks: Vec<DMatrix<f64>>,
+    pub densities: Vec<DMatrix<f64>>,
+    /// F P S − S P F per channel
+    pub errors: Vec<DMatrix<f64>>,
+    pub energy: f64,
+}
+
+impl DiisEntry {
+    /// max |e_μν| over all channels
+    pub fn max_error(&self) -> f64 {
+        self.errors.iter().map(|e| e.amax()).fold(0.0, f64::max)
+    }
+}
 
 pub struct Diis {
     max_vecs: usize,
-    focks: Vec<Vec<Vec<f64>>>,
-    errors: Vec<Vec<f64>>, // flattened error matrices
+    entries: Vec<DiisEntry>,
+    /// Set once the error has dropped below the switch threshold
+    pulay_active: bool,
 }
 
 impl Diis {
     pub fn new(max_vecs: usize) -> Self {
         Self {
             max_vecs,
-            focks: Vec::new(),
-            errors: Vec::new(),
+            entries: Vec::new(),
+            pulay_active: false,
         }
     }
 
-    /// Push a new (Fock, error) pair
-    pub fn push(&mut self, fock: Vec<Vec<f64>>, error: Vec<Vec<f64>>) {
-        let e_flat = flatten(&error);
+    /// Number of stored vectors
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
 
-        self.focks.push(fock);
-        self.errors.push(e_flat);
+    /// Push a new iterate, dropping the oldest beyond `max_vecs`
+    pub fn push(&mut self, entry: DiisEntry) {
+        self.entries.push(entry);
 
-        if self.focks.len() > self.max_vecs {
-            self.focks.remove(0);
-            self.errors.remove(0);
+        if self.entries.len() > self.max_vecs {
+            self.entries.remove(0);
         }
     }
 
-    /// Extrapolate a new Fock matrix using DIIS
+    /// Hybrid extrapolation: EDIIS/ADIIS while the newest error is
+    /// above `switch`, Pulay DIIS from then on
     ///
     /// Returns None if not enough vectors
-    pub fn extrapolate(&self) -> Option<Vec<Vec<f64>>> {
-        let m = self.errors.len();
+    pub fn extrapolate_hybrid(
+        &mut self,
+        accelerator: Accelerator,
+        switch: f64,
+    ) -> Option<Vec<DMatrix<f64>>> {
+        let newest = self.entries.last()?;
+
+        if accelerator == Accelerator::Pulay || newest.max_error() < switch {
+            self.pulay_active = true;
+        }
+
+        if self.pulay_active {
+            return self.extrapolate();
+        }
+
+        let coeffs = match accelerator {
+            Accelerator::EDiis => self.ediis_coefficients()?,
+            _ => self.adiis_coefficients()?,
+        };
+
+        let start = self.entries.len() - coeffs.len();
+        Some(self.combine(start, &coeffs))
+    }
+
+    /// Extrapolate a new Fock matrix (per channel) using Pulay DIIS
+    ///
+    /// Returns None if not enough vectors
+    pub fn extrapolate(&self) -> Option<Vec<DMatrix<f64>>> {
+        let m = self.entries.len();
         if m < 2 {
             return None;
         }
 
-        // Build B matrix (size m+1)
-        let mut b = vec![vec![0.0; m + 1]; m + 1];
+        // B_ij = Σ_σ ⟨e_i, e_j⟩, bordered by −1
+        let mut b = DMatrix::zeros(m + 1, m + 1);
 
+        for i in 0..m {
+            for j in 0..=i {
+                let bij: f64 = self.entries[i]
+                    .errors
+                    .iter()
+                    .zip(self.entries[j].errors.iter())
+                    .map(|(ei, ej)| ei.dot(ej))
+                    .sum();
+                b[(i, j)] = bij;
+                b[(j, i)] = bij;
+            }
+            b[(i, m)] = -1.0;
+            b[(m, i)] = -1.0;
+        }
+
+        // Scale the error block for conditioning
+        let scale = (0..m).map(|i| b[(i, i)]).fold(0.0, f64::max);
+        if scale <= 0.0 {
+            return None;
+        }
         for i in 0..m {
             for j in 0..m {
-                b[i][j] = dot(&self.errors[i], &self.errors[j]);
+                b[(i, j)] /= scale;
             }
-            b[i][m] = -1.0;
-            b[m][i] = -1.0;
         }
-        b[m][m] = 0.0;
 
-        // RHS
-        let mut rhs = vec![0.0; m + 1];
+        let mut rhs = DVector::zeros(m + 1);
         rhs[m] = -1.0;
 
-        // Solve linear system
-        let coeffs = solve_linear(&b, &rhs)?;
+        // Least squares via SVD (robust to near-linear dependence)
+        let svd = b.svd(true, true);
+        let cutoff = SVD_CUTOFF * svd.singular_values.max();
+        let coeffs = svd.solve(&rhs, cutoff).ok()?;
+
+        Some(self.combine(0, &coeffs.as_slice()[..m]))
+    }
+
+    /// EDIIS: min Σ c_i E_i − ¼ Σ c_i c_j tr[(P_i − P_j)(F_i − F_j)]
+    fn ediis_coefficients(&self) -> Option<Vec<f64>> {
+        let start = self.entries.len().saturating_sub(MAX_EDIIS_VECS);
+        let entries = &self.entries[start..];
+        let m = entries.len();
+
+        let g: Vec<f64> = entries.iter().map(|e| e.energy).collect();
+
+        let mut h = DMatrix::zeros(m, m);
+        for i in 0..m {
+            for j in 0..i {
+                let mij: f64 = (0..entries[i].focks.len())
+                    .map(|s| {
+                        let dp = &entries[i].densities[s] - &entries[j].densities[s];
+                        let df = &entries[i].focks[s] - &entries[j].focks[s];
+                        dp.dot(&df)
+                    })
+                    .sum();
+                h[(i, j)] = -0.5 * mij;
+                h[(j, i)] = -0.5 * mij;
+            }
+        }
+
+        minimize_on_simplex(&g, &h)
+    }
+
+    /// ADIIS: min Σ c_i tr[ΔP_i F_n] + ½ Σ c_i c_j tr[ΔP_i ΔF_j]
+    ///
+    /// Δ relative to the newest iterate n
+    fn adiis_coefficients(&self) -> Option<Vec<f64>> {
+        let start = self.entries.len().saturating_sub(MAX_EDIIS_VECS);
+        let entries = &self.entries[start..];
+        let m = entries.len();
+        let n = entries.last()?;
+        let n_ch = n.focks.len();
+
+        let dp: Vec<Vec<DMatrix<f64>>> = entries
+            .iter()
+            .map(|e| (0..n_ch).map(|s| &e.densities[s] - &n.densities[s]).collect())
+            .collect();
+        let df: Vec<Vec<DMatrix<f64>>> = entries
+            .iter()
+            .map(|e| (0..n_ch).map(|s| &e.focks[s] - &n.focks[s]).collect())
+            .collect();
 
-        // Combine Fock matrices
-        let n = self.focks[0].len();
-        let mut f_new = vec![vec![0.0; n]; n];
+        let g: Vec<f64> = (0..m)
+            .map(|i| (0..n_ch).map(|s| dp[i][s].dot(&n.focks[s])).sum())
+            .collect();
 
+        let mut h = DMatrix::zeros(m, m);
         for i in 0..m {
-            let c = coeffs[i];
-            for p in 0..n {
-                for q in 0..n {
-                    f_new[p][q] += c * self.focks[i][p][q];
-                }
+            for j in 0..m {
+                let t: f64 = (0..n_ch).map(|s| dp[i][s].dot(&df[j][s])).sum();
+                h[(i, j)] += 0.5 * t;
+                h[(j, i)] += 0.5 * t;
+            }
+        }
+
+        minimize_on_simplex(&g, &h)
+    }
+
+    /// Σ_i c_i F_i per channel, using entries from `start` on
+    fn combine(&self, start: usize, coeffs: &[f64]) -> Vec<DMatrix<f64>> {
+        let entries = &self.entries[start..];
+        let mut focks: Vec<DMatrix<f64>> = entries[0]
+            .focks
+            .iter()
+            .map(|f| DMatrix::zeros(f.nrows(), f.ncols()))
+            .collect();
+
+        for (entry, c) in entries.iter().zip(coeffs.iter()) {
+            for (f_new, f) in focks.iter_mut().zip(entry.focks.iter()) {
+                *f_new += f * *c;
             }
         }
 
-        Some(f_new)
+        focks
     }
 }
 
 // ---------- helpers ----------
 
-fn flatten(m: &Vec<Vec<f64>>) -> Vec<f64> {
-    m.iter().flat_map(|row| row.iter()).cloned().collect()
-}
+/// min gᵀc + ½ cᵀ H c  subject to  c ≥ 0, Σ c = 1
+///
+/// H may be indefinite: the global minimum lies in the relative
+/// interior of some face of the simplex, so every face is visited
+/// and its KKT system solved; singular faces are covered by their
+/// lower-dimensional boundary.
+fn minimize_on_simplex(g: &[f64], h: &DMatrix<f64>) -> Option<Vec<f64>> {
+    let m = g.len();
+    if m == 0 {
+        return None;
+    }
 
-fn dot(a: &Vec<f64>, b: &Vec<f64>) -> f64 {
-    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
-}
+    let objective = |c: &[f64]| -> f64 {
+        let mut f = 0.0;
+        for i in 0..m {
+            f += g[i] * c[i];
+            for j in 0..m {
+                f += 0.5 * c[i] * h[(i, j)] * c[j];
+            }
+        }
+        f
+    };
+
+    let mut best: Option<(f64, Vec<f64>)> = None;
 
-/// Very small Gaussian elimination solver (for DIIS only)
-fn solve_linear(a: &Vec<Vec<f64>>, b: &Vec<f64>) -> Option<Vec<f64>> {
-    let n = b.len();
-    let mut a = a.clone();
-    let mut b = b.clone();
-
-    for i in 0..n {
-        // Pivot
-        let mut max = i;
-        for k in (i + 1)..n {
-            if a[k][i].abs() > a[max][i].abs() {
-                max = k;
+    for mask in 1usize..(1 << m) {
+        let face: Vec<usize> = (0..m).filter(|i| mask & (1 << i) != 0).collect();
+        let k = face.len();
+
+        // [H_FF 1; 1ᵀ 0] [c; λ] = [−g_F; 1]
+        let mut kkt = DMatrix::zeros(k + 1, k + 1);
+        let mut rhs = DVector::zeros(k + 1);
+        for (a, &i) in face.iter().enumerate() {
+            for (b, &j) in face.iter().enumerate() {
+                kkt[(a, b)] = h[(i, j)];
             }
+            kkt[(a, k)] = 1.0;
+            kkt[(k, a)] = 1.0;
+            rhs[a] = -g[i];
         }
-        if a[max][i].abs() < 1e-12 {
-            return None;
+        rhs[k] = 1.0;
+
+        let Some(sol) = kkt.lu().solve(&rhs) else {
+            continue;
+        };
+
+        if (0..k).any(|a| !sol[a].is_finite() || sol[a] < -1e-12) {
+            continue;
         }
-        a.swap(i, max);
-        b.swap(i, max);
 
-        // Normalize
-        let diag = a[i][i];
-        for j in i..n {
-            a[i][j] /= diag;
+        let mut c = vec![0.0; m];
+        for (a, &i) in face.iter().enumerate() {
+            c[i] = sol[a].max(0.0);
         }
-        b[i] /= diag;
+        let norm: f64 = c.iter().sum();
+        c.iter_mut().for_each(|x| *x /= norm);
 
-        // Eliminate
-        for k in 0..n {
-            if k != i {
-                let factor = a[k][i];
-                for j in i..n {
-                    a[k][j] -= factor * a[i][j];
-                }
-                b[k] -= factor * b[i];
+        let f = objective(&c);
+        if best.as_ref().is_none_or(|(fb, _)| f < *fb) {
+            best = Some((f, c));
+        }
+    }
+
+    best.map(|(_, c)| c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(f: f64, p: f64, e: f64, energy: f64) -> DiisEntry {
+        let m = |x: f64| DMatrix::from_row_slice(2, 2, &[x, 0.3 * x, 0.3 * x, -x]);
+        DiisEntry {
+            focks: vec![m(f)],
+            densities: vec![m(p)],
+            errors: vec![m(e)],
+            energy,
+        }
+    }
+
+    #[test]
+    fn simplex_minimum_is_a_convex_combination_below_a_grid_search() {
+        let g = [0.3, -0.2, 0.1];
+        let h = DMatrix::from_row_slice(3, 3, &[1.0, -2.0, 0.5, -2.0, 0.4, 1.5, 0.5, 1.5, -0.8]);
+        let objective = |c: &[f64]| -> f64 {
+            (0..3)
+                .map(|i| g[i] * c[i] + 0.5 * c[i] * (0..3).map(|j| h[(i, j)] * c[j]).sum::<f64>())
+                .sum()
+        };
+
+        let c = minimize_on_simplex(&g, &h).unwrap();
+        assert!((c.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(c.iter().all(|&x| x >= 0.0));
+
+        let n = 50;
+        for i in 0..=n {
+            for j in 0..=n - i {
+                let x = [i as f64 / n as f64, j as f64 / n as f64, (n - i - j) as f64 / n as f64];
+                assert!(objective(&c) <= objective(&x) + 1e-12);
             }
         }
     }
 
-    Some(b)
+    #[test]
+    fn ediis_and_adiis_coefficients_lie_on_the_simplex() {
+        let mut diis = Diis::new(8);
+        for (k, energy) in [-1.00, -1.20, -1.15, -1.22].iter().enumerate() {
+            let x = k as f64;
+            diis.push(entry(0.5 - 0.1 * x, 1.0 + 0.2 * x, 0.4 / (1.0 + x), *energy));
+        }
+
+        for coeffs in [diis.ediis_coefficients().unwrap(), diis.adiis_coefficients().unwrap()] {
+            assert_eq!(coeffs.len(), 4);
+            assert!((coeffs.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            assert!(coeffs.iter().all(|&c| c >= 0.0));
+        }
+    }
+
+    #[test]
+    fn pulay_cancels_opposite_errors() {
+        let mut diis = Diis::new(8);
+        diis.push(entry(1.0, 0.0, 0.2, 0.0));
+        diis.push(entry(3.0, 0.0, -0.2, 0.0));
+
+        let f = diis.extrapolate().unwrap();
+        assert!((&f[0] - entry(2.0, 0.0, 0.0, 0.0).focks[0].clone()).amax() < 1e-12);
+    }
+
+    #[test]
+    fn hybrid_switches_to_pulay_once_and_stays() {
+        let mut diis = Diis::new(8);
+        diis.push(entry(1.0, 1.0, 0.5, -1.0));
+        diis.push(entry(2.0, 1.5, 0.4, -1.1));
+
+        diis.extrapolate_hybrid(Accelerator::EDiis, 0.1).unwrap();
+        assert!(!diis.pulay_active);
+
+        diis.push(entry(2.2, 1.6, 0.05, -1.12));
+        diis.extrapolate_hybrid(Accelerator::EDiis, 0.1).unwrap();
+        assert!(diis.pulay_active);
+
+        // a later error spike does not bring EDIIS back
+        diis.push(entry(2.4, 1.7, 0.3, -1.11));
+        let hybrid = diis.extrapolate_hybrid(Accelerator::EDiis, 0.1).unwrap();
+        assert!((&hybrid[0] - &diis.extrapolate().unwrap()[0]).amax() < 1e-14);
+    }
 }
@@ -0,0 +1,317 @@
+//! External potentials added to the Fock build
+//!
+//! Anything acting on the electrons through an AO matrix plugs in here:
+//! homogeneous fields, point charges, solvent reaction fields, embedding
+//! potentials. Every SCF driver adds, for P = P^α + P^β,
+//!
+//!   F^σ += Σ_ext V_ext[P]      E += Σ_ext E_ext[P, V_ext]
+//!
+//! and `total_gradient` adds Σ_ext ∂E_ext/∂R at fixed P. Register the
+//! potentials in `ScfOptions::external`.
+
+use std::rc::Rc;
+
+use nalgebra::DMatrix;
+
+use crate::basis::shell::Shell;
+use crate::integrals::multipole::dipole_integrals;
+use crate::integrals::nuclear_attraction::{point_charges_shell_shell, PointCharge};
+use crate::system::atom::Atom;
+
+/// Nuclear displacement of the default finite-difference gradient (bohr)
+const DISPLACEMENT: f64 = 1e-4;
+
+pub trait ExternalPotential {
+    /// V_μν, added to F^α and F^β
+    ///
+    /// `density` is P^α + P^β (ignored by fixed potentials); the
+    /// matrix is rebuilt at every Fock build.
+    fn matrix(&self, shells: &[Shell], atoms: &[Atom], density: &DMatrix<f64>) -> DMatrix<f64>;
+
+    /// Energy for `density` with its `matrix`
+    ///
+    /// Σ P V by default (linear potentials); implementations add their
+    /// interaction with the nuclei, density-dependent ones their own
+    /// functional (e.g. ½ Σ P V for a reaction field).
+    fn energy(&self, _atoms: &[Atom], density: &DMatrix<f64>, matrix: &DMatrix<f64>) -> f64 {
+        density.dot(matrix)
+    }
+
+    /// V depends affinely on P (fixed potentials, linear reaction fields)
+    ///
+    /// The second-order SCF and the stability analysis take the Fock
+    /// response as the exact difference F(P + ΔP) − F(P) only when every
+    /// potential is linear; otherwise they fall back to central differences.
+    fn is_linear(&self) -> bool {
+        true
+    }
+
+    /// ∂E/∂R_A at fixed P (the Pulay term comes with the Fock matrix)
+    ///
+    /// Central differences of `energy` by default, moving every shell
+    /// with its atom.
+    fn gradient(&self, shells: &[Shell], atoms: &[Atom], density: &DMatrix<f64>) -> Vec<[f64; 3]> {
+        let energy = |shells: &[Shell], atoms: &[Atom]| {
+            let v = self.matrix(shells, atoms, density);
+            self.energy(atoms, density, &v)
+        };
+
+        let mut grad = vec![[0.0; 3]; atoms.len()];
+
+        for (a, g) in grad.iter_mut().enumerate() {
+            for (k, g_k) in g.iter_mut().enumerate() {
+                let (s_p, a_p) = displaced(shells, atoms, a, k, DISPLACEMENT);
+                let (s_m, a_m) = displaced(shells, atoms, a, k, -DISPLACEMENT);
+                *g_k = (energy(&s_p, &a_p) - energy(&s_m, &a_m)) / (2.0 * DISPLACEMENT);
+            }
+        }
+
+        grad
+    }
+}
+
+/// Σ_ext V_ext and Σ_ext E_ext for total density `density`
+/// (None without external potentials)
+pub(crate) fn external_contribution(
+    potentials: &[Rc<dyn ExternalPotential>],
+    shells: &[Shell],
+    atoms: &[Atom],
+    density: &DMatrix<f64>,
+) -> Option<(DMatrix<f64>, f64)> {
+
+    let mut total: Option<(DMatrix<f64>, f64)> = None;
+
+    for potential in potentials {
+        let v = potential.matrix(shells, atoms, density);
+        let e = potential.energy(atoms, density, &v);
+
+        total = Some(match total {
+            Some((v_sum, e_sum)) => (v_sum + v, e_sum + e),
+            None => (v, e),
+        });
+    }
+
+    total
+}
+
+/// Σ_ext ∂E_ext/∂R_A
+pub fn external_gradient(
+    potentials: &[Rc<dyn ExternalPotential>],
+    shells: &[Shell],
+    atoms: &[Atom],
+    density: &DMatrix<f64>,
+) -> Vec<[f64; 3]> {
+
+    let mut grad = vec![[0.0; 3]; atoms.len()];
+
+    for potential in potentials {
+        for (g, dg) in grad.iter_mut().zip(potential.gradient(shells, atoms, density)) {
+            for k in 0..3 {
+                g[k] += dg[k];
+            }
+        }
+    }
+
+    grad
+}
+
+/// Atom `a` and its shells moved by `delta` along `k`
+fn displaced(
+    shells: &[Shell],
+    atoms: &[Atom],
+    a: usize,
+    k: usize,
+    delta: f64,
+) -> (Vec<Shell>, Vec<Atom>) {
+
+    let origin = atoms[a].position;
+
+    let mut moved_atoms = atoms.to_vec();
+    moved_atoms[a].position[k] += delta;
+
+    let moved_shells = shells
+        .iter()
+        .map(|shell| {
+            let mut shell = shell.clone();
+            if shell.center == origin {
+                shell.center[k] += delta;
+            }
+            shell
+        })
+        .collect();
+
+    (moved_shells, moved_atoms)
+}
+
+// ======================================================
+// Homogeneous electric field
+// ======================================================
+
+/// Uniform field F (a.u.): V = F·(r − O) per electron,
+/// E_nuc = −Σ_A Z_A F·(R_A − O)
+#[derive(Clone, Copy, Debug)]
+pub struct ElectricField {
+    pub field: [f64; 3],
+    /// Gauge origin (bohr); only shifts the energy of charged systems
+    pub origin: [f64; 3],
+}
+
+impl ExternalPotential for ElectricField {
+    fn matrix(&self, shells: &[Shell], _atoms: &[Atom], _density: &DMatrix<f64>) -> DMatrix<f64> {
+        let dipole = dipole_integrals(shells, self.origin);
+        let nao = dipole[0].len();
+
+        DMatrix::from_fn(nao, nao, |i, j| {
+            (0..3).map(|k| self.field[k] * dipole[k][i][j]).sum()
+        })
+    }
+
+    fn energy(&self, atoms: &[Atom], density: &DMatrix<f64>, matrix: &DMatrix<f64>) -> f64 {
+        let nuclear: f64 = atoms
+            .iter()
+            .map(|atom| {
+                let r: f64 = (0..3)
+                    .map(|k| self.field[k] * (atom.position[k] - self.origin[k]))
+                    .sum();
+                -atom.nuclear_charge() * r
+            })
+            .sum();
+
+        density.dot(matrix) + nuclear
+    }
+}
+
+// ======================================================
+// Point charges
+// ======================================================
+
+/// Classical point charges (QM/MM electrostatic embedding)
+///
+/// Electrons: Σ_C ⟨μ| −q_C/|r − C| |ν⟩; nuclei: Σ_A Σ_C Z_A q_C / |R_A − C|.
+/// Smeared charges act on the electrons with their Gaussian shape and
+/// on the nuclei as points.
+#[derive(Clone, Debug)]
+pub struct PointCharges {
+    pub charges: Vec<PointCharge>,
+}
+
+impl ExternalPotential for PointCharges {
+    fn matrix(&self, shells: &[Shell], _atoms: &[Atom], _density: &DMatrix<f64>) -> DMatrix<f64> {
+        let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
+        let mut v = DMatrix::zeros(nao, nao);
+
+        for si in shells {
+            for sj in shells {
+                let block = point_charges_shell_shell(si, sj, &self.charges);
+
+                for mu in 0..si.n_orbitals() {
+                    for nu in 0..sj.n_orbitals() {
+                        v[(si.offset + mu, sj.offset + nu)] = block[mu][nu];
+                    }
+                }
+            }
+        }
+
+        v
+    }
+
+    fn energy(&self, atoms: &[Atom], density: &DMatrix<f64>, matrix: &DMatrix<f64>) -> f64 {
+        let mut nuclear = 0.0;
+
+        for atom in atoms {
+            for q in &self.charges {
+                let r = (0..3)
+                    .map(|k| (atom.position[k] - q.position[k]).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                nuclear += atom.nuclear_charge() * q.charge / r;
+            }
+        }
+
+        density.dot(matrix) + nuclear
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, ScfOptions};
+    use crate::scf::soscf::{SecondOrder, SoscfOptions};
+    use crate::scf::utils::vec2d_ref_to_dmatrix;
+
+    /// E = (k/3) d³ with d = Σ P V⁰ (V⁰ a field along z): V = k d² V⁰
+    struct CubicDipole {
+        field: ElectricField,
+        k: f64,
+    }
+
+    impl CubicDipole {
+        fn dipole(&self, shells: &[Shell], atoms: &[Atom], density: &DMatrix<f64>) -> f64 {
+            density.dot(&self.field.matrix(shells, atoms, density))
+        }
+    }
+
+    impl ExternalPotential for CubicDipole {
+        fn matrix(&self, shells: &[Shell], atoms: &[Atom], density: &DMatrix<f64>) -> DMatrix<f64> {
+            let d = self.dipole(shells, atoms, density);
+            self.field.matrix(shells, atoms, density) * (self.k * d * d)
+        }
+
+        fn energy(&self, _atoms: &[Atom], density: &DMatrix<f64>, matrix: &DMatrix<f64>) -> f64 {
+            // Σ P V = k d³
+            density.dot(matrix) / 3.0
+        }
+
+        fn is_linear(&self) -> bool {
+            false
+        }
+    }
+
+    fn cubic() -> Rc<dyn ExternalPotential> {
+        Rc::new(CubicDipole {
+            field: ElectricField { field: [0.0, 0.0, 1.0], origin: [0.0, 0.0, 0.3] },
+            k: 0.2,
+        })
+    }
+
+    #[test]
+    fn fock_is_linear_only_without_non_linear_potentials() {
+        let field: Rc<dyn ExternalPotential> =
+            Rc::new(ElectricField { field: [0.0, 0.0, 0.01], origin: [0.0; 3] });
+
+        let linear = ScfOptions { external: vec![field.clone()], ..quiet_options() };
+        let non_linear = ScfOptions { external: vec![field, cubic()], ..quiet_options() };
+
+        assert!(quiet_options().fock_is_linear());
+        assert!(linear.fock_is_linear());
+        assert!(!non_linear.fock_is_linear());
+    }
+
+    #[test]
+    fn trah_with_a_non_linear_potential_reaches_the_diis_solution() {
+        let (molecule, shells, centers) = h2();
+        let options = ScfOptions { external: vec![cubic()], ..quiet_options() };
+
+        let diis = run_scf(&molecule, &shells, &centers, &options);
+        let trah = run_scf(
+            &molecule,
+            &shells,
+            &centers,
+            &ScfOptions {
+                soscf: SoscfOptions { mode: SecondOrder::Always, ..SoscfOptions::default() },
+                ..options.clone()
+            },
+        );
+
+        assert!(diis.converged && trah.converged);
+        assert!((trah.energy - diis.energy).abs() < 1e-8, "{} vs {}", trah.energy, diis.energy);
+
+        // E_ext evaluated on the returned density
+        let p = vec2d_ref_to_dmatrix(&trah.density);
+        let potential = cubic();
+        let v = potential.matrix(&shells, &molecule.atoms, &p);
+        let e_external = potential.energy(&molecule.atoms, &p, &v);
+        assert!((trah.components.external - e_external).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,57 @@
+//! Small molecules for the SCF tests
+//!
+//! Hydrogen atoms carrying two uncontracted s primitives each: small
+//! enough for full SCF runs in unoptimized test builds.
+
+use crate::basis::primitive::Primitive;
+use crate::basis::shell::Shell;
+use crate::scf::scf_cycle::ScfOptions;
+use crate::system::atom::Atom;
+use crate::system::molecule::Molecule;
+
+/// Exponents of the hydrogen s functions
+const H_EXPONENTS: [f64; 2] = [1.3, 0.25];
+
+/// Molecule, shells and shell centers of H atoms at `positions`
+pub(crate) fn hydrogens(
+    positions: &[[f64; 3]],
+    charge: i32,
+    multiplicity: usize,
+) -> (Molecule, Vec<Shell>, Vec<[f64; 3]>) {
+    let atoms: Vec<Atom> = positions
+        .iter()
+        .map(|&r| Atom::new("H".to_string(), 1, r))
+        .collect();
+
+    let mut shells = Vec::new();
+    let mut centers = Vec::new();
+    for &r in positions {
+        for alpha in H_EXPONENTS {
+            let primitive = Primitive::new(alpha, 1.0, r, [0, 0, 0]);
+            shells.push(Shell::new(vec![primitive], [0, 0, 0], r, shells.len()));
+            centers.push(r);
+        }
+    }
+
+    let molecule = Molecule { atoms, charge, multiplicity };
+    (molecule, shells, centers)
+}
+
+/// H₂ at 1.4 bohr
+pub(crate) fn h2() -> (Molecule, Vec<Shell>, Vec<[f64; 3]>) {
+    hydrogens(&[[0.0, 0.0, 0.0], [0.0, 0.0, 1.4]], 0, 1)
+}
+
+/// Quiet SCF options with tight convergence
+pub(crate) fn quiet_options() -> ScfOptions {
+    let mut options = ScfOptions {
+        print_iterations: false,
+        max_iter: 100,
+        ..ScfOptions::default()
+    };
+    options.convergence.energy = 1e-10;
+    options.convergence.density_rms = 1e-8;
+    options.convergence.density_max = 1e-7;
+    options.convergence.commutator = 1e-7;
+    options
+}
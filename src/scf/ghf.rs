@@ -0,0 +1,358 @@
+//! Generalized (two-component) Hartree–Fock and Kohn–Sham (GHF / GKS)
+//!
+//! Spinors over the 2·nao basis {χ_μ α} ∪ {χ_μ β}:
+//!
+//!   P = Σ_i^occ C_i C_i†  =  [ P^αα  P^αβ ]
+//!                             [ P^βα  P^ββ ]
+//!
+//!   F^αα = H + J[P^αα + P^ββ] − K_eff[P^αα] + V_xc^αα
+//!   F^ββ = H + J[P^αα + P^ββ] − K_eff[P^ββ] + V_xc^ββ
+//!   F^αβ = −K_eff[P^αβ] + V_xc^αβ,   F^βα = (F^αβ)†
+//!
+//! Generic over the scalar (`f64` or `Complex<f64>`, see `scf::scalar`);
+//! the real J/K builders are applied block by block to the real and
+//! imaginary parts. V_xc is the noncollinear functional of
+//! `dft::vxc::build_vxc_noncollinear`.
+//!
+//! A collinear guess stays collinear, so the α HOMO is rotated into the
+//! β LUMO by `ScfOptions::guess_rotation` (with phase i for complex
+//! spinors). Stabilizers and TRAH are not used here.
+
+use std::time::Instant;
+
+use nalgebra::DMatrix;
+
+use crate::basis::shell::Shell;
+use crate::system::molecule::Molecule;
+use crate::dft::vxc::{build_vxc_noncollinear, ExchangeMixing};
+use crate::scf::diis::{Diis, DiisEntry};
+use crate::scf::external::external_contribution;
+use crate::scf::guess::initial_guess;
+use crate::scf::complex::coulomb_exchange;
+use crate::scf::scalar::{
+    commutator, from_parts, imag_part, promote, real_part, realify, rotate_pair,
+    solve_hermitian, unrealify, weighted_density, ScfScalar,
+};
+use crate::scf::scf_cycle::{
+    report_non_convergence, EnergyComponents, IterationRecord, Reference,
+    ScfOptions, ScfSystem,
+};
+use crate::scf::utils::{dmatrix_to_vec2d, vec2d_ref_to_dmatrix};
+
+/// Converged GHF / GKS solution
+pub struct GhfResult<T: ScfScalar> {
+    /// Energía total (incluye repulsión nuclear)
+    pub energy: f64,
+    pub components: EnergyComponents,
+    /// Spinors C (2·nao × 2·nao), α rows first
+    pub coefficients: DMatrix<T>,
+    /// ε ascending
+    pub energies: Vec<f64>,
+    pub n_electrons: usize,
+    /// P (2·nao × 2·nao)
+    pub density: DMatrix<T>,
+    /// F (2·nao × 2·nao) of the last cycle, not extrapolated
+    pub fock: DMatrix<T>,
+    pub iterations: usize,
+    pub converged: bool,
+    pub history: Vec<IterationRecord>,
+}
+
+impl<T: ScfScalar> GhfResult<T> {
+    /// (⟨S_x⟩, ⟨S_y⟩, ⟨S_z⟩) = ½ ∫ m(r) dr
+    pub fn spin_expectation(&self, overlap: &DMatrix<f64>) -> [f64; 3] {
+        let n = overlap.nrows();
+        let s: DMatrix<T> = promote(overlap);
+        let trace = |i: usize, j: usize| {
+            (self.density.view((i * n, j * n), (n, n)) * &s).trace()
+        };
+
+        let ab = trace(0, 1);
+        [
+            ab.real(),
+            -ab.imaginary(),
+            0.5 * (trace(0, 0).real() - trace(1, 1).real()),
+        ]
+    }
+}
+
+/// GHF / GKS: HF or noncollinear DFT according to `options.xc_method`
+///
+/// `options.reference` is ignored; the multiplicity of `molecule` only
+/// sets the collinear initial guess. There is no observed variant (see
+/// `crate::observer`).
+pub fn run_ghf<T: ScfScalar>(
+    molecule: &Molecule,
+    shells: &[Shell],
+    shell_centers: &[[f64; 3]],
+    options: &ScfOptions,
+) -> GhfResult<T> {
+
+    assert!(options.max_iter > 0, "SCF max_iter must be positive");
+
+    let sys = ScfSystem::new(molecule, shells, shell_centers, options);
+    let (n_alpha, n_beta) = (sys.n_alpha, sys.n_beta);
+    let nelec = n_alpha + n_beta;
+
+    let nao = sys.overlap.nrows();
+    let overlap = block_diagonal(&sys.overlap, &sys.overlap);
+    let mixing = options.exchange_mixing();
+
+    // -----------------------------
+    // Guess colineal → espinores
+    // -----------------------------
+    let guess_options = ScfOptions {
+        reference: Reference::Unrestricted,
+        ..options.clone()
+    };
+    let guess = initial_guess(&sys, &guess_options);
+    let (c_a, c_b) = &guess.orbitals;
+
+    // Columns: α occ, β occ, α virt, β virt
+    let mut spinors: DMatrix<f64> = DMatrix::zeros(2 * nao, 2 * nao);
+    let mut col = 0;
+    for (c, offset, range) in [
+        (c_a, 0, 0..n_alpha),
+        (c_b, nao, 0..n_beta),
+        (c_a, 0, n_alpha..nao),
+        (c_b, nao, n_beta..nao),
+    ] {
+        for k in range {
+            spinors.view_mut((offset, col), (nao, 1)).copy_from(&c.column(k));
+            col += 1;
+        }
+    }
+    let mut spinors: DMatrix<T> = promote(&spinors);
+
+    let mut p: DMatrix<T> = if options.guess_rotation != 0.0 && n_alpha > 0 && n_beta < nao {
+        let (homo, lumo) = (n_alpha - 1, nao + n_beta);
+        rotate_pair(&mut spinors, homo, lumo, options.guess_rotation);
+        weighted_density(&spinors, &vec![1.0; nelec])
+    } else {
+        promote(&block_diagonal(&guess.p_alpha, &guess.p_beta))
+    };
+
+    // -----------------------------
+    // Iteraciones
+    // -----------------------------
+    let mut diis = Diis::new(options.diis_size);
+
+    let mut energy_old = 0.0;
+    let mut delta_p = (0.0, 0.0);
+    let mut history: Vec<IterationRecord> = Vec::new();
+    let mut clock = Instant::now();
+
+    if options.print_iterations {
+        IterationRecord::print_header();
+    }
+
+    for iter in 0..options.max_iter {
+
+        let (fock, components) = ghf_fock(&sys, &p, options, &mixing);
+        let energy = components.total();
+
+        let error = commutator(&fock, &p, &overlap);
+
+        if options.diis_size > 0 {
+            diis.push(DiisEntry {
+                focks: vec![realify(&fock)],
+                densities: vec![realify(&p)],
+                errors: vec![realify(&error)],
+                energy,
+            });
+        }
+
+        let record = IterationRecord {
+            iteration: iter + 1,
+            energy,
+            delta_e: (energy - energy_old).abs(),
+            density_rms: delta_p.0,
+            density_max: delta_p.1,
+            commutator: error.camax(),
+            diis_size: diis.len(),
+            time: clock.elapsed(),
+        };
+        clock = Instant::now();
+
+        if options.print_iterations {
+            record.print();
+        }
+
+        let converged = iter > 0 && options.convergence.is_satisfied(&record);
+        history.push(record);
+
+        if converged || iter + 1 == options.max_iter {
+            if !converged {
+                report_non_convergence(options, history.last().unwrap());
+            }
+
+            // P, F y energía de los espinores devueltos
+            let (coefficients, energies) = solve_hermitian(&fock, &overlap);
+            let density = weighted_density(&coefficients, &vec![1.0; nelec]);
+            let (fock, components) = ghf_fock(&sys, &density, options, &mixing);
+
+            return GhfResult {
+                energy: components.total(),
+                components,
+                coefficients,
+                energies,
+                n_electrons: nelec,
+                density,
+                fock,
+                iterations: history.len(),
+                converged,
+                history,
+            };
+        }
+
+        let fock = match diis.extrapolate_hybrid(options.accelerator, options.diis_switch) {
+            Some(mut focks) => unrealify(&focks.remove(0)),
+            None => fock,
+        };
+
+        let (c, _) = solve_hermitian(&fock, &overlap);
+        let p_new = weighted_density(&c, &vec![1.0; nelec]);
+
+        let dp = &p_new - &p;
+        delta_p = (dp.norm() / (2 * nao) as f64, dp.camax());
+
+        p = p_new;
+        energy_old = energy;
+    }
+
+    unreachable!()
+}
+
+/// Two-component Fock matrix and energy components for P
+fn ghf_fock<T: ScfScalar>(
+    sys: &ScfSystem,
+    p: &DMatrix<T>,
+    options: &ScfOptions,
+    mixing: &ExchangeMixing,
+) -> (DMatrix<T>, EnergyComponents) {
+
+    let n = sys.h_core.nrows();
+    let block = |i: usize, j: usize| p.view((i * n, j * n), (n, n)).into_owned();
+    let (p_aa, p_ab, p_bb) = (block(0, 0), block(0, 1), block(1, 1));
+
+    // J de la parte real de P^αα + P^ββ; K_eff por bloque de espín
+    let p_tot = real_part(&(&p_aa + &p_bb));
+    let (j, k) = coulomb_exchange(sys, &p_tot, &[&p_aa, &p_ab, &p_bb], mixing);
+    let k = spin_blocks(&k[0], &k[1], &k[2]);
+
+    let hj: DMatrix<T> = promote(&(&sys.h_core + &j));
+    let mut fock = spin_blocks(&hj, &DMatrix::zeros(n, n), &hj) - &k;
+
+    // Potenciales externos: diagonales en espín
+    let mut e_external = 0.0;
+    if let Some((v, e)) = external_contribution(&options.external, sys.shells, sys.atoms, &p_tot) {
+        let v: DMatrix<T> = promote(&v);
+        fock += spin_blocks(&v, &DMatrix::zeros(n, n), &v);
+        e_external = e;
+    }
+
+    // -----------------------------
+    // XC no colineal
+    // -----------------------------
+    let mut e_xc = 0.0;
+
+    if let Some(xc) = &options.xc_method {
+        let (v, dft_energy) = build_vxc_noncollinear(
+            sys.shells,
+            &dmatrix_to_vec2d(&real_part(&p_aa)),
+            &dmatrix_to_vec2d(&real_part(&p_bb)),
+            &dmatrix_to_vec2d(&real_part(&p_ab)),
+            &dmatrix_to_vec2d(&imag_part(&p_ab)),
+            sys.atoms,
+            xc.clone(),
+        );
+
+        let v_ab = from_parts(&vec2d_ref_to_dmatrix(&v.ab_re), &vec2d_ref_to_dmatrix(&v.ab_im));
+        fock += spin_blocks(
+            &promote(&vec2d_ref_to_dmatrix(&v.aa)),
+            &v_ab,
+            &promote(&vec2d_ref_to_dmatrix(&v.bb)),
+        );
+        e_xc = dft_energy.exc;
+    }
+
+    let components = EnergyComponents {
+        nuclear: sys.e_nuc,
+        one_electron: p_tot.dot(&sys.h_core),
+        coulomb: 0.5 * p_tot.dot(&j),
+        exchange: -0.5 * (&k * p).trace().real(),
+        xc: e_xc,
+        external: e_external,
+        dispersion: options.xc_method.as_ref().map_or(0.0, |xc| xc.dispersion_energy(sys.atoms)),
+    };
+
+    (fock, components)
+}
+
+// ---------- helpers ----------
+
+/// [[A, 0], [0, B]]
+fn block_diagonal(a: &DMatrix<f64>, b: &DMatrix<f64>) -> DMatrix<f64> {
+    let n = a.nrows();
+    let mut m = DMatrix::zeros(2 * n, 2 * n);
+    m.view_mut((0, 0), (n, n)).copy_from(a);
+    m.view_mut((n, n), (n, n)).copy_from(b);
+    m
+}
+
+/// [[aa, ab], [ab†, bb]]
+fn spin_blocks<T: ScfScalar>(aa: &DMatrix<T>, ab: &DMatrix<T>, bb: &DMatrix<T>) -> DMatrix<T> {
+    let n = aa.nrows();
+    let mut m = DMatrix::zeros(2 * n, 2 * n);
+    m.view_mut((0, 0), (n, n)).copy_from(aa);
+    m.view_mut((0, n), (n, n)).copy_from(ab);
+    m.view_mut((n, 0), (n, n)).copy_from(&ab.adjoint());
+    m.view_mut((n, n), (n, n)).copy_from(bb);
+    m
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+    use crate::scf::scf_cycle::run_scf;
+
+    #[test]
+    fn closed_shell_ghf_reproduces_rhf() {
+        let (molecule, shells, centers) = h2();
+        let options = quiet_options();
+
+        let rhf = run_scf(&molecule, &shells, &centers, &options);
+        let ghf: GhfResult<f64> = run_ghf(&molecule, &shells, &centers, &options);
+
+        assert!(ghf.converged);
+        assert!((ghf.energy - rhf.energy).abs() < 1e-8);
+
+        // P devuelta = P de los espinores devueltos
+        let p = weighted_density(&ghf.coefficients, &vec![1.0; ghf.n_electrons]);
+        assert!((&p - &ghf.density).amax() < 1e-12);
+    }
+
+    #[test]
+    fn ghf_lies_at_or_below_uhf_when_stretched() {
+        let stretched = [
+            (vec![[0.0; 3], [0.0, 0.0, 4.0]], 1),
+            (vec![[0.0; 3], [0.0, 0.0, 3.0], [0.0, 0.0, 6.0]], 2),
+        ];
+
+        for (positions, multiplicity) in stretched {
+            let (molecule, shells, centers) = hydrogens(&positions, 0, multiplicity);
+            let options = ScfOptions {
+                reference: Reference::Unrestricted,
+                guess_rotation: 0.6,
+                ..quiet_options()
+            };
+
+            let uhf = run_scf(&molecule, &shells, &centers, &options);
+            let ghf: GhfResult<f64> = run_ghf(&molecule, &shells, &centers, &options);
+
+            assert!(uhf.converged && ghf.converged);
+            assert!(ghf.energy <= uhf.energy + 1e-8);
+        }
+    }
+}
@@ -1,37 +1,605 @@
+//! Initial SCF guesses
+//!
+//! - Core: H_core C = S C ε
+//! - SAD: P = Σ_A P_A, superposition of spherically averaged atomic
+//!   densities from an unrestricted atomic SCF (same Hamiltonian
+//!   and functional), run once per unique element
+//! - Hückel: extended Hückel in the minimal basis of the occupied atomic
+//!   SAD orbitals: H_ii = ε_i, H_ij = ½ K (ε_i + ε_j) S_ij
+//! - GWH: generalized Wolfsberg–Helmholz on the AO core Hamiltonian:
+//!   F_μμ = H_μμ, F_μν = ½ K (H_μμ + H_νν) S_μν
+//! - Projected: occupied orbitals of a previous run in another basis,
+//!   C_2 = S_22⁻¹ S_21 C_1, Löwdin re-orthonormalized
+//! - Broken symmetry: high-spin densities with the spin density of
+//!   selected atoms (fragments) flipped, for UHF / UKS
+//!
+//! Density-only guesses (SAD, Hückel, projected) also return the natural orbitals
+//! of each spin density, so meta-GGA and TRAH always have orbitals.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use nalgebra::DMatrix;
+
 use crate::basis::shell::Shell;
-use crate::integrals::overlap_contracted::overlap_shell_shell;
-use crate::integrals::kinetic::kinetic_shell_shell;
-use crate::integrals::nuclear_attraction::nuclear_attraction_shell_shell;
+use crate::system::atom::Atom;
+use crate::scf::diis::{Diis, DiisEntry};
+use crate::scf::scf_cycle::{
+    build_fock, core_hamiltonian, weighted_density, Reference, ScfOptions, ScfResult,
+    ScfSystem,
+};
 use crate::scf::density::build_density;
-use crate::scf::utils::solve_roothaan;
+use crate::scf::scalar::rotate_pair;
+use crate::scf::utils::{
+    build_mixed_overlap_matrix, build_one_electron_matrix, build_overlap_matrix, diis_error,
+    solve_roothaan, vec2d_ref_to_dmatrix,
+};
+
+/// Wolfsberg–Helmholz constant
+const WOLFSBERG_HELMHOLZ_K: f64 = 1.75;
+
+/// Atomic SCF: iterations, max |ΔP| threshold and DIIS size
+const ATOMIC_MAX_ITER: usize = 100;
+const ATOMIC_CONVERGENCE: f64 = 1e-6;
+const ATOMIC_DIIS_SIZE: usize = 8;
+
+/// Orbital energies closer than this share their occupation
+const DEGENERACY_TOL: f64 = 1e-3;
+
+/// Eigenvalues of Cᵀ S C below this are dropped on re-orthonormalization
+const PROJECTION_CUTOFF: f64 = 1e-10;
+
+/// Initial guess (`ScfOptions::guess`)
+#[derive(Clone, Debug)]
+pub enum InitialGuess {
+    /// Diagonalize the core Hamiltonian
+    Core,
+    /// Superposition of atomic densities
+    Sad,
+    /// Extended Hückel on atomic SAD orbitals
+    Huckel,
+    /// Generalized Wolfsberg–Helmholz
+    Gwh,
+    /// Orbitals of a previous run projected into the current basis
+    Projected(ProjectionSource),
+    /// High-spin solution with flipped spin on some atoms (UHF / UKS)
+    BrokenSymmetry(BrokenSymmetrySource),
+}
+
+impl InitialGuess {
+    /// Parse the input keyword (core | sad | huckel | gwh)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "core" | "hcore" => Some(Self::Core),
+            "sad" => Some(Self::Sad),
+            "huckel" | "hückel" => Some(Self::Huckel),
+            "gwh" => Some(Self::Gwh),
+            _ => None,
+        }
+    }
+}
+
+/// Converged orbitals of a previous run, in its own basis
+///
+/// The old shells keep their centers: for a geometry series the
+/// projection is taken from the previous geometry as is.
+#[derive(Clone, Debug)]
+pub struct ProjectionSource {
+    pub shells: Vec<Shell>,
+    /// C^α (old AO × MO, ε ascending)
+    pub alpha: DMatrix<f64>,
+    /// C^β; equal to C^α for restricted runs
+    pub beta: DMatrix<f64>,
+}
+
+impl ProjectionSource {
+    /// From an in-memory result and the shells it was computed with
+    pub fn from_result(result: &ScfResult, shells: &[Shell]) -> Self {
+        let alpha = vec2d_ref_to_dmatrix(&result.alpha.coefficients);
+        let beta = match &result.beta {
+            Some(beta) => vec2d_ref_to_dmatrix(&beta.coefficients),
+            None => alpha.clone(),
+        };
+
+        Self {
+            shells: shells.to_vec(),
+            alpha,
+            beta,
+        }
+    }
+}
+
+/// Converged high-spin densities and the atoms whose spin is flipped
+///
+/// Same basis and geometry as the broken-symmetry run; the molecule of
+/// that run carries the broken-symmetry multiplicity (M_S = S_A − S_B).
+#[derive(Clone, Debug)]
+pub struct BrokenSymmetrySource {
+    pub p_alpha: DMatrix<f64>,
+    pub p_beta: DMatrix<f64>,
+    /// Indices into the molecule's atoms; a fragment is the list of its atoms
+    pub flip: Vec<usize>,
+}
+
+impl BrokenSymmetrySource {
+    /// From an in-memory high-spin result
+    pub fn from_result(result: &ScfResult, flip: Vec<usize>) -> Self {
+        let (p_alpha, p_beta) = result.spin_densities();
+
+        Self {
+            p_alpha: vec2d_ref_to_dmatrix(&p_alpha),
+            p_beta: vec2d_ref_to_dmatrix(&p_beta),
+            flip,
+        }
+    }
+}
+
+/// Starting point of the SCF
+pub struct Guess {
+    pub p_alpha: DMatrix<f64>,
+    pub p_beta: DMatrix<f64>,
+    /// (C^α, C^β), full AO × MO sets
+    pub orbitals: (DMatrix<f64>, DMatrix<f64>),
+}
+
+/// Converged atomic calculation (AO basis of the atom)
+struct AtomicGuess {
+    /// Spherically averaged P^α + P^β
+    density: DMatrix<f64>,
+    /// Occupied (or partially occupied) α orbitals, AO × n_min
+    orbitals: DMatrix<f64>,
+    energies: Vec<f64>,
+}
+
+/// Build the initial spin densities and orbitals for `sys`
+pub(crate) fn initial_guess(sys: &ScfSystem, options: &ScfOptions) -> Guess {
+    match &options.guess {
+        InitialGuess::Core => aufbau(sys, &sys.h_core),
+        InitialGuess::Gwh => aufbau(sys, &gwh_matrix(&sys.h_core, &sys.overlap)),
+        InitialGuess::Sad => {
+            let atomic = atomic_guesses(sys, options);
+            let nao = sys.overlap.nrows();
+            let mut p = DMatrix::zeros(nao, nao);
+
+            for (guess, aos) in atomic.iter().flatten() {
+                for (a, &mu) in aos.iter().enumerate() {
+                    for (b, &nu) in aos.iter().enumerate() {
+                        p[(mu, nu)] = guess.density[(a, b)];
+                    }
+                }
+            }
+
+            // Renormalize to the molecular electron count (ions)
+            let n_total = (sys.n_alpha + sys.n_beta) as f64;
+            let n_guess = p.dot(&sys.overlap);
+            if n_guess > 0.0 {
+                p *= n_total / n_guess;
+            }
+
+            let p_alpha = &p * (sys.n_alpha as f64 / n_total);
+            let p_beta = &p * (sys.n_beta as f64 / n_total);
+            from_densities(sys, p_alpha, p_beta)
+        }
+        InitialGuess::Huckel => huckel(sys, options),
+        InitialGuess::Projected(source) => projected(sys, source),
+        InitialGuess::BrokenSymmetry(source) => {
+            assert!(
+                options.reference == Reference::Unrestricted,
+                "Broken-symmetry guess requires an unrestricted reference"
+            );
+            broken_symmetry(sys, source)
+        }
+    }
+}
+
+/// Rotate HOMO and LUMO of each spin, α by +θ and β by −θ
+///
+/// Breaks the α/β symmetry of a closed-shell-like guess so that UHF / UKS
+/// can reach a broken-symmetry (singlet diradical) solution.
+pub(crate) fn mix_homo_lumo(sys: &ScfSystem, guess: Guess, theta: f64) -> Guess {
+    let nao = sys.overlap.nrows();
+    let (mut c_a, mut c_b) = guess.orbitals;
+
+    for (c, n_occ, angle) in [(&mut c_a, sys.n_alpha, theta), (&mut c_b, sys.n_beta, -theta)] {
+        if n_occ > 0 && n_occ < nao {
+            rotate_pair(c, n_occ - 1, n_occ, angle);
+        }
+    }
+
+    Guess {
+        p_alpha: weighted_density(&c_a, &vec![1.0; sys.n_alpha]),
+        p_beta: weighted_density(&c_b, &vec![1.0; sys.n_beta]),
+        orbitals: (c_a, c_b),
+    }
+}
+
+// ======================================================
+// Orbital guesses
+// ======================================================
+
+/// Diagonalize a model Fock matrix and occupy by aufbau
+fn aufbau(sys: &ScfSystem, fock: &DMatrix<f64>) -> Guess {
+    let (c, _) = solve_roothaan(fock, &sys.overlap);
+
+    Guess {
+        p_alpha: weighted_density(&c, &vec![1.0; sys.n_alpha]),
+        p_beta: weighted_density(&c, &vec![1.0; sys.n_beta]),
+        orbitals: (c.clone(), c),
+    }
+}
+
+/// F_μν = ½ K (H_μμ + H_νν) S_μν, F_μμ = H_μμ
+fn gwh_matrix(h_core: &DMatrix<f64>, overlap: &DMatrix<f64>) -> DMatrix<f64> {
+    let n = h_core.nrows();
+
+    DMatrix::from_fn(n, n, |i, j| {
+        if i == j {
+            h_core[(i, i)]
+        } else {
+            0.5 * WOLFSBERG_HELMHOLZ_K * (h_core[(i, i)] + h_core[(j, j)]) * overlap[(i, j)]
+        }
+    })
+}
+
+/// Extended Hückel in the minimal basis of atomic SAD orbitals
+fn huckel(sys: &ScfSystem, options: &ScfOptions) -> Guess {
+    let atomic = atomic_guesses(sys, options);
+    let nao = sys.overlap.nrows();
+    let n_min: usize = atomic.iter().flatten().map(|(g, _)| g.energies.len()).sum();
+
+    if n_min < sys.n_alpha.max(sys.n_beta) {
+        eprintln!(
+            "WARNING: Hückel minimal basis ({}) smaller than the occupied space; \
+             using the GWH guess",
+            n_min
+        );
+        return aufbau(sys, &gwh_matrix(&sys.h_core, &sys.overlap));
+    }
+
+    // Atomic orbitals in the molecular AO basis (AO × n_min)
+    let mut c_min = DMatrix::zeros(nao, n_min);
+    let mut eps = Vec::with_capacity(n_min);
+
+    for (guess, aos) in atomic.iter().flatten() {
+        let col = eps.len();
+        for k in 0..guess.energies.len() {
+            for (a, &mu) in aos.iter().enumerate() {
+                c_min[(mu, col + k)] = guess.orbitals[(a, k)];
+            }
+        }
+        eps.extend_from_slice(&guess.energies);
+    }
+
+    let s_min = c_min.transpose() * &sys.overlap * &c_min;
+
+    let h_min = DMatrix::from_fn(n_min, n_min, |i, j| {
+        if i == j {
+            eps[i]
+        } else {
+            0.5 * WOLFSBERG_HELMHOLZ_K * (eps[i] + eps[j]) * s_min[(i, j)]
+        }
+    });
+
+    let (u, _) = solve_roothaan(&h_min, &s_min);
+    let c = c_min * u;
+
+    let p_alpha = weighted_density(&c, &vec![1.0; sys.n_alpha]);
+    let p_beta = weighted_density(&c, &vec![1.0; sys.n_beta]);
+    from_densities(sys, p_alpha, p_beta)
+}
+
+/// Project the occupied orbitals of `source` into the current basis
+fn projected(sys: &ScfSystem, source: &ProjectionSource) -> Guess {
+    let n_old = source.alpha.ncols();
+
+    assert!(
+        n_old >= sys.n_alpha.max(sys.n_beta),
+        "Projection guess: {} orbitals in the old basis, {} occupied",
+        n_old,
+        sys.n_alpha.max(sys.n_beta)
+    );
+
+    // S_22⁻¹ S_21 (pseudo-inverse guards near-linear dependence)
+    let s_21 = build_mixed_overlap_matrix(sys.shells, &source.shells);
+    let s_22_inv = sys
+        .overlap
+        .clone()
+        .pseudo_inverse(PROJECTION_CUTOFF)
+        .expect("Projection guess: S pseudo-inverse failed");
+    let projector = s_22_inv * s_21;
+
+    let project = |c_old: &DMatrix<f64>, n_occ: usize| {
+        if n_occ == 0 {
+            return DMatrix::zeros(sys.overlap.nrows(), sys.overlap.nrows());
+        }
+
+        let c = &projector * c_old.columns(0, n_occ);
+
+        // C (Cᵀ S C)^{-1/2}
+        let m = c.transpose() * &sys.overlap * &c;
+        let eig = m.symmetric_eigen();
+        let inv_sqrt = eig.eigenvalues.map(|x| {
+            if x > PROJECTION_CUTOFF { 1.0 / x.sqrt() } else { 0.0 }
+        });
+        let m_inv_sqrt = &eig.eigenvectors
+            * DMatrix::from_diagonal(&inv_sqrt)
+            * eig.eigenvectors.transpose();
+
+        let c = c * m_inv_sqrt;
+        &c * c.transpose()
+    };
+
+    let p_alpha = project(&source.alpha, sys.n_alpha);
+    let p_beta = project(&source.beta, sys.n_beta);
+    from_densities(sys, p_alpha, p_beta)
+}
+
+/// Flip the spin density of the selected atoms of a high-spin solution
+///
+/// With T = P^α + P^β and D = P^α − P^β, D changes sign on blocks within
+/// the flipped atoms and vanishes on blocks coupling flipped and
+/// unflipped atoms; P^σ = ½ (T ± D) are then renormalized to n_σ.
+fn broken_symmetry(sys: &ScfSystem, source: &BrokenSymmetrySource) -> Guess {
+    let nao = sys.overlap.nrows();
+
+    assert!(
+        source.p_alpha.nrows() == nao,
+        "Broken-symmetry guess: high-spin density has {} AOs, basis has {}",
+        source.p_alpha.nrows(),
+        nao
+    );
+
+    let mut sign = vec![1.0; nao];
+    for &a in &source.flip {
+        let atom = sys
+            .atoms
+            .get(a)
+            .unwrap_or_else(|| panic!("Broken-symmetry guess: no atom {}", a));
+        for mu in atom_shells(sys.shells, atom).1 {
+            sign[mu] = -1.0;
+        }
+    }
+
+    let total = &source.p_alpha + &source.p_beta;
+    let spin = &source.p_alpha - &source.p_beta;
+    let spin = DMatrix::from_fn(nao, nao, |mu, nu| {
+        0.5 * (sign[mu] + sign[nu]) * spin[(mu, nu)]
+    });
+
+    let normalize = |mut p: DMatrix<f64>, n: usize| {
+        let trace = p.dot(&sys.overlap);
+        if trace > 0.0 {
+            p *= n as f64 / trace;
+        }
+        p
+    };
+
+    let p_alpha = normalize(0.5 * (&total + &spin), sys.n_alpha);
+    let p_beta = normalize(0.5 * (&total - &spin), sys.n_beta);
+    from_densities(sys, p_alpha, p_beta)
+}
+
+/// Attach natural orbitals (S P S c = n S c, n descending)
+fn from_densities(sys: &ScfSystem, p_alpha: DMatrix<f64>, p_beta: DMatrix<f64>) -> Guess {
+    let natural = |p: &DMatrix<f64>| {
+        let sps = &sys.overlap * p * &sys.overlap;
+        solve_roothaan(&(-sps), &sys.overlap).0
+    };
+
+    let c_a = natural(&p_alpha);
+    let c_b = if p_alpha == p_beta { c_a.clone() } else { natural(&p_beta) };
+
+    Guess {
+        p_alpha,
+        p_beta,
+        orbitals: (c_a, c_b),
+    }
+}
+
+// ======================================================
+// Atomic calculations
+// ======================================================
+
+/// One atomic calculation per unique element, with the molecular AO
+/// indices of every atom (None for atoms without basis functions)
+fn atomic_guesses(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+) -> Vec<Option<(Rc<AtomicGuess>, Vec<usize>)>> {
+    let mut cache: HashMap<String, Rc<AtomicGuess>> = HashMap::new();
+    let mut result = Vec::with_capacity(sys.atoms.len());
+
+    for atom in sys.atoms {
+        let (shells, aos) = atom_shells(sys.shells, atom);
+        if shells.is_empty() {
+            result.push(None);
+            continue;
+        }
+
+        // ECP and finite-nucleus settings are per element
+        let key = format!("{}:{}", atom.symbol, atom.core_electrons);
+
+        let guess = cache
+            .entry(key)
+            .or_insert_with(|| Rc::new(atomic_scf(&shells, atom, options)))
+            .clone();
+
+        result.push(Some((guess, aos)));
+    }
+
+    result
+}
+
+/// Spherical atomic densities P_A (atom AO basis) and their molecular AO
+/// indices, one per atom; the Hirshfeld promolecule
+pub(crate) fn promolecule_densities(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+) -> Vec<Option<(DMatrix<f64>, Vec<usize>)>> {
+    atomic_guesses(sys, options)
+        .into_iter()
+        .map(|entry| entry.map(|(guess, aos)| (guess.density.clone(), aos)))
+        .collect()
+}
+
+/// Shells centered on `atom` (offsets renumbered) and their AO indices
+pub(crate) fn atom_shells(shells: &[Shell], atom: &Atom) -> (Vec<Shell>, Vec<usize>) {
+    let mut local = Vec::new();
+    let mut aos = Vec::new();
+    let mut offset = 0;
+
+    for shell in shells {
+        let d2: f64 = (0..3)
+            .map(|k| (shell.center[k] - atom.position[k]).powi(2))
+            .sum();
+        if d2 > 1e-12 {
+            continue;
+        }
+
+        let n = shell.n_orbitals();
+        aos.extend(shell.offset..shell.offset + n);
+
+        let mut shell = shell.clone();
+        shell.offset = offset;
+        offset += n;
+        local.push(shell);
+    }
+
+    (local, aos)
+}
+
+/// Spin-unrestricted atomic SCF with spherically averaged occupations
+fn atomic_scf(shells: &[Shell], atom: &Atom, options: &ScfOptions) -> AtomicGuess {
+    let atoms = std::slice::from_ref(atom);
+    let centers: Vec<[f64; 3]> = shells.iter().map(|s| s.center).collect();
+
+    // Neutral atom, lowest spin
+    let n_elec = atom.atomic_number - atom.core_electrons;
+    let n_beta = n_elec / 2;
+    let n_alpha = n_elec - n_beta;
+
+    // Átomos aislados: sin potenciales externos
+    let atomic_options = ScfOptions {
+        reference: Reference::Unrestricted,
+        external: Vec::new(),
+        ..options.clone()
+    };
+    let mixing = atomic_options.exchange_mixing();
+
+    let sys = ScfSystem {
+        shells,
+        shell_centers: &centers,
+        atoms,
+        h_core: core_hamiltonian(shells, atoms, &atomic_options),
+        overlap: build_overlap_matrix(shells),
+        e_nuc: 0.0,
+        n_alpha,
+        n_beta,
+        constraint_potential: None,
+        observer: None,
+    };
+
+    let nao = sys.overlap.nrows();
+    let mut p_a = DMatrix::zeros(nao, nao);
+    let mut p_b = DMatrix::zeros(nao, nao);
+    let mut orbitals: Option<(DMatrix<f64>, DMatrix<f64>)> = None;
+    let mut alpha = (DMatrix::zeros(nao, nao), Vec::new(), Vec::new());
+
+    let mut diis = Diis::new(ATOMIC_DIIS_SIZE);
+
+    for iter in 0..ATOMIC_MAX_ITER {
+        let (f_a, f_b, components) =
+            build_fock(&sys, &p_a, &p_b, orbitals.as_ref(), &atomic_options, &mixing);
+
+        if iter > 0 {
+            diis.push(DiisEntry {
+                errors: vec![
+                    diis_error(&f_a, &p_a, &sys.overlap),
+                    diis_error(&f_b, &p_b, &sys.overlap),
+                ],
+                focks: vec![f_a.clone(), f_b.clone()],
+                densities: vec![p_a.clone(), p_b.clone()],
+                energy: components.total(),
+            });
+        }
+
+        let focks = diis.extrapolate().unwrap_or_else(|| vec![f_a, f_b]);
+
+        let (c_a, eps_a) = solve_roothaan(&focks[0], &sys.overlap);
+        let (c_b, eps_b) = solve_roothaan(&focks[1], &sys.overlap);
+
+        let occ_a = averaged_occupations(&eps_a, n_alpha);
+        let occ_b = averaged_occupations(&eps_b, n_beta);
+
+        let p_a_new = weighted_density(&c_a, &occ_a);
+        let p_b_new = weighted_density(&c_b, &occ_b);
+
+        let change = (&p_a_new - &p_a).amax().max((&p_b_new - &p_b).amax());
+
+        p_a = p_a_new;
+        p_b = p_b_new;
+        alpha = (c_a.clone(), eps_a, occ_a);
+        orbitals = Some((c_a, c_b));
+
+        if iter > 0 && change < ATOMIC_CONVERGENCE {
+            break;
+        }
+    }
+
+    // Minimal basis: every α orbital with nonzero occupation
+    let (c_a, eps_a, occ_a) = alpha;
+    let n_min = occ_a.iter().filter(|&&n| n > 0.0).count();
+
+    AtomicGuess {
+        density: p_a + p_b,
+        orbitals: c_a.columns(0, n_min).into_owned(),
+        energies: eps_a[..n_min].to_vec(),
+    }
+}
+
+/// Aufbau with degenerate groups sharing their electrons equally
+/// (per-orbital occupations in [0, 1]; ε ascending)
+fn averaged_occupations(eps: &[f64], n_electrons: usize) -> Vec<f64> {
+    let mut occ = vec![0.0; eps.len()];
+    let mut left = n_electrons as f64;
+    let mut i = 0;
+
+    while left > 0.0 && i < eps.len() {
+        let mut j = i + 1;
+        while j < eps.len() && eps[j] - eps[i] < DEGENERACY_TOL {
+            j += 1;
+        }
+
+        let size = (j - i) as f64;
+        let fill = left.min(size);
+        occ[i..j].iter_mut().for_each(|n| *n = fill / size);
+
+        left -= fill;
+        i = j;
+    }
+
+    occ
+}
+
+// ======================================================
+// Legacy helpers (closed-shell core guess, AO matrices)
+// ======================================================
 
 /// Build initial density using Core-Hamiltonian guess
 pub fn core_h_guess(
     shells: &[Shell],
-    shell_centers: &[[f64; 3]],
-    atoms: &[crate::system::atom::Atom],
+    _shell_centers: &[[f64; 3]],
+    atoms: &[Atom],
     n_electrons: usize,
 ) -> Vec<Vec<f64>> {
-    let nao = shells.iter().map(|s| s.orbitals.len()).sum::<usize>();
-
-    // Build S, T, V
-    let s = build_matrix(shells, shell_centers, |a, ca, b, cb| {
-        overlap_shell_shell(a, ca, b, cb)
-    });
-    let t = build_matrix(shells, shell_centers, |a, ca, b, cb| {
-        kinetic_shell_shell(a, ca, b, cb)
-    });
-    let v = build_matrix(shells, shell_centers, |a, ca, b, cb| {
-        nuclear_attraction_shell_shell(a, ca, b, cb, atoms)
-    });
-
-    let hcore = add(&t, &v);
+    let hcore = build_one_electron_matrix(shells, atoms);
+    let s = build_overlap_matrix(shells);
 
     // Solve Hcore C = S C eps
     let (coeff, _) = solve_roothaan(&hcore, &s);
 
     // Build density
-    build_density(&coeff, n_electrons)
+    build_density(coeff, n_electrons)
 }
 
 pub fn build_matrix<F>(
@@ -42,22 +610,15 @@ pub fn build_matrix<F>(
 where
     F: Fn(&Shell, [f64; 3], &Shell, [f64; 3]) -> Vec<Vec<f64>>,
 {
-    let nao = shells.iter().map(|s| s.orbitals.len()).sum::<usize>();
+    let nao = shells.iter().map(|s| s.n_orbitals()).sum::<usize>();
     let mut mat = vec![vec![0.0; nao]; nao];
 
-    let mut offsets = Vec::new();
-    let mut off = 0;
-    for sh in shells {
-        offsets.push(off);
-        off += sh.orbitals.len();
-    }
-
     for i in 0..shells.len() {
         for j in 0..shells.len() {
             let block = kernel(&shells[i], centers[i], &shells[j], centers[j]);
             for a in 0..block.len() {
                 for b in 0..block[0].len() {
-                    mat[offsets[i] + a][offsets[j] + b] = block[a][b];
+                    mat[shells[i].offset + a][shells[j].offset + b] = block[a][b];
                 }
             }
         }
@@ -66,13 +627,141 @@ where
     mat
 }
 
-pub fn add(a: &Vec<Vec<f64>>, b: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
-    let n = a.len();
-    let mut c = vec![vec![0.0; n]; n];
-    for i in 0..n {
-        for j in 0..n {
-            c[i][j] = a[i][j] + b[i][j];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+    use crate::scf::scf_cycle::run_scf;
+
+    const H3: [[f64; 3]; 3] = [[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]];
+
+    #[test]
+    fn every_guess_holds_the_electron_count() {
+        let (molecule, shells, centers) = hydrogens(&H3, 0, 2);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+        let sys = ScfSystem::new(&molecule, &shells, &centers, &options);
+        let s = &sys.overlap;
+
+        let guesses =
+            [InitialGuess::Core, InitialGuess::Sad, InitialGuess::Huckel, InitialGuess::Gwh];
+
+        for guess in guesses {
+            let with_guess = ScfOptions { guess: guess.clone(), ..options.clone() };
+            let start = initial_guess(&sys, &with_guess);
+
+            assert!((start.p_alpha.dot(s) - 2.0).abs() < 1e-8, "{:?}", guess);
+            assert!((start.p_beta.dot(s) - 1.0).abs() < 1e-8, "{:?}", guess);
+            for c in [&start.orbitals.0, &start.orbitals.1] {
+                let metric = c.transpose() * s * c;
+                assert!((metric - DMatrix::identity(6, 6)).amax() < 1e-8, "{:?}", guess);
+            }
         }
     }
-    c
+
+    #[test]
+    fn sad_runs_one_atomic_scf_per_element() {
+        let (molecule, shells, centers) = hydrogens(&H3, 0, 2);
+        let options = quiet_options();
+        let sys = ScfSystem::new(&molecule, &shells, &centers, &options);
+
+        let atomic = atomic_guesses(&sys, &options);
+        let (first, aos) = atomic[0].as_ref().unwrap();
+        assert_eq!(aos, &vec![0, 1]);
+        for entry in &atomic[1..] {
+            assert!(Rc::ptr_eq(first, &entry.as_ref().unwrap().0));
+        }
+
+        // spherically averaged hydrogen atom: one electron
+        let (atom_shells, _) = atom_shells(&shells, &molecule.atoms[0]);
+        let s_atom = build_overlap_matrix(&atom_shells);
+        assert!((first.density.dot(&s_atom) - 1.0).abs() < 1e-8);
+    }
+
+    #[test]
+    fn degenerate_levels_share_their_electrons() {
+        let occ = averaged_occupations(&[-1.0, -0.5, -0.5, -0.5, 0.2], 3);
+        let third = 2.0 / 3.0;
+        assert_eq!(occ[0], 1.0);
+        assert!(occ[1..4].iter().all(|&n| (n - third).abs() < 1e-15));
+        assert_eq!(occ[4], 0.0);
+    }
+
+    #[test]
+    fn guesses_converge_to_the_same_solution() {
+        let (molecule, shells, centers) = hydrogens(&H3, 1, 1);
+        let reference = run_scf(&molecule, &shells, &centers, &quiet_options());
+
+        for guess in [InitialGuess::Sad, InitialGuess::Huckel, InitialGuess::Gwh] {
+            let result = run_scf(
+                &molecule,
+                &shells,
+                &centers,
+                &ScfOptions { guess: guess.clone(), ..quiet_options() },
+            );
+            assert!((result.energy - reference.energy).abs() < 1e-8, "{:?}", guess);
+        }
+    }
+
+    /// One s primitive (ζ = 1.3) per hydrogen
+    fn minimal_basis(positions: &[[f64; 3]]) -> (Vec<Shell>, Vec<[f64; 3]>) {
+        let shells = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| {
+                Shell::new(vec![Primitive::new(1.3, 1.0, r, [0, 0, 0])], [0, 0, 0], r, i)
+            })
+            .collect();
+        (shells, positions.to_vec())
+    }
+
+    #[test]
+    fn projection_onto_the_same_basis_restarts_at_convergence() {
+        let (molecule, shells, centers) = h2();
+        let options = quiet_options();
+        let first = run_scf(&molecule, &shells, &centers, &options);
+
+        let source = ProjectionSource::from_result(&first, &shells);
+        let projected = ScfOptions { guess: InitialGuess::Projected(source), ..options.clone() };
+
+        let sys = ScfSystem::new(&molecule, &shells, &centers, &projected);
+        let start = initial_guess(&sys, &projected);
+        let p_first = vec2d_ref_to_dmatrix(&first.density);
+        assert!((&start.p_alpha + &start.p_beta - p_first).amax() < 1e-8);
+
+        let restart = run_scf(&molecule, &shells, &centers, &projected);
+        assert!(restart.converged && restart.iterations <= 2, "{}", restart.iterations);
+        assert!((restart.energy - first.energy).abs() < 1e-10);
+    }
+
+    #[test]
+    fn projection_from_a_smaller_basis_reaches_the_large_basis_solution() {
+        let (molecule, shells, centers) = hydrogens(&H3, 0, 2);
+        let (small_shells, small_centers) = minimal_basis(&H3);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+
+        let small = run_scf(&molecule, &small_shells, &small_centers, &options);
+        let source = ProjectionSource::from_result(&small, &small_shells);
+        let projected = ScfOptions { guess: InitialGuess::Projected(source), ..options.clone() };
+
+        let sys = ScfSystem::new(&molecule, &shells, &centers, &projected);
+        let start = initial_guess(&sys, &projected);
+        assert!((start.p_alpha.dot(&sys.overlap) - 2.0).abs() < 1e-8);
+        assert!((start.p_beta.dot(&sys.overlap) - 1.0).abs() < 1e-8);
+
+        let large = run_scf(&molecule, &shells, &centers, &options);
+        let restart = run_scf(&molecule, &shells, &centers, &projected);
+        assert!(restart.converged);
+        assert!(large.energy < small.energy);
+        let gap = restart.energy - large.energy;
+        assert!(gap.abs() < 1e-8, "{} vs {}", restart.energy, large.energy);
+    }
+
+    #[test]
+    fn guess_keywords() {
+        assert!(matches!(InitialGuess::from_name("SAD"), Some(InitialGuess::Sad)));
+        assert!(matches!(InitialGuess::from_name("hückel"), Some(InitialGuess::Huckel)));
+        assert!(matches!(InitialGuess::from_name("hcore"), Some(InitialGuess::Core)));
+        assert!(InitialGuess::from_name("minao").is_none());
+    }
 }
@@ -1,5 +1,10 @@
 use crate::basis::shell::Shell;
 use crate::integrals::eri::eri_contracted::eri_shell_shell_shell_shell;
+use crate::integrals::eri::eri_erf::eri_erf_shell_quartet;
+use crate::integrals::schwarz::schwarz_factors;
+
+/// Schwarz screening threshold on Q_ab Q_cd
+const SCREEN_CUTOFF: f64 = 1e-12;
 
 /// Build Coulomb (J) and Exchange (K) matrices
 ///
@@ -10,13 +15,49 @@ use crate::integrals::eri::eri_contracted::eri_shell_shell_shell_shell;
 /// Returns (J, K)
 pub fn build_jk(
     shells: &[Shell],
-    shell_centers: &[[f64; 3]],
+    _shell_centers: &[[f64; 3]],
     density: &Vec<Vec<f64>>,
 ) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
-    let nao = density.len();
+    let mut jk = build_jk_set(shells, Some(density), &[density], None);
+    (jk.j, jk.k.remove(0))
+}
+
+/// Coulomb and exchange matrices of one pass over the ERIs
+pub struct JkSet {
+    /// J[P_J]
+    pub j: Vec<Vec<f64>>,
+    /// K[D_i], in the order of the densities
+    pub k: Vec<Vec<Vec<f64>>>,
+    /// K^{lr}(ω)[D_i] with erf(ω r12)/r12; empty without ω
+    pub k_lr: Vec<Vec<Vec<f64>>>,
+}
 
-    let mut j = vec![vec![0.0; nao]; nao];
-    let mut k = vec![vec![0.0; nao]; nao];
+/// J[P_J], K[D_i] and K^{lr}[D_i] from a single pass over the
+/// symmetry-unique shell quartets (ab|cd), a ≥ b, c ≥ d, ab ≥ cd
+///
+/// Every quartet is scattered to its distinct permutations, so the
+/// densities need not be symmetric (e.g. the α–β block of a GHF
+/// density). Quartets with Q_ab Q_cd below the Schwarz cutoff are
+/// skipped; the erf block is computed only when `omega` is given.
+pub fn build_jk_set(
+    shells: &[Shell],
+    j_density: Option<&Vec<Vec<f64>>>,
+    k_densities: &[&Vec<Vec<f64>>],
+    omega: Option<f64>,
+) -> JkSet {
+    let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
+    let nk = k_densities.len();
+    let zeros = || vec![vec![0.0; nao]; nao];
+
+    let mut jk = JkSet {
+        j: zeros(),
+        k: (0..nk).map(|_| zeros()).collect(),
+        k_lr: if omega.is_some() { (0..nk).map(|_| zeros()).collect() } else { Vec::new() },
+    };
+
+    if j_density.is_none() && nk == 0 {
+        return jk;
+    }
 
     // AO index offset per shell
     let mut shell_offsets = Vec::new();
@@ -26,13 +67,18 @@ pub fn build_jk(
         offset += sh.n_orbitals();
     }
 
+    let q = schwarz_factors(shells);
     let nshells = shells.len();
 
-    // Loop over shell quartets
     for a in 0..nshells {
-        for b in 0..nshells {
-            for c in 0..nshells {
-                for d in 0..nshells {
+        for b in 0..=a {
+            for c in 0..=a {
+                let d_top = if c == a { b } else { c };
+
+                for d in 0..=d_top {
+                    if q[a][b] * q[c][d] < SCREEN_CUTOFF {
+                        continue;
+                    }
 
                     let eri_block = eri_shell_shell_shell_shell(
                         &shells[a],
@@ -40,37 +86,51 @@ pub fn build_jk(
                         &shells[c],
                         &shells[d],
                     );
+                    let eri_lr = omega.map(|w| {
+                        eri_erf_shell_quartet(&shells[a], &shells[b], &shells[c], &shells[d], w)
+                    });
+
+                    let perms = distinct_permutations([a, b, c, d]);
 
                     let na = shells[a].n_orbitals();
                     let nb = shells[b].n_orbitals();
                     let nc = shells[c].n_orbitals();
                     let nd = shells[d].n_orbitals();
 
-                    let oa = shell_offsets[a];
-                    let ob = shell_offsets[b];
-                    let oc = shell_offsets[c];
-                    let od = shell_offsets[d];
-
                     let idx = |i, j, k, l| ((i * nb + j) * nc + k) * nd + l;
 
                     for ia in 0..na {
                         for ib in 0..nb {
-                            let mu = oa + ia;
-                            let nu = ob + ib;
-
                             for ic in 0..nc {
                                 for id in 0..nd {
-                                    let lam = oc + ic;
-                                    let sig = od + id;
+                                    let q_idx = idx(ia, ib, ic, id);
+                                    let eri = eri_block[q_idx];
+                                    let eri_lr = eri_lr.as_ref().map_or(0.0, |e| e[q_idx]);
 
-                                    let eri = eri_block[idx(ia, ib, ic, id)];
-                                    let p = density[lam][sig];
+                                    let aos = [
+                                        shell_offsets[a] + ia,
+                                        shell_offsets[b] + ib,
+                                        shell_offsets[c] + ic,
+                                        shell_offsets[d] + id,
+                                    ];
 
-                                    // Coulomb
-                                    j[mu][nu] += p * eri;
+                                    for perm in &perms {
+                                        let [mu, nu, lam, sig] = perm.map(|x| aos[x]);
 
-                                    // Exchange
-                                    k[mu][lam] += density[nu][sig] * eri;
+                                        // Coulomb
+                                        if let Some(p) = j_density {
+                                            jk.j[mu][nu] += p[lam][sig] * eri;
+                                        }
+
+                                        // Exchange
+                                        for (kd, dens) in k_densities.iter().enumerate() {
+                                            let p = dens[nu][sig];
+                                            jk.k[kd][mu][lam] += p * eri;
+                                            if omega.is_some() {
+                                                jk.k_lr[kd][mu][lam] += p * eri_lr;
+                                            }
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -80,5 +140,99 @@ pub fn build_jk(
         }
     }
 
-    (j, k)
+    jk
+}
+
+/// Index permutations of (ab|cd) that give distinct shell quartets
+///
+/// Each is a map from positions of (μν|λσ) to the AO tuple of the
+/// unique quartet.
+fn distinct_permutations(s: [usize; 4]) -> Vec<[usize; 4]> {
+    const PERMUTATIONS: [[usize; 4]; 8] = [
+        [0, 1, 2, 3],
+        [1, 0, 2, 3],
+        [0, 1, 3, 2],
+        [1, 0, 3, 2],
+        [2, 3, 0, 1],
+        [3, 2, 0, 1],
+        [2, 3, 1, 0],
+        [3, 2, 1, 0],
+    ];
+
+    let mut seen: Vec<[usize; 4]> = Vec::with_capacity(8);
+    let mut perms = Vec::with_capacity(8);
+
+    for perm in PERMUTATIONS {
+        let shells = perm.map(|x| s[x]);
+        if !seen.contains(&shells) {
+            seen.push(shells);
+            perms.push(perm);
+        }
+    }
+
+    perms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+
+    fn s_shell(alpha: f64, center: [f64; 3], offset: usize) -> Shell {
+        Shell::new(vec![Primitive::new(alpha, 1.0, center, [0, 0, 0])], [0, 0, 0], center, offset)
+    }
+
+    #[test]
+    fn unique_quartets_match_the_full_loop() {
+        let shells = vec![
+            s_shell(1.2, [0.0, 0.0, 0.0], 0),
+            s_shell(0.4, [0.0, 0.0, 0.0], 1),
+            s_shell(0.9, [0.0, 1.4, 0.3], 2),
+            s_shell(0.6, [1.1, -0.2, 0.8], 3),
+        ];
+        let n = 4;
+
+        // Deliberately non-symmetric, as the α–β block of a GHF density
+        let d: Vec<Vec<f64>> = (0..n)
+            .map(|i| (0..n).map(|j| 0.1 * (1 + i) as f64 - 0.07 * j as f64).collect())
+            .collect();
+
+        let omega = 0.4;
+        let jk = build_jk_set(&shells, Some(&d), &[&d], Some(omega));
+
+        let mut j_ref = vec![vec![0.0; n]; n];
+        let mut k_ref = vec![vec![0.0; n]; n];
+        let mut klr_ref = vec![vec![0.0; n]; n];
+
+        for a in 0..n {
+            for b in 0..n {
+                for c in 0..n {
+                    for e in 0..n {
+                        let (sa, sb, sc, se) = (&shells[a], &shells[b], &shells[c], &shells[e]);
+                        let v = eri_shell_shell_shell_shell(sa, sb, sc, se)[0];
+                        let v_lr = eri_erf_shell_quartet(sa, sb, sc, se, omega)[0];
+                        j_ref[a][b] += d[c][e] * v;
+                        k_ref[a][c] += d[b][e] * v;
+                        klr_ref[a][c] += d[b][e] * v_lr;
+                    }
+                }
+            }
+        }
+
+        for i in 0..n {
+            for j in 0..n {
+                assert!((jk.j[i][j] - j_ref[i][j]).abs() < 1e-10);
+                assert!((jk.k[0][i][j] - k_ref[i][j]).abs() < 1e-10);
+                assert!((jk.k_lr[0][i][j] - klr_ref[i][j]).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn distinct_permutations_count_degeneracy() {
+        assert_eq!(distinct_permutations([3, 2, 1, 0]).len(), 8);
+        assert_eq!(distinct_permutations([1, 1, 0, 0]).len(), 2);
+        assert_eq!(distinct_permutations([1, 0, 1, 0]).len(), 4);
+        assert_eq!(distinct_permutations([2, 2, 2, 2]).len(), 1);
+    }
 }
@@ -2,7 +2,20 @@ pub mod density;
 pub mod jk;
 pub mod scf_cycle;
 pub mod diis;
+pub mod stabilizers;
+pub mod soscf;
+pub mod rohf;
+pub mod mom;
+pub mod spin;
+pub mod scalar;
+pub mod complex;
+pub mod stability;
+pub mod cdft;
+pub mod external;
+pub mod ghf;
 pub mod uhf;
 pub mod udft;
 pub mod utils;
 pub mod guess;
+#[cfg(test)]
+pub(crate) mod fixtures;
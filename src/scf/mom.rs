@@ -0,0 +1,313 @@
+//! Maximum overlap method (MOM / IMOM) for ΔSCF states
+//!
+//! After every diagonalization the occupied orbitals are not chosen by
+//! aufbau but by their projection onto a reference occupied space
+//!
+//!   p_j = Σ_i |(C_refᵀ S C)_ij|²
+//!
+//! where C_ref are the occupied orbitals of the previous iteration (MOM)
+//! or of the first one (IMOM). The first diagonalization is occupied by
+//! aufbau with the requested `OccupationChange`s applied, which fixes
+//! the target state (excitation, core hole).
+//!
+//! Selected orbitals are moved to the front of C (ROHF / ROKS: closed,
+//! then open), keeping the ε order within each block, so the rest of the
+//! driver keeps treating the first n_σ columns as occupied. Orbital
+//! energies of a MOM solution are therefore not ascending.
+
+use nalgebra::DMatrix;
+
+use crate::scf::scf_cycle::Reference;
+
+/// Reference occupied space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MomVariant {
+    /// Previous iteration
+    Mom,
+    /// First iteration (initial MOM); avoids drifting back to the ground state
+    Imom,
+}
+
+impl MomVariant {
+    /// Parse the input keyword (mom | imom)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mom" => Some(Self::Mom),
+            "imom" => Some(Self::Imom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpinChannel {
+    Alpha,
+    Beta,
+}
+
+impl SpinChannel {
+    /// Parse the input keyword (alpha | beta)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "alpha" | "a" => Some(Self::Alpha),
+            "beta" | "b" => Some(Self::Beta),
+            _ => None,
+        }
+    }
+}
+
+/// Change of the aufbau occupation of the first diagonalization
+///
+/// Orbital indices are 0-based in ε order. With a restricted reference
+/// the spin is ignored and the spatial orbital moves both electrons.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OccupationChange {
+    /// One electron `from` → `to` (e.g. HOMO → LUMO)
+    Excitation { spin: SpinChannel, from: usize, to: usize },
+    /// Hole in `orbital`: the n_σ electrons occupy the lowest n_σ + 1
+    /// orbitals except `orbital` (charge and multiplicity of the ion)
+    Ionization { spin: SpinChannel, orbital: usize },
+}
+
+#[derive(Clone, Debug)]
+pub struct MomOptions {
+    pub variant: MomVariant,
+    pub changes: Vec<OccupationChange>,
+}
+
+impl MomOptions {
+    /// Single σ HOMO → LUMO excitation
+    pub fn homo_lumo(variant: MomVariant, spin: SpinChannel, n_occupied: usize) -> Self {
+        assert!(n_occupied > 0, "HOMO → LUMO requires an occupied orbital");
+        Self {
+            variant,
+            changes: vec![OccupationChange::Excitation {
+                spin,
+                from: n_occupied - 1,
+                to: n_occupied,
+            }],
+        }
+    }
+
+    /// Core hole in σ orbital `orbital` (XPS)
+    pub fn core_hole(variant: MomVariant, spin: SpinChannel, orbital: usize) -> Self {
+        Self {
+            variant,
+            changes: vec![OccupationChange::Ionization { spin, orbital }],
+        }
+    }
+}
+
+/// Occupation selection state of one SCF run
+pub(crate) struct MomState {
+    variant: MomVariant,
+    /// Occupied indices of the first diagonalization per group
+    /// (restricted / UHF: one group per channel; ROHF: closed, open)
+    initial: Vec<Vec<Vec<usize>>>,
+    /// Reference occupied orbitals per channel and group
+    reference: Vec<Option<Vec<DMatrix<f64>>>>,
+}
+
+impl MomState {
+    pub(crate) fn new(
+        options: &MomOptions,
+        reference: Reference,
+        n_alpha: usize,
+        n_beta: usize,
+    ) -> Self {
+
+        let mut sets = [(0..n_alpha).collect::<Vec<usize>>(), (0..n_beta).collect()];
+
+        for change in &options.changes {
+            let (spin, remove, add) = match *change {
+                OccupationChange::Excitation { spin, from, to } => (spin, from, Some(to)),
+                OccupationChange::Ionization { spin, orbital } => (spin, orbital, None),
+            };
+
+            let set = match reference {
+                Reference::Restricted => &mut sets[0],
+                _ => &mut sets[spin as usize],
+            };
+            let add = add.unwrap_or_else(|| set.iter().max().map_or(0, |m| m + 1));
+
+            let pos = set
+                .iter()
+                .position(|&i| i == remove)
+                .unwrap_or_else(|| panic!("MOM: orbital {} is not occupied", remove));
+            assert!(!set.contains(&add), "MOM: orbital {} is already occupied", add);
+
+            set[pos] = add;
+            set.sort_unstable();
+        }
+
+        let [alpha, beta] = sets;
+
+        let initial = match reference {
+            Reference::Restricted => vec![vec![alpha]],
+            Reference::Unrestricted => vec![vec![alpha], vec![beta]],
+            Reference::RestrictedOpenShell => {
+                assert!(
+                    beta.iter().all(|i| alpha.contains(i)),
+                    "ROHF MOM: β-occupied orbitals must be α-occupied"
+                );
+                let open = alpha.into_iter().filter(|i| !beta.contains(i)).collect();
+                vec![vec![beta, open]]
+            }
+        };
+
+        let channels = initial.len();
+        Self {
+            variant: options.variant,
+            initial,
+            reference: vec![None; channels],
+        }
+    }
+
+    /// Reorder (C, ε) of `channel` so that the selected orbitals come first
+    pub(crate) fn select(
+        &mut self,
+        channel: usize,
+        coeff: DMatrix<f64>,
+        eps: Vec<f64>,
+        overlap: &DMatrix<f64>,
+    ) -> (DMatrix<f64>, Vec<f64>) {
+
+        let groups: Vec<Vec<usize>> = match &self.reference[channel] {
+            None => self.initial[channel].clone(),
+            Some(reference) => {
+                let mut taken = vec![false; coeff.ncols()];
+                reference
+                    .iter()
+                    .map(|c_ref| {
+                        let o = c_ref.transpose() * overlap * &coeff;
+                        let mut candidates: Vec<(usize, f64)> = (0..coeff.ncols())
+                            .filter(|&j| !taken[j])
+                            .map(|j| (j, o.column(j).norm_squared()))
+                            .collect();
+                        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+                        let mut chosen: Vec<usize> =
+                            candidates.iter().take(c_ref.ncols()).map(|&(j, _)| j).collect();
+                        chosen.sort_unstable();
+                        for &j in &chosen {
+                            taken[j] = true;
+                        }
+                        chosen
+                    })
+                    .collect()
+            }
+        };
+
+        let selected: Vec<usize> = groups.iter().flatten().copied().collect();
+        let order: Vec<usize> = selected
+            .iter()
+            .copied()
+            .chain((0..coeff.ncols()).filter(|j| !selected.contains(j)))
+            .collect();
+
+        let coeff = DMatrix::from_fn(coeff.nrows(), coeff.ncols(), |mu, k| coeff[(mu, order[k])]);
+        let eps = order.iter().map(|&k| eps[k]).collect();
+
+        if self.variant == MomVariant::Mom || self.reference[channel].is_none() {
+            let mut start = 0;
+            let blocks = groups
+                .iter()
+                .map(|g| {
+                    let block = coeff.columns(start, g.len()).into_owned();
+                    start += g.len();
+                    block
+                })
+                .collect();
+            self.reference[channel] = Some(blocks);
+        }
+
+        (coeff, eps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, ScfOptions};
+    use crate::scf::utils::{build_overlap_matrix, vec2d_ref_to_dmatrix};
+
+    #[test]
+    fn changes_edit_the_aufbau_sets() {
+        let homo_lumo = MomOptions::homo_lumo(MomVariant::Imom, SpinChannel::Beta, 2);
+        let state = MomState::new(&homo_lumo, Reference::Unrestricted, 3, 2);
+        assert_eq!(state.initial, vec![vec![vec![0, 1, 2]], vec![vec![0, 2]]]);
+
+        let hole = MomOptions::core_hole(MomVariant::Mom, SpinChannel::Alpha, 0);
+        let state = MomState::new(&hole, Reference::Restricted, 2, 2);
+        assert_eq!(state.initial, vec![vec![vec![1, 2]]]);
+
+        // ROHF: β 1 → 2 closes {0, 2} and leaves 1 open
+        let state = MomState::new(&homo_lumo, Reference::RestrictedOpenShell, 3, 2);
+        assert_eq!(state.initial, vec![vec![vec![0, 2], vec![1]]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "MOM: orbital 4 is not occupied")]
+    fn only_occupied_orbitals_can_be_excited() {
+        let excitation = OccupationChange::Excitation { spin: SpinChannel::Alpha, from: 4, to: 5 };
+        let options = MomOptions { variant: MomVariant::Mom, changes: vec![excitation] };
+        MomState::new(&options, Reference::Unrestricted, 2, 2);
+    }
+
+    #[test]
+    fn selection_follows_the_orbitals_not_their_energies() {
+        let options = MomOptions::homo_lumo(MomVariant::Imom, SpinChannel::Alpha, 1);
+        let mut state = MomState::new(&options, Reference::Restricted, 1, 1);
+        let one = DMatrix::<f64>::identity(3, 3);
+
+        // first diagonalization: orbital 1 occupied by the excitation
+        let (c, eps) = state.select(0, one.clone(), vec![-1.0, 0.0, 1.0], &one);
+        assert_eq!(eps, vec![0.0, -1.0, 1.0]);
+        assert_eq!(c.column(0), one.column(1));
+
+        // the occupied orbital turns up last in ε order and is still chosen
+        let swapped = DMatrix::from_columns(&[one.column(0), one.column(2), one.column(1)]);
+        let (c, eps) = state.select(0, swapped, vec![-1.0, 0.5, 2.0], &one);
+        assert_eq!(eps[0], 2.0);
+        assert_eq!(c.column(0), one.column(1));
+    }
+
+    #[test]
+    fn excited_state_keeps_the_excited_occupation() {
+        let (molecule, shells, centers) = h2();
+        let overlap = build_overlap_matrix(&shells);
+        let unrestricted = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+        let ground = run_scf(&molecule, &shells, &centers, &unrestricted);
+        let lumo = vec2d_ref_to_dmatrix(&ground.alpha.coefficients).column(1).into_owned();
+
+        for variant in [MomVariant::Mom, MomVariant::Imom] {
+            let options = ScfOptions {
+                mom: Some(MomOptions::homo_lumo(variant, SpinChannel::Alpha, 1)),
+                ..unrestricted.clone()
+            };
+            let excited = run_scf(&molecule, &shells, &centers, &options);
+            let homo = vec2d_ref_to_dmatrix(&excited.alpha.coefficients).column(0).into_owned();
+
+            assert!(excited.converged, "{:?}", variant);
+            assert!(excited.energy > ground.energy + 0.1, "{:?}", variant);
+            assert!((homo.transpose() * &overlap * &lumo)[(0, 0)].powi(2) > 0.9, "{:?}", variant);
+        }
+    }
+
+    #[test]
+    fn core_hole_lies_above_the_ion_ground_state() {
+        let (molecule, shells, centers) = hydrogens(&[[0.0; 3], [0.0, 0.0, 1.4]], 1, 2);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+        let ground = run_scf(&molecule, &shells, &centers, &options);
+
+        let hole = ScfOptions {
+            mom: Some(MomOptions::core_hole(MomVariant::Imom, SpinChannel::Alpha, 0)),
+            ..options
+        };
+        let ionized = run_scf(&molecule, &shells, &centers, &hole);
+
+        assert!(ionized.converged);
+        assert!(ionized.energy > ground.energy + 0.1, "{} vs {}", ionized.energy, ground.energy);
+    }
+}
@@ -0,0 +1,194 @@
+//! ROHF / ROKS effective Fock operator
+//!
+//! Orbitals are split into closed (c, n_β), open (o, n_α − n_β) and
+//! virtual (v) blocks. In the MO basis of the current orbitals
+//!
+//! ```text
+//!          c                    o                    v
+//!   c   A_c F^α + B_c F^β      F^β                  ½ (F^α + F^β)
+//!   o   F^β                    A_o F^α + B_o F^β    F^α
+//!   v   ½ (F^α + F^β)          F^α                  A_v F^α + B_v F^β
+//! ```
+//!
+//! The off-diagonal blocks are the ROHF orbital gradient and vanish at
+//! convergence for every coupling, so energy and density do not depend
+//! on (A, B); only the orbital energies and the convergence behaviour do.
+
+use nalgebra::DMatrix;
+
+/// Diagonal-block coupling coefficients (A, B) of the effective Fock
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RohfCoupling {
+    /// c: (−½, 3⁄2)  o: (½, ½)  v: (3⁄2, −½)
+    Roothaan,
+    /// (½, ½) in every block
+    #[default]
+    GuestSaunders,
+    /// c: (⅓, ⅔)  o: (⅓, ⅓)  v: (⅔, ⅓)
+    McWeenyDiercksen,
+    /// c: (½, ½)  o: (1, 0)  v: (0, 1)
+    BinkleyPopleDobosh,
+    /// c: (½, ½)  o: (1, 0)  v: (½, ½)
+    FaegriManne,
+    /// Explicit [(A_c, B_c), (A_o, B_o), (A_v, B_v)]
+    Custom([(f64, f64); 3]),
+}
+
+impl RohfCoupling {
+    /// [(A_c, B_c), (A_o, B_o), (A_v, B_v)]
+    pub fn coefficients(&self) -> [(f64, f64); 3] {
+        match self {
+            RohfCoupling::Roothaan => [(-0.5, 1.5), (0.5, 0.5), (1.5, -0.5)],
+            RohfCoupling::GuestSaunders => [(0.5, 0.5); 3],
+            RohfCoupling::McWeenyDiercksen => {
+                [(1.0 / 3.0, 2.0 / 3.0), (1.0 / 3.0, 1.0 / 3.0), (2.0 / 3.0, 1.0 / 3.0)]
+            }
+            RohfCoupling::BinkleyPopleDobosh => [(0.5, 0.5), (1.0, 0.0), (0.0, 1.0)],
+            RohfCoupling::FaegriManne => [(0.5, 0.5), (1.0, 0.0), (0.5, 0.5)],
+            RohfCoupling::Custom(c) => *c,
+        }
+    }
+
+    /// Parse the input keyword
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "roothaan" => Some(RohfCoupling::Roothaan),
+            "guestsaunders" => Some(RohfCoupling::GuestSaunders),
+            "mcweeny" | "mcweenydiercksen" => Some(RohfCoupling::McWeenyDiercksen),
+            "binkleypopledobosh" | "bpd" => Some(RohfCoupling::BinkleyPopleDobosh),
+            "faegrimanne" => Some(RohfCoupling::FaegriManne),
+            _ => None,
+        }
+    }
+}
+
+/// Effective Fock in the AO basis: F_eff = S C R Cᵀ S
+///
+/// `coeff` are the orbitals defining the c/o/v partition (ε ascending,
+/// first n_β closed, next n_α − n_β open).
+pub fn effective_fock(
+    f_alpha: &DMatrix<f64>,
+    f_beta: &DMatrix<f64>,
+    coeff: &DMatrix<f64>,
+    overlap: &DMatrix<f64>,
+    n_alpha: usize,
+    n_beta: usize,
+    coupling: RohfCoupling,
+) -> DMatrix<f64> {
+
+    let fa = coeff.transpose() * f_alpha * coeff;
+    let fb = coeff.transpose() * f_beta * coeff;
+    let coefficients = coupling.coefficients();
+
+    // 0 = closed, 1 = open, 2 = virtual
+    let block = |i: usize| {
+        if i < n_beta {
+            0
+        } else if i < n_alpha {
+            1
+        } else {
+            2
+        }
+    };
+
+    let r = DMatrix::from_fn(fa.nrows(), fa.ncols(), |i, j| {
+        match (block(i), block(j)) {
+            (bi, bj) if bi == bj => {
+                let (a, b) = coefficients[bi];
+                a * fa[(i, j)] + b * fb[(i, j)]
+            }
+            (0, 1) | (1, 0) => fb[(i, j)],
+            (1, 2) | (2, 1) => fa[(i, j)],
+            _ => 0.5 * (fa[(i, j)] + fb[(i, j)]),
+        }
+    });
+
+    let sc = overlap * coeff;
+    &sc * r * sc.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, Reference, ScfOptions};
+    use crate::scf::utils::{build_overlap_matrix, vec2d_ref_to_dmatrix};
+
+    const H3: [[f64; 3]; 3] = [[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]];
+    const H4: [[f64; 3]; 4] = [[0.0; 3], [0.0, 0.0, 1.5], [0.0, 0.0, 3.0], [0.0, 0.0, 4.5]];
+
+    fn rohf(coupling: RohfCoupling) -> ScfOptions {
+        ScfOptions {
+            reference: Reference::RestrictedOpenShell,
+            rohf_coupling: coupling,
+            ..quiet_options()
+        }
+    }
+
+    #[test]
+    fn mo_blocks_follow_the_coupling_table() {
+        // C = S = 1: F_eff is the MO matrix itself; n_β = 1, n_α = 2
+        let f_a = DMatrix::from_fn(3, 3, |i, j| 1.0 + (i + j) as f64);
+        let f_b = DMatrix::from_fn(3, 3, |i, j| 10.0 * (1.0 + (i * j) as f64));
+        let one = DMatrix::identity(3, 3);
+
+        let f = effective_fock(&f_a, &f_b, &one, &one, 2, 1, RohfCoupling::Roothaan);
+
+        assert_eq!(f[(0, 0)], -0.5 * f_a[(0, 0)] + 1.5 * f_b[(0, 0)]);
+        assert_eq!(f[(1, 1)], 0.5 * f_a[(1, 1)] + 0.5 * f_b[(1, 1)]);
+        assert_eq!(f[(2, 2)], 1.5 * f_a[(2, 2)] - 0.5 * f_b[(2, 2)]);
+        assert_eq!(f[(0, 1)], f_b[(0, 1)]);
+        assert_eq!(f[(1, 2)], f_a[(1, 2)]);
+        assert_eq!(f[(0, 2)], 0.5 * (f_a[(0, 2)] + f_b[(0, 2)]));
+        assert_eq!(f, f.transpose());
+    }
+
+    #[test]
+    fn couplings_share_energy_and_density() {
+        let (molecule, shells, centers) = hydrogens(&H3, 0, 2);
+        let reference = run_scf(&molecule, &shells, &centers, &rohf(RohfCoupling::default()));
+        let p_ref = vec2d_ref_to_dmatrix(&reference.density);
+
+        for coupling in [
+            RohfCoupling::Roothaan,
+            RohfCoupling::McWeenyDiercksen,
+            RohfCoupling::BinkleyPopleDobosh,
+            RohfCoupling::FaegriManne,
+        ] {
+            let result = run_scf(&molecule, &shells, &centers, &rohf(coupling));
+            let p = vec2d_ref_to_dmatrix(&result.density);
+
+            assert!(result.converged, "{:?}", coupling);
+            assert!((result.energy - reference.energy).abs() < 1e-8, "{:?}", coupling);
+            assert!((p - &p_ref).amax() < 1e-5, "{:?}", coupling);
+        }
+    }
+
+    #[test]
+    fn rohf_is_a_spin_eigenfunction_above_uhf() {
+        for (positions, multiplicity, s) in [(&H3[..], 2, 0.5), (&H4[..], 3, 1.0)] {
+            let (molecule, shells, centers) = hydrogens(positions, 0, multiplicity);
+            let overlap = build_overlap_matrix(&shells);
+
+            let rohf = run_scf(&molecule, &shells, &centers, &rohf(RohfCoupling::default()));
+            let uhf = run_scf(
+                &molecule,
+                &shells,
+                &centers,
+                &ScfOptions { reference: Reference::Unrestricted, ..quiet_options() },
+            );
+
+            assert!((rohf.s_squared(&overlap) - s * (s + 1.0)).abs() < 1e-8);
+            assert!(uhf.s_squared(&overlap) > s * (s + 1.0));
+            assert!(uhf.energy < rohf.energy, "{} vs {}", uhf.energy, rohf.energy);
+        }
+    }
+
+    #[test]
+    fn coupling_keywords() {
+        assert_eq!(RohfCoupling::from_name("Guest-Saunders"), Some(RohfCoupling::GuestSaunders));
+        assert_eq!(RohfCoupling::from_name("bpd"), Some(RohfCoupling::BinkleyPopleDobosh));
+        assert_eq!(RohfCoupling::from_name("mcweeny"), Some(RohfCoupling::McWeenyDiercksen));
+        assert_eq!(RohfCoupling::from_name("davidson"), None);
+    }
+}
@@ -0,0 +1,154 @@
+//! Scalar type of SCF matrices (real or complex)
+//!
+//! Integrals stay real; complex densities X = A + iB are contracted
+//! through the real J/K builders by linearity, K[X] = K[A] + i K[B].
+//!
+//! Complex matrices are passed to the real `Diis` in the realified form
+//!
+//!   A + iB  →  1/√2 [ A  −B ]
+//!                   [ B   A ]
+//!
+//! whose Frobenius product is Re⟨X, Y⟩, so the Pulay/EDIIS/ADIIS
+//! coefficients are the usual real ones.
+
+use nalgebra::{Complex, ComplexField, DMatrix, SymmetricEigen};
+
+/// f64 or Complex<f64>
+pub trait ScfScalar: ComplexField<RealField = f64> + Copy {
+    const IS_COMPLEX: bool;
+
+    fn from_parts(re: f64, im: f64) -> Self;
+}
+
+impl ScfScalar for f64 {
+    const IS_COMPLEX: bool = false;
+
+    fn from_parts(re: f64, _im: f64) -> Self {
+        re
+    }
+}
+
+impl ScfScalar for Complex<f64> {
+    const IS_COMPLEX: bool = true;
+
+    fn from_parts(re: f64, im: f64) -> Self {
+        Complex::new(re, im)
+    }
+}
+
+pub fn real_part<T: ScfScalar>(m: &DMatrix<T>) -> DMatrix<f64> {
+    m.map(|x| x.real())
+}
+
+pub fn imag_part<T: ScfScalar>(m: &DMatrix<T>) -> DMatrix<f64> {
+    m.map(|x| x.imaginary())
+}
+
+/// A + iB (B ignored for real T)
+pub fn from_parts<T: ScfScalar>(re: &DMatrix<f64>, im: &DMatrix<f64>) -> DMatrix<T> {
+    re.zip_map(im, T::from_parts)
+}
+
+/// Real matrix promoted to T
+pub fn promote<T: ScfScalar>(m: &DMatrix<f64>) -> DMatrix<T> {
+    m.map(T::from_real)
+}
+
+/// [[A, −B], [B, A]] / √2 for complex T, A for real T
+pub fn realify<T: ScfScalar>(m: &DMatrix<T>) -> DMatrix<f64> {
+    let a = real_part(m);
+    if !T::IS_COMPLEX {
+        return a;
+    }
+
+    let a = a * std::f64::consts::FRAC_1_SQRT_2;
+    let b = imag_part(m) * std::f64::consts::FRAC_1_SQRT_2;
+    let (r, c) = a.shape();
+    let mut out = DMatrix::zeros(2 * r, 2 * c);
+    out.view_mut((0, 0), (r, c)).copy_from(&a);
+    out.view_mut((r, c), (r, c)).copy_from(&a);
+    out.view_mut((r, 0), (r, c)).copy_from(&b);
+    out.view_mut((0, c), (r, c)).copy_from(&(-b));
+    out
+}
+
+/// Inverse of `realify`
+pub fn unrealify<T: ScfScalar>(m: &DMatrix<f64>) -> DMatrix<T> {
+    if !T::IS_COMPLEX {
+        return promote(m);
+    }
+
+    let (r, c) = (m.nrows() / 2, m.ncols() / 2);
+    let a = m.view((0, 0), (r, c)) * std::f64::consts::SQRT_2;
+    let b = m.view((r, 0), (r, c)) * std::f64::consts::SQRT_2;
+    from_parts(&a, &b)
+}
+
+/// F C = S C ε for Hermitian F and real S (ε ascending)
+pub fn solve_hermitian<T: ScfScalar>(
+    fock: &DMatrix<T>,
+    overlap: &DMatrix<f64>,
+) -> (DMatrix<T>, Vec<f64>) {
+
+    // S^(-1/2)
+    let s_eig = SymmetricEigen::new(overlap.clone());
+    let s_inv_sqrt = s_eig.eigenvalues.map(|x| 1.0 / x.sqrt());
+    let x = &s_eig.eigenvectors
+        * DMatrix::from_diagonal(&s_inv_sqrt)
+        * s_eig.eigenvectors.transpose();
+    let x: DMatrix<T> = promote(&x);
+
+    let f_prime = x.adjoint() * fock * &x;
+    let eig = SymmetricEigen::new(f_prime);
+
+    let mut order: Vec<usize> = (0..eig.eigenvalues.len()).collect();
+    order.sort_by(|&a, &b| eig.eigenvalues[a].total_cmp(&eig.eigenvalues[b]));
+
+    let vecs = DMatrix::from_fn(fock.nrows(), order.len(), |i, k| {
+        eig.eigenvectors[(i, order[k])]
+    });
+
+    let c = x * vecs;
+    let eps = order.iter().map(|&k| eig.eigenvalues[k]).collect();
+
+    (c, eps)
+}
+
+/// e = F P S − S P F
+pub fn commutator<T: ScfScalar>(
+    fock: &DMatrix<T>,
+    density: &DMatrix<T>,
+    overlap: &DMatrix<f64>,
+) -> DMatrix<T> {
+    let s: DMatrix<T> = promote(overlap);
+    fock * density * &s - &s * density * fock
+}
+
+/// P = Σ_i n_i C_i C_i†
+pub fn weighted_density<T: ScfScalar>(coeff: &DMatrix<T>, occupations: &[f64]) -> DMatrix<T> {
+    let n = coeff.nrows();
+    let mut p = DMatrix::zeros(n, n);
+
+    for (i, &occ) in occupations.iter().enumerate() {
+        if occ > 0.0 {
+            let c = coeff.column(i);
+            p += c * c.adjoint() * T::from_real(occ);
+        }
+    }
+
+    p
+}
+
+/// c_i ← cos θ c_i + sin θ e^{iφ} c_j,  c_j ← −sin θ e^{−iφ} c_i + cos θ c_j
+///
+/// φ = π/2 for complex T, 0 otherwise.
+pub fn rotate_pair<T: ScfScalar>(coeff: &mut DMatrix<T>, i: usize, j: usize, theta: f64) {
+    let (cos, sin) = (theta.cos(), theta.sin());
+    let phase = if T::IS_COMPLEX { T::from_parts(0.0, 1.0) } else { T::one() };
+
+    let ci = coeff.column(i).into_owned();
+    let cj = coeff.column(j).into_owned();
+
+    coeff.set_column(i, &(&ci * T::from_real(cos) + &cj * (phase * T::from_real(sin))));
+    coeff.set_column(j, &(&cj * T::from_real(cos) - &ci * (phase.conjugate() * T::from_real(sin))));
+}
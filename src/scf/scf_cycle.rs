@@ -1,33 +1,516 @@
 //! Self-Consistent Field (SCF) cycle
 //!
-//! RHF / DFT / híbridos
+//! RHF / UHF / ROHF / RKS / UKS / ROKS
 //! Shells con AO implícitos (sin `orbitals`)
+//!
+//! `run_scf` es el punto de entrada único. Trabaja siempre con
+//! densidades de espín (en referencia restringida P^α = P^β = P/2):
+//!
+//!   F^σ = H + J[P^α + P^β] − α K[P^σ] − β K^{lr}[P^σ] + V_xc^σ
+//!
+//!   E = E_nuc + Σ P h + ½ Σ P J − ½ Σ_σ Σ P^σ K_eff^σ + E_xc
+//!
+//! ROHF / ROKS usa la misma energía con orbitales comunes a ambos
+//! espines, obtenidos del Fock efectivo de `scf::rohf`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use nalgebra::DMatrix;
+
+use crate::basis::reader::Ecp;
 use crate::basis::shell::Shell;
 use crate::system::atom::Atom;
-use crate::scf::density::build_density;
-use crate::scf::jk::build_jk;
-use crate::scf::utils::solve_roothaan;
-use crate::dft::vxc::{XcMethod, build_vxc};
+use crate::system::molecule::Molecule;
+use crate::system::nuclear_model::nuclear_repulsion_energy;
+use crate::integrals::ecp::add_ecp_to_hcore;
+use crate::observer::{ScfHook, ScfIteration, ScfObserver};
+use crate::integrals::relativistic::{
+    relativistic_core_hamiltonian, RelativisticHamiltonian,
+};
+use crate::scf::cdft::{run_cdft, CdftOptions, CdftSolution};
+use crate::scf::diis::{Accelerator, Diis, DiisEntry};
+use crate::scf::external::{external_contribution, ExternalPotential};
+use crate::scf::guess::{initial_guess, mix_homo_lumo, Guess, InitialGuess};
+use crate::scf::jk::build_jk_set;
+use crate::scf::mom::{MomOptions, MomState};
+use crate::scf::rohf::{effective_fock, RohfCoupling};
+use crate::scf::soscf::{FockBuilder, SoscfOptions, Trah};
+use crate::scf::stability::{follow_instabilities, StabilityMode, StabilityOptions};
+use crate::scf::stabilizers::{StabilizerState, Stabilizers};
+use crate::scf::utils::{
+    build_one_electron_matrix, build_overlap_matrix, diis_error,
+    dmatrix_to_vec2d, solve_roothaan, vec2d_ref_to_dmatrix,
+};
+use crate::dft::vxc::{ExchangeMixing, XcMethod, build_vxc, build_vxc_udft};
+
+
+/// Referencia de espín
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reference {
+    /// Capa cerrada, orbitales espaciales comunes (RHF / RKS)
+    Restricted,
+    /// Orbitales α y β independientes (UHF / UKS)
+    Unrestricted,
+    /// Capa abierta con orbitales espaciales comunes (ROHF / ROKS)
+    RestrictedOpenShell,
+}
 
+impl Reference {
+    /// Palabra clave de la entrada (restricted | unrestricted | rohf)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "restricted" | "rhf" | "rks" => Some(Self::Restricted),
+            "unrestricted" | "uhf" | "uks" => Some(Self::Unrestricted),
+            "rohf" | "roks" | "restricted-open-shell" => Some(Self::RestrictedOpenShell),
+            _ => None,
+        }
+    }
+}
+
+/// Método SCF efectivo (referencia + HF/DFT)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScfMethod {
+    RHF,
+    UHF,
+    ROHF,
+    RKS,
+    UKS,
+    ROKS,
+}
+
+/// Criterios de convergencia (deben cumplirse todos)
+#[derive(Clone, Copy, Debug)]
+pub struct ConvergenceCriteria {
+    /// |ΔE| entre iteraciones
+    pub energy: f64,
+    /// RMS de ΔP (por espín)
+    pub density_rms: f64,
+    /// max |ΔP_μν| (por espín)
+    pub density_max: f64,
+    /// max |F P S − S P F| (error DIIS)
+    pub commutator: f64,
+}
+
+impl Default for ConvergenceCriteria {
+    fn default() -> Self {
+        Self {
+            energy: 1e-8,
+            density_rms: 1e-6,
+            density_max: 1e-5,
+            commutator: 1e-5,
+        }
+    }
+}
+
+impl ConvergenceCriteria {
+    pub fn is_satisfied(&self, record: &IterationRecord) -> bool {
+        record.delta_e < self.energy
+            && record.density_rms < self.density_rms
+            && record.density_max < self.density_max
+            && record.commutator < self.commutator
+    }
+}
+
+/// Acción al agotar `max_iter` sin converger
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NonConvergence {
+    /// panic con el estado de la última iteración
+    Fail,
+    /// aviso por stderr y `ScfResult::converged == false`
+    Warn,
+}
+
+impl NonConvergence {
+    /// Palabra clave de la entrada (fail | warn)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "fail" => Some(Self::Fail),
+            "warn" => Some(Self::Warn),
+            _ => None,
+        }
+    }
+}
+
+/// Una fila de la tabla de iteraciones
+#[derive(Clone, Debug)]
+pub struct IterationRecord {
+    pub iteration: usize,
+    pub energy: f64,
+    pub delta_e: f64,
+    pub density_rms: f64,
+    pub density_max: f64,
+    pub commutator: f64,
+    /// Vectores en el subespacio DIIS
+    pub diis_size: usize,
+    /// Tiempo de pared desde la iteración anterior
+    pub time: Duration,
+}
+
+impl IterationRecord {
+    pub fn print_header() {
+        println!(
+            "{:>5} {:>20} {:>10} {:>10} {:>10} {:>10} {:>5} {:>8}",
+            "iter", "E (Eh)", "|ΔE|", "rms ΔP", "max ΔP", "[F,PS]", "DIIS", "t (s)"
+        );
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{:5} {:20.12} {:10.3e} {:10.3e} {:10.3e} {:10.3e} {:5} {:8.3}",
+            self.iteration,
+            self.energy,
+            self.delta_e,
+            self.density_rms,
+            self.density_max,
+            self.commutator,
+            self.diis_size,
+            self.time.as_secs_f64()
+        );
+    }
+}
 
 /// Opciones SCF
+#[derive(Clone)]
 pub struct ScfOptions {
     pub max_iter: usize,
-    pub conv_tol: f64,
+    pub convergence: ConvergenceCriteria,
+    pub on_max_iter: NonConvergence,
+    /// Imprimir la tabla de iteraciones
+    pub print_iterations: bool,
     /// None → HF
     /// Some(XcMethod) → DFT / híbrido
     pub xc_method: Option<XcMethod>,
+    pub reference: Reference,
+    /// Acoplamiento del Fock efectivo ROHF / ROKS
+    pub rohf_coupling: RohfCoupling,
+    /// T + V o hamiltoniano escalar-relativista (X2C / DKH2)
+    pub relativistic: RelativisticHamiltonian,
+    /// ECPs por símbolo de elemento (vacío → todos los electrones)
+    pub ecps: HashMap<String, Ecp>,
+    /// Densidad inicial (SAD por defecto)
+    pub guess: InitialGuess,
+    /// Tamaño del subespacio DIIS (0 → sin DIIS)
+    pub diis_size: usize,
+    /// Pulay / EDIIS+DIIS / ADIIS+DIIS
+    pub accelerator: Accelerator,
+    /// max |FPS − SPF| por debajo del cual EDIIS/ADIIS pasa a Pulay
+    pub diis_switch: f64,
+    /// Level shift, damping y smearing (desactivados por defecto)
+    pub stabilizers: Stabilizers,
+    /// SCF de segundo orden (TRAH); requiere estabilizadores inactivos
+    pub soscf: SoscfOptions,
+    /// MOM / IMOM para estados ΔSCF (None → aufbau)
+    pub mom: Option<MomOptions>,
+    /// Análisis de estabilidad tras converger (desactivado por defecto)
+    pub stability: StabilityOptions,
+    /// DFT / HF con restricciones de carga o espín (None → sin CDFT)
+    pub cdft: Option<CdftOptions>,
+    /// Ángulo (rad) de rotación HOMO–LUMO del guess: UHF / UKS
+    /// (α +θ, β −θ; simetría rota), SCF complejo (fase i) y
+    /// GHF / GKS (HOMO α – LUMO β); 0 → sin rotación
+    pub guess_rotation: f64,
+    /// Potenciales externos sumados al Fock (campo, cargas, solvente...)
+    pub external: Vec<Rc<dyn ExternalPotential>>,
+}
+
+impl Default for ScfOptions {
+    fn default() -> Self {
+        Self {
+            max_iter: 50,
+            convergence: ConvergenceCriteria::default(),
+            on_max_iter: NonConvergence::Fail,
+            print_iterations: true,
+            xc_method: None,
+            reference: Reference::Restricted,
+            rohf_coupling: RohfCoupling::default(),
+            relativistic: RelativisticHamiltonian::NonRelativistic,
+            ecps: HashMap::new(),
+            guess: InitialGuess::Sad,
+            diis_size: 8,
+            accelerator: Accelerator::Pulay,
+            diis_switch: 1e-1,
+            stabilizers: Stabilizers::default(),
+            soscf: SoscfOptions::default(),
+            mom: None,
+            stability: StabilityOptions::default(),
+            cdft: None,
+            guess_rotation: 0.0,
+            external: Vec::new(),
+        }
+    }
+}
+
+impl ScfOptions {
+    pub fn method(&self) -> ScfMethod {
+        match (self.reference, self.xc_method.is_some()) {
+            (Reference::Restricted, false) => ScfMethod::RHF,
+            (Reference::Unrestricted, false) => ScfMethod::UHF,
+            (Reference::Restricted, true) => ScfMethod::RKS,
+            (Reference::Unrestricted, true) => ScfMethod::UKS,
+            (Reference::RestrictedOpenShell, false) => ScfMethod::ROHF,
+            (Reference::RestrictedOpenShell, true) => ScfMethod::ROKS,
+        }
+    }
+
+    /// Intercambio exacto: K_eff = α K + β K^{lr}(ω)
+    pub fn exchange_mixing(&self) -> ExchangeMixing {
+        match &self.xc_method {
+            Some(xc) => xc.exchange_mixing(),
+            None => ExchangeMixing::hartree_fock(),
+        }
+    }
+
+    /// F es afín en P: HF sin potenciales externos no lineales
+    ///
+    /// Decide si la respuesta G[ΔP] de TRAH y del análisis de
+    /// estabilidad puede tomarse como diferencia exacta.
+    pub fn fock_is_linear(&self) -> bool {
+        self.xc_method.is_none() && self.external.iter().all(|v| v.is_linear())
+    }
+}
+
+/// Contribuciones a la energía total
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnergyComponents {
+    /// Repulsión nuclear
+    pub nuclear: f64,
+    /// Σ P h (T + V, relativista y ECP) y Σ_σ Σ P^σ V^σ del observador
+    pub one_electron: f64,
+    /// ½ Σ P J[P]
+    pub coulomb: f64,
+    /// Intercambio exacto (α K + β K^{lr}, ya escalado)
+    pub exchange: f64,
+    /// E_xc del funcional (0 en HF)
+    pub xc: f64,
+    /// Potenciales externos (con su interacción con los núcleos)
+    pub external: f64,
+    /// Dispersión empírica del funcional (−D de ωB97X-D; solo geometría)
+    pub dispersion: f64,
+}
+
+impl EnergyComponents {
+    pub fn electronic(&self) -> f64 {
+        self.one_electron + self.coulomb + self.exchange + self.xc + self.external
+    }
+
+    pub fn total(&self) -> f64 {
+        self.nuclear + self.dispersion + self.electronic()
+    }
+}
+
+/// Orbitales de un canal de espín
+pub struct SpinOrbitals {
+    /// C (AO × MO)
+    pub coefficients: Vec<Vec<f64>>,
+    /// ε en orden ascendente
+    pub energies: Vec<f64>,
+    /// 2/0 en referencia restringida, 1/0 por espín si no
+    pub occupations: Vec<f64>,
+    /// F^σ (AO) sin extrapolar del último ciclo
+    /// (en ROHF / ROKS el Fock de espín, no el efectivo)
+    pub fock: Vec<Vec<f64>>,
+    /// P = Σ_i n_i C_i C_iᵀ
+    pub density: Vec<Vec<f64>>,
+}
+
+impl SpinOrbitals {
+    pub fn n_occupied(&self) -> usize {
+        self.occupations.iter().filter(|&&n| n > 0.0).count()
+    }
 }
 
 /// Resultado SCF
 pub struct ScfResult {
+    pub method: ScfMethod,
+    /// Energía total (incluye repulsión nuclear)
     pub energy: f64,
+    pub components: EnergyComponents,
+    /// Densidad total P^α + P^β
     pub density: Vec<Vec<f64>>,
+    /// Orbitales espaciales (restringido) u orbitales α
+    pub alpha: SpinOrbitals,
+    /// Orbitales β (no restringido y ROHF / ROKS)
+    pub beta: Option<SpinOrbitals>,
+    pub iterations: usize,
+    pub converged: bool,
+    /// Tabla de iteraciones
+    pub history: Vec<IterationRecord>,
+    /// Multiplicadores y pesos CDFT (None sin restricciones)
+    pub cdft: Option<CdftSolution>,
+}
+
+impl ScfResult {
+    /// (P^α, P^β); en referencia restringida P/2 cada una
+    pub fn spin_densities(&self) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        match &self.beta {
+            Some(beta) => (self.alpha.density.clone(), beta.density.clone()),
+            None => {
+                let half: Vec<Vec<f64>> = self
+                    .density
+                    .iter()
+                    .map(|row| row.iter().map(|x| 0.5 * x).collect())
+                    .collect();
+                (half.clone(), half)
+            }
+        }
+    }
+}
+
+/// Datos fijos durante el SCF
+pub(crate) struct ScfSystem<'a> {
+    pub(crate) shells: &'a [Shell],
+    pub(crate) shell_centers: &'a [[f64; 3]],
+    pub(crate) atoms: &'a [Atom],
+    pub(crate) h_core: DMatrix<f64>,
+    pub(crate) overlap: DMatrix<f64>,
+    pub(crate) e_nuc: f64,
+    pub(crate) n_alpha: usize,
+    pub(crate) n_beta: usize,
+    /// Potencial de restricción CDFT (V^α, V^β) = Σ_c V_c s_c^σ W_c
+    pub(crate) constraint_potential: Option<(DMatrix<f64>, DMatrix<f64>)>,
+    /// Observador externo y el potencial que haya inyectado
+    pub(crate) observer: Option<RefCell<ScfHook<'a>>>,
+}
+
+impl<'a> ScfSystem<'a> {
+    /// H_core, S, E_nuc y n_α / n_β de la multiplicidad de `molecule`
+    pub(crate) fn new(
+        molecule: &'a Molecule,
+        shells: &'a [Shell],
+        shell_centers: &'a [[f64; 3]],
+        options: &ScfOptions,
+    ) -> Self {
+
+        // Los electrones de core deben coincidir con los ECP de H_core
+        for atom in &molecule.atoms {
+            let n_core = options.ecps.get(&atom.symbol).map_or(0, |ecp| ecp.n_core);
+            assert_eq!(
+                atom.core_electrons, n_core,
+                "{} has {} core electrons but its ECP replaces {}; call Molecule::apply_ecp \
+                 with the ECPs of the SCF options",
+                atom.symbol, atom.core_electrons, n_core
+            );
+        }
+
+        let nelec = molecule.n_electrons().unwrap_or_else(|e| panic!("{}", e));
+        let n_unpaired = molecule.multiplicity.saturating_sub(1);
+
+        assert!(
+            n_unpaired <= nelec && (nelec + n_unpaired).is_multiple_of(2),
+            "Multiplicity {} incompatible with {} electrons",
+            molecule.multiplicity,
+            nelec
+        );
+
+        let n_alpha = (nelec + n_unpaired) / 2;
+        let atoms = &molecule.atoms;
+
+        ScfSystem {
+            shells,
+            shell_centers,
+            atoms,
+            h_core: core_hamiltonian(shells, atoms, options),
+            overlap: build_overlap_matrix(shells),
+            e_nuc: nuclear_repulsion_energy(atoms),
+            n_alpha,
+            n_beta: nelec - n_alpha,
+            constraint_potential: None,
+            observer: None,
+        }
+    }
+
+    /// El observador pidió detener el cálculo
+    pub(crate) fn stopped(&self) -> bool {
+        self.observer.as_ref().is_some_and(|hook| hook.borrow().stopped)
+    }
+}
+
+/// H_core = T + V (o X2C / DKH2) + Σ_C U_C^{ECP}
+pub fn core_hamiltonian(
+    shells: &[Shell],
+    atoms: &[Atom],
+    options: &ScfOptions,
+) -> DMatrix<f64> {
+
+    let mut h = match options.relativistic {
+        RelativisticHamiltonian::NonRelativistic => {
+            dmatrix_to_vec2d(&build_one_electron_matrix(shells, atoms))
+        }
+        method => relativistic_core_hamiltonian(shells, atoms, method),
+    };
+
+    if !options.ecps.is_empty() {
+        add_ecp_to_hcore(&mut h, shells, atoms, &options.ecps);
+    }
+
+    vec2d_ref_to_dmatrix(&h)
+}
+
+/// Punto de entrada SCF: RHF / UHF / RKS / UKS según `options`
+///
+/// La multiplicidad y la carga se toman de `molecule`; los electrones
+/// de core de los ECP deben estar ya asignados (`Molecule::apply_ecp`).
+pub fn run_scf(
+    molecule: &Molecule,
+    shells: &[Shell],
+    shell_centers: &[[f64; 3]],
+    options: &ScfOptions,
+) -> ScfResult {
+
+    solve(ScfSystem::new(molecule, shells, shell_centers, options), molecule, options)
+}
+
+/// `run_scf` llamando a `observer` en cada iteración
+///
+/// El observador puede detener el ciclo (resultado no convergido) o
+/// añadir un potencial a los Fock; ver `crate::observer`.
+pub fn run_scf_observed(
+    molecule: &Molecule,
+    shells: &[Shell],
+    shell_centers: &[[f64; 3]],
+    options: &ScfOptions,
+    observer: &mut dyn ScfObserver,
+) -> ScfResult {
+
+    let mut system = ScfSystem::new(molecule, shells, shell_centers, options);
+    system.observer = Some(RefCell::new(ScfHook::new(&mut *observer)));
+
+    let result = solve(system, molecule, options);
+    observer.on_finish(&result);
+    result
+}
+
+fn solve(mut system: ScfSystem, molecule: &Molecule, options: &ScfOptions) -> ScfResult {
+
+    if options.reference == Reference::Restricted && system.n_alpha != system.n_beta {
+        panic!(
+            "Restricted SCF requires a closed shell (multiplicity {}); \
+             use Reference::Unrestricted or RestrictedOpenShell",
+            molecule.multiplicity
+        );
+    }
+
+    // CDFT: multiplicadores en un bucle externo (sin análisis de estabilidad)
+    if let Some(cdft) = &options.cdft {
+        return run_cdft(&mut system, options, cdft);
+    }
+
+    let result = iterate(&system, options);
+
+    // Análisis de estabilidad y, si procede, seguimiento de inestabilidades
+    if options.stability.mode == StabilityMode::Off || system.stopped() {
+        return result;
+    }
+    follow_instabilities(&system, options, result)
 }
 
-/// Ciclo SCF principal
+/// Ciclo SCF de capa cerrada con H y S dados
+///
+/// Se fuerza referencia restringida; la energía devuelta incluye
+/// la repulsión nuclear de `atoms`.
 pub fn scf_cycle(
     shells: &[Shell],
     atoms: &[Atom],
@@ -37,119 +520,670 @@ pub fn scf_cycle(
     options: &ScfOptions,
 ) -> ScfResult {
 
-    // Número total de AO
-    let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
+    assert!(nelec.is_multiple_of(2), "scf_cycle requires an even electron count");
 
     // Centros de shells (una sola vez)
     let shell_centers: Vec<[f64; 3]> =
         shells.iter().map(|s| s.center).collect();
 
-    // Convertir H y S a DMatrix (nalgebra)
-    let h_core_mat = DMatrix::from_fn(nao, nao, |i, j| h_core[i][j]);
-    let overlap_mat = DMatrix::from_fn(nao, nao, |i, j| overlap[i][j]);
+    let system = ScfSystem {
+        shells,
+        shell_centers: &shell_centers,
+        atoms,
+        h_core: vec2d_ref_to_dmatrix(h_core),
+        overlap: vec2d_ref_to_dmatrix(overlap),
+        e_nuc: nuclear_repulsion_energy(atoms),
+        n_alpha: nelec / 2,
+        n_beta: nelec / 2,
+        constraint_potential: None,
+        observer: None,
+    };
+
+    let restricted = ScfOptions {
+        reference: Reference::Restricted,
+        ..options.clone()
+    };
+
+    iterate(&system, &restricted)
+}
+
+// ======================================================
+// Núcleo del ciclo
+// ======================================================
+
+pub(crate) fn iterate(sys: &ScfSystem, options: &ScfOptions) -> ScfResult {
+    // Densidad y orbitales iniciales (core / SAD / Hückel / GWH)
+    let mut guess = initial_guess(sys, options);
+
+    // Simetría rota: mezcla HOMO / LUMO con signo opuesto en α y β
+    if options.reference == Reference::Unrestricted && options.guess_rotation != 0.0 {
+        guess = mix_homo_lumo(sys, guess, options.guess_rotation);
+    }
+
+    iterate_from(sys, options, guess)
+}
+
+/// Ciclo SCF desde un guess dado (reinicio tras una inestabilidad)
+pub(crate) fn iterate_from(sys: &ScfSystem, options: &ScfOptions, guess: Guess) -> ScfResult {
+
+    assert!(options.max_iter > 0, "SCF max_iter must be positive");
+
+    let nao = sys.h_core.nrows();
+    let restricted = options.reference == Reference::Restricted;
+    let rohf = options.reference == Reference::RestrictedOpenShell;
+    // Un único conjunto de orbitales (RHF / RKS y ROHF / ROKS)
+    let single = restricted || rohf;
 
-    // Densidad inicial
-    let mut p: Vec<Vec<f64>> = vec![vec![0.0; nao]; nao];
+    let mixing = options.exchange_mixing();
+
+    let mut p_a = guess.p_alpha;
+    let mut p_b = guess.p_beta;
+    let mut orbitals = Some(guess.orbitals);
+
+    let mut diis = Diis::new(options.diis_size);
+    let mut stabilizers = StabilizerState::new(options.stabilizers);
+
+    // ΔSCF: ocupaciones por máximo solapamiento
+    assert!(
+        options.mom.is_none() || options.stabilizers.smearing.is_none(),
+        "MOM and smearing are mutually exclusive"
+    );
+    let mut mom = options
+        .mom
+        .as_ref()
+        .map(|m| MomState::new(m, options.reference, sys.n_alpha, sys.n_beta));
+
+    // Respuesta de Fock para los productos Hessiano-vector de TRAH
+    let fock_response = |pa: &DMatrix<f64>, pb: &DMatrix<f64>, c: &[DMatrix<f64>]| {
+        let coeffs = (c[0].clone(), c.last().unwrap().clone());
+        let (fa, fb, _) = build_fock(sys, pa, pb, Some(&coeffs), options, &mixing);
+        (fa, fb)
+    };
+    let fock_response: &FockBuilder = &fock_response;
+    let mut trah: Option<Trah> = None;
 
     let mut energy_old = 0.0;
+    let mut delta_p = (0.0, 0.0);
+    let mut history: Vec<IterationRecord> = Vec::new();
+    let mut clock = Instant::now();
+
+    if options.print_iterations {
+        IterationRecord::print_header();
+    }
 
     for iter in 0..options.max_iter {
 
         // -----------------------------
-        // Construcción J y K
+        // Fock y energía de P actual
         // -----------------------------
-        let (j_mat, k_mat) = build_jk(shells, &shell_centers, &p);
+        let (f_a, f_b, components) =
+            build_fock(sys, &p_a, &p_b, orbitals.as_ref(), options, &mixing);
 
-        // -----------------------------
-        // Construcción Fock
-        // -----------------------------
-        let mut fock = h_core_mat.clone();
+        let energy = components.total();
+
+        // ROHF / ROKS: F^α, F^β → Fock efectivo común a ambos espines
+        let (f_a, f_b) = if rohf {
+            let (c, _) = orbitals.as_ref().expect("ROHF requires guess orbitals");
+            let f = effective_fock(
+                &f_a, &f_b, c, &sys.overlap, sys.n_alpha, sys.n_beta, options.rohf_coupling,
+            );
+            (f.clone(), f)
+        } else {
+            (f_a, f_b)
+        };
+
+        // Error DIIS FPS − SPF por espín (ROHF: F_eff con P total)
+        let (err_a, err_b) = if rohf {
+            let err = diis_error(&f_a, &(&p_a + &p_b), &sys.overlap);
+            (err.clone(), err)
+        } else {
+            (
+                diis_error(&f_a, &p_a, &sys.overlap),
+                diis_error(&f_b, &p_b, &sys.overlap),
+            )
+        };
 
-        for i in 0..nao {
-            for j in 0..nao {
-                fock[(i, j)] += 2.0 * j_mat[i][j] - k_mat[i][j];
+        // Restringido y ROHF: un canal (F, P total); no restringido: α y β
+        let commutator = err_a.amax().max(err_b.amax());
+
+        if options.diis_size > 0 {
+            diis.push(if single {
+                DiisEntry {
+                    focks: vec![f_a.clone()],
+                    densities: vec![&p_a + &p_b],
+                    errors: vec![err_a.clone()],
+                    energy,
+                }
+            } else {
+                DiisEntry {
+                    focks: vec![f_a.clone(), f_b.clone()],
+                    densities: vec![p_a.clone(), p_b.clone()],
+                    errors: vec![err_a.clone(), err_b.clone()],
+                    energy,
+                }
+            });
+        }
+
+        let record = IterationRecord {
+            iteration: iter + 1,
+            energy,
+            delta_e: (energy - energy_old).abs(),
+            density_rms: delta_p.0,
+            density_max: delta_p.1,
+            commutator,
+            diis_size: diis.len(),
+            time: clock.elapsed(),
+        };
+        clock = Instant::now();
+
+        if options.print_iterations {
+            record.print();
+        }
+
+        stabilizers.update(energy, commutator, iter == 0);
+
+        // Convergencia (nunca con ocupaciones fraccionarias)
+        let converged = iter > 0
+            && !stabilizers.is_smearing()
+            && options.convergence.is_satisfied(&record);
+        history.push(record);
+
+        // Observador externo: progreso, parada anticipada o potencial extra
+        let stop = sys.observer.as_ref().is_some_and(|hook| {
+            hook.borrow_mut().notify(&ScfIteration {
+                record: history.last().unwrap(),
+                components: &components,
+                p_alpha: &p_a,
+                p_beta: &p_b,
+                f_alpha: &f_a,
+                f_beta: &f_b,
+                error_alpha: &err_a,
+                error_beta: &err_b,
+            })
+        });
+
+        if converged || stop || iter + 1 == options.max_iter {
+            if !converged && !stop {
+                report_non_convergence(options, history.last().unwrap());
             }
+            return finalize(sys, options, &f_a, &f_b, mom, history, converged);
         }
 
         // -----------------------------
-        // XC (DFT / híbrido)
+        // Segundo orden (TRAH): sustituye DIIS + diagonalización
         // -----------------------------
-        let mut dft_energy_exc = 0.0;
-        let mut dft_energy_rho_vxc = 0.0;
-
-        if let Some(xc) = &options.xc_method {
-
-            let (vxc_mat, dft_energy) = build_vxc(
-                shells,
-                &shell_centers,
-                &p,
-                None,      // coeff (solo meta-GGA)
-                None,      // n_occ
-                atoms,
-                xc.clone(),
-            );
+        let commutators: Vec<f64> = history.iter().map(|r| r.commutator).collect();
 
-            for i in 0..nao {
-                for j in 0..nao {
-                    fock[(i, j)] += vxc_mat[i][j];
-                }
+        // TRAH no cubre el gradiente orbital ROHF, la selección MOM
+        // ni el lagrangiano CDFT
+        if trah.is_none()
+            && !rohf
+            && mom.is_none()
+            && sys.constraint_potential.is_none()
+            && orbitals.is_some()
+            && !stabilizers.is_active()
+            && options.soscf.should_switch(&commutators)
+        {
+            if options.print_iterations {
+                println!("  switching to second-order SCF (TRAH)");
             }
+            let n_occ = if restricted {
+                vec![sys.n_alpha]
+            } else {
+                vec![sys.n_alpha, sys.n_beta]
+            };
+            trah = Some(Trah::new(
+                fock_response,
+                n_occ,
+                options.fock_is_linear(),
+                options.soscf,
+            ));
+        }
+
+        if let Some(trah) = trah.as_mut() {
+            let (c_a, c_b) = orbitals.take().unwrap();
+            let (channels, focks) = if restricted {
+                (vec![c_a], vec![f_a])
+            } else {
+                (vec![c_a, c_b], vec![f_a, f_b])
+            };
+
+            let mut rotated = trah.step(channels, focks, energy);
+            let c_b = if restricted { rotated[0].clone() } else { rotated.pop().unwrap() };
+            let c_a = rotated.pop().unwrap();
 
-            dft_energy_exc = dft_energy.exc;
-            dft_energy_rho_vxc = dft_energy.int_rho_vxc;
+            let p_a_new = weighted_density(&c_a, &vec![1.0; sys.n_alpha]);
+            let p_b_new = weighted_density(&c_b, &vec![1.0; sys.n_beta]);
+
+            let d_a = &p_a_new - &p_a;
+            let d_b = &p_b_new - &p_b;
+            delta_p = (
+                d_a.norm().max(d_b.norm()) / nao as f64,
+                d_a.amax().max(d_b.amax()),
+            );
+
+            p_a = p_a_new;
+            p_b = p_b_new;
+            orbitals = Some((c_a, c_b));
+            energy_old = energy;
+            continue;
         }
 
         // -----------------------------
-        // Resolver Roothaan
+        // DIIS / EDIIS / ADIIS (coeficientes comunes a ambos espines)
         // -----------------------------
-        let (coeff, _eps) = solve_roothaan(&fock, &overlap_mat);
+        let extrapolated = diis.extrapolate_hybrid(options.accelerator, options.diis_switch);
+        let (f_a_x, f_b_x) = match extrapolated {
+            Some(mut focks) if single => {
+                let f = focks.remove(0);
+                (f.clone(), f)
+            }
+            Some(mut focks) => {
+                let f_b_x = focks.remove(1);
+                (focks.remove(0), f_b_x)
+            }
+            None => (f_a, f_b),
+        };
 
         // -----------------------------
-        // Nueva densidad
+        // Estabilizadores: damping de Fock y level shift
         // -----------------------------
-        let p_new = build_density(coeff, nelec);
+        let mut damped = stabilizers.damp_focks(vec![f_a_x, f_b_x]);
+        let f_b_x = damped.pop().unwrap();
+        let f_a_x = damped.pop().unwrap();
+
+        let f_a_x = stabilizers.shift(f_a_x, &sys.overlap, &p_a);
+        let f_b_x = stabilizers.shift(f_b_x, &sys.overlap, &p_b);
 
         // -----------------------------
-        // Energía electrónica
+        // Resolver Roothaan / Pople–Nesbet
         // -----------------------------
-        let mut energy = 0.0;
-        for i in 0..nao {
-            for j in 0..nao {
-                energy += p_new[i][j] * (h_core[i][j] + fock[(i, j)]);
+        let (mut c_a, mut eps_a) = solve_roothaan(&f_a_x, &sys.overlap);
+        if let Some(mom) = mom.as_mut() {
+            (c_a, eps_a) = mom.select(0, c_a, eps_a, &sys.overlap);
+        }
+
+        let (c_b, eps_b) = if single {
+            (c_a.clone(), eps_a.clone())
+        } else {
+            let (c_b, eps_b) = solve_roothaan(&f_b_x, &sys.overlap);
+            match mom.as_mut() {
+                Some(mom) => mom.select(1, c_b, eps_b, &sys.overlap),
+                None => (c_b, eps_b),
             }
+        };
+
+        // Aufbau o smearing (con MOM los ocupados ya van primero)
+        let occ_a = stabilizers.occupations(&eps_a, sys.n_alpha);
+        let occ_b = stabilizers.occupations(&eps_b, sys.n_beta);
+
+        let p_a_new = weighted_density(&c_a, &occ_a);
+        let p_b_new = weighted_density(&c_b, &occ_b);
+
+        let d_a = &p_a_new - &p_a;
+        let d_b = &p_b_new - &p_b;
+        delta_p = (
+            d_a.norm().max(d_b.norm()) / nao as f64,
+            d_a.amax().max(d_b.amax()),
+        );
+
+        let p_a_new = stabilizers.damp_density(p_a_new, &p_a);
+        let p_b_new = stabilizers.damp_density(p_b_new, &p_b);
+
+        p_a = p_a_new;
+        p_b = p_b_new;
+        orbitals = Some((c_a, c_b));
+        energy_old = energy;
+    }
+
+    unreachable!()
+}
+
+/// F^α, F^β y componentes de energía para (P^α, P^β)
+pub(crate) fn build_fock(
+    sys: &ScfSystem,
+    p_a: &DMatrix<f64>,
+    p_b: &DMatrix<f64>,
+    orbitals: Option<&(DMatrix<f64>, DMatrix<f64>)>,
+    options: &ScfOptions,
+    mixing: &ExchangeMixing,
+) -> (DMatrix<f64>, DMatrix<f64>, EnergyComponents) {
+
+    let restricted = options.reference == Reference::Restricted;
+
+    let p_tot = p_a + p_b;
+    let p_tot_vec = dmatrix_to_vec2d(&p_tot);
+
+    // -----------------------------
+    // J y K_eff por espín
+    // -----------------------------
+    let omega = mixing.has_long_range().then_some(mixing.omega);
+
+    let (j_mat, k_a, k_b) = if restricted {
+        // K[P^σ] = ½ K[P]
+        let jk = build_jk_set(sys.shells, Some(&p_tot_vec), &[&p_tot_vec], omega);
+        let mut k_eff = vec2d_ref_to_dmatrix(&jk.k[0]) * (0.5 * mixing.full);
+
+        if let Some(k_lr) = jk.k_lr.first() {
+            k_eff += vec2d_ref_to_dmatrix(k_lr) * (0.5 * mixing.long_range);
         }
-        energy *= 0.5;
 
-        // Corrección DFT: Exc − ∫ρ vxc
-        if options.xc_method.is_some() {
-            energy += dft_energy_exc - dft_energy_rho_vxc;
+        (vec2d_ref_to_dmatrix(&jk.j), k_eff.clone(), k_eff)
+    } else {
+        let p_a_vec = dmatrix_to_vec2d(p_a);
+        let p_b_vec = dmatrix_to_vec2d(p_b);
+
+        // J[P^α + P^β], K y K^{lr} de ambos espines en una sola pasada
+        let jk = build_jk_set(sys.shells, Some(&p_tot_vec), &[&p_a_vec, &p_b_vec], omega);
+
+        let mut k_a = vec2d_ref_to_dmatrix(&jk.k[0]) * mixing.full;
+        let mut k_b = vec2d_ref_to_dmatrix(&jk.k[1]) * mixing.full;
+
+        if let [klr_a, klr_b] = jk.k_lr.as_slice() {
+            k_a += vec2d_ref_to_dmatrix(klr_a) * mixing.long_range;
+            k_b += vec2d_ref_to_dmatrix(klr_b) * mixing.long_range;
         }
 
-        let delta_e = (energy - energy_old).abs();
+        (vec2d_ref_to_dmatrix(&jk.j), k_a, k_b)
+    };
 
-        println!(
-            "SCF iter {:3}  E = {:18.12}  ΔE = {:.3e}",
-            iter + 1,
-            energy,
-            delta_e
-        );
+    let mut f_a = &sys.h_core + &j_mat - &k_a;
+    let mut f_b = &sys.h_core + &j_mat - &k_b;
 
-        // Convergencia
-        if delta_e < options.conv_tol {
-            return ScfResult {
-                energy,
-                density: p_new,
-            };
+    // Restricciones CDFT (fuera de la energía: se reporta E[P], no W)
+    if let Some((v_a, v_b)) = &sys.constraint_potential {
+        f_a += v_a;
+        f_b += v_b;
+    }
+
+    // Potenciales externos registrados en `options`
+    let mut e_external = 0.0;
+    if let Some((v, e)) = external_contribution(&options.external, sys.shells, sys.atoms, &p_tot) {
+        f_a += &v;
+        f_b += &v;
+        e_external = e;
+    }
+
+    // Potencial inyectado por el observador (energía en `one_electron`)
+    let mut e_observer = 0.0;
+    if let Some(hook) = &sys.observer {
+        if let Some((v_a, v_b)) = &hook.borrow().potential {
+            f_a += v_a;
+            f_b += v_b;
+            e_observer = p_a.dot(v_a) + p_b.dot(v_b);
         }
+    }
 
-        // Actualizar
-        p = p_new;
-        energy_old = energy;
+    // -----------------------------
+    // XC (DFT / híbrido)
+    // -----------------------------
+    let mut e_xc = 0.0;
+
+    if let Some(xc) = &options.xc_method {
+
+        // Orbitales del ciclo anterior (solo meta-GGA)
+        let coeffs = orbitals.map(|(c_a, c_b)| (dmatrix_to_vec2d(c_a), dmatrix_to_vec2d(c_b)));
+
+        if restricted {
+            let (vxc, dft_energy) = build_vxc(
+                sys.shells,
+                sys.shell_centers,
+                &p_tot_vec,
+                coeffs.as_ref().map(|(c, _)| c),
+                Some(sys.n_alpha),
+                sys.atoms,
+                xc.clone(),
+            );
+
+            let vxc = vec2d_ref_to_dmatrix(&vxc);
+            f_a += &vxc;
+            f_b += &vxc;
+            e_xc = dft_energy.exc;
+        } else {
+            let (vxa, vxb, dft_energy) = build_vxc_udft(
+                sys.shells,
+                sys.shell_centers,
+                &dmatrix_to_vec2d(p_a),
+                &dmatrix_to_vec2d(p_b),
+                coeffs.as_ref().map(|(c, _)| c),
+                coeffs.as_ref().map(|(_, c)| c),
+                Some(sys.n_alpha),
+                Some(sys.n_beta),
+                sys.atoms,
+                xc.clone(),
+            );
+
+            f_a += vec2d_ref_to_dmatrix(&vxa);
+            f_b += vec2d_ref_to_dmatrix(&vxb);
+            e_xc = dft_energy.exc;
+        }
     }
 
-    panic!(
-        "SCF did not converge after {} iterations",
-        options.max_iter
+    // -----------------------------
+    // Energía
+    // -----------------------------
+    let components = EnergyComponents {
+        nuclear: sys.e_nuc,
+        one_electron: p_tot.dot(&sys.h_core) + e_observer,
+        coulomb: 0.5 * p_tot.dot(&j_mat),
+        exchange: -0.5 * (p_a.dot(&k_a) + p_b.dot(&k_b)),
+        xc: e_xc,
+        external: e_external,
+        dispersion: options.xc_method.as_ref().map_or(0.0, |xc| xc.dispersion_energy(sys.atoms)),
+    };
+
+    (f_a, f_b, components)
+}
+
+/// Aviso o error explícito al agotar `max_iter`
+pub(crate) fn report_non_convergence(options: &ScfOptions, last: &IterationRecord) {
+    let message = format!(
+        "SCF not converged after {} iterations: |ΔE| = {:.3e}, rms ΔP = {:.3e}, \
+         max ΔP = {:.3e}, max [F,PS] = {:.3e}",
+        options.max_iter,
+        last.delta_e,
+        last.density_rms,
+        last.density_max,
+        last.commutator
     );
+
+    match options.on_max_iter {
+        NonConvergence::Fail => panic!("{}", message),
+        NonConvergence::Warn => eprintln!("WARNING: {}", message),
+    }
+}
+
+/// P = Σ_i n_i C_i C_iᵀ
+pub(crate) fn weighted_density(coeff: &DMatrix<f64>, occupations: &[f64]) -> DMatrix<f64> {
+    let nao = coeff.nrows();
+    let mut p = DMatrix::zeros(nao, nao);
+
+    for (i, &n) in occupations.iter().enumerate() {
+        if n > 0.0 {
+            let c = coeff.column(i);
+            p += c * c.transpose() * n;
+        }
+    }
+
+    p
+}
+
+/// Diagonaliza los Fock finales (sin DIIS) y empaqueta el resultado
+///
+/// En ROHF / ROKS `f_a` = `f_b` = F_eff. Los orbitales nuevos dan una
+/// densidad distinta de la del último ciclo, así que Fock y energía se
+/// recalculan sobre ella: orbitales, densidad, Fock y energía devueltos
+/// son coherentes entre sí. Con MOM los orbitales ocupados van primero
+/// (ver `scf::mom`).
+fn finalize(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+    f_a: &DMatrix<f64>,
+    f_b: &DMatrix<f64>,
+    mut mom: Option<MomState>,
+    history: Vec<IterationRecord>,
+    converged: bool,
+) -> ScfResult {
+
+    // MOM: mismos criterios de ocupación que en el ciclo
+    let mut solve = |fock: &DMatrix<f64>, channel: usize| {
+        let (c, eps) = solve_roothaan(fock, &sys.overlap);
+        match mom.as_mut() {
+            Some(mom) => mom.select(channel, c, eps, &sys.overlap),
+            None => (c, eps),
+        }
+    };
+    let solved_a = solve(f_a, 0);
+    let solved_b = match options.reference {
+        Reference::Unrestricted => solve(f_b, 1),
+        _ => solved_a.clone(),
+    };
+
+    let aufbau = |n_eps: usize, n_occ: usize, occupation: f64| -> Vec<f64> {
+        (0..n_eps).map(|i| if i < n_occ { occupation } else { 0.0 }).collect()
+    };
+
+    // Fock y energía de la densidad que se devuelve
+    let p_a = weighted_density(&solved_a.0, &aufbau(solved_a.1.len(), sys.n_alpha, 1.0));
+    let p_b = weighted_density(&solved_b.0, &aufbau(solved_b.1.len(), sys.n_beta, 1.0));
+    let orbitals = (solved_a.0.clone(), solved_b.0.clone());
+    let (fs_a, fs_b, components) =
+        build_fock(sys, &p_a, &p_b, Some(&orbitals), options, &options.exchange_mixing());
+
+    let channel = |solved: &(DMatrix<f64>, Vec<f64>),
+                   fock: &DMatrix<f64>,
+                   n_occ: usize,
+                   occupation: f64| {
+        let (c, eps) = solved;
+        let occupations = aufbau(eps.len(), n_occ, occupation);
+
+        SpinOrbitals {
+            coefficients: dmatrix_to_vec2d(c),
+            density: dmatrix_to_vec2d(&weighted_density(c, &occupations)),
+            energies: eps.clone(),
+            occupations,
+            fock: dmatrix_to_vec2d(fock),
+        }
+    };
+
+    // ROHF / ROKS: se guardan F^α y F^β (no F_eff)
+    let (alpha, beta) = match options.reference {
+        Reference::Restricted => (channel(&solved_a, &fs_a, sys.n_alpha, 2.0), None),
+        _ => (
+            channel(&solved_a, &fs_a, sys.n_alpha, 1.0),
+            Some(channel(&solved_b, &fs_b, sys.n_beta, 1.0)),
+        ),
+    };
+
+    let density = match &beta {
+        Some(beta) => alpha
+            .density
+            .iter()
+            .zip(beta.density.iter())
+            .map(|(ra, rb)| ra.iter().zip(rb.iter()).map(|(a, b)| a + b).collect())
+            .collect(),
+        None => alpha.density.clone(),
+    };
+
+    ScfResult {
+        method: options.method(),
+        energy: components.total(),
+        components,
+        density,
+        alpha,
+        beta,
+        iterations: history.len(),
+        converged,
+        history,
+        cdft: None,
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+
+    #[test]
+    fn returned_density_reproduces_the_energy() {
+        let (molecule, shells, centers) = h2();
+        let options = ScfOptions {
+            max_iter: 2,
+            on_max_iter: NonConvergence::Warn,
+            guess: InitialGuess::Core,
+            ..quiet_options()
+        };
+
+        let result = run_scf(&molecule, &shells, &centers, &options);
+        assert!(!result.converged);
+
+        let sys = ScfSystem::new(&molecule, &shells, &centers, &options);
+        let p_half = vec2d_ref_to_dmatrix(&result.density) * 0.5;
+        let (fock, _, components) =
+            build_fock(&sys, &p_half, &p_half, None, &options, &options.exchange_mixing());
+
+        assert!((components.total() - result.energy).abs() < 1e-12);
+        assert!((fock - vec2d_ref_to_dmatrix(&result.alpha.fock)).amax() < 1e-12);
+    }
+
+    #[test]
+    fn convergence_needs_every_criterion() {
+        let criteria = ConvergenceCriteria::default();
+        let record = IterationRecord {
+            iteration: 5,
+            energy: -1.0,
+            delta_e: 1e-10,
+            density_rms: 1e-8,
+            density_max: 1e-7,
+            commutator: 1e-7,
+            diis_size: 4,
+            time: Duration::ZERO,
+        };
+        assert!(criteria.is_satisfied(&record));
+
+        for loose in [
+            IterationRecord { delta_e: 1e-6, ..record.clone() },
+            IterationRecord { density_rms: 1e-5, ..record.clone() },
+            IterationRecord { density_max: 1e-4, ..record.clone() },
+            IterationRecord { commutator: 1e-4, ..record.clone() },
+        ] {
+            assert!(!criteria.is_satisfied(&loose));
+        }
+    }
+
+    #[test]
+    fn history_records_every_iteration() {
+        let (molecule, shells, centers) = h2();
+        let result = run_scf(&molecule, &shells, &centers, &quiet_options());
+
+        assert!(result.converged);
+        assert_eq!(result.history.len(), result.iterations);
+        assert!(result.history.iter().enumerate().all(|(i, r)| r.iteration == i + 1));
+        assert!(quiet_options().convergence.is_satisfied(result.history.last().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "SCF not converged after 2 iterations")]
+    fn non_convergence_fails_by_default() {
+        let (molecule, shells, centers) = h2();
+        let options = ScfOptions {
+            max_iter: 2,
+            guess: InitialGuess::Core,
+            ..quiet_options()
+        };
+        run_scf(&molecule, &shells, &centers, &options);
+    }
+
+    #[test]
+    fn rohf_skips_the_stability_analysis() {
+        let (molecule, shells, centers) = hydrogens(&[[0.0; 3], [0.0, 0.0, 1.4]], 0, 3);
+        let mut options = ScfOptions {
+            reference: Reference::RestrictedOpenShell,
+            ..quiet_options()
+        };
+        options.stability.mode = StabilityMode::Follow;
+
+        let result = run_scf(&molecule, &shells, &centers, &options);
+        assert!(result.converged);
+    }
+}
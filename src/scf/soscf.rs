@@ -0,0 +1,569 @@
+//! Second-order SCF: trust-region augmented Hessian (TRAH)
+//!
+//! Orbital rotations per channel
+//!   C(κ) = C exp(K),   K = [[0, −κᵀ], [κ, 0]]   (κ: vir × occ)
+//!
+//!   g^σ      = 2w C_vᵀ F^σ C_o
+//!   (H x)^σ  = 2w [F_vv x − x F_oo + C_vᵀ G^σ[ΔP(x)] C_o]
+//!   ΔP^σ(x)  = C_v x C_oᵀ + C_o xᵀ C_vᵀ
+//!
+//! w = 2 for the single spatial channel of a restricted reference and
+//! 1 per spin otherwise. G^σ[ΔP] is the linear response of the Fock
+//! builder (J, K, K^{lr} and V_xc): the exact difference
+//! F(P + ΔP) − F(P) for HF, a central difference for DFT or when an
+//! external potential is not linear in P.
+//!
+//! Each macro step solves the augmented Hessian eigenproblem
+//!   [[0, α gᵀ], [α g, H]] (u0, u) = μ (u0, u),   x = u / (α u0)
+//! by Davidson, raising α until ‖x‖ ≤ trust radius. The radius
+//! follows the ratio of actual to predicted energy change; steps that
+//! raise the energy are rejected.
+
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+
+/// Relative step for the central-difference XC response
+const RESPONSE_STEP: f64 = 1e-4;
+
+/// When to use the second-order optimizer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SecondOrder {
+    Off,
+    /// Switch from DIIS when max |FPS − SPF| stops improving
+    OnStall,
+    /// From the first set of orbitals on
+    Always,
+}
+
+impl SecondOrder {
+    /// Parse the input keyword (off | stall | always)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "stall" | "onstall" => Some(Self::OnStall),
+            "always" => Some(Self::Always),
+            _ => None,
+        }
+    }
+}
+
+/// Orbital Hessian used in the micro-iterations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrbitalHessian {
+    /// Full Fock response (one or two Fock builds per product)
+    Exact,
+    /// 2w (F_aa − F_ii) only (no Fock builds)
+    Diagonal,
+}
+
+impl OrbitalHessian {
+    /// Parse the input keyword (exact | diagonal)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "exact" => Some(Self::Exact),
+            "diagonal" => Some(Self::Diagonal),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SoscfOptions {
+    pub mode: SecondOrder,
+    pub hessian: OrbitalHessian,
+    /// Initial trust radius (‖κ‖)
+    pub trust_radius: f64,
+    /// Davidson micro-iterations per macro step
+    pub max_micro: usize,
+    /// OnStall: iterations without a 10 % improvement of [F,PS]
+    pub stall_window: usize,
+}
+
+impl Default for SoscfOptions {
+    fn default() -> Self {
+        Self {
+            mode: SecondOrder::Off,
+            hessian: OrbitalHessian::Exact,
+            trust_radius: 0.5,
+            max_micro: 12,
+            stall_window: 6,
+        }
+    }
+}
+
+impl SoscfOptions {
+    /// Switch test on the history of max |FPS − SPF|
+    pub fn should_switch(&self, commutators: &[f64]) -> bool {
+        match self.mode {
+            SecondOrder::Off => false,
+            SecondOrder::Always => true,
+            SecondOrder::OnStall => {
+                let n = commutators.len();
+                let w = self.stall_window.max(1);
+                if n <= w {
+                    return false;
+                }
+
+                // every guess is a density of its own: the first entry counts
+                let best_before = commutators[..n - w]
+                    .iter()
+                    .fold(f64::INFINITY, |a, &b| a.min(b));
+                let best_recent = commutators[n - w..]
+                    .iter()
+                    .fold(f64::INFINITY, |a, &b| a.min(b));
+
+                best_recent > 0.9 * best_before
+            }
+        }
+    }
+}
+
+/// Fock builder (P^α, P^β, C per channel) → (F^α, F^β)
+pub type FockBuilder<'a> =
+    dyn Fn(&DMatrix<f64>, &DMatrix<f64>, &[DMatrix<f64>]) -> (DMatrix<f64>, DMatrix<f64>) + 'a;
+
+/// Last point of the trust-region walk
+struct Accepted {
+    coefficients: Vec<DMatrix<f64>>,
+    focks: Vec<DMatrix<f64>>,
+    energy: f64,
+    predicted: f64,
+}
+
+/// Trust-region augmented Hessian optimizer
+pub struct Trah<'a> {
+    build_fock: &'a FockBuilder<'a>,
+    /// Occupied orbitals per channel (one channel if restricted)
+    n_occ: Vec<usize>,
+    /// F affine in P (HF, linear external potentials): exact one-sided response
+    linear: bool,
+    options: SoscfOptions,
+    trust: f64,
+    last: Option<Accepted>,
+}
+
+impl<'a> Trah<'a> {
+    pub fn new(
+        build_fock: &'a FockBuilder<'a>,
+        n_occ: Vec<usize>,
+        linear: bool,
+        options: SoscfOptions,
+    ) -> Self {
+        Self {
+            build_fock,
+            n_occ,
+            linear,
+            options,
+            trust: options.trust_radius,
+            last: None,
+        }
+    }
+
+    pub fn trust_radius(&self) -> f64 {
+        self.trust
+    }
+
+    fn weight(&self) -> f64 {
+        if self.n_occ.len() == 1 { 2.0 } else { 1.0 }
+    }
+
+    /// One macro iteration from the current point
+    ///
+    /// `coefficients` must be the orbitals of the density that gave
+    /// `focks` and `energy`. Returns the orbitals of the next trial
+    /// point (the previous point again, with a smaller radius, if the
+    /// last step raised the energy).
+    pub fn step(
+        &mut self,
+        coefficients: Vec<DMatrix<f64>>,
+        focks: Vec<DMatrix<f64>>,
+        energy: f64,
+    ) -> Vec<DMatrix<f64>> {
+
+        let (c, f, e) = match self.last.take() {
+            Some(prev) if energy - prev.energy > 1e-12 * prev.energy.abs().max(1.0) => {
+                self.trust *= 0.5;
+                (prev.coefficients, prev.focks, prev.energy)
+            }
+            Some(prev) => {
+                if prev.predicted < -1e-14 {
+                    let ratio = (energy - prev.energy) / prev.predicted;
+                    if ratio < 0.25 {
+                        self.trust *= 0.7;
+                    } else if ratio > 0.75 {
+                        self.trust = (1.2 * self.trust).min(1.0);
+                    }
+                }
+                (coefficients, focks, energy)
+            }
+            None => (coefficients, focks, energy),
+        };
+
+        let (x, predicted) = self.solve(&c, &f);
+
+        let rotated = self.rotate(&c, &x);
+
+        self.last = Some(Accepted {
+            coefficients: c,
+            focks: f,
+            energy: e,
+            predicted,
+        });
+
+        rotated
+    }
+
+    // --------------------------------------------------
+    // Orbital blocks
+    // --------------------------------------------------
+
+    fn blocks(&self, c: &[DMatrix<f64>]) -> Vec<(usize, usize)> {
+        c.iter()
+            .zip(self.n_occ.iter())
+            .map(|(cs, &no)| (no, cs.ncols() - no))
+            .collect()
+    }
+
+    fn unpack(&self, c: &[DMatrix<f64>], x: &DVector<f64>) -> Vec<DMatrix<f64>> {
+        let mut off = 0;
+        self.blocks(c)
+            .into_iter()
+            .map(|(no, nv)| {
+                let k = DMatrix::from_column_slice(nv, no, &x.as_slice()[off..off + nv * no]);
+                off += nv * no;
+                k
+            })
+            .collect()
+    }
+
+    fn pack(kappas: &[DMatrix<f64>]) -> DVector<f64> {
+        DVector::from_iterator(
+            kappas.iter().map(|k| k.len()).sum(),
+            kappas.iter().flat_map(|k| k.iter().copied()),
+        )
+    }
+
+    fn spin_densities(&self, c: &[DMatrix<f64>]) -> (DMatrix<f64>, DMatrix<f64>) {
+        let p: Vec<DMatrix<f64>> = c
+            .iter()
+            .zip(self.n_occ.iter())
+            .map(|(cs, &no)| {
+                let co = cs.columns(0, no);
+                co * co.transpose()
+            })
+            .collect();
+
+        match p.len() {
+            1 => (p[0].clone(), p[0].clone()),
+            _ => (p[0].clone(), p[1].clone()),
+        }
+    }
+
+    /// g^σ = 2w C_vᵀ F C_o
+    fn gradient(&self, c: &[DMatrix<f64>], f: &[DMatrix<f64>]) -> DVector<f64> {
+        let w = self.weight();
+        let kappas: Vec<DMatrix<f64>> = self
+            .blocks(c)
+            .into_iter()
+            .enumerate()
+            .map(|(s, (no, nv))| {
+                let co = c[s].columns(0, no);
+                let cv = c[s].columns(no, nv);
+                cv.transpose() * &f[s] * co * (2.0 * w)
+            })
+            .collect();
+
+        Self::pack(&kappas)
+    }
+
+    /// 2w (F_aa − F_ii) in the current orbitals
+    fn diagonal(&self, c: &[DMatrix<f64>], f: &[DMatrix<f64>]) -> DVector<f64> {
+        let w = self.weight();
+        let kappas: Vec<DMatrix<f64>> = self
+            .blocks(c)
+            .into_iter()
+            .enumerate()
+            .map(|(s, (no, nv))| {
+                let f_mo = c[s].transpose() * &f[s] * &c[s];
+                DMatrix::from_fn(nv, no, |a, i| {
+                    2.0 * w * (f_mo[(no + a, no + a)] - f_mo[(i, i)])
+                })
+            })
+            .collect();
+
+        Self::pack(&kappas)
+    }
+
+    /// H x (exact Fock response)
+    fn hessian_product(
+        &self,
+        c: &[DMatrix<f64>],
+        f: &[DMatrix<f64>],
+        p: &(DMatrix<f64>, DMatrix<f64>),
+        x: &DVector<f64>,
+    ) -> DVector<f64> {
+
+        let w = self.weight();
+        let kappas = self.unpack(c, x);
+        let blocks = self.blocks(c);
+
+        // ΔP^σ
+        let dp: Vec<DMatrix<f64>> = blocks
+            .iter()
+            .enumerate()
+            .map(|(s, &(no, nv))| {
+                let co = c[s].columns(0, no);
+                let cv = c[s].columns(no, nv);
+                let d = cv * &kappas[s] * co.transpose();
+                &d + d.transpose()
+            })
+            .collect();
+        let (dp_a, dp_b) = match dp.len() {
+            1 => (&dp[0], &dp[0]),
+            _ => (&dp[0], &dp[1]),
+        };
+
+        // G^σ[ΔP]
+        let (g_a, g_b) = if self.linear {
+            let (fa, fb) = (self.build_fock)(&(&p.0 + dp_a), &(&p.1 + dp_b), c);
+            (fa - &f[0], fb - f.last().unwrap())
+        } else {
+            let h = RESPONSE_STEP / x.norm().max(1e-12);
+            let (fa_p, fb_p) = (self.build_fock)(&(&p.0 + dp_a * h), &(&p.1 + dp_b * h), c);
+            let (fa_m, fb_m) = (self.build_fock)(&(&p.0 - dp_a * h), &(&p.1 - dp_b * h), c);
+            ((fa_p - fa_m) / (2.0 * h), (fb_p - fb_m) / (2.0 * h))
+        };
+        let g = [g_a, g_b];
+
+        let out: Vec<DMatrix<f64>> = blocks
+            .iter()
+            .enumerate()
+            .map(|(s, &(no, nv))| {
+                let co = c[s].columns(0, no);
+                let cv = c[s].columns(no, nv);
+                let f_vv = cv.transpose() * &f[s] * cv;
+                let f_oo = co.transpose() * &f[s] * co;
+                let resp = cv.transpose() * &g[s] * co;
+                (&f_vv * &kappas[s] - &kappas[s] * &f_oo + resp) * (2.0 * w)
+            })
+            .collect();
+
+        Self::pack(&out)
+    }
+
+    // --------------------------------------------------
+    // Augmented Hessian (Davidson)
+    // --------------------------------------------------
+
+    /// Step x and predicted ΔE = gᵀx + ½ xᵀHx
+    fn solve(&self, c: &[DMatrix<f64>], f: &[DMatrix<f64>]) -> (DVector<f64>, f64) {
+
+        let g = self.gradient(c, f);
+        let gnorm = g.norm();
+        if gnorm < 1e-14 {
+            return (DVector::zeros(g.len()), 0.0);
+        }
+
+        let diag = self.diagonal(c, f);
+        let p = self.spin_densities(c);
+
+        let apply = |v: &DVector<f64>| match self.options.hessian {
+            OrbitalHessian::Exact => self.hessian_product(c, f, &p, v),
+            OrbitalHessian::Diagonal => v.component_mul(&diag),
+        };
+
+        let tol = gnorm * gnorm.min(0.1);
+
+        let mut basis: Vec<DVector<f64>> = vec![&g / gnorm];
+        let mut sigma: Vec<DVector<f64>> = vec![apply(&basis[0])];
+
+        loop {
+            let k = basis.len();
+            let g_red = DVector::from_fn(k, |i, _| basis[i].dot(&g));
+            let h_red = DMatrix::from_fn(k, k, |i, j| {
+                0.5 * (basis[i].dot(&sigma[j]) + basis[j].dot(&sigma[i]))
+            });
+
+            let Some((y, mu)) = reduced_step(&g_red, &h_red, self.trust) else {
+                // degenerate augmented problem: steepest descent
+                let x = &g * (-self.trust / gnorm);
+                let predicted = -self.trust * gnorm;
+                return (x, predicted);
+            };
+
+            let combine = |vectors: &[DVector<f64>]| {
+                let zero = DVector::zeros(g.len());
+                vectors.iter().zip(y.iter()).fold(zero, |acc, (v, &yi)| acc + v * yi)
+            };
+            let x = combine(&basis);
+            let hx = combine(&sigma);
+
+            let predicted = g_red.dot(&y) + 0.5 * y.dot(&(&h_red * &y));
+
+            // Level-shifted Newton equation g + (H − μ) x = 0
+            let residual = &g + &hx - &x * mu;
+
+            if residual.norm() < tol || k >= self.options.max_micro || k >= g.len() {
+                return (x, predicted);
+            }
+
+            // Preconditioned new direction
+            let mut t = DVector::from_fn(g.len(), |i, _| {
+                let d = diag[i] - mu;
+                -residual[i] / if d.abs() < 1e-8 { 1e-8f64.copysign(d) } else { d }
+            });
+
+            for _ in 0..2 {
+                for b in &basis {
+                    let proj = b.dot(&t);
+                    t -= b * proj;
+                }
+            }
+
+            let tn = t.norm();
+            if tn < 1e-10 {
+                return (x, predicted);
+            }
+            t /= tn;
+
+            sigma.push(apply(&t));
+            basis.push(t);
+        }
+    }
+
+    /// C exp(K(x)) per channel
+    fn rotate(&self, c: &[DMatrix<f64>], x: &DVector<f64>) -> Vec<DMatrix<f64>> {
+        let kappas = self.unpack(c, x);
+
+        c.iter()
+            .zip(kappas.iter())
+            .zip(self.n_occ.iter())
+            .map(|((cs, k), &no)| {
+                let nmo = cs.ncols();
+                let mut gen = DMatrix::zeros(nmo, nmo);
+                for a in 0..nmo - no {
+                    for i in 0..no {
+                        gen[(no + a, i)] = k[(a, i)];
+                        gen[(i, no + a)] = -k[(a, i)];
+                    }
+                }
+                cs * gen.exp()
+            })
+            .collect()
+    }
+}
+
+/// Lowest eigenpair of the reduced augmented Hessian with ‖x‖ ≤ trust
+///
+/// Returns (y, μ) with x = V y; None if u0 vanishes.
+fn reduced_step(
+    g: &DVector<f64>,
+    h: &DMatrix<f64>,
+    trust: f64,
+) -> Option<(DVector<f64>, f64)> {
+
+    let k = g.len();
+
+    let solve = |alpha: f64| -> Option<(DVector<f64>, f64)> {
+        let mut m = DMatrix::zeros(k + 1, k + 1);
+        for i in 0..k {
+            m[(0, i + 1)] = alpha * g[i];
+            m[(i + 1, 0)] = alpha * g[i];
+            for j in 0..k {
+                m[(i + 1, j + 1)] = h[(i, j)];
+            }
+        }
+
+        let eig = SymmetricEigen::new(m);
+        let (low, _) = eig
+            .eigenvalues
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))?;
+
+        let u = eig.eigenvectors.column(low);
+        if u[0].abs() < 1e-12 {
+            return None;
+        }
+
+        let y = DVector::from_fn(k, |i, _| u[i + 1] / (alpha * u[0]));
+        Some((y, eig.eigenvalues[low]))
+    };
+
+    let (y, mu) = solve(1.0)?;
+    if y.norm() <= trust {
+        return Some((y, mu));
+    }
+
+    // ‖x(α)‖ decreases with α: bracket, then bisect
+    let mut lo = 1.0;
+    let mut hi = 2.0;
+    while solve(hi).is_some_and(|(y, _)| y.norm() > trust) && hi < 1e8 {
+        lo = hi;
+        hi *= 2.0;
+    }
+
+    for _ in 0..60 {
+        let mid = 0.5 * (lo + hi);
+        match solve(mid) {
+            Some((y, _)) if y.norm() > trust => lo = mid,
+            _ => hi = mid,
+        }
+    }
+
+    solve(hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, Reference, ScfOptions};
+
+    fn on_stall(window: usize) -> SoscfOptions {
+        SoscfOptions { mode: SecondOrder::OnStall, stall_window: window, ..SoscfOptions::default() }
+    }
+
+    #[test]
+    fn fixed_modes_ignore_the_history() {
+        let history = [1e-1, 1e-2];
+        assert!(!SoscfOptions::default().should_switch(&history));
+        assert!(SoscfOptions { mode: SecondOrder::Always, ..SoscfOptions::default() }
+            .should_switch(&history));
+    }
+
+    #[test]
+    fn on_stall_waits_for_a_window_without_progress() {
+        let options = on_stall(3);
+
+        assert!(!options.should_switch(&[1e-2, 1e-2, 1e-2]));
+        assert!(!options.should_switch(&[1e-1, 1e-2, 1e-3, 1e-4]));
+        assert!(options.should_switch(&[1e-2, 1e-2, 1e-2, 1e-2]));
+    }
+
+    #[test]
+    fn on_stall_compares_with_the_guess_commutator() {
+        // a good guess (restart) followed by no improvement is a stall
+        assert!(on_stall(2).should_switch(&[1e-4, 5e-3, 2e-3]));
+    }
+
+    #[test]
+    fn trah_reaches_the_diis_solution_for_uhf() {
+        let (molecule, shells, centers) =
+            hydrogens(&[[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]], 0, 2);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+
+        let diis = run_scf(&molecule, &shells, &centers, &options);
+        let trah = run_scf(
+            &molecule,
+            &shells,
+            &centers,
+            &ScfOptions {
+                soscf: SoscfOptions { mode: SecondOrder::Always, ..SoscfOptions::default() },
+                ..options.clone()
+            },
+        );
+
+        assert!(trah.converged);
+        assert!((trah.energy - diis.energy).abs() < 1e-8, "{} vs {}", trah.energy, diis.energy);
+    }
+}
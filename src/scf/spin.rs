@@ -0,0 +1,458 @@
+//! Spin analysis of unrestricted solutions
+//!
+//!   ⟨S²⟩ = S_z (S_z + 1) + N_β − Σ_ij |(C_αᵀ S C_β)_ij|²
+//!
+//! The singular value decomposition of the occupied α/β overlap gives the
+//! corresponding orbitals (Amos–Hall): pairs (a_i, b_i) with overlap d_i
+//! plus n_α − n_β unpaired α orbitals. Each pair is singlet with weight
+//! (1 + d_i²)/2 and triplet (M = 0) with weight (1 − d_i²)/2; coupling
+//! them one by one to the unpaired spin S_z gives the exact weights of
+//! every spin component, and the ⟨S²⟩ left after annihilating the
+//! largest contaminant.
+//!
+//! The Heisenberg coupling H = −2J S_A·S_B of two magnetic centers
+//! follows from a high-spin / broken-symmetry pair (Yamaguchi)
+//!
+//!   J = (E_BS − E_HS) / (⟨S²⟩_HS − ⟨S²⟩_BS)
+//!
+//! which reduces to the Noodleman and Ruiz–Bencini limits for weak and
+//! strong overlap of the magnetic orbitals. J < 0: antiferromagnetic.
+
+use nalgebra::DMatrix;
+
+use crate::basis::shell::Shell;
+use crate::dft::density::spin_density_at_point;
+use crate::system::atom::Atom;
+use crate::scf::guess::atom_shells;
+use crate::scf::scf_cycle::{ScfResult, SpinOrbitals};
+use crate::scf::uhf::UhfSolution;
+use crate::scf::utils::{solve_roothaan, vec2d_ref_to_dmatrix};
+use crate::system::units::HARTREE_TO_WAVENUMBER;
+
+/// Spin components with a smaller weight are not reported
+const WEIGHT_CUTOFF: f64 = 1e-10;
+
+impl ScfResult {
+    /// ⟨S²⟩ of the single determinant (exact S(S+1) for RHF / ROHF)
+    pub fn s_squared(&self, overlap: &DMatrix<f64>) -> f64 {
+        let (p_a, p_b) = self.spin_densities();
+        let p_a = vec2d_ref_to_dmatrix(&p_a);
+        let p_b = vec2d_ref_to_dmatrix(&p_b);
+
+        let n_a = p_a.dot(overlap);
+        let n_b = p_b.dot(overlap);
+        let s_z = 0.5 * (n_a - n_b);
+
+        s_z * (s_z + 1.0) + n_b - (&p_a * overlap * &p_b * overlap).trace()
+    }
+}
+
+/// Occupied α and β orbitals of one determinant
+pub struct SpinAnalysis {
+    /// C_α (AO × n_α)
+    pub alpha: DMatrix<f64>,
+    /// C_β (AO × n_β)
+    pub beta: DMatrix<f64>,
+    pub overlap: DMatrix<f64>,
+}
+
+/// Natural orbitals of P^α + P^β: S P S c = n S c, n descending
+pub struct NaturalOrbitals {
+    pub coefficients: DMatrix<f64>,
+    /// 0 ≤ n ≤ 2; UHF values away from 0 / 2 signal contamination
+    pub occupations: Vec<f64>,
+}
+
+impl SpinAnalysis {
+    pub fn new(alpha: DMatrix<f64>, beta: DMatrix<f64>, overlap: DMatrix<f64>) -> Self {
+        assert!(
+            alpha.ncols() >= beta.ncols(),
+            "Spin analysis expects n_α ≥ n_β ({} < {})",
+            alpha.ncols(),
+            beta.ncols()
+        );
+
+        Self { alpha, beta, overlap }
+    }
+
+    /// Occupied orbitals of an SCF result (fractional occupations rounded)
+    pub fn from_result(result: &ScfResult, overlap: &DMatrix<f64>) -> Self {
+        let occupied = |orbitals: &SpinOrbitals, threshold: f64| {
+            let c = vec2d_ref_to_dmatrix(&orbitals.coefficients);
+            let columns: Vec<usize> = (0..c.ncols())
+                .filter(|&k| orbitals.occupations[k] >= threshold)
+                .collect();
+            c.select_columns(&columns)
+        };
+
+        let (alpha, beta) = match &result.beta {
+            Some(beta) => (occupied(&result.alpha, 0.5), occupied(beta, 0.5)),
+            None => {
+                let c = occupied(&result.alpha, 1.0);
+                (c.clone(), c)
+            }
+        };
+
+        Self::new(alpha, beta, overlap.clone())
+    }
+
+    pub fn from_uhf(solution: &UhfSolution, overlap: &DMatrix<f64>) -> Self {
+        Self::new(
+            solution.c_alpha.columns(0, solution.n_alpha).into_owned(),
+            solution.c_beta.columns(0, solution.n_beta).into_owned(),
+            overlap.clone(),
+        )
+    }
+
+    /// S_z = ½ (n_α − n_β)
+    pub fn s_z(&self) -> f64 {
+        0.5 * (self.alpha.ncols() as f64 - self.beta.ncols() as f64)
+    }
+
+    /// C_αᵀ S C_β (n_α × n_β)
+    pub fn alpha_beta_overlap(&self) -> DMatrix<f64> {
+        self.alpha.transpose() * &self.overlap * &self.beta
+    }
+
+    pub fn s_squared(&self) -> f64 {
+        let s_z = self.s_z();
+        s_z * (s_z + 1.0) + self.beta.ncols() as f64
+            - self.alpha_beta_overlap().norm_squared()
+    }
+
+    /// ⟨S²⟩ − S_z (S_z + 1)
+    pub fn contamination(&self) -> f64 {
+        let s_z = self.s_z();
+        self.s_squared() - s_z * (s_z + 1.0)
+    }
+
+    /// Overlaps d_i of the corresponding orbital pairs, descending
+    pub fn pair_overlaps(&self) -> Vec<f64> {
+        if self.beta.ncols() == 0 {
+            return Vec::new();
+        }
+
+        let mut d: Vec<f64> = self
+            .alpha_beta_overlap()
+            .singular_values()
+            .iter()
+            .map(|&x| x.min(1.0))
+            .collect();
+        d.sort_by(|a, b| b.total_cmp(a));
+        d
+    }
+
+    /// (S, weight) of every spin component, S ascending
+    pub fn spin_weights(&self) -> Vec<(f64, f64)> {
+        let s_z = self.s_z();
+        // weights[k]: S = S_z + k
+        let mut weights = vec![1.0];
+
+        for d in self.pair_overlaps() {
+            let triplet = 0.5 * (1.0 - d * d);
+            let mut next = vec![0.0; weights.len() + 1];
+
+            for (k, &w) in weights.iter().enumerate() {
+                let j = s_z + k as f64;
+                next[k] += w * (1.0 - triplet);
+
+                // ⟨j m; 1 0 | J m⟩² with m = S_z
+                next[k + 1] += w * triplet * (j - s_z + 1.0) * (j + s_z + 1.0)
+                    / ((2.0 * j + 1.0) * (j + 1.0));
+                if j > 0.0 {
+                    next[k] += w * triplet * s_z * s_z / (j * (j + 1.0));
+                }
+                if k > 0 {
+                    next[k - 1] += w * triplet * (j - s_z) * (j + s_z) / (j * (2.0 * j + 1.0));
+                }
+            }
+
+            weights = next;
+        }
+
+        weights
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, w)| w > WEIGHT_CUTOFF)
+            .map(|(k, w)| (s_z + k as f64, w))
+            .collect()
+    }
+
+    /// Largest contaminant S and ⟨S²⟩ once it is projected out
+    ///
+    /// None for a pure spin state.
+    pub fn annihilate(&self) -> Option<(f64, f64)> {
+        let s_z = self.s_z();
+        let weights = self.spin_weights();
+
+        let (contaminant, _) = weights
+            .iter()
+            .copied()
+            .filter(|&(s, _)| s > s_z + 0.5)
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        let rest: Vec<(f64, f64)> = weights
+            .into_iter()
+            .filter(|&(s, _)| s != contaminant)
+            .collect();
+        let norm: f64 = rest.iter().map(|&(_, w)| w).sum();
+        let s2 = rest.iter().map(|&(s, w)| w * s * (s + 1.0)).sum::<f64>() / norm;
+
+        Some((contaminant, s2))
+    }
+
+    /// (P^α, P^β)
+    pub fn densities(&self) -> (DMatrix<f64>, DMatrix<f64>) {
+        (
+            &self.alpha * self.alpha.transpose(),
+            &self.beta * self.beta.transpose(),
+        )
+    }
+
+    /// P^α − P^β
+    pub fn spin_density(&self) -> DMatrix<f64> {
+        let (p_a, p_b) = self.densities();
+        p_a - p_b
+    }
+
+    pub fn natural_orbitals(&self) -> NaturalOrbitals {
+        let (p_a, p_b) = self.densities();
+        let sps = &self.overlap * (p_a + p_b) * &self.overlap;
+        let (coefficients, eps) = solve_roothaan(&(-sps), &self.overlap);
+
+        NaturalOrbitals {
+            coefficients,
+            occupations: eps.iter().map(|x| -x).collect(),
+        }
+    }
+
+    /// Mulliken spin populations Σ_{μ∈A} (D S)_μμ, one per atom
+    pub fn atomic_spin_populations(&self, shells: &[Shell], atoms: &[Atom]) -> Vec<f64> {
+        let ds = self.spin_density() * &self.overlap;
+
+        atoms
+            .iter()
+            .map(|atom| atom_shells(shells, atom).1.iter().map(|&mu| ds[(mu, mu)]).sum())
+            .collect()
+    }
+
+    /// ρ_α(r) − ρ_β(r) at arbitrary points (e.g. `DftGrid` or cube points)
+    pub fn spin_density_at_points(
+        &self,
+        shells: &[Shell],
+        shell_centers: &[[f64; 3]],
+        points: &[[f64; 3]],
+    ) -> Vec<f64> {
+        let (p_a, p_b) = self.densities();
+
+        points
+            .iter()
+            .map(|&r| {
+                let rho = spin_density_at_point(shells, shell_centers, &p_a, &p_b, r);
+                rho.rho_a - rho.rho_b
+            })
+            .collect()
+    }
+
+    pub fn print(&self, shells: &[Shell], atoms: &[Atom]) {
+        let s_z = self.s_z();
+
+        println!("\nSpin analysis");
+        println!("  <S^2> = {:.6}   (S_z(S_z+1) = {:.6})", self.s_squared(), s_z * (s_z + 1.0));
+        for (s, w) in self.spin_weights() {
+            println!("    S = {:4.1}   weight = {:.6}", s, w);
+        }
+        if let Some((s, s2)) = self.annihilate() {
+            println!("  <S^2> after annihilation of S = {:.1}: {:.6}", s, s2);
+        }
+
+        let natural = self.natural_orbitals();
+        let n = (self.alpha.ncols() + 2).min(natural.occupations.len());
+        let lo = self.beta.ncols().saturating_sub(2);
+        println!("  Natural occupations (frontier):");
+        for k in lo..n {
+            println!("    {:4}  {:.6}", k + 1, natural.occupations[k]);
+        }
+
+        println!("  Mulliken spin populations:");
+        for (atom, q) in atoms.iter().zip(self.atomic_spin_populations(shells, atoms)) {
+            println!("    {:<3} {:10.6}", atom.symbol, q);
+        }
+    }
+}
+
+/// Energy and ⟨S²⟩ of one spin state
+#[derive(Clone, Copy, Debug)]
+pub struct SpinState {
+    pub energy: f64,
+    pub s_squared: f64,
+}
+
+impl SpinState {
+    pub fn from_result(result: &ScfResult, overlap: &DMatrix<f64>) -> Self {
+        Self {
+            energy: result.energy,
+            s_squared: result.s_squared(overlap),
+        }
+    }
+}
+
+/// Heisenberg J from a high-spin / broken-symmetry pair
+#[derive(Clone, Copy, Debug)]
+pub struct ExchangeCoupling {
+    pub high_spin: SpinState,
+    pub broken_symmetry: SpinState,
+    /// J (Hartree), H = −2J S_A·S_B
+    pub j: f64,
+}
+
+impl ExchangeCoupling {
+    /// Yamaguchi spin projection
+    pub fn yamaguchi(high_spin: SpinState, broken_symmetry: SpinState) -> Self {
+        let delta_s2 = high_spin.s_squared - broken_symmetry.s_squared;
+        assert!(
+            delta_s2 > 1e-6,
+            "Yamaguchi J: ⟨S²⟩_HS ({:.4}) must exceed ⟨S²⟩_BS ({:.4})",
+            high_spin.s_squared,
+            broken_symmetry.s_squared
+        );
+
+        Self {
+            high_spin,
+            broken_symmetry,
+            j: (broken_symmetry.energy - high_spin.energy) / delta_s2,
+        }
+    }
+
+    /// J in cm⁻¹
+    pub fn wavenumber(&self) -> f64 {
+        self.j * HARTREE_TO_WAVENUMBER
+    }
+
+    pub fn print(&self) {
+        println!("\nExchange coupling (Yamaguchi, H = -2J S_A.S_B)");
+        println!("  {:<18} {:>18} {:>10}", "state", "E (Eh)", "<S^2>");
+        let states = [("high spin", &self.high_spin), ("broken symmetry", &self.broken_symmetry)];
+        for (name, state) in states {
+            println!("  {:<18} {:>18.10} {:>10.4}", name, state.energy, state.s_squared);
+        }
+        println!("  J = {:.6e} Eh = {:.2} cm^-1", self.j, self.wavenumber());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+    use crate::scf::guess::{BrokenSymmetrySource, InitialGuess};
+    use crate::scf::scf_cycle::{run_scf, Reference, ScfOptions};
+    use crate::scf::uhf::run_uhf;
+    use crate::scf::utils::build_overlap_matrix;
+
+    const STRETCHED_H2: [[f64; 3]; 2] = [[0.0; 3], [0.0, 0.0, 4.0]];
+
+    #[test]
+    fn high_spin_triplet_is_pure() {
+        let (molecule, shells, centers) = hydrogens(&STRETCHED_H2, 0, 3);
+        let overlap = build_overlap_matrix(&shells);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+
+        let result = run_scf(&molecule, &shells, &centers, &options);
+        assert!((result.s_squared(&overlap) - 2.0).abs() < 1e-10);
+
+        let analysis = SpinAnalysis::from_result(&result, &overlap);
+        assert!((analysis.s_squared() - 2.0).abs() < 1e-10);
+        assert_eq!(analysis.spin_weights(), vec![(1.0, 1.0)]);
+        assert!(analysis.annihilate().is_none());
+
+        // Σ_A spin populations = N_α − N_β
+        let populations = analysis.atomic_spin_populations(&shells, &molecule.atoms);
+        assert!((populations.iter().sum::<f64>() - 2.0).abs() < 1e-10);
+        assert!((populations[0] - populations[1]).abs() < 1e-8);
+    }
+
+    #[test]
+    fn broken_symmetry_singlet_mixes_singlet_and_triplet() {
+        let (molecule, shells, centers) = hydrogens(&STRETCHED_H2, 0, 1);
+        let overlap = build_overlap_matrix(&shells);
+        let options = ScfOptions { guess_rotation: 0.6, ..quiet_options() };
+
+        let uhf = run_uhf(&molecule, &shells, &centers, &options);
+        let analysis = SpinAnalysis::from_uhf(&uhf, &overlap);
+        let s2 = analysis.s_squared();
+        assert!(s2 > 0.5 && s2 < 1.0, "{}", s2);
+
+        // S = 0 and S = 1 only, weights reproduce ⟨S²⟩
+        let weights = analysis.spin_weights();
+        assert_eq!(weights.len(), 2);
+        assert!((weights.iter().map(|&(_, w)| w).sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((weights.iter().map(|&(s, w)| w * s * (s + 1.0)).sum::<f64>() - s2).abs() < 1e-10);
+
+        let (contaminant, projected) = analysis.annihilate().unwrap();
+        assert_eq!(contaminant, 1.0);
+        assert!(projected.abs() < 1e-10);
+
+        // opposite spins on the two centers, 0 ≤ n ≤ 2 with Σ n = N
+        let populations = analysis.atomic_spin_populations(&shells, &molecule.atoms);
+        assert!(populations[0] * populations[1] < 0.0);
+        let occupations = analysis.natural_orbitals().occupations;
+        assert!(occupations.iter().all(|&n| (-1e-10..=2.0 + 1e-10).contains(&n)));
+        assert!((occupations.iter().sum::<f64>() - 2.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn flipped_fragment_guess_gives_the_broken_symmetry_state() {
+        let (triplet, shells, centers) = hydrogens(&STRETCHED_H2, 0, 3);
+        let (singlet, _, _) = hydrogens(&STRETCHED_H2, 0, 1);
+        let overlap = build_overlap_matrix(&shells);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+
+        let high_spin = run_scf(&triplet, &shells, &centers, &options);
+        let source = BrokenSymmetrySource::from_result(&high_spin, vec![1]);
+        let broken = run_scf(
+            &singlet,
+            &shells,
+            &centers,
+            &ScfOptions { guess: InitialGuess::BrokenSymmetry(source), ..options.clone() },
+        );
+        let rhf = run_scf(&singlet, &shells, &centers, &quiet_options());
+
+        assert!(broken.converged);
+        assert!(broken.energy < rhf.energy - 1e-4);
+
+        let analysis = SpinAnalysis::from_result(&broken, &overlap);
+        let populations = analysis.atomic_spin_populations(&shells, &singlet.atoms);
+        assert!(populations[0] > 0.5 && populations[1] < -0.5, "{:?}", populations);
+
+        let coupling = ExchangeCoupling::yamaguchi(
+            SpinState::from_result(&high_spin, &overlap),
+            SpinState::from_result(&broken, &overlap),
+        );
+        assert!((coupling.high_spin.s_squared - 2.0).abs() < 1e-10);
+        let s2 = coupling.broken_symmetry.s_squared;
+        assert!(s2 > 0.5 && s2 < 1.0, "{}", s2);
+    }
+
+    #[test]
+    fn yamaguchi_reduces_to_the_two_center_limits() {
+        let high_spin = SpinState { energy: -1.0, s_squared: 2.0 };
+
+        // pure singlet (strong overlap): J = (E_S − E_T) / 2
+        let singlet = SpinState { energy: -1.004, s_squared: 0.0 };
+        let strong = ExchangeCoupling::yamaguchi(high_spin, singlet);
+        assert!((strong.j - -0.002).abs() < 1e-15);
+
+        // ⟨S²⟩_BS = 1 (weak overlap, Noodleman): J = E_BS − E_HS
+        let broken = SpinState { energy: -1.001, s_squared: 1.0 };
+        let weak = ExchangeCoupling::yamaguchi(high_spin, broken);
+        assert!((weak.j - -0.001).abs() < 1e-15);
+        assert!((weak.wavenumber() - weak.j * HARTREE_TO_WAVENUMBER).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "must exceed")]
+    fn yamaguchi_requires_a_spin_gap() {
+        let state = SpinState { energy: -1.0, s_squared: 1.0 };
+        ExchangeCoupling::yamaguchi(state, state);
+    }
+}
@@ -0,0 +1,770 @@
+//! SCF wavefunction stability analysis
+//!
+//! Second derivative of the energy with respect to occupied–virtual
+//! rotations C(Z) = C exp(X), X = [[0, −Z†], [Z, 0]], at a converged
+//! solution. With the TRAH conventions of `scf::soscf`
+//!
+//!   (H Z)^σ = 2w [F_vv Z − Z F_oo + C_v† G^σ[ΔP] C_o]
+//!   ΔP^σ    = C_v Z C_o† + C_o Z† C_v†
+//!
+//! where G^σ is the Fock response (exact difference for HF, central
+//! difference for DFT or non-linear external potentials) and w = 2 for a
+//! spatial channel shared by α and β, 1 per spin otherwise. Tests:
+//!
+//! - internal: rotations within the reference (RHF → RHF, UHF → UHF)
+//! - external: Z^β = −Z^α from a restricted reference (RHF → UHF)
+//!
+//! Over `f64` only real rotations are probed (A + B); over
+//! `Complex<f64>` Z is complex and the spectrum also contains the
+//! imaginary block (A − B), i.e. real → complex instabilities. The
+//! lowest eigenvalues come from a Davidson solver on the realified Z.
+//!
+//! An eigenvalue below `StabilityOptions::threshold` is followed by a
+//! line search along the mode and a new SCF from the rotated orbitals.
+
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+
+use crate::basis::shell::Shell;
+use crate::system::molecule::Molecule;
+use crate::scf::complex::{complex_fock, iterate_complex, ComplexScfResult};
+use crate::scf::guess::Guess;
+use crate::scf::scalar::{real_part, weighted_density, ScfScalar};
+use crate::scf::scf_cycle::{
+    iterate_from, Reference, ScfMethod, ScfOptions, ScfResult, ScfSystem,
+};
+use crate::scf::utils::vec2d_ref_to_dmatrix;
+
+/// Relative step for the central-difference XC response
+const RESPONSE_STEP: f64 = 1e-4;
+
+/// Trial rotation angles ‖Z‖ of the line search along an unstable mode
+const FOLLOW_STEPS: [f64; 5] = [0.05, 0.1, 0.2, 0.4, 0.8];
+
+/// Stability check after convergence
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StabilityMode {
+    Off,
+    /// Report the lowest Hessian eigenvalues
+    Analyze,
+    /// Report, and re-run the SCF along unstable modes
+    Follow,
+}
+
+impl StabilityMode {
+    /// Parse the input keyword (off | analyze | follow)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "analyze" | "analyse" => Some(Self::Analyze),
+            "follow" => Some(Self::Follow),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StabilityOptions {
+    pub mode: StabilityMode,
+    /// Also test RHF → UHF (restricted references only)
+    pub external: bool,
+    /// Eigenvalues computed per test
+    pub n_roots: usize,
+    /// Eigenvalue below which the solution is unstable
+    pub threshold: f64,
+    /// Davidson iterations and residual norm
+    pub max_iter: usize,
+    pub tolerance: f64,
+    /// New SCF runs at most (Follow)
+    pub max_follow: usize,
+}
+
+impl Default for StabilityOptions {
+    fn default() -> Self {
+        Self {
+            mode: StabilityMode::Off,
+            external: true,
+            n_roots: 3,
+            threshold: -1e-4,
+            max_iter: 50,
+            tolerance: 1e-5,
+            max_follow: 3,
+        }
+    }
+}
+
+/// Rotation space of a test
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StabilityTest {
+    /// RHF → RHF or UHF → UHF
+    Internal,
+    /// RHF → UHF
+    External,
+}
+
+/// One Hessian eigenpair
+pub struct StabilityRoot<T: ScfScalar> {
+    pub test: StabilityTest,
+    pub eigenvalue: f64,
+    /// Unit rotation Z (vir × occ) per channel of the test
+    pub rotation: Vec<DMatrix<T>>,
+}
+
+/// Lowest eigenvalues of every test, ascending
+pub struct StabilityReport<T: ScfScalar> {
+    pub roots: Vec<StabilityRoot<T>>,
+}
+
+impl<T: ScfScalar> StabilityReport<T> {
+    /// Lowest root below `threshold`, if any
+    pub fn unstable(&self, threshold: f64) -> Option<&StabilityRoot<T>> {
+        self.roots.first().filter(|r| r.eigenvalue < threshold)
+    }
+
+    pub fn is_stable(&self, threshold: f64) -> bool {
+        self.unstable(threshold).is_none()
+    }
+
+    pub fn print(&self) {
+        let kind = if T::IS_COMPLEX { "complex" } else { "real" };
+        for test in [StabilityTest::Internal, StabilityTest::External] {
+            let values: Vec<String> = self
+                .roots
+                .iter()
+                .filter(|r| r.test == test)
+                .map(|r| format!("{:10.6}", r.eigenvalue))
+                .collect();
+            if !values.is_empty() {
+                println!("  stability ({:?}, {}): {}", test, kind, values.join(" "));
+            }
+        }
+    }
+}
+
+/// Stability of a converged real RHF / UHF / RKS / UKS solution
+///
+/// ROHF / ROKS solutions are rejected with an error.
+pub fn analyze_stability(
+    molecule: &Molecule,
+    shells: &[Shell],
+    shell_centers: &[[f64; 3]],
+    result: &ScfResult,
+    options: &ScfOptions,
+) -> Result<StabilityReport<f64>, String> {
+    let sys = ScfSystem::new(molecule, shells, shell_centers, options);
+    analyze(&sys, options, &StationaryPoint::from_real(result))
+}
+
+/// Stability of a converged complex-driver solution
+///
+/// With `T = Complex<f64>` this includes real → complex instabilities.
+pub fn analyze_stability_complex<T: ScfScalar>(
+    molecule: &Molecule,
+    shells: &[Shell],
+    shell_centers: &[[f64; 3]],
+    result: &ComplexScfResult<T>,
+    options: &ScfOptions,
+) -> Result<StabilityReport<T>, String> {
+    let sys = ScfSystem::new(molecule, shells, shell_centers, options);
+    analyze(&sys, options, &StationaryPoint::from_complex(result))
+}
+
+/// Análisis tras `run_scf` y reinicio a lo largo de modos inestables
+pub(crate) fn follow_instabilities(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+    mut result: ScfResult,
+) -> ScfResult {
+
+    for cycle in 0..=options.stability.max_follow {
+        let point = StationaryPoint::from_real(&result);
+        let report = match analyze(sys, options, &point) {
+            Ok(report) => report,
+            Err(msg) => {
+                eprintln!("WARNING: {}; skipped", msg);
+                return result;
+            }
+        };
+
+        if options.print_iterations {
+            report.print();
+        }
+
+        let Some(root) = report.unstable(options.stability.threshold) else {
+            return result;
+        };
+        if options.stability.mode != StabilityMode::Follow
+            || cycle == options.stability.max_follow
+        {
+            return result;
+        }
+
+        let (reference, c_a, c_b) = point.follow(sys, options, root);
+        if options.print_iterations {
+            println!("  following {:?} instability", root.test);
+        }
+
+        let restart = ScfOptions { reference, ..options.clone() };
+        let guess = Guess {
+            p_alpha: weighted_density(&c_a, &vec![1.0; sys.n_alpha]),
+            p_beta: weighted_density(&c_b, &vec![1.0; sys.n_beta]),
+            orbitals: (c_a, c_b),
+        };
+        result = iterate_from(sys, &restart, guess);
+        if sys.stopped() {
+            return result;
+        }
+    }
+
+    result
+}
+
+/// Igual que `follow_instabilities` para el SCF complejo
+pub(crate) fn follow_complex_instabilities<T: ScfScalar>(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+    mut result: ComplexScfResult<T>,
+) -> ComplexScfResult<T> {
+
+    for cycle in 0..=options.stability.max_follow {
+        let point = StationaryPoint::from_complex(&result);
+        let report = match analyze(sys, options, &point) {
+            Ok(report) => report,
+            Err(msg) => {
+                eprintln!("WARNING: {}; skipped", msg);
+                return result;
+            }
+        };
+
+        if options.print_iterations {
+            report.print();
+        }
+
+        let Some(root) = report.unstable(options.stability.threshold) else {
+            return result;
+        };
+        if options.stability.mode != StabilityMode::Follow
+            || cycle == options.stability.max_follow
+        {
+            return result;
+        }
+
+        let (reference, c_a, c_b) = point.follow(sys, options, root);
+        if options.print_iterations {
+            println!("  following {:?} instability", root.test);
+        }
+
+        let restart = ScfOptions { reference, ..options.clone() };
+        result = iterate_complex(
+            sys,
+            &restart,
+            weighted_density(&c_a, &vec![1.0; sys.n_alpha]),
+            weighted_density(&c_b, &vec![1.0; sys.n_beta]),
+        );
+        if sys.stopped() {
+            return result;
+        }
+    }
+
+    result
+}
+
+// ======================================================
+// Hessiano orbital
+// ======================================================
+
+/// Converged orbitals (one channel if restricted)
+struct StationaryPoint<T: ScfScalar> {
+    reference: Reference,
+    coefficients: Vec<DMatrix<T>>,
+}
+
+impl StationaryPoint<f64> {
+    fn from_real(result: &ScfResult) -> Self {
+        let mut coefficients = vec![vec2d_ref_to_dmatrix(&result.alpha.coefficients)];
+        if let Some(beta) = &result.beta {
+            coefficients.push(vec2d_ref_to_dmatrix(&beta.coefficients));
+        }
+        Self { reference: reference_of(result.method), coefficients }
+    }
+}
+
+impl<T: ScfScalar> StationaryPoint<T> {
+    fn from_complex(result: &ComplexScfResult<T>) -> Self {
+        let mut coefficients = vec![result.alpha.coefficients.clone()];
+        if let Some(beta) = &result.beta {
+            coefficients.push(beta.coefficients.clone());
+        }
+        Self { reference: reference_of(result.method), coefficients }
+    }
+
+    /// (C^α, C^β)
+    fn spin_orbitals(&self) -> (DMatrix<T>, DMatrix<T>) {
+        (self.coefficients[0].clone(), self.coefficients.last().unwrap().clone())
+    }
+
+    /// Orbitals rotated by t·Z along `root` at the lowest line-search
+    /// energy, with the reference of the restart
+    fn follow(
+        &self,
+        sys: &ScfSystem,
+        options: &ScfOptions,
+        root: &StabilityRoot<T>,
+    ) -> (Reference, DMatrix<T>, DMatrix<T>) {
+
+        let (reference, n_occ) = match (self.reference, root.test) {
+            (Reference::Unrestricted, _) => (Reference::Unrestricted, [sys.n_alpha, sys.n_beta]),
+            (_, StabilityTest::Internal) => (Reference::Restricted, [sys.n_alpha; 2]),
+            (_, StabilityTest::External) => (Reference::Unrestricted, [sys.n_alpha; 2]),
+        };
+
+        let rotated = |t: f64| -> (DMatrix<T>, DMatrix<T>) {
+            let z = &root.rotation;
+            let (c_a, c_b) = self.spin_orbitals();
+            match (self.reference, root.test) {
+                (Reference::Unrestricted, _) => (
+                    rotate(&c_a, n_occ[0], &(&z[0] * T::from_real(t))),
+                    rotate(&c_b, n_occ[1], &(&z[1] * T::from_real(t))),
+                ),
+                (_, StabilityTest::Internal) => {
+                    let c = rotate(&c_a, n_occ[0], &(&z[0] * T::from_real(t)));
+                    (c.clone(), c)
+                }
+                (_, StabilityTest::External) => (
+                    rotate(&c_a, n_occ[0], &(&z[0] * T::from_real(t))),
+                    rotate(&c_b, n_occ[1], &(&z[0] * T::from_real(-t))),
+                ),
+            }
+        };
+
+        let options = ScfOptions { reference, ..options.clone() };
+        let mixing = options.exchange_mixing();
+
+        let energy = |orbitals: &(DMatrix<T>, DMatrix<T>)| {
+            let p_a = weighted_density(&orbitals.0, &vec![1.0; n_occ[0]]);
+            let p_b = weighted_density(&orbitals.1, &vec![1.0; n_occ[1]]);
+            complex_fock(sys, &p_a, &p_b, Some(orbitals), &options, &mixing).2.total()
+        };
+
+        let (c_a, c_b) = FOLLOW_STEPS
+            .iter()
+            .map(|&t| {
+                let orbitals = rotated(t);
+                (energy(&orbitals), orbitals)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap()
+            .1;
+
+        (reference, c_a, c_b)
+    }
+}
+
+/// Matrix-free Hessian of one test
+struct OrbitalRotations<'a, 's, T: ScfScalar> {
+    sys: &'a ScfSystem<'s>,
+    test: StabilityTest,
+    /// Restricted internal: response with P^α = P^β
+    options: ScfOptions,
+    coefficients: Vec<DMatrix<T>>,
+    n_occ: Vec<usize>,
+    densities: (DMatrix<T>, DMatrix<T>),
+    focks: (DMatrix<T>, DMatrix<T>),
+    weight: f64,
+}
+
+impl<'a, 's, T: ScfScalar> OrbitalRotations<'a, 's, T> {
+    fn new(
+        sys: &'a ScfSystem<'s>,
+        options: &ScfOptions,
+        point: &StationaryPoint<T>,
+        test: StabilityTest,
+    ) -> Self {
+
+        let restricted = point.reference == Reference::Restricted;
+
+        let (coefficients, n_occ, weight) = if restricted {
+            (vec![point.coefficients[0].clone()], vec![sys.n_alpha], 2.0)
+        } else {
+            (point.coefficients.clone(), vec![sys.n_alpha, sys.n_beta], 1.0)
+        };
+
+        // External: α y β responden por separado
+        let reference = if restricted && test == StabilityTest::Internal {
+            Reference::Restricted
+        } else {
+            Reference::Unrestricted
+        };
+        let options = ScfOptions { reference, ..options.clone() };
+
+        let (c_a, c_b) = point.spin_orbitals();
+        let p_a = weighted_density(&c_a, &vec![1.0; sys.n_alpha]);
+        let p_b = weighted_density(&c_b, &vec![1.0; sys.n_beta]);
+
+        let (f_a, f_b, _) = complex_fock(
+            sys, &p_a, &p_b, Some(&(c_a, c_b)), &options, &options.exchange_mixing(),
+        );
+
+        Self {
+            sys,
+            test,
+            options,
+            coefficients,
+            n_occ,
+            densities: (p_a, p_b),
+            focks: (f_a, f_b),
+            weight,
+        }
+    }
+
+    fn focks(&self) -> [&DMatrix<T>; 2] {
+        [&self.focks.0, &self.focks.1]
+    }
+
+    fn blocks(&self) -> Vec<(usize, usize)> {
+        self.coefficients
+            .iter()
+            .zip(self.n_occ.iter())
+            .map(|(c, &no)| (no, c.ncols() - no))
+            .collect()
+    }
+
+    /// Real parameters per complex entry
+    fn width() -> usize {
+        if T::IS_COMPLEX { 2 } else { 1 }
+    }
+
+    fn dimension(&self) -> usize {
+        self.blocks().iter().map(|(no, nv)| no * nv).sum::<usize>() * Self::width()
+    }
+
+    /// [Re Z^α, Im Z^α, Re Z^β, Im Z^β] (column-major)
+    fn pack(&self, z: &[DMatrix<T>]) -> DVector<f64> {
+        let mut values = Vec::with_capacity(self.dimension());
+        for zs in z {
+            values.extend(zs.iter().map(|x| x.real()));
+            if T::IS_COMPLEX {
+                values.extend(zs.iter().map(|x| x.imaginary()));
+            }
+        }
+        DVector::from_vec(values)
+    }
+
+    fn unpack(&self, x: &DVector<f64>) -> Vec<DMatrix<T>> {
+        let mut off = 0;
+        self.blocks()
+            .into_iter()
+            .map(|(no, nv)| {
+                let n = no * nv;
+                let re = &x.as_slice()[off..off + n];
+                let im = if T::IS_COMPLEX { &x.as_slice()[off + n..off + 2 * n] } else { re };
+                off += n * Self::width();
+                DMatrix::from_fn(nv, no, |a, i| T::from_parts(re[a + i * nv], im[a + i * nv]))
+            })
+            .collect()
+    }
+
+    /// 2w (F_aa − F_ii) in the reference orbitals
+    fn diagonal(&self) -> DVector<f64> {
+        let focks = self.focks();
+        let z: Vec<DMatrix<T>> = self
+            .blocks()
+            .into_iter()
+            .enumerate()
+            .map(|(s, (no, nv))| {
+                let c = &self.coefficients[s];
+                let f_mo = real_part(&(c.adjoint() * focks[s] * c));
+                DMatrix::from_fn(nv, no, |a, i| {
+                    let d = 2.0 * self.weight * (f_mo[(no + a, no + a)] - f_mo[(i, i)]);
+                    T::from_parts(d, d)
+                })
+            })
+            .collect();
+
+        self.pack(&z)
+    }
+
+    /// H x
+    fn product(&self, x: &DVector<f64>) -> DVector<f64> {
+
+        let z = self.unpack(x);
+        let blocks = self.blocks();
+
+        // ΔP por canal
+        let dp: Vec<DMatrix<T>> = blocks
+            .iter()
+            .enumerate()
+            .map(|(s, &(no, nv))| {
+                let c = &self.coefficients[s];
+                let d = c.columns(no, nv) * &z[s] * c.columns(0, no).adjoint();
+                &d + d.adjoint()
+            })
+            .collect();
+
+        let (dp_a, dp_b) = match (dp.len(), self.test) {
+            (1, StabilityTest::Internal) => (dp[0].clone(), dp[0].clone()),
+            (1, StabilityTest::External) => (dp[0].clone(), -&dp[0]),
+            _ => (dp[0].clone(), dp[1].clone()),
+        };
+
+        // G^σ[ΔP]
+        let (p_a, p_b) = &self.densities;
+        let mixing = self.options.exchange_mixing();
+        let orbitals = (self.coefficients[0].clone(), self.coefficients.last().unwrap().clone());
+        let fock = |pa: &DMatrix<T>, pb: &DMatrix<T>| {
+            let (fa, fb, _) =
+                complex_fock(self.sys, pa, pb, Some(&orbitals), &self.options, &mixing);
+            (fa, fb)
+        };
+
+        let (g_a, g_b) = if self.options.fock_is_linear() {
+            let (fa, fb) = fock(&(p_a + &dp_a), &(p_b + &dp_b));
+            (fa - &self.focks.0, fb - &self.focks.1)
+        } else {
+            let h = T::from_real(RESPONSE_STEP / x.norm().max(1e-12));
+            let (fa_p, fb_p) = fock(&(p_a + &dp_a * h), &(p_b + &dp_b * h));
+            let (fa_m, fb_m) = fock(&(p_a - &dp_a * h), &(p_b - &dp_b * h));
+            let scale = T::from_real(0.5) / h;
+            ((fa_p - fa_m) * scale, (fb_p - fb_m) * scale)
+        };
+        let g = [g_a, g_b];
+        let focks = self.focks();
+
+        let out: Vec<DMatrix<T>> = blocks
+            .iter()
+            .enumerate()
+            .map(|(s, &(no, nv))| {
+                let c = &self.coefficients[s];
+                let (co, cv) = (c.columns(0, no), c.columns(no, nv));
+                let f_vv = cv.adjoint() * focks[s] * cv;
+                let f_oo = co.adjoint() * focks[s] * co;
+                let resp = cv.adjoint() * &g[s] * co;
+                (&f_vv * &z[s] - &z[s] * &f_oo + resp) * T::from_real(2.0 * self.weight)
+            })
+            .collect();
+
+        self.pack(&out)
+    }
+}
+
+/// Lowest eigenpairs of every applicable test, ascending
+fn analyze<T: ScfScalar>(
+    sys: &ScfSystem,
+    options: &ScfOptions,
+    point: &StationaryPoint<T>,
+) -> Result<StabilityReport<T>, String> {
+
+    if point.reference == Reference::RestrictedOpenShell {
+        return Err("stability analysis is not available for ROHF / ROKS".to_string());
+    }
+
+    let mut tests = vec![StabilityTest::Internal];
+    if point.reference == Reference::Restricted && options.stability.external {
+        tests.push(StabilityTest::External);
+    }
+
+    let mut roots: Vec<StabilityRoot<T>> = Vec::new();
+
+    for test in tests {
+        let hessian = OrbitalRotations::new(sys, options, point, test);
+        if hessian.dimension() == 0 {
+            continue;
+        }
+
+        let pairs = davidson(
+            &|x| hessian.product(x),
+            &hessian.diagonal(),
+            options.stability.n_roots,
+            options.stability.max_iter,
+            options.stability.tolerance,
+        );
+
+        roots.extend(pairs.into_iter().map(|(eigenvalue, v)| StabilityRoot {
+            test,
+            eigenvalue,
+            rotation: hessian.unpack(&v),
+        }));
+    }
+
+    roots.sort_by(|a, b| a.eigenvalue.total_cmp(&b.eigenvalue));
+    Ok(StabilityReport { roots })
+}
+
+/// Lowest `n_roots` eigenpairs of a symmetric operator (Davidson)
+fn davidson(
+    apply: &dyn Fn(&DVector<f64>) -> DVector<f64>,
+    diag: &DVector<f64>,
+    n_roots: usize,
+    max_iter: usize,
+    tol: f64,
+) -> Vec<(f64, DVector<f64>)> {
+
+    let n = diag.len();
+    let n_roots = n_roots.clamp(1, n);
+    let max_basis = n.min((8 * n_roots).max(24));
+
+    // Vectores unitarios en los menores elementos diagonales
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| diag[a].total_cmp(&diag[b]));
+
+    let mut basis: Vec<DVector<f64>> = order
+        .iter()
+        .take((2 * n_roots).min(n))
+        .map(|&i| {
+            let mut v = DVector::zeros(n);
+            v[i] = 1.0;
+            v
+        })
+        .collect();
+    let mut sigma: Vec<DVector<f64>> = basis.iter().map(apply).collect();
+
+    for iter in 0.. {
+        let k = basis.len();
+        let h_red = DMatrix::from_fn(k, k, |i, j| {
+            0.5 * (basis[i].dot(&sigma[j]) + basis[j].dot(&sigma[i]))
+        });
+        let eig = SymmetricEigen::new(h_red);
+
+        let mut idx: Vec<usize> = (0..k).collect();
+        idx.sort_by(|&a, &b| eig.eigenvalues[a].total_cmp(&eig.eigenvalues[b]));
+        idx.truncate(n_roots);
+
+        let ritz: Vec<(f64, DVector<f64>, DVector<f64>)> = idx
+            .iter()
+            .map(|&r| {
+                let y = eig.eigenvectors.column(r);
+                let combine = |vectors: &[DVector<f64>]| {
+                    let zero = DVector::zeros(n);
+                    vectors.iter().zip(y.iter()).fold(zero, |acc, (v, &c)| acc + v * c)
+                };
+                let x = combine(&basis);
+                let hx = combine(&sigma);
+                (eig.eigenvalues[r], x, hx)
+            })
+            .collect();
+
+        let residuals: Vec<DVector<f64>> =
+            ritz.iter().map(|(theta, x, hx)| hx - x * *theta).collect();
+        let done = residuals.iter().all(|r| r.norm() < tol);
+
+        if done || k >= n || iter + 1 >= max_iter {
+            return ritz.into_iter().map(|(theta, x, _)| (theta, x)).collect();
+        }
+
+        // Reinicio con los vectores de Ritz
+        if k + n_roots > max_basis {
+            sigma = ritz.iter().map(|(_, _, hx)| hx.clone()).collect();
+            basis = ritz.iter().map(|(_, x, _)| x.clone()).collect();
+        }
+
+        // Nuevas direcciones precondicionadas
+        let mut added = 0;
+        for ((theta, _, _), r) in ritz.iter().zip(residuals.iter()) {
+            if r.norm() < tol {
+                continue;
+            }
+
+            let mut t = DVector::from_fn(n, |i, _| {
+                let d = diag[i] - theta;
+                -r[i] / if d.abs() < 1e-8 { 1e-8f64.copysign(d) } else { d }
+            });
+
+            for _ in 0..2 {
+                for b in &basis {
+                    let proj = b.dot(&t);
+                    t -= b * proj;
+                }
+            }
+
+            let tn = t.norm();
+            if tn > 1e-8 {
+                t /= tn;
+                sigma.push(apply(&t));
+                basis.push(t);
+                added += 1;
+            }
+        }
+
+        if added == 0 {
+            return ritz.into_iter().map(|(theta, x, _)| (theta, x)).collect();
+        }
+    }
+
+    unreachable!()
+}
+
+/// C exp(X), X = [[0, −Z†], [Z, 0]] in the MO basis
+fn rotate<T: ScfScalar>(coeff: &DMatrix<T>, n_occ: usize, z: &DMatrix<T>) -> DMatrix<T> {
+    let nmo = coeff.ncols();
+    let mut gen = DMatrix::zeros(nmo, nmo);
+    gen.view_mut((n_occ, 0), (nmo - n_occ, n_occ)).copy_from(z);
+    gen.view_mut((0, n_occ), (n_occ, nmo - n_occ)).copy_from(&(-z.adjoint()));
+    coeff * gen.exp()
+}
+
+fn reference_of(method: ScfMethod) -> Reference {
+    match method {
+        ScfMethod::RHF | ScfMethod::RKS => Reference::Restricted,
+        ScfMethod::UHF | ScfMethod::UKS => Reference::Unrestricted,
+        ScfMethod::ROHF | ScfMethod::ROKS => Reference::RestrictedOpenShell,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{h2, hydrogens, quiet_options};
+    use crate::scf::scf_cycle::run_scf;
+
+    #[test]
+    fn equilibrium_rhf_is_stable() {
+        let (molecule, shells, centers) = h2();
+        let options = quiet_options();
+
+        let result = run_scf(&molecule, &shells, &centers, &options);
+        let report = analyze_stability(&molecule, &shells, &centers, &result, &options).unwrap();
+
+        assert!(report.is_stable(options.stability.threshold));
+        assert!(report.roots.iter().any(|r| r.test == StabilityTest::External));
+    }
+
+    #[test]
+    fn rohf_solutions_are_rejected() {
+        let positions = [[0.0; 3], [0.0, 0.0, 1.4], [0.0, 0.0, 2.8]];
+        let (molecule, shells, centers) = hydrogens(&positions, 0, 2);
+        let options = ScfOptions {
+            reference: Reference::RestrictedOpenShell,
+            ..quiet_options()
+        };
+
+        let result = run_scf(&molecule, &shells, &centers, &options);
+        let report = analyze_stability(&molecule, &shells, &centers, &result, &options);
+
+        assert!(report.is_err());
+    }
+
+    #[test]
+    fn stretched_rhf_has_an_external_instability() {
+        let (molecule, shells, centers) = hydrogens(&[[0.0; 3], [0.0, 0.0, 5.0]], 0, 1);
+        let options = quiet_options();
+
+        let result = run_scf(&molecule, &shells, &centers, &options);
+        let report = analyze_stability(&molecule, &shells, &centers, &result, &options).unwrap();
+
+        let root = report.unstable(options.stability.threshold).unwrap();
+        assert_eq!(root.test, StabilityTest::External);
+    }
+
+    #[test]
+    fn following_the_instability_lowers_the_energy() {
+        let (molecule, shells, centers) = hydrogens(&[[0.0; 3], [0.0, 0.0, 5.0]], 0, 1);
+        let options = quiet_options();
+        let mut follow = options.clone();
+        follow.stability.mode = StabilityMode::Follow;
+
+        let rhf = run_scf(&molecule, &shells, &centers, &options);
+        let followed = run_scf(&molecule, &shells, &centers, &follow);
+
+        assert!(followed.beta.is_some());
+        assert!(followed.energy < rhf.energy - 1e-4);
+    }
+}
@@ -0,0 +1,423 @@
+//! SCF stabilizers
+//!
+//! - Virtual level shift:  F' = F + b (S − S P^σ S)
+//! - Density or Fock damping:  X' = (1 − a) X + a X_prev, with the
+//!   factor adapted to the energy trend
+//! - Fractional occupations by Fermi–Dirac or Gaussian smearing,
+//!   with geometric temperature annealing
+//!
+//! All of them are switched off for good once max |FPS − SPF| drops
+//! below `Stabilizers::off_below`, so the converged solution is the
+//! plain aufbau SCF solution.
+
+use nalgebra::DMatrix;
+
+/// Damping target
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DampingTarget {
+    Density,
+    Fock,
+}
+
+impl DampingTarget {
+    /// Parse the input keyword (density | fock)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "density" => Some(Self::Density),
+            "fock" => Some(Self::Fock),
+            _ => None,
+        }
+    }
+}
+
+/// Damping settings
+#[derive(Clone, Copy, Debug)]
+pub struct Damping {
+    pub target: DampingTarget,
+    /// Initial mixing factor a (weight of the previous iterate)
+    pub factor: f64,
+    /// a ← min(1.5 a, 0.9) when the energy rises, a ← 0.8 a otherwise
+    pub adaptive: bool,
+}
+
+impl Damping {
+    /// Adaptive damping starting from `factor`
+    pub fn adaptive(target: DampingTarget, factor: f64) -> Self {
+        Self { target, factor, adaptive: true }
+    }
+}
+
+/// Smearing function
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SmearingKind {
+    /// f = 1 / (1 + exp((ε − μ)/T))
+    FermiDirac,
+    /// f = ½ erfc((ε − μ)/T)
+    Gaussian,
+}
+
+impl SmearingKind {
+    /// Parse the input keyword (fermi | gaussian)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "fermi" | "fermi-dirac" => Some(Self::FermiDirac),
+            "gaussian" => Some(Self::Gaussian),
+            _ => None,
+        }
+    }
+}
+
+/// Fractional-occupation settings
+#[derive(Clone, Copy, Debug)]
+pub struct Smearing {
+    pub kind: SmearingKind,
+    /// Initial electronic temperature / width (Eh)
+    pub temperature: f64,
+    /// T ← annealing · T every iteration
+    pub annealing: f64,
+    /// Lower bound for T while smearing is on
+    pub min_temperature: f64,
+}
+
+impl Smearing {
+    /// T annealed ×0.7 per iteration down to 10⁻³ Eh
+    pub fn annealed(kind: SmearingKind, temperature: f64) -> Self {
+        Self { kind, temperature, annealing: 0.7, min_temperature: 1e-3 }
+    }
+}
+
+/// Stabilizer configuration (`ScfOptions::stabilizers`)
+#[derive(Clone, Copy, Debug)]
+pub struct Stabilizers {
+    /// Virtual level shift b (Eh); 0 → off
+    pub level_shift: f64,
+    pub damping: Option<Damping>,
+    pub smearing: Option<Smearing>,
+    /// max |FPS − SPF| below which all stabilizers are turned off
+    pub off_below: f64,
+}
+
+impl Default for Stabilizers {
+    fn default() -> Self {
+        Self {
+            level_shift: 0.0,
+            damping: None,
+            smearing: None,
+            off_below: 1e-2,
+        }
+    }
+}
+
+impl Stabilizers {
+    pub fn any(&self) -> bool {
+        self.level_shift != 0.0 || self.damping.is_some() || self.smearing.is_some()
+    }
+}
+
+/// Per-run stabilizer state
+pub struct StabilizerState {
+    config: Stabilizers,
+    active: bool,
+    damping: f64,
+    temperature: f64,
+    previous_energy: Option<f64>,
+    previous_focks: Option<Vec<DMatrix<f64>>>,
+}
+
+impl StabilizerState {
+    pub fn new(config: Stabilizers) -> Self {
+        Self {
+            config,
+            active: config.any(),
+            damping: config.damping.map_or(0.0, |d| d.factor),
+            temperature: config.smearing.map_or(0.0, |s| s.temperature),
+            previous_energy: None,
+            previous_focks: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Smearing still in effect (fractional occupations)
+    pub fn is_smearing(&self) -> bool {
+        self.active && self.config.smearing.is_some()
+    }
+
+    /// Once per iteration: switch-off check, adaptive damping, annealing
+    pub fn update(&mut self, energy: f64, commutator: f64, first: bool) {
+        if !self.active {
+            return;
+        }
+
+        if !first && commutator < self.config.off_below {
+            self.active = false;
+            self.previous_focks = None;
+            return;
+        }
+
+        if let (Some(d), Some(e_old)) = (self.config.damping, self.previous_energy) {
+            if d.adaptive {
+                self.damping = if energy > e_old {
+                    (1.5 * self.damping).min(0.9)
+                } else {
+                    0.8 * self.damping
+                };
+            }
+        }
+        self.previous_energy = Some(energy);
+
+        if let Some(s) = self.config.smearing {
+            self.temperature = (s.annealing * self.temperature).max(s.min_temperature);
+        }
+    }
+
+    /// F' = (1 − a) F + a F_prev  (Fock damping only)
+    pub fn damp_focks(&mut self, focks: Vec<DMatrix<f64>>) -> Vec<DMatrix<f64>> {
+        let fock_damping = self
+            .config
+            .damping
+            .is_some_and(|d| d.target == DampingTarget::Fock);
+
+        if !self.active || !fock_damping {
+            return focks;
+        }
+
+        let a = self.damping;
+        let damped: Vec<DMatrix<f64>> = match &self.previous_focks {
+            Some(prev) => focks
+                .iter()
+                .zip(prev.iter())
+                .map(|(f, fp)| f * (1.0 - a) + fp * a)
+                .collect(),
+            None => focks,
+        };
+
+        self.previous_focks = Some(damped.clone());
+        damped
+    }
+
+    /// P' = (1 − a) P_new + a P_old  (density damping only)
+    pub fn damp_density(&self, new: DMatrix<f64>, old: &DMatrix<f64>) -> DMatrix<f64> {
+        let density_damping = self
+            .config
+            .damping
+            .is_some_and(|d| d.target == DampingTarget::Density);
+
+        if !self.active || !density_damping {
+            return new;
+        }
+
+        new * (1.0 - self.damping) + old * self.damping
+    }
+
+    /// F + b (S − S P^σ S); `spin_density` is the occupied projector
+    pub fn shift(
+        &self,
+        fock: DMatrix<f64>,
+        overlap: &DMatrix<f64>,
+        spin_density: &DMatrix<f64>,
+    ) -> DMatrix<f64> {
+        if !self.active || self.config.level_shift == 0.0 {
+            return fock;
+        }
+
+        let virt = overlap - overlap * spin_density * overlap;
+        fock + virt * self.config.level_shift
+    }
+
+    /// Occupations of one spin channel (per-orbital in [0, 1])
+    ///
+    /// Aufbau unless smearing is active; ε must be ascending.
+    pub fn occupations(&self, eps: &[f64], n_electrons: usize) -> Vec<f64> {
+        match self.config.smearing {
+            Some(s) if self.active && self.temperature > 0.0 => {
+                smeared_occupations(eps, n_electrons as f64, s.kind, self.temperature)
+            }
+            _ => (0..eps.len())
+                .map(|i| if i < n_electrons { 1.0 } else { 0.0 })
+                .collect(),
+        }
+    }
+}
+
+/// Fractional occupations with Σ f_i = n, μ found by bisection
+pub fn smeared_occupations(
+    eps: &[f64],
+    n_electrons: f64,
+    kind: SmearingKind,
+    temperature: f64,
+) -> Vec<f64> {
+
+    let occupation = |e: f64, mu: f64| {
+        let x = (e - mu) / temperature;
+        match kind {
+            SmearingKind::FermiDirac => {
+                if x > 0.0 {
+                    let ex = (-x).exp();
+                    ex / (1.0 + ex)
+                } else {
+                    1.0 / (1.0 + x.exp())
+                }
+            }
+            SmearingKind::Gaussian => 0.5 * libm::erfc(x),
+        }
+    };
+
+    if n_electrons <= 0.0 || eps.is_empty() {
+        return vec![0.0; eps.len()];
+    }
+
+    let count = |mu: f64| eps.iter().map(|&e| occupation(e, mu)).sum::<f64>();
+
+    let margin = 50.0 * temperature + 1.0;
+    let mut lo = eps[0] - margin;
+    let mut hi = eps[eps.len() - 1] + margin;
+
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if count(mid) < n_electrons {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let mu = 0.5 * (lo + hi);
+    eps.iter().map(|&e| occupation(e, mu)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+    use crate::scf::scf_cycle::{run_scf, Reference, ScfOptions};
+
+    fn damped(adaptive: bool) -> StabilizerState {
+        StabilizerState::new(Stabilizers {
+            damping: Some(Damping { target: DampingTarget::Density, factor: 0.5, adaptive }),
+            ..Stabilizers::default()
+        })
+    }
+
+    #[test]
+    fn smeared_occupations_hold_the_electron_count() {
+        let eps = [-1.0, -0.3, 0.0, 0.0, 0.8];
+
+        for kind in [SmearingKind::FermiDirac, SmearingKind::Gaussian] {
+            let f = smeared_occupations(&eps, 3.0, kind, 0.01);
+            assert!((f.iter().sum::<f64>() - 3.0).abs() < 1e-10);
+            assert!(f.windows(2).all(|w| w[0] >= w[1]));
+            // the degenerate pair shares the last electron
+            assert!((f[2] - 0.5).abs() < 1e-8 && (f[3] - 0.5).abs() < 1e-8);
+            assert!(f[0] > 1.0 - 1e-10 && f[4] < 1e-10);
+        }
+    }
+
+    #[test]
+    fn level_shift_raises_only_virtual_orbitals() {
+        let config = Stabilizers { level_shift: 0.4, ..Stabilizers::default() };
+        let state = StabilizerState::new(config);
+        let overlap = DMatrix::identity(2, 2);
+        let occupied = DMatrix::from_diagonal(&nalgebra::DVector::from_vec(vec![1.0, 0.0]));
+        let fock = DMatrix::from_row_slice(2, 2, &[-1.0, 0.1, 0.1, 0.5]);
+
+        let shifted = state.shift(fock.clone(), &overlap, &occupied);
+        assert_eq!(shifted - fock, DMatrix::from_row_slice(2, 2, &[0.0, 0.0, 0.0, 0.4]));
+    }
+
+    #[test]
+    fn adaptive_damping_follows_the_energy_trend() {
+        let old = DMatrix::zeros(1, 1);
+        let new = DMatrix::from_element(1, 1, 1.0);
+        let factor = |state: &StabilizerState| {
+            1.0 - state.damp_density(new.clone(), &old)[(0, 0)]
+        };
+
+        let mut state = damped(true);
+        state.update(-1.0, 1.0, true);
+        assert!((factor(&state) - 0.5).abs() < 1e-15);
+        state.update(-0.9, 1.0, false);
+        assert!((factor(&state) - 0.75).abs() < 1e-15);
+        state.update(-0.8, 1.0, false);
+        assert!((factor(&state) - 0.9).abs() < 1e-15);
+        state.update(-1.1, 1.0, false);
+        assert!((factor(&state) - 0.72).abs() < 1e-15);
+
+        let mut fixed = damped(false);
+        fixed.update(-1.0, 1.0, true);
+        fixed.update(-0.5, 1.0, false);
+        assert!((factor(&fixed) - 0.5).abs() < 1e-15);
+    }
+
+    #[test]
+    fn stabilizers_switch_off_for_good_near_convergence() {
+        let mut state = damped(true);
+
+        // never on the first iteration, whatever the commutator
+        state.update(-1.0, 1e-6, true);
+        assert!(state.is_active());
+
+        state.update(-1.0, 1e-3, false);
+        assert!(!state.is_active());
+        state.update(-1.0, 1.0, false);
+        assert!(!state.is_active());
+
+        let new = DMatrix::from_element(1, 1, 1.0);
+        assert_eq!(state.damp_density(new.clone(), &DMatrix::zeros(1, 1)), new);
+    }
+
+    #[test]
+    fn annealing_stops_at_the_minimum_temperature() {
+        let mut state = StabilizerState::new(Stabilizers {
+            smearing: Some(Smearing {
+                kind: SmearingKind::FermiDirac,
+                temperature: 0.1,
+                annealing: 0.5,
+                min_temperature: 0.02,
+            }),
+            ..Stabilizers::default()
+        });
+
+        for _ in 0..10 {
+            state.update(-1.0, 1.0, false);
+        }
+        let f = state.occupations(&[-0.01, 0.01], 1);
+        let expected = smeared_occupations(&[-0.01, 0.01], 1.0, SmearingKind::FermiDirac, 0.02);
+        assert!((f[0] - expected[0]).abs() < 1e-12);
+        assert!(state.is_smearing());
+    }
+
+    #[test]
+    fn stabilized_scf_reaches_the_plain_solution() {
+        let (molecule, shells, centers) =
+            hydrogens(&[[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]], 0, 2);
+        let options = ScfOptions { reference: Reference::Unrestricted, ..quiet_options() };
+        let plain = run_scf(&molecule, &shells, &centers, &options);
+
+        for damping in [DampingTarget::Density, DampingTarget::Fock] {
+            let stabilizers = Stabilizers {
+                level_shift: 0.3,
+                damping: Some(Damping { target: damping, factor: 0.4, adaptive: true }),
+                smearing: Some(Smearing {
+                    kind: SmearingKind::Gaussian,
+                    temperature: 0.02,
+                    annealing: 0.7,
+                    min_temperature: 1e-3,
+                }),
+                off_below: 1e-2,
+            };
+            let stabilized = run_scf(
+                &molecule,
+                &shells,
+                &centers,
+                &ScfOptions { stabilizers, ..options.clone() },
+            );
+
+            assert!(stabilized.converged);
+            assert!((stabilized.energy - plain.energy).abs() < 1e-8, "{:?}", damping);
+            assert!(stabilized.alpha.occupations.iter().all(|&f| f == 0.0 || f == 1.0));
+        }
+    }
+}
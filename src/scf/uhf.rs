@@ -1,123 +1,117 @@
+//! Unrestricted Hartree–Fock driver
+//!
+//! Thin wrapper over `run_scf` with `Reference::Unrestricted`: DIIS,
+//! convergence control, external potentials and the iteration report
+//! are those of the unified cycle (see `scf::scf_cycle`).
+
+use nalgebra::DMatrix;
+
 use crate::basis::shell::Shell;
-use crate::scf::density::build_spin_density;
-use crate::scf::jk::build_jk;
-use crate::scf::diis::Diis;
-use crate::scf::guess::{core_h_guess, build_matrix};
-use crate::scf::utils::{add, solve_roothaan, diis_error};
-use crate::integrals::overlap_contracted::overlap_shell_shell;
-use crate::integrals::kinetic::kinetic_shell_shell;
-use crate::integrals::nuclear_attraction::nuclear_attraction_shell_shell;
+use crate::scf::scf_cycle::{run_scf, Reference, ScfOptions, ScfResult};
+use crate::scf::utils::vec2d_ref_to_dmatrix;
+use crate::system::molecule::Molecule;
+
+/// UHF / UDFT solution
+pub struct UhfSolution {
+    pub p_alpha: Vec<Vec<f64>>,
+    pub p_beta: Vec<Vec<f64>>,
+    /// C^α, C^β (AO × MO, ε ascending)
+    pub c_alpha: DMatrix<f64>,
+    pub c_beta: DMatrix<f64>,
+    pub eps_alpha: Vec<f64>,
+    pub eps_beta: Vec<f64>,
+    pub n_alpha: usize,
+    pub n_beta: usize,
+    pub energy: f64,
+    pub converged: bool,
+}
+
+impl UhfSolution {
+    /// α / β channels of an unrestricted `ScfResult`
+    pub fn from_result(result: &ScfResult) -> Self {
+        let beta = result
+            .beta
+            .as_ref()
+            .expect("UhfSolution requires an unrestricted result");
+
+        Self {
+            p_alpha: result.alpha.density.clone(),
+            p_beta: beta.density.clone(),
+            c_alpha: vec2d_ref_to_dmatrix(&result.alpha.coefficients),
+            c_beta: vec2d_ref_to_dmatrix(&beta.coefficients),
+            eps_alpha: result.alpha.energies.clone(),
+            eps_beta: beta.energies.clone(),
+            n_alpha: result.alpha.n_occupied(),
+            n_beta: beta.n_occupied(),
+            energy: result.energy,
+            converged: result.converged,
+        }
+    }
+}
 
 /// Run unrestricted Hartree–Fock (UHF)
+///
+/// n_α / n_β follow from the charge and multiplicity of `molecule`;
+/// `options.xc_method` is ignored and the reference forced to
+/// unrestricted. For an observed run use `run_scf_observed` with the
+/// same options.
 pub fn run_uhf(
+    molecule: &Molecule,
     shells: &[Shell],
     shell_centers: &[[f64; 3]],
-    atoms: &[crate::system::atom::Atom],
-    n_alpha: usize,
-    n_beta: usize,
-    max_iter: usize,
-    conv_thresh: f64,
-) -> (Vec<Vec<f64>>, Vec<Vec<f64>>, f64) {
-    let nao = shells.iter().map(|s| s.n_orbitals()).sum::<usize>();
-
-    // --- Core Hamiltonian ---
-    let s = build_matrix(
-        shells, 
-        shell_centers, 
-        |sa, _ca, sb, _cb| {
-            overlap_shell_shell(sa, sb)
-        },
-    );
-
-
-    let t_vec = build_one_electron_matrix(shells, shell_centers, atoms);
-    let v_vec = build_one_electron_matrix(shells, shell_centers, atoms);
-
-    // Convertir a DMatrix
-    let t = vec2d_ref_to_dmatrix(&t_vec);
-    let v = vec2d_ref_to_dmatrix(&v_vec);
-    let hcore = add(&t, &v);
-
-
-    // --- Initial guess (same for alpha/beta) ---
-    let p0 = core_h_guess(shells, shell_centers, atoms, n_alpha + n_beta);
-
-    let mut p_alpha = p0.clone();
-    let mut p_beta  = p0.clone();
-
-    let mut diis_a = Diis::new(6);
-    let mut diis_b = Diis::new(6);
-
-    let mut e_old = 0.0;
-
-    for iter in 0..max_iter {
-        // Total density
-        let p_tot = add(&p_alpha, &p_beta);
-
-        //let p_tot_vec = dmatrix_to_vec2d(&p_tot);
-        //let (j, _) = build_jk(shells, shell_centers, &p_tot_vec);
-        //let j = vec2d_ref_to_dmatrix(&j);
-
-        // J/K from total density
-        let (j, _) = build_jk(shells, shell_centers, &p_tot);
-        let (_, k_alpha) = build_jk(shells, shell_centers, &p_alpha);
-        let (_, k_beta)  = build_jk(shells, shell_centers, &p_beta);
-
-        // Fock matrices
-        let f_alpha = build_fock(&hcore, &j, &k_alpha);
-        let f_beta  = build_fock(&hcore, &j, &k_beta);
-
-        pub fn build_fock(
-            hcore: &DMatrix<f64>,
-            j: &DMatrix<f64>, 
-            k: &DMatrix<f64>
-            ) -> DMatrix<f64> {
-        // F = H + 2J - K
-            hcore + 2.0 * j - k
-        }
-
-let f_alpha = build_fock(&hcore, &j, &k_alpha);
-let f_beta  = build_fock(&hcore, &j, &k_beta);
-
-
-        // DIIS errors
-        let err_a = diis_error(&f_alpha, &p_alpha, &s);
-        let err_b = diis_error(&f_beta, &p_beta, &s);
-
-        diis_a.push(f_alpha.clone(), err_a);
-        diis_b.push(f_beta.clone(), err_b);
-
-        let f_alpha = diis_a.extrapolate().unwrap_or(f_alpha);
-        let f_beta  = diis_b.extrapolate().unwrap_or(f_beta);
+    options: &ScfOptions,
+) -> UhfSolution {
+    let options = ScfOptions {
+        reference: Reference::Unrestricted,
+        xc_method: None,
+        ..options.clone()
+    };
+
+    UhfSolution::from_result(&run_scf(molecule, shells, shell_centers, &options))
+}
 
-        // Solve Roothaan
-        let (c_a, _) = solve_roothaan(&f_alpha, &s);
-        let (c_b, _) = solve_roothaan(&f_beta, &s);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::diis::Accelerator;
+    use crate::scf::fixtures::{hydrogens, quiet_options};
+
+    #[test]
+    fn broken_symmetry_uhf_lies_below_rhf_for_stretched_h2() {
+        let (molecule, shells, centers) = hydrogens(&[[0.0; 3], [0.0, 0.0, 5.0]], 0, 1);
+        let options = quiet_options();
+
+        let rhf = run_scf(&molecule, &shells, &centers, &options);
+        let uhf = run_uhf(
+            &molecule,
+            &shells,
+            &centers,
+            &ScfOptions { guess_rotation: 0.5, ..options },
+        );
 
-        // New densities
-        let p_alpha_new = build_spin_density(&c_a, n_alpha);
-        let p_beta_new  = build_spin_density(&c_b, n_beta);
+        assert!(uhf.converged);
+        assert_eq!((uhf.n_alpha, uhf.n_beta), (1, 1));
+        assert!(uhf.energy < rhf.energy - 1e-4, "{} vs {}", uhf.energy, rhf.energy);
+    }
 
-        // Energy
-        let e = uhf_energy(&p_alpha_new, &p_beta_new, &hcore, &j, &k_alpha, &k_beta);
+    #[test]
+    fn ediis_and_adiis_reach_the_pulay_solution() {
+        let (molecule, shells, centers) =
+            hydrogens(&[[0.0; 3], [0.0, 0.0, 1.6], [0.0, 1.1, 2.9]], 0, 2);
+        let options = quiet_options();
 
-        let dE = (e - e_old).abs();
-        let dP = rms_diff(&p_alpha, &p_alpha_new)
-               + rms_diff(&p_beta,  &p_beta_new);
+        let pulay = run_uhf(&molecule, &shells, &centers, &options);
 
-        println!(
-            "UHF iter {:3}  E = {:16.10}  dE = {:10.3e}  dP = {:10.3e}",
-            iter, e, dE, dP
-        );
+        for accelerator in [Accelerator::EDiis, Accelerator::ADiis] {
+            let hybrid = run_uhf(
+                &molecule,
+                &shells,
+                &centers,
+                &ScfOptions { accelerator, diis_switch: 1e-2, ..options.clone() },
+            );
 
-        if dE < conv_thresh && dP < conv_thresh {
-            return (p_alpha_new, p_beta_new, e);
+            assert!(hybrid.converged);
+            assert!((hybrid.energy - pulay.energy).abs() < 1e-8, "{:?}", accelerator);
         }
-
-        p_alpha = p_alpha_new;
-        p_beta  = p_beta_new;
-        e_old = e;
     }
-
-    panic!("UHF did not converge");
 }
@@ -10,6 +10,9 @@
 use nalgebra::{DMatrix, DVector, SymmetricEigen};
 
 use crate::basis::shell::Shell;
+use crate::integrals::kinetic::kinetic_shell_shell;
+use crate::integrals::nuclear_attraction::nuclear_attraction_shell_shell;
+use crate::integrals::overlap_contracted::overlap_shell_shell;
 use crate::system::atom::Atom;
 
 // ======================================================
@@ -35,7 +38,7 @@ pub fn vec2d_to_dmatrix(v: Vec<Vec<f64>>) -> DMatrix<f64> {
         data.extend(row);
     }
     
-    DMatrix::from_vec(nrows, ncols, data)
+    DMatrix::from_row_slice(nrows, ncols, &data)
 }
 
 /// Convierte &Vec<Vec<f64>> a DMatrix<f64>
@@ -48,7 +51,14 @@ pub fn vec2d_ref_to_dmatrix(v: &Vec<Vec<f64>>) -> DMatrix<f64> {
         data.extend(row.iter().copied());
     }
     
-    DMatrix::from_vec(nrows, ncols, data)
+    DMatrix::from_row_slice(nrows, ncols, &data)
+}
+
+/// Convierte DMatrix<f64> a Vec<Vec<f64>>
+pub fn dmatrix_to_vec2d(m: &DMatrix<f64>) -> Vec<Vec<f64>> {
+    (0..m.nrows())
+        .map(|i| (0..m.ncols()).map(|j| m[(i, j)]).collect())
+        .collect()
 }
 
 
@@ -61,24 +71,20 @@ pub fn vec2d_ref_to_dmatrix(v: &Vec<Vec<f64>>) -> DMatrix<f64> {
 /// Build Hcore = T + V_nuc
 pub fn build_one_electron_matrix(
     shells: &[Shell],
-    shell_centers: &[[f64; 3]],
     atoms: &[Atom],
 ) -> DMatrix<f64> {
 
-    let nao = shells.last().unwrap().offset + shells.last().unwrap().orbitals.len();
+    let nao: usize = shells.iter().map(|s| s.n_orbitals()).sum();
     let mut h = DMatrix::zeros(nao, nao);
 
-    for (si, ci) in shells.iter().zip(shell_centers.iter()) {
-        for (sj, cj) in shells.iter().zip(shell_centers.iter()) {
-            let off_i = si.offset;
-            let off_j = sj.offset;
-
-            let t = si.kinetic(sj, *ci, *cj);
-            let v = si.nuclear_attraction(sj, *ci, *cj, atoms);
+    for si in shells {
+        for sj in shells {
+            let t = kinetic_shell_shell(si, sj);
+            let v = nuclear_attraction_shell_shell(si, sj, atoms);
 
-            for mu in 0..si.orbitals.len() {
-                for nu in 0..sj.orbitals.len() {
-                    h[(off_i + mu, off_j + nu)] =
+            for mu in 0..si.n_orbitals() {
+                for nu in 0..sj.n_orbitals() {
+                    h[(si.offset + mu, sj.offset + nu)] =
                         t[mu][nu] + v[mu][nu];
                 }
             }
@@ -97,6 +103,33 @@ pub fn build_one_electron_matrix(
     h
 }
 
+/// Build the AO overlap matrix S
+pub fn build_overlap_matrix(shells: &[Shell]) -> DMatrix<f64> {
+    build_mixed_overlap_matrix(shells, shells)
+}
+
+/// Mixed-basis overlap S_12 (rows: `shells_1`, columns: `shells_2`)
+pub fn build_mixed_overlap_matrix(shells_1: &[Shell], shells_2: &[Shell]) -> DMatrix<f64> {
+
+    let n1: usize = shells_1.iter().map(|s| s.n_orbitals()).sum();
+    let n2: usize = shells_2.iter().map(|s| s.n_orbitals()).sum();
+    let mut s = DMatrix::zeros(n1, n2);
+
+    for si in shells_1 {
+        for sj in shells_2 {
+            let block = overlap_shell_shell(si, sj);
+
+            for mu in 0..si.n_orbitals() {
+                for nu in 0..sj.n_orbitals() {
+                    s[(si.offset + mu, sj.offset + nu)] = block[mu][nu];
+                }
+            }
+        }
+    }
+
+    s
+}
+
 // ======================================================
 // Fock matrix
 // ======================================================
@@ -138,7 +171,7 @@ pub fn electronic_energy_scaled(
 // Roothaan equations
 // ======================================================
 
-/// Solve FC = S C ε
+/// Solve FC = S C ε  (ε in ascending order)
 pub fn solve_roothaan(
     fock: &DMatrix<f64>,
     overlap: &DMatrix<f64>,
@@ -157,8 +190,16 @@ pub fn solve_roothaan(
 
     let eig = SymmetricEigen::new(f_prime);
 
-    let c = x * eig.eigenvectors;
-    let eps = eig.eigenvalues.iter().copied().collect();
+    // Orden ascendente de energías orbitales
+    let mut order: Vec<usize> = (0..eig.eigenvalues.len()).collect();
+    order.sort_by(|&a, &b| eig.eigenvalues[a].total_cmp(&eig.eigenvalues[b]));
+
+    let vecs = DMatrix::from_fn(fock.nrows(), order.len(), |i, k| {
+        eig.eigenvectors[(i, order[k])]
+    });
+
+    let c = x * vecs;
+    let eps = order.iter().map(|&k| eig.eigenvalues[k]).collect();
 
     (c, eps)
 }
@@ -2,20 +2,22 @@
 
 use crate::basis::shell::Shell;
 use crate::system::atom::Atom;
-use crate::integrals::dipole::dipole_integrals;
+use crate::integrals::multipole::dipole_integrals;
 
-/// μ = μ_nuclear + μ_electronic
+/// μ = μ_nuclear + μ_electronic  (a.u., about `origin`)
+///
+/// `density` is the total AO density (P_α + P_β for UHF).
 pub fn dipole_moment(
     shells: &[Shell],
-    centers: &[[f64;3]],
     density: &Vec<Vec<f64>>,
     atoms: &[Atom],
-) -> [f64;3] {
+    origin: [f64; 3],
+) -> [f64; 3] {
 
     let nao = density.len();
-    let mut mu_e = [0.0;3];
+    let mut mu_e = [0.0; 3];
 
-    let dip = dipole_integrals(shells, centers);
+    let dip = dipole_integrals(shells, origin);
 
     for mu in 0..nao {
         for nu in 0..nao {
@@ -25,10 +27,10 @@ pub fn dipole_moment(
         }
     }
 
-    let mut mu_n = [0.0;3];
+    let mut mu_n = [0.0; 3];
     for a in atoms {
         for k in 0..3 {
-            mu_n[k] += a.charge as f64 * a.position[k];
+            mu_n[k] += a.nuclear_charge() * (a.position[k] - origin[k]);
         }
     }
 
@@ -38,4 +40,3 @@ pub fn dipole_moment(
         mu_n[2] + mu_e[2],
     ]
 }
-
@@ -1,6 +1,6 @@
 //! Infrared intensities
 
-use nalgebra::{DMatrix, DVector};
+use nalgebra::DMatrix;
 
 /// IR intensities from dipole derivatives and normal modes
 ///
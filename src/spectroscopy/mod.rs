@@ -1,4 +1,5 @@
 pub mod dipole;
-pud mod ir;
+pub mod ir;
+pub mod multipole;
 pub mod polarizability;
 pub mod raman;
@@ -0,0 +1,288 @@
+//! Molecular multipole moments (dipole, quadrupole, octupole)
+//!
+//! Traceless (Buckingham) definitions:
+//!   Θ_ij  = ½ Σ q (3 r_i r_j − r² δ_ij)
+//!   Ω_ijk = ½ Σ q (5 r_i r_j r_k − r² (r_i δ_jk + r_j δ_ik + r_k δ_ij))
+//!
+//! Electrons enter with q = −1 through the AO density; nuclei with q = Z.
+//! Internally everything is in atomic units; `print` reports
+//! Debye, Buckingham and Debye·Å².
+
+use crate::basis::shell::Shell;
+use crate::system::atom::Atom;
+use crate::system::units::{AU_TO_DEBYE, AU_TO_BUCKINGHAM, AU_TO_DEBYE_ANG2};
+use crate::integrals::multipole::{
+    multipole_components, multipole_integrals, MultipoleOrigin,
+};
+
+/// Multipole moments about `origin` (atomic units)
+pub struct MultipoleMoments {
+    pub origin: [f64; 3],
+    pub dipole: [f64; 3],
+    /// Traceless quadrupole Θ
+    pub quadrupole: [[f64; 3]; 3],
+    /// Traceless octupole Ω
+    pub octupole: [[[f64; 3]; 3]; 3],
+}
+
+/// Raw Cartesian moments Σ q (r−O)^e of one order
+fn cartesian_moments(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+    origin: [f64; 3],
+    order: usize,
+) -> Vec<f64> {
+
+    let nao = density.len();
+    let ints = multipole_integrals(shells, origin, order);
+
+    multipole_components(order)
+        .iter()
+        .zip(ints.iter())
+        .map(|(e, m)| {
+            let mut val = 0.0;
+
+            // electrons
+            for mu in 0..nao {
+                for nu in 0..nao {
+                    val -= density[mu][nu] * m[mu][nu];
+                }
+            }
+
+            // nuclei
+            for a in atoms {
+                let mut r = 1.0;
+                for k in 0..3 {
+                    r *= (a.position[k] - origin[k]).powi(e[k] as i32);
+                }
+                val += a.nuclear_charge() * r;
+            }
+
+            val
+        })
+        .collect()
+}
+
+/// Index of the component whose exponents are given by `axes`
+fn component_index(axes: &[usize]) -> usize {
+    let mut e = [0usize; 3];
+    for &k in axes {
+        e[k] += 1;
+    }
+    multipole_components(axes.len())
+        .iter()
+        .position(|c| *c == e)
+        .unwrap()
+}
+
+/// Dipole, traceless quadrupole and octupole moments
+///
+/// `density` is the total AO density (RHF: P, UHF: P_α + P_β).
+pub fn multipole_moments(
+    shells: &[Shell],
+    density: &[Vec<f64>],
+    atoms: &[Atom],
+    origin: MultipoleOrigin,
+) -> MultipoleMoments {
+
+    let o = origin.resolve(atoms);
+
+    let m1 = cartesian_moments(shells, density, atoms, o, 1);
+    let m2 = cartesian_moments(shells, density, atoms, o, 2);
+    let m3 = cartesian_moments(shells, density, atoms, o, 3);
+
+    let mut dipole = [0.0; 3];
+    for i in 0..3 {
+        dipole[i] = m1[component_index(&[i])];
+    }
+
+    // --------------------------------------------------
+    // Θ_ij = ½ (3 M_ij − δ_ij tr M)
+    // --------------------------------------------------
+    let tr2: f64 = (0..3).map(|l| m2[component_index(&[l, l])]).sum();
+
+    let mut quadrupole = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            let delta = if i == j { 1.0 } else { 0.0 };
+            quadrupole[i][j] =
+                0.5 * (3.0 * m2[component_index(&[i, j])] - delta * tr2);
+        }
+    }
+
+    // --------------------------------------------------
+    // Ω_ijk = ½ (5 M_ijk − δ_jk T_i − δ_ik T_j − δ_ij T_k)
+    // T_i = Σ_l M_ill
+    // --------------------------------------------------
+    let mut t = [0.0; 3];
+    for i in 0..3 {
+        t[i] = (0..3).map(|l| m3[component_index(&[i, l, l])]).sum();
+    }
+
+    let d = |a: usize, b: usize| if a == b { 1.0 } else { 0.0 };
+
+    let mut octupole = [[[0.0; 3]; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            for k in 0..3 {
+                octupole[i][j][k] = 0.5 * (
+                    5.0 * m3[component_index(&[i, j, k])]
+                  - d(j, k) * t[i]
+                  - d(i, k) * t[j]
+                  - d(i, j) * t[k]
+                );
+            }
+        }
+    }
+
+    MultipoleMoments {
+        origin: o,
+        dipole,
+        quadrupole,
+        octupole,
+    }
+}
+
+/// Multipole moments from UHF / UDFT spin densities
+pub fn multipole_moments_uhf(
+    shells: &[Shell],
+    p_alpha: &[Vec<f64>],
+    p_beta: &[Vec<f64>],
+    atoms: &[Atom],
+    origin: MultipoleOrigin,
+) -> MultipoleMoments {
+
+    let p_tot: Vec<Vec<f64>> = p_alpha
+        .iter()
+        .zip(p_beta.iter())
+        .map(|(ra, rb)| ra.iter().zip(rb.iter()).map(|(a, b)| a + b).collect())
+        .collect();
+
+    multipole_moments(shells, &p_tot, atoms, origin)
+}
+
+impl MultipoleMoments {
+    /// |μ| in Debye
+    pub fn dipole_debye(&self) -> f64 {
+        let d = self.dipole;
+        (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt() * AU_TO_DEBYE
+    }
+
+    pub fn print(&self) {
+        let ax = ['X', 'Y', 'Z'];
+
+        println!(
+            "Multipole origin (bohr): {:12.6} {:12.6} {:12.6}",
+            self.origin[0], self.origin[1], self.origin[2]
+        );
+
+        println!("Dipole moment (Debye):");
+        for (a, d) in ax.iter().zip(self.dipole) {
+            println!("  {}  {:14.6}", a, d * AU_TO_DEBYE);
+        }
+        println!("  |μ| {:14.6}", self.dipole_debye());
+
+        println!("Traceless quadrupole (Buckingham):");
+        for i in 0..3 {
+            for j in i..3 {
+                println!(
+                    "  {}{}  {:14.6}",
+                    ax[i], ax[j],
+                    self.quadrupole[i][j] * AU_TO_BUCKINGHAM
+                );
+            }
+        }
+
+        println!("Traceless octupole (Debye·Å²):");
+        for i in 0..3 {
+            for j in i..3 {
+                for k in j..3 {
+                    println!(
+                        "  {}{}{}  {:14.6}",
+                        ax[i], ax[j], ax[k],
+                        self.octupole[i][j][k] * AU_TO_DEBYE_ANG2
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+
+    /// H₃ with s shells on every atom and a p shell on the last one
+    fn h3() -> (Vec<Atom>, Vec<Shell>) {
+        let positions = [[0.0, 0.0, 0.0], [0.0, 0.0, 1.4], [0.0, 1.2, 2.6]];
+        let atoms = positions.iter().map(|&r| Atom::new("H".to_string(), 1, r)).collect();
+
+        let mut shells = Vec::new();
+        for (atom, ang, alpha) in
+            [(0, [0, 0, 0], 0.9), (1, [0, 0, 0], 0.7), (2, [0, 0, 0], 1.1), (2, [0, 1, 0], 0.6)]
+        {
+            let center = positions[atom];
+            let offset = shells.iter().map(Shell::n_orbitals).sum();
+            let primitive = Primitive::new(alpha, 1.0, center, ang);
+            shells.push(Shell::new(vec![primitive], ang, center, offset));
+        }
+
+        (atoms, shells)
+    }
+
+    /// n c cᵀ / (cᵀ S c): `electrons` electrons in one orbital
+    fn density(shells: &[Shell], c: &[f64], electrons: f64) -> Vec<Vec<f64>> {
+        let s = &multipole_integrals(shells, [0.0; 3], 0)[0];
+        let norm: f64 =
+            (0..c.len()).flat_map(|i| (0..c.len()).map(move |j| c[i] * s[i][j] * c[j])).sum();
+        c.iter().map(|x| c.iter().map(|y| electrons * x * y / norm).collect()).collect()
+    }
+
+    #[test]
+    fn dipole_shifts_with_the_charge_and_higher_moments_are_traceless() {
+        let (atoms, shells) = h3();
+        // H₃⁺: two electrons
+        let p = density(&shells, &[0.5, 0.4, 0.3, 0.2, -0.1, 0.15], 2.0);
+
+        let at = |origin| multipole_moments(&shells, &p, &atoms, origin);
+        let com = at(MultipoleOrigin::CenterOfMass);
+        let shifted = at(MultipoleOrigin::Point([0.5, -1.0, 2.0]));
+
+        // μ(O') = μ(O) − Q (O' − O) with Q = +1
+        for k in 0..3 {
+            let expected = com.dipole[k] - (shifted.origin[k] - com.origin[k]);
+            assert!((shifted.dipole[k] - expected).abs() < 1e-8);
+        }
+
+        let trace = (0..3).map(|i| com.quadrupole[i][i]).sum::<f64>();
+        assert!(trace.abs() < 1e-10);
+        for i in 0..3 {
+            let t: f64 = (0..3).map(|l| com.octupole[i][l][l]).sum();
+            assert!(t.abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn uhf_moments_use_the_total_density() {
+        let (atoms, shells) = h3();
+        let p_a = density(&shells, &[0.5, 0.4, 0.3, 0.2, -0.1, 0.15], 2.0);
+        let p_b = density(&shells, &[0.2, 0.6, -0.3, 0.1, 0.3, 0.0], 1.0);
+        let total: Vec<Vec<f64>> = p_a
+            .iter()
+            .zip(&p_b)
+            .map(|(ra, rb)| ra.iter().zip(rb).map(|(a, b)| a + b).collect())
+            .collect();
+
+        let origin = MultipoleOrigin::Origin;
+        let moments = multipole_moments(&shells, &total, &atoms, origin);
+        let spin = multipole_moments_uhf(&shells, &p_a, &p_b, &atoms, origin);
+
+        for k in 0..3 {
+            assert!((moments.dipole[k] - spin.dipole[k]).abs() < 1e-12);
+            assert!((moments.quadrupole[k][k] - spin.quadrupole[k][k]).abs() < 1e-12);
+        }
+        assert!(moments.dipole_debye() > 0.0);
+    }
+}
@@ -1,7 +1,5 @@
 //! Static polarizability via finite electric fields
 
-use crate::spectroscopy::dipole::dipole_moment;
-
 pub fn polarizability(
     field: f64,
     eval_mu: &dyn Fn(&[f64;3]) -> [f64;3],
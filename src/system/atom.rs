@@ -12,6 +12,10 @@ pub struct Atom {
     pub symbol: String,
     pub atomic_number: usize,
     pub position: [f64; 3],
+    /// Core electrons replaced by an ECP (0 for all-electron atoms)
+    pub core_electrons: usize,
+    /// Gaussian nuclear exponent ζ (None = point nucleus)
+    pub nuclear_exponent: Option<f64>,
 }
 
 impl Atom {
@@ -24,7 +28,14 @@ impl Atom {
             symbol,
             atomic_number,
             position,
+            core_electrons: 0,
+            nuclear_exponent: None,
         }
     }
+
+    /// Charge seen by the valence electrons: Z − core electrons
+    pub fn nuclear_charge(&self) -> f64 {
+        (self.atomic_number - self.core_electrons) as f64
+    }
 }
 
@@ -4,3 +4,4 @@ pub mod molecule;
 pub mod parser_xyz;
 pub mod units;
 pub mod periodic_table;
+pub mod nuclear_model;
@@ -1,4 +1,8 @@
+use std::collections::HashMap;
+
+use crate::basis::reader::Ecp;
 use crate::system::atom::Atom;
+use crate::system::nuclear_model::NuclearModel;
 use crate::system::parser_xyz::read_xyz;
 
 pub struct Molecule {
@@ -19,5 +23,92 @@ impl Molecule {
             multiplicity,
         })
     }
+
+    /// Attach ECPs (keyed by element symbol) to the atoms
+    ///
+    /// Core electrons are removed from the electron count and from the
+    /// nuclear charges seen by the valence electrons.
+    pub fn apply_ecp(&mut self, ecps: &HashMap<String, Ecp>) -> Result<(), String> {
+        for atom in &mut self.atoms {
+            let n_core = ecps.get(&atom.symbol).map_or(0, |ecp| ecp.n_core);
+
+            if n_core > atom.atomic_number {
+                return Err(format!(
+                    "ECP of {} replaces {} electrons but Z = {}",
+                    atom.symbol, n_core, atom.atomic_number
+                ));
+            }
+
+            atom.core_electrons = n_core;
+        }
+
+        Ok(())
+    }
+
+    /// Select point or Gaussian (Visscher–Dyall) nuclei
+    pub fn set_nuclear_model(&mut self, model: NuclearModel) {
+        for atom in &mut self.atoms {
+            atom.nuclear_exponent = model.exponent(atom);
+        }
+    }
+
+    /// Number of explicitly treated electrons
+    pub fn n_electrons(&self) -> Result<usize, String> {
+        let valence: i64 = self
+            .atoms
+            .iter()
+            .map(|a| a.atomic_number as i64 - a.core_electrons as i64)
+            .sum();
+
+        let n = valence - self.charge as i64;
+        if n < 0 {
+            return Err(format!(
+                "Charge {} exceeds the {} valence electrons",
+                self.charge, valence
+            ));
+        }
+
+        Ok(n as usize)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn molecule(charge: i32) -> Molecule {
+        Molecule {
+            atoms: vec![
+                Atom::new("Rb".to_string(), 37, [0.0, 0.0, 0.0]),
+                Atom::new("H".to_string(), 1, [0.0, 0.0, 4.5]),
+            ],
+            charge,
+            multiplicity: 1,
+        }
+    }
+
+    fn ecps(n_core: usize) -> HashMap<String, Ecp> {
+        let ecp = Ecp { n_core, local: Vec::new(), semilocal: Vec::new() };
+        HashMap::from([("Rb".to_string(), ecp)])
+    }
+
+    #[test]
+    fn ecp_core_electrons_leave_the_count() {
+        let mut mol = molecule(0);
+        assert_eq!(mol.n_electrons(), Ok(38));
+
+        mol.apply_ecp(&ecps(28)).unwrap();
+        assert_eq!(mol.atoms[0].nuclear_charge(), 9.0);
+        assert_eq!(mol.n_electrons(), Ok(10));
+    }
+
+    #[test]
+    fn impossible_counts_are_errors() {
+        let mut mol = molecule(0);
+        assert!(mol.apply_ecp(&ecps(38)).is_err());
+
+        mol.apply_ecp(&ecps(28)).unwrap();
+        mol.charge = 11;
+        assert!(mol.n_electrons().is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Nuclear charge distribution models
+//!
+//! - point nuclei:     ρ(r) = Z δ(r − R)
+//! - Gaussian nuclei:  ρ(r) = Z (ζ/π)^{3/2} exp(−ζ |r − R|²)
+//!
+//! Gaussian exponents follow Visscher & Dyall (At. Data Nucl. Data
+//! Tables 67, 207 (1997)):
+//!   ζ = 3 / (2 ⟨r²⟩),  ⟨r²⟩^{½} = 0.836 A^{1/3} + 0.570 fm
+//! with A the mass number of the most abundant isotope.
+//!
+//! Two nuclei interact through
+//!   E_AB = Z_A Z_B erf(μ R) / R,   1/μ² = 1/ζ_A + 1/ζ_B
+//! which reduces to Z_A Z_B / R for point nuclei.
+
+use std::f64::consts::PI;
+
+use crate::system::atom::Atom;
+use crate::system::units::BOHR_TO_ANGSTROM;
+
+/// Nuclear model used for attraction integrals and nuclear repulsion
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NuclearModel {
+    PointCharge,
+    Gaussian,
+}
+
+/// Mass numbers of the reference isotopes, Z = 1 … 86
+const MASS_NUMBERS: [usize; 86] = [
+      1,   4,   7,   9,  11,  12,  14,  16,  19,  20,
+     23,  24,  27,  28,  31,  32,  35,  40,  39,  40,
+     45,  48,  51,  52,  55,  56,  59,  58,  63,  64,
+     69,  74,  75,  80,  79,  84,  85,  88,  89,  90,
+     93,  98,  98, 102, 103, 106, 107, 114, 115, 120,
+    121, 130, 127, 132, 133, 138, 139, 140, 141, 142,
+    145, 152, 153, 158, 159, 164, 165, 166, 169, 174,
+    175, 180, 181, 184, 187, 192, 193, 195, 197, 202,
+    205, 208, 209, 209, 210, 222,
+];
+
+/// 1 fm in bohr
+const FERMI_TO_BOHR: f64 = 1e-5 / BOHR_TO_ANGSTROM;
+
+/// Mass number of the reference isotope
+pub fn mass_number(atomic_number: usize) -> Option<usize> {
+    MASS_NUMBERS.get(atomic_number.checked_sub(1)?).copied()
+}
+
+/// Visscher–Dyall Gaussian exponent ζ (bohr⁻²)
+pub fn gaussian_nuclear_exponent(mass_number: usize) -> f64 {
+    let r_rms = (0.836 * (mass_number as f64).cbrt() + 0.570) * FERMI_TO_BOHR;
+    1.5 / (r_rms * r_rms)
+}
+
+impl NuclearModel {
+    /// Parse the input keyword (point | gaussian)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "point" => Some(Self::PointCharge),
+            "gaussian" => Some(Self::Gaussian),
+            _ => None,
+        }
+    }
+
+    /// Nuclear exponent of an atom under this model (None = point)
+    pub fn exponent(&self, atom: &Atom) -> Option<f64> {
+        match self {
+            NuclearModel::PointCharge => None,
+            NuclearModel::Gaussian => {
+                let a = mass_number(atom.atomic_number).unwrap_or_else(|| {
+                    panic!("No reference isotope for Z = {}", atom.atomic_number)
+                });
+                Some(gaussian_nuclear_exponent(a))
+            }
+        }
+    }
+}
+
+/// f(R), f'(R), f''(R) of the nuclear pair interaction E_AB = Z_A Z_B f(R)
+pub fn nuclear_pair_function(a: &Atom, b: &Atom, r: f64) -> [f64; 3] {
+    let inv_mu2 = a.nuclear_exponent.map_or(0.0, |z| 1.0 / z)
+        + b.nuclear_exponent.map_or(0.0, |z| 1.0 / z);
+
+    if inv_mu2 == 0.0 {
+        return [1.0 / r, -1.0 / (r * r), 2.0 / (r * r * r)];
+    }
+
+    let mu = inv_mu2.sqrt().recip();
+    let g = libm::erf(mu * r);
+    let dg = 2.0 * mu / PI.sqrt() * (-mu * mu * r * r).exp();
+
+    [
+        g / r,
+        dg / r - g / (r * r),
+        -2.0 * mu * mu * dg - 2.0 * dg / (r * r) + 2.0 * g / (r * r * r),
+    ]
+}
+
+/// Nuclear repulsion energy Σ_{A<B} Z_A Z_B f(R_AB)
+pub fn nuclear_repulsion_energy(atoms: &[Atom]) -> f64 {
+    let mut e = 0.0;
+
+    for (i, a) in atoms.iter().enumerate() {
+        for b in &atoms[..i] {
+            let r = (0..3)
+                .map(|k| (a.position[k] - b.position[k]).powi(2))
+                .sum::<f64>()
+                .sqrt();
+
+            e += a.nuclear_charge() * b.nuclear_charge() * nuclear_pair_function(a, b, r)[0];
+        }
+    }
+
+    e
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::primitive::Primitive;
+    use crate::basis::shell::Shell;
+    use crate::gradients::nuclear_repulsion::grad_nuclear_repulsion;
+    use crate::integrals::nuclear_attraction::nuclear_attraction_shell_shell;
+
+    fn gaussian_atom(symbol: &str, z: usize, position: [f64; 3]) -> Atom {
+        let mut atom = Atom::new(symbol.to_string(), z, position);
+        atom.nuclear_exponent = NuclearModel::Gaussian.exponent(&atom);
+        atom
+    }
+
+    #[test]
+    fn hydrogen_exponent_matches_visscher_dyall() {
+        let zeta = gaussian_nuclear_exponent(mass_number(1).unwrap());
+        assert!((zeta / 2.1248239171e9 - 1.0).abs() < 1e-4, "{}", zeta);
+        assert_eq!(mass_number(0), None);
+        assert_eq!(mass_number(87), None);
+    }
+
+    #[test]
+    fn pair_function_derivatives_match_finite_differences() {
+        let a = gaussian_atom("Pb", 82, [0.0; 3]);
+        let b = gaussian_atom("Hg", 80, [0.0; 3]);
+        // inside the nuclear overlap region, where erf(μR) differs from 1
+        let mu = (1.0 / a.nuclear_exponent.unwrap() + 1.0 / b.nuclear_exponent.unwrap())
+            .sqrt()
+            .recip();
+        let r = 0.8 / mu;
+        let h = 1e-4 * r;
+
+        let [f, df, d2f] = nuclear_pair_function(&a, &b, r);
+        let [fp, dfp, _] = nuclear_pair_function(&a, &b, r + h);
+        let [fm, dfm, _] = nuclear_pair_function(&a, &b, r - h);
+
+        assert!(f < 1.0 / r);
+        assert!(((fp - fm) / (2.0 * h) / df - 1.0).abs() < 1e-6);
+        assert!(((dfp - dfm) / (2.0 * h) / d2f - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn gaussian_nuclei_act_as_points_at_bond_distances() {
+        let point = [
+            Atom::new("C".to_string(), 6, [0.0; 3]),
+            Atom::new("O".to_string(), 8, [0.0, 0.3, 2.1]),
+        ];
+        let gaussian = point
+            .clone()
+            .map(|a| gaussian_atom(&a.symbol, a.atomic_number, a.position));
+
+        let e_point = nuclear_repulsion_energy(&point);
+        assert!((nuclear_repulsion_energy(&gaussian) - e_point).abs() < 1e-12 * e_point);
+
+        let (gp, gg) = (grad_nuclear_repulsion(&point), grad_nuclear_repulsion(&gaussian));
+        for a in 0..2 {
+            for k in 0..3 {
+                assert!((gp[a][k] - gg[a][k]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn smeared_nucleus_weakens_the_attraction_of_tight_functions() {
+        let point = Atom::new("Rn".to_string(), 86, [0.0; 3]);
+        let smeared = gaussian_atom("Rn", 86, [0.0; 3]);
+        let v = |alpha: f64, atom: &Atom| {
+            let primitive = Primitive::new(alpha, 1.0, [0.0; 3], [0, 0, 0]);
+            let s = Shell::new(vec![primitive], [0, 0, 0], [0.0; 3], 0);
+            nuclear_attraction_shell_shell(&s, &s, std::slice::from_ref(atom))[0][0]
+        };
+
+        // tight core function: noticeably less negative
+        assert!(v(1e8, &smeared) > v(1e8, &point) * (1.0 - 1e-3));
+        // valence function: the model does not matter
+        assert!((v(1.0, &smeared) / v(1.0, &point) - 1.0).abs() < 1e-8);
+    }
+}
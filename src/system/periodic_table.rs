@@ -14,6 +14,82 @@ pub fn atomic_number(symbol: &str) -> Option<usize> {
         "O" => Some(8),
         "F" => Some(9),
         "Ne" => Some(10),
+        "Na" => Some(11),
+        "Mg" => Some(12),
+        "Al" => Some(13),
+        "Si" => Some(14),
+        "P" => Some(15),
+        "S" => Some(16),
+        "Cl" => Some(17),
+        "Ar" => Some(18),
+        "K" => Some(19),
+        "Ca" => Some(20),
+        "Sc" => Some(21),
+        "Ti" => Some(22),
+        "V" => Some(23),
+        "Cr" => Some(24),
+        "Mn" => Some(25),
+        "Fe" => Some(26),
+        "Co" => Some(27),
+        "Ni" => Some(28),
+        "Cu" => Some(29),
+        "Zn" => Some(30),
+        "Ga" => Some(31),
+        "Ge" => Some(32),
+        "As" => Some(33),
+        "Se" => Some(34),
+        "Br" => Some(35),
+        "Kr" => Some(36),
+        "Rb" => Some(37),
+        "Sr" => Some(38),
+        "Y" => Some(39),
+        "Zr" => Some(40),
+        "Nb" => Some(41),
+        "Mo" => Some(42),
+        "Tc" => Some(43),
+        "Ru" => Some(44),
+        "Rh" => Some(45),
+        "Pd" => Some(46),
+        "Ag" => Some(47),
+        "Cd" => Some(48),
+        "In" => Some(49),
+        "Sn" => Some(50),
+        "Sb" => Some(51),
+        "Te" => Some(52),
+        "I" => Some(53),
+        "Xe" => Some(54),
+        "Cs" => Some(55),
+        "Ba" => Some(56),
+        "La" => Some(57),
+        "Ce" => Some(58),
+        "Pr" => Some(59),
+        "Nd" => Some(60),
+        "Pm" => Some(61),
+        "Sm" => Some(62),
+        "Eu" => Some(63),
+        "Gd" => Some(64),
+        "Tb" => Some(65),
+        "Dy" => Some(66),
+        "Ho" => Some(67),
+        "Er" => Some(68),
+        "Tm" => Some(69),
+        "Yb" => Some(70),
+        "Lu" => Some(71),
+        "Hf" => Some(72),
+        "Ta" => Some(73),
+        "W" => Some(74),
+        "Re" => Some(75),
+        "Os" => Some(76),
+        "Ir" => Some(77),
+        "Pt" => Some(78),
+        "Au" => Some(79),
+        "Hg" => Some(80),
+        "Tl" => Some(81),
+        "Pb" => Some(82),
+        "Bi" => Some(83),
+        "Po" => Some(84),
+        "At" => Some(85),
+        "Rn" => Some(86),
         // se puede extender sin tocar Atom
         _ => None,
     }
@@ -10,6 +10,21 @@ pub const ANGSTROM_TO_BOHR: f64 = 1.0 / BOHR_TO_ANGSTROM;
 /// Hartree energy in eV (for reporting only)
 pub const HARTREE_TO_EV: f64 = 27.211386245988;
 
+/// Hartree energy in cm⁻¹ (for reporting only)
+pub const HARTREE_TO_WAVENUMBER: f64 = 219474.6313632;
+
+/// Speed of light in atomic units (CODATA 2018)
+pub const SPEED_OF_LIGHT: f64 = 137.035999084;
+
+/// Dipole: e·a0 in Debye
+pub const AU_TO_DEBYE: f64 = 2.541746473;
+
+/// Quadrupole: e·a0² in Buckingham (Debye·Å)
+pub const AU_TO_BUCKINGHAM: f64 = AU_TO_DEBYE * BOHR_TO_ANGSTROM;
+
+/// Octupole: e·a0³ in Debye·Å²
+pub const AU_TO_DEBYE_ANG2: f64 = AU_TO_DEBYE * BOHR_TO_ANGSTROM * BOHR_TO_ANGSTROM;
+
 /// Convert Angstrom to Bohr
 #[inline]
 pub fn angstrom_to_bohr(x: f64) -> f64 {
@@ -1,20 +1,127 @@
 //! Vibrational analysis driver
 
+use std::path::Path;
+
+use crate::checkpoint::{Checkpoint, HessianProgress};
+use crate::observer::{Control, FrequencyObserver};
 use crate::vibrations::{
-    hessian_fd::hessian_fd,
+    hessian_fd::{hessian_fd, hessian_fd_resume},
     projector::project_tr_rotation,
     mass::mass_weight_hessian,
     frequencies::vibrational_frequencies,
 };
 
+/// Finite-difference step (bohr)
+const FD_STEP: f64 = 1e-3;
+
 pub fn compute_frequencies(
     coords: &Vec<f64>,
     masses: &Vec<f64>,
     gradient: &dyn Fn(&Vec<f64>) -> Vec<f64>,
 ) -> Vec<f64> {
 
-    let h = hessian_fd(coords, gradient, 1e-3);
-    let h_proj = project_tr_rotation(&h, coords, masses);
+    let h = hessian_fd(coords, gradient, FD_STEP);
+    frequencies_from_hessian(&h, coords, masses)
+}
+
+/// `compute_frequencies` reporting every gradient column to `observer`;
+/// None if it stopped the Hessian
+pub fn compute_frequencies_observed(
+    coords: &Vec<f64>,
+    masses: &Vec<f64>,
+    gradient: &dyn Fn(&Vec<f64>) -> Vec<f64>,
+    observer: &mut dyn FrequencyObserver,
+) -> Option<Vec<f64>> {
+
+    let mut progress = HessianProgress::new(coords, FD_STEP);
+    let h = hessian_fd_resume(&mut progress, gradient, observer)?;
+    Some(frequencies_from_hessian(&h, coords, masses))
+}
+
+/// `compute_frequencies` saving every gradient column to `path`
+/// (a failed save only warns)
+///
+/// A Hessian in progress in `checkpoint` for the same coordinates is
+/// resumed; otherwise a new one is started.
+pub fn compute_frequencies_resume(
+    coords: &Vec<f64>,
+    masses: &Vec<f64>,
+    gradient: &dyn Fn(&Vec<f64>) -> Vec<f64>,
+    checkpoint: &mut Checkpoint,
+    path: &Path,
+) -> Vec<f64> {
+
+    let mut progress = match checkpoint.hessian.take() {
+        Some(p) if p.x == *coords && p.step == FD_STEP => {
+            println!("Resuming Hessian: {}/{} columns done", p.completed(), p.x.len());
+            p
+        }
+        _ => HessianProgress::new(coords, FD_STEP),
+    };
+
+    let h = hessian_fd_resume(&mut progress, gradient, &mut |p: &HessianProgress| {
+        checkpoint.hessian = Some(p.clone());
+        if let Err(e) = checkpoint.save(path) {
+            eprintln!("WARNING: {}", e);
+        }
+        Control::Continue
+    })
+    .expect("Hessian columns complete");
+
+    // Progreso completo (también si todas las columnas venían del archivo)
+    checkpoint.hessian = Some(progress);
+    if let Err(e) = checkpoint.save(path) {
+        eprintln!("WARNING: {}", e);
+    }
+
+    frequencies_from_hessian(&h, coords, masses)
+}
+
+fn frequencies_from_hessian(h: &Vec<Vec<f64>>, coords: &Vec<f64>, masses: &Vec<f64>) -> Vec<f64> {
+    let h_proj = project_tr_rotation(h, coords, masses);
     let h_mw = mass_weight_hessian(&h_proj, masses);
     vibrational_frequencies(&h_mw)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scf::fixtures::h2;
+
+    /// Gradient of ½ k (|r₁ − r₂| − r₀)²
+    fn harmonic_gradient(x: &[f64]) -> Vec<f64> {
+        let d: Vec<f64> = (0..3).map(|k| x[k] - x[k + 3]).collect();
+        let r = d.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let f = 0.4 * (r - 1.4) / r;
+        let g: Vec<f64> = d.iter().map(|v| f * v).collect();
+        g.iter().copied().chain(g.iter().map(|v| -v)).collect()
+    }
+
+    #[test]
+    fn finished_hessian_is_saved_even_when_fully_resumed() {
+        let dir = std::env::temp_dir().join(format!("vibrations-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("h2.chk");
+
+        let (molecule, shells, _) = h2();
+        let coords: Vec<f64> = molecule.atoms.iter().flat_map(|a| a.position).collect();
+        let masses = vec![1.008, 1.008];
+
+        // Todas las columnas ya calculadas: no hay guardado por columna
+        let mut checkpoint = Checkpoint::new(&molecule, &shells);
+        let mut done = HessianProgress::new(&coords, FD_STEP);
+        let gradient = |x: &Vec<f64>| harmonic_gradient(x);
+        hessian_fd_resume(&mut done, &gradient, &mut |_: &HessianProgress| {
+            Control::Continue
+        });
+        checkpoint.hessian = Some(done);
+
+        compute_frequencies_resume(&coords, &masses, &gradient, &mut checkpoint, &path);
+
+        let saved = Checkpoint::load(&path).unwrap().hessian.unwrap();
+        assert_eq!(saved.completed(), coords.len());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
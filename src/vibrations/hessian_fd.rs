@@ -1,17 +1,39 @@
 //! Hessian via finite differences of analytic gradients
 
+use crate::checkpoint::HessianProgress;
+use crate::observer::{Control, FrequencyObserver};
+
 pub fn hessian_fd(
     x: &Vec<f64>,
     grad: &dyn Fn(&Vec<f64>) -> Vec<f64>,
     step: f64,
 ) -> Vec<Vec<f64>> {
 
-    let n = x.len();
-    let mut h = vec![vec![0.0; n]; n];
+    let mut progress = HessianProgress::new(x, step);
+    hessian_fd_resume(&mut progress, grad, &mut |_: &HessianProgress| Control::Continue)
+        .expect("Hessian columns complete")
+}
+
+/// Same as `hessian_fd`, skipping the columns already in `progress`
+///
+/// `observer` is called after every new column (e.g. to save a checkpoint);
+/// None if it stopped before the last column.
+pub fn hessian_fd_resume(
+    progress: &mut HessianProgress,
+    grad: &dyn Fn(&Vec<f64>) -> Vec<f64>,
+    observer: &mut dyn FrequencyObserver,
+) -> Option<Vec<Vec<f64>>> {
+
+    let n = progress.x.len();
+    let step = progress.step;
 
     for i in 0..n {
-        let mut x_p = x.clone();
-        let mut x_m = x.clone();
+        if progress.columns[i].is_some() {
+            continue;
+        }
+
+        let mut x_p = progress.x.clone();
+        let mut x_m = progress.x.clone();
 
         x_p[i] += step;
         x_m[i] -= step;
@@ -19,8 +41,19 @@ pub fn hessian_fd(
         let g_p = grad(&x_p);
         let g_m = grad(&x_m);
 
+        progress.columns[i] = Some(
+            (0..n).map(|j| (g_p[j] - g_m[j]) / (2.0 * step)).collect()
+        );
+        if observer.on_column(progress) == Control::Stop && progress.completed() < n {
+            return None;
+        }
+    }
+
+    let mut h = vec![vec![0.0; n]; n];
+    for (i, column) in progress.columns.iter().enumerate() {
+        let column = column.as_ref().unwrap();
         for j in 0..n {
-            h[j][i] = (g_p[j] - g_m[j]) / (2.0 * step);
+            h[j][i] = column[j];
         }
     }
 
@@ -33,5 +66,5 @@ pub fn hessian_fd(
         }
     }
 
-    h
+    Some(h)
 }